@@ -27,5 +27,7 @@ fn main() {
         tracing_subscriber::fmt::init();
     }
     info!("Voxelicous Engine Benchmarks");
-    info!("Run with: cargo bench -p voxelicous-voxel");
+    info!(
+        "Run with: cargo bench -p voxelicous-voxel -p voxelicous-world --features voxelicous-world/bench"
+    );
 }
@@ -40,19 +40,23 @@ impl Dashboard {
         let sections = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Header
-                Constraint::Length(3), // Frame info
-                Constraint::Min(10),   // Stats table
-                Constraint::Length(3), // Queue info
-                Constraint::Length(3), // Footer
+                Constraint::Length(3),  // Header
+                Constraint::Length(3),  // Frame info
+                Constraint::Length(3),  // Frame time histogram
+                Constraint::Min(10),    // Stats table
+                Constraint::Length(11), // Slowest pages
+                Constraint::Length(3),  // Queue info
+                Constraint::Length(3),  // Footer
             ])
             .split(area);
 
         self.render_header(frame, sections[0], connection_state, snapshot);
         self.render_frame_info(frame, sections[1], snapshot);
-        self.render_stats_table(frame, sections[2], snapshot);
-        self.render_queue_info(frame, sections[3], snapshot);
-        self.render_footer(frame, sections[4]);
+        self.render_histogram(frame, sections[2], snapshot);
+        self.render_stats_table(frame, sections[3], snapshot);
+        self.render_slowest_pages(frame, sections[4], snapshot);
+        self.render_queue_info(frame, sections[5], snapshot);
+        self.render_footer(frame, sections[6]);
     }
 
     fn render_header(
@@ -198,6 +202,38 @@ impl Dashboard {
         frame.render_widget(widget, area);
     }
 
+    fn render_histogram(&self, frame: &mut Frame, area: Rect, snapshot: Option<&ProfilerSnapshot>) {
+        let histogram = snapshot.map_or_else(Default::default, |s| s.frame_time_histogram.clone());
+        let total = histogram.total().max(1);
+
+        let mut spans = Vec::with_capacity(histogram.counts.len() * 2);
+        for (index, &count) in histogram.counts.iter().enumerate() {
+            let (label, color) = match histogram.edges_ms.get(index) {
+                Some(edge) if *edge <= 16.7 => (format!(" <={edge}ms: "), Color::Green),
+                Some(edge) if *edge <= 33.4 => (format!(" <={edge}ms: "), Color::Yellow),
+                Some(edge) => (format!(" <={edge}ms: "), Color::Red),
+                None => {
+                    let last = histogram.edges_ms.last().copied().unwrap_or(0.0);
+                    (format!(" >{last}ms: "), Color::Red)
+                }
+            };
+            spans.push(Span::raw(label));
+            spans.push(Span::styled(
+                format!("{} ({}%)", count, count * 100 / total),
+                Style::default().fg(color),
+            ));
+        }
+
+        let widget = Paragraph::new(Line::from(spans)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Frame Time Histogram ")
+                .border_style(Style::default().fg(Color::Blue)),
+        );
+
+        frame.render_widget(widget, area);
+    }
+
     fn render_stats_table(
         &self,
         frame: &mut Frame,
@@ -225,6 +261,7 @@ impl Dashboard {
                         voxelicous_profiler::EventCategory::FrameRender => Color::Magenta,
                         voxelicous_profiler::EventCategory::GpuSubmit => Color::Green,
                         voxelicous_profiler::EventCategory::FramePresent => Color::Cyan,
+                        voxelicous_profiler::EventCategory::FramePacing => Color::DarkGray,
                         voxelicous_profiler::EventCategory::ClipmapPageBuild => Color::LightGreen,
                         voxelicous_profiler::EventCategory::ClipmapEncode => Color::LightCyan,
                         voxelicous_profiler::EventCategory::GpuClipmapUpload => Color::LightMagenta,
@@ -234,7 +271,8 @@ impl Dashboard {
                     };
 
                     Row::new(vec![
-                        Cell::from(stat.category.name()).style(Style::default().fg(color)),
+                        Cell::from(s.category_name(stat.category))
+                            .style(Style::default().fg(color)),
                         Cell::from(format!("{}", stat.count)),
                         Cell::from(format!("{:.2}ms", stat.avg_ms())),
                         Cell::from(format!("{:.2}ms", stat.min_ms())),
@@ -267,6 +305,55 @@ impl Dashboard {
         frame.render_widget(table, area);
     }
 
+    fn render_slowest_pages(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        snapshot: Option<&ProfilerSnapshot>,
+    ) {
+        let header_cells = ["Stage", "Page", "Time"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+        let header = Row::new(header_cells)
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .height(1);
+
+        let rows: Vec<Row> = snapshot.map_or_else(Vec::new, |s| {
+            s.slowest_page_events
+                .iter()
+                .map(|event| {
+                    let color = match event.category {
+                        voxelicous_profiler::EventCategory::ClipmapPageBuild => Color::LightGreen,
+                        voxelicous_profiler::EventCategory::ClipmapEncode => Color::LightCyan,
+                        _ => Color::White,
+                    };
+                    let [x, y, z] = event.context;
+
+                    Row::new(vec![
+                        Cell::from(event.category.name()).style(Style::default().fg(color)),
+                        Cell::from(format!("({}, {}, {})", x, y, z)),
+                        Cell::from(format!("{:.2}ms", event.duration_ns as f64 / 1_000_000.0)),
+                    ])
+                })
+                .collect()
+        });
+
+        let widths = [
+            Constraint::Length(15),
+            Constraint::Length(24),
+            Constraint::Length(10),
+        ];
+
+        let table = Table::new(rows, widths).header(header).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Slowest Pages ")
+                .border_style(Style::default().fg(Color::LightGreen)),
+        );
+
+        frame.render_widget(table, area);
+    }
+
     fn render_queue_info(
         &self,
         frame: &mut Frame,
@@ -1,5 +1,7 @@
 //! Viewer application implementation with clipmap streaming.
 
+use std::path::PathBuf;
+
 use anyhow::Context;
 use ash::vk;
 use glam::Vec3;
@@ -7,26 +9,25 @@ use tracing::{error, info};
 use winit::window::{CursorGrabMode, Window};
 
 use voxelicous_app::{
-    AppContext, Camera, DeviceEvent, DeviceId, FrameContext, VoxelApp, WindowEvent,
+    AppContext, Camera, DeviceEvent, DeviceId, FrameContext, PresentMode, VoxelApp, WindowEvent,
 };
 use voxelicous_input::{ActionMap, CursorMode, InputManager, KeyCode, MouseButton};
+use voxelicous_render::time_of_day::HOURS_PER_DAY;
 use voxelicous_render::{
-    save_screenshot, CameraUniforms, ClipmapRayMarchPipeline, ClipmapRenderer, DebugMode,
-    ScreenshotConfig,
+    save_screenshot, CameraMode, CameraPath, CameraUniforms, ClipmapRayMarchPipeline,
+    ClipmapRenderer, DebugMode, FlyCamController, FlyCamInput, FogSettings, FovMode, MaxStepsTuner,
+    OutputFormat, ScreenshotConfig, SkyBlend, SkyConfig, TimeOfDay, UploadBudget, WindSettings,
+    WorkgroupSize,
 };
 use voxelicous_world::{ClipmapStreamingController, TerrainConfig, TerrainGenerator};
 
 #[cfg(feature = "profiling")]
-use voxelicous_profiler::QueueSizes;
+use voxelicous_profiler::{MemoryStats, QueueSizes};
 
 /// Maximum ray marching steps per pixel.
 const MAX_STEPS: u32 = 1024;
-
-/// Camera movement speed in units per second.
-const CAMERA_SPEED: f32 = 30.0;
-
-/// Camera sprint multiplier.
-const CAMERA_SPRINT_MULT: f32 = 2.5;
+/// Lowest step limit the frame-time auto-tuner may pick.
+const AUTO_TUNE_MIN_STEPS: u32 = 128;
 
 /// Mouse sensitivity for camera rotation (radians per pixel).
 const MOUSE_SENSITIVITY: f32 = 0.002;
@@ -36,12 +37,33 @@ const DAY_NIGHT_CYCLE_SECONDS: f32 = 240.0;
 const BLOCK_EDIT_REACH: f32 = 10.0;
 /// Runtime LOD distance change step (pages per axis).
 const LOD_DISTANCE_PAGE_STEP: usize = 2;
+/// LOD0 pages built synchronously around the camera before the first frame.
+const INITIAL_PAGE_RADIUS: usize = 1;
+/// Frames between render stats log lines while stats are on (F7).
+const RENDER_STATS_LOG_INTERVAL: u64 = 60;
+/// Horizontal distance of the demo orbit (F8) from its center.
+const ORBIT_RADIUS: f32 = 96.0;
+/// Demo orbit turn rate in radians per second.
+const ORBIT_SPEED: f32 = 0.2;
 
 /// Configuration for clipmap rendering (from CLI or defaults).
 #[derive(Debug, Clone)]
 pub struct ClipmapParams {
     pub seed: u64,
     pub max_steps: u32,
+    pub base_steps: Option<u32>,
+    pub lod_step_falloff: f32,
+    pub target_frame_ms: Option<f32>,
+    pub upload_budget_kb: Option<u64>,
+    pub camera_path: Option<PathBuf>,
+    pub fog_density: f32,
+    pub fog_affects_sky: bool,
+    pub wind_strength: f32,
+    pub wind_speed: f32,
+    pub lod_transitions: Vec<f32>,
+    pub mouse_smoothing: f32,
+    pub fov_degrees: f32,
+    pub fov_mode: FovMode,
     pub debug_skip_ray_march: bool,
     pub debug_disable_shadows: bool,
 }
@@ -51,6 +73,19 @@ impl Default for ClipmapParams {
         Self {
             seed: 42,
             max_steps: MAX_STEPS,
+            base_steps: None,
+            lod_step_falloff: 0.0,
+            target_frame_ms: None,
+            upload_budget_kb: None,
+            camera_path: None,
+            fog_density: 0.0,
+            fog_affects_sky: false,
+            wind_strength: 0.3,
+            wind_speed: 1.5,
+            lod_transitions: Vec::new(),
+            mouse_smoothing: 0.0,
+            fov_degrees: 60.0,
+            fov_mode: FovMode::Vertical,
             debug_skip_ray_march: false,
             debug_disable_shadows: false,
         }
@@ -82,6 +117,113 @@ impl ClipmapParams {
                         }
                     }
                 }
+                "--base-steps" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<u32>() {
+                            params.base_steps = Some(v.max(1));
+                            i += 1;
+                        }
+                    }
+                }
+                "--lod-step-falloff" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.lod_step_falloff = v.max(0.0);
+                            i += 1;
+                        }
+                    }
+                }
+                "--upload-budget-kb" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<u64>() {
+                            params.upload_budget_kb = Some(v.max(1));
+                            i += 1;
+                        }
+                    }
+                }
+                "--target-frame-ms" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.target_frame_ms = (v > 0.0).then_some(v);
+                            i += 1;
+                        }
+                    }
+                }
+                "--camera-path" => {
+                    if i + 1 < args.len() {
+                        params.camera_path = Some(PathBuf::from(&args[i + 1]));
+                        i += 1;
+                    }
+                }
+                "--fog-density" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.fog_density = v.max(0.0);
+                            i += 1;
+                        }
+                    }
+                }
+                "--fog-affects-sky" => {
+                    params.fog_affects_sky = true;
+                }
+                "--wind-strength" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.wind_strength = v.clamp(0.0, 1.0);
+                            i += 1;
+                        }
+                    }
+                }
+                "--wind-speed" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.wind_speed = v.max(0.0);
+                            i += 1;
+                        }
+                    }
+                }
+                "--lod-transitions" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1]
+                            .split(',')
+                            .map(|field| field.trim().parse::<f32>())
+                            .collect()
+                        {
+                            params.lod_transitions = v;
+                            i += 1;
+                        }
+                    }
+                }
+                "--mouse-smoothing" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.mouse_smoothing = v.clamp(0.0, 0.99);
+                            i += 1;
+                        }
+                    }
+                }
+                "--fov" => {
+                    if i + 1 < args.len() {
+                        if let Ok(v) = args[i + 1].parse::<f32>() {
+                            params.fov_degrees = v.clamp(10.0, 170.0);
+                            i += 1;
+                        }
+                    }
+                }
+                "--fov-mode" => {
+                    if i + 1 < args.len() {
+                        let mode = match args[i + 1].as_str() {
+                            "vertical" => Some(FovMode::Vertical),
+                            "horizontal" => Some(FovMode::Horizontal),
+                            "hor4x3" => Some(FovMode::Hor4x3),
+                            _ => None,
+                        };
+                        if let Some(mode) = mode {
+                            params.fov_mode = mode;
+                            i += 1;
+                        }
+                    }
+                }
                 "--debug-skip-raymarch" => {
                     params.debug_skip_ray_march = true;
                 }
@@ -107,10 +249,12 @@ pub struct Viewer {
     pipeline: Option<ClipmapRayMarchPipeline>,
     /// Camera for viewing the world.
     camera: Camera,
-    /// Camera yaw (rotation around Y axis) in radians.
-    camera_yaw: f32,
-    /// Camera pitch (rotation around X axis) in radians.
-    camera_pitch: f32,
+    /// Fly-camera look and movement.
+    fly_cam: FlyCamController,
+    /// Free-fly or automatic orbit, cycled with F8.
+    camera_mode: CameraMode,
+    /// Scripted camera path that replaces fly-camera control, if set.
+    camera_path: Option<CameraPath>,
     /// Input manager for keyboard and mouse.
     input: InputManager,
     /// Screenshot configuration.
@@ -119,14 +263,24 @@ pub struct Viewer {
     should_exit: bool,
     /// Current debug visualization mode.
     debug_mode: DebugMode,
-    /// Day/night phase in [0.0, 1.0).
-    day_phase: f32,
+    /// Day/night cycle driving the sun and sky.
+    time_of_day: TimeOfDay,
+    /// Sky colors easing toward the biome under the camera.
+    sky: SkyBlend,
+    /// Seconds of animation time, for leaf wind.
+    animation_time: f32,
     /// Runtime ray march step limit (debug-tunable).
     max_steps: u32,
+    /// Adjusts `max_steps` toward the target frame time, when enabled.
+    step_tuner: Option<MaxStepsTuner>,
+    /// Bytes of clipmap data uploaded per frame, when limited.
+    upload_budget: Option<UploadBudget>,
     /// Debug toggle to skip compute ray marching entirely.
     debug_skip_ray_march: bool,
     /// Debug toggle to disable secondary shadow rays in the shader.
     debug_disable_shadows: bool,
+    /// Last present mode requested with F5 (may differ from the one in use).
+    present_mode: PresentMode,
 }
 
 impl VoxelApp for Viewer {
@@ -162,9 +316,24 @@ impl VoxelApp for Viewer {
         let generator = TerrainGenerator::new(terrain_config);
 
         // Create clipmap streaming controller and renderer
-        let mut clipmap = ClipmapStreamingController::new(generator);
+        let mut clipmap = ClipmapStreamingController::new(generator)
+            .with_initial_page_radius(INITIAL_PAGE_RADIUS);
         let frames_in_flight = ctx.frames_in_flight();
         let mut clipmap_renderer = ClipmapRenderer::new(frames_in_flight);
+        clipmap_renderer
+            .set_lod_step_scaling(clipmap_params.base_steps, clipmap_params.lod_step_falloff);
+        clipmap_renderer.set_fog(FogSettings {
+            start: 0.0,
+            density: clipmap_params.fog_density,
+            affects_sky: clipmap_params.fog_affects_sky,
+        });
+        clipmap_renderer.set_wind(WindSettings {
+            strength: clipmap_params.wind_strength,
+            speed: clipmap_params.wind_speed,
+        });
+        clipmap_renderer
+            .set_lod_transition_distances(&clipmap_params.lod_transitions)
+            .context("Invalid --lod-transitions")?;
         info!(
             "LOD distance pages per axis: {}",
             clipmap.visible_page_grid()
@@ -181,12 +350,13 @@ impl VoxelApp for Viewer {
         // Create rendering pipeline with frames_in_flight for per-frame buffers
         let pipeline = unsafe {
             let mut allocator = ctx.gpu.allocator().lock();
-            ClipmapRayMarchPipeline::new(
+            ClipmapRayMarchPipeline::new_with_workgroup(
                 ctx.gpu.device(),
                 &mut allocator,
                 ctx.width(),
                 ctx.height(),
                 frames_in_flight,
+                WorkgroupSize::for_vendor(ctx.gpu.capabilities().vendor),
             )?
         };
 
@@ -195,20 +365,33 @@ impl VoxelApp for Viewer {
         // Set up camera - start in the air above terrain
         let start_pos = Vec3::new(64.0, 120.0, 64.0);
         let look_at = Vec3::new(64.0, 64.0, 64.0);
-        let camera = Camera::new(
+        let mut camera = Camera::new(
             start_pos,
             look_at,
             Vec3::Y,
-            60.0_f32.to_radians(),
+            clipmap_params.fov_degrees.to_radians(),
             ctx.aspect_ratio(),
             0.1,
             1000.0,
         );
+        camera.set_fov_mode(clipmap_params.fov_mode);
+        let camera_path = match &clipmap_params.camera_path {
+            Some(path) => {
+                let camera_path = CameraPath::load(path)
+                    .with_context(|| format!("loading camera path {}", path.display()))?;
+                info!(
+                    "Camera path: {} keyframes from {}",
+                    camera_path.keyframes().len(),
+                    path.display()
+                );
+                camera_path.apply(&mut camera, 0);
+                Some(camera_path)
+            }
+            None => None,
+        };
+        let start_pos = camera.position;
 
-        // Calculate initial yaw and pitch from camera direction
-        let dir = (look_at - start_pos).normalize();
-        let camera_yaw = dir.x.atan2(dir.z);
-        let camera_pitch = (-dir.y).asin();
+        let fly_cam = FlyCamController::new(&camera);
 
         // Set up input manager with action bindings
         let actions = ActionMap::builder()
@@ -228,17 +411,25 @@ impl VoxelApp for Viewer {
             .bind("toggle_cursor", KeyCode::Escape)
             .bind("debug_cycle", KeyCode::F3)
             .bind("toggle_lod", KeyCode::F4)
+            .bind("cycle_present_mode", KeyCode::F5)
+            .bind("write_diagnostics", KeyCode::F6)
+            .bind("toggle_render_stats", KeyCode::F7)
+            .bind("cycle_camera_mode", KeyCode::F8)
             .bind("lod_distance_increase", KeyCode::PageUp)
             .bind("lod_distance_decrease", KeyCode::PageDown)
             .bind("destroy_block", MouseButton::Left)
             .build();
         let mut input = InputManager::with_actions(actions);
+        input.set_look_sensitivity(MOUSE_SENSITIVITY);
+        input.set_mouse_smoothing(clipmap_params.mouse_smoothing);
 
         // Start with cursor locked for FPS controls
         input.set_cursor_mode(CursorMode::Locked);
         apply_cursor_mode(&ctx.window, CursorMode::Locked);
 
         // Initialize clipmap around the starting camera position.
+        let initial_pages = clipmap.generate_initial_pages(start_pos);
+        info!("Generated {initial_pages} initial clipmap pages");
         clipmap.update(start_pos);
         let dirty = clipmap.take_dirty_state();
         {
@@ -251,6 +442,7 @@ impl VoxelApp for Viewer {
                 dirty,
                 0,
                 initial_frame_number,
+                None,
             )?;
         }
 
@@ -259,6 +451,10 @@ impl VoxelApp for Viewer {
         info!("Viewer initialized successfully!");
 
         let max_steps = clipmap_params.max_steps;
+        let step_tuner = clipmap_params.target_frame_ms.map(|target| {
+            info!("Auto-tuning max steps for {target} ms frames");
+            MaxStepsTuner::new(target, AUTO_TUNE_MIN_STEPS.min(max_steps), max_steps)
+        });
         let debug_skip_ray_march = clipmap_params.debug_skip_ray_march;
         let debug_disable_shadows = clipmap_params.debug_disable_shadows;
 
@@ -267,16 +463,24 @@ impl VoxelApp for Viewer {
             clipmap_renderer,
             pipeline: Some(pipeline),
             camera,
-            camera_yaw,
-            camera_pitch,
+            fly_cam,
+            camera_mode: CameraMode::default(),
+            camera_path,
             input,
             screenshot_config,
             should_exit: false,
             debug_mode: DebugMode::default(),
-            day_phase: 0.25,
+            time_of_day: TimeOfDay::new(12.0),
+            sky: SkyBlend::default(),
+            animation_time: 0.0,
             max_steps,
+            step_tuner,
+            upload_budget: clipmap_params
+                .upload_budget_kb
+                .map(|kb| UploadBudget::new(kb * 1024)),
             debug_skip_ray_march,
             debug_disable_shadows,
+            present_mode: ctx.present_mode(),
         })
     }
 
@@ -318,6 +522,58 @@ impl VoxelApp for Viewer {
             }
         }
 
+        if self.input.is_action_just_pressed("cycle_present_mode") {
+            // Cycle through requests so unsupported modes (which fall back
+            // to FIFO) don't trap the toggle.
+            self.present_mode = match self.present_mode {
+                PresentMode::Fifo => PresentMode::Mailbox,
+                PresentMode::Mailbox => PresentMode::Immediate,
+                PresentMode::Immediate => PresentMode::Fifo,
+            };
+            ctx.set_present_mode(self.present_mode);
+            info!("Present mode: requested {:?}", self.present_mode);
+        }
+
+        if self.input.is_action_just_pressed("write_diagnostics") {
+            match self.write_diagnostics() {
+                Ok(path) => info!("Wrote diagnostics to {}", path.display()),
+                Err(e) => error!("Failed to write diagnostics: {e:#}"),
+            }
+        }
+
+        if self.input.is_action_just_pressed("toggle_render_stats") {
+            if let Some(pipeline) = &mut self.pipeline {
+                let enabled = !pipeline.render_stats_enabled();
+                pipeline.set_render_stats_enabled(enabled);
+                info!("Render stats: {}", if enabled { "on" } else { "off" });
+            }
+        }
+        if let Some(stats) = self
+            .pipeline
+            .as_ref()
+            .and_then(ClipmapRayMarchPipeline::take_render_stats)
+        {
+            if ctx.frame_count % RENDER_STATS_LOG_INTERVAL == 0 {
+                info!(
+                    "Render stats: {} rays, {:.1}% hits, {:.1} steps/ray",
+                    stats.rays,
+                    stats.hit_rate() * 100.0,
+                    stats.steps_per_ray()
+                );
+            }
+        }
+
+        if self.input.is_action_just_pressed("cycle_camera_mode") {
+            self.camera_mode = if self.camera_mode.is_orbit() {
+                // Pick up free flight looking where the orbit left off.
+                self.fly_cam = FlyCamController::new(&self.camera);
+                CameraMode::FreeFly
+            } else {
+                CameraMode::orbit_in_front(&self.camera, ORBIT_RADIUS, ORBIT_SPEED)
+            };
+            info!("Camera mode: {:?}", self.camera_mode);
+        }
+
         if self.input.is_action_just_pressed("lod_distance_increase") {
             let target = self
                 .clipmap
@@ -344,64 +600,21 @@ impl VoxelApp for Viewer {
             }
         }
 
-        // Camera rotation from mouse (only when cursor is locked)
-        if self.input.cursor_mode() == CursorMode::Locked {
-            let mouse_delta = self.input.mouse_raw_delta();
-            self.camera_yaw -= mouse_delta.x * MOUSE_SENSITIVITY;
-            self.camera_pitch += mouse_delta.y * MOUSE_SENSITIVITY;
-
-            // Clamp pitch to prevent flipping
-            self.camera_pitch = self.camera_pitch.clamp(
-                -std::f32::consts::FRAC_PI_2 + 0.01,
-                std::f32::consts::FRAC_PI_2 - 0.01,
-            );
-        }
-
-        // Update camera direction from yaw/pitch
-        let direction = Vec3::new(
-            self.camera_pitch.cos() * self.camera_yaw.sin(),
-            -self.camera_pitch.sin(),
-            self.camera_pitch.cos() * self.camera_yaw.cos(),
-        )
-        .normalize();
-        self.camera.direction = direction;
-
-        // Calculate movement vectors
-        let forward = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
-        let right = forward.cross(Vec3::Y).normalize_or_zero();
-
-        // Calculate movement speed
-        let speed = if self.input.is_action_pressed("sprint") {
-            CAMERA_SPEED * CAMERA_SPRINT_MULT
+        // Mouse look (only when cursor is locked) and movement, unless a
+        // scripted path or the orbit drives the camera.
+        if let Some(path) = &self.camera_path {
+            path.apply(&mut self.camera, ctx.frame_count);
+        } else if self.camera_mode.is_orbit() {
+            let before = self.camera.position;
+            self.camera_mode.update(&mut self.camera, dt);
+            if dt > 0.0 {
+                self.clipmap
+                    .set_camera_velocity((self.camera.position - before) / dt);
+            }
         } else {
-            CAMERA_SPEED
-        };
-
-        // Accumulate movement direction
-        let mut movement = Vec3::ZERO;
-        if self.input.is_action_pressed("move_forward") {
-            movement += forward;
-        }
-        if self.input.is_action_pressed("move_back") {
-            movement -= forward;
-        }
-        if self.input.is_action_pressed("move_right") {
-            movement += right;
-        }
-        if self.input.is_action_pressed("move_left") {
-            movement -= right;
-        }
-        if self.input.is_action_pressed("move_up") {
-            movement += Vec3::Y;
-        }
-        if self.input.is_action_pressed("move_down") {
-            movement -= Vec3::Y;
-        }
-
-        // Normalize and apply movement
-        if movement != Vec3::ZERO {
-            movement = movement.normalize() * speed * dt;
-            self.camera.position += movement;
+            self.fly_cam
+                .update(&mut self.camera, &FlyCamInput::from_input(&self.input), dt);
+            self.clipmap.set_camera_velocity(self.fly_cam.velocity());
         }
 
         // Destroy block at crosshair (left mouse).
@@ -411,11 +624,28 @@ impl VoxelApp for Viewer {
             self.try_destroy_aimed_block();
         }
 
+        // Trade ray march quality for frame time.
+        if let Some(tuner) = &mut self.step_tuner {
+            #[cfg(feature = "profiling")]
+            let frame_time_ms = voxelicous_profiler::snapshot().frame_time_ms;
+            #[cfg(not(feature = "profiling"))]
+            let frame_time_ms = dt * 1000.0;
+            self.max_steps = tuner.update(frame_time_ms);
+        }
+
         // End input frame (must be called at end of update)
         self.input.end_frame();
 
         // Advance day/night cycle.
-        self.day_phase = (self.day_phase + dt / DAY_NIGHT_CYCLE_SECONDS).fract();
+        self.time_of_day
+            .advance(dt / DAY_NIGHT_CYCLE_SECONDS * HOURS_PER_DAY);
+
+        // Ease the sky toward the local biome's colors.
+        let (_, biome_sky) = SkyConfig::at_camera(self.clipmap.generator(), self.camera.position);
+        self.sky.set_sky(biome_sky);
+        self.sky.update(dt);
+        self.animation_time += dt;
+        self.time_of_day.set_day_sky(*self.sky.current());
 
         // Update clipmap around the camera position
         self.clipmap.update(self.camera.position);
@@ -429,9 +659,13 @@ impl VoxelApp for Viewer {
                 pending_page_builds: 0,
                 pages_building: 0,
                 resident_pages: 0,
-                gpu_pages: 0,
+                gpu_pages: self.clipmap_renderer.gpu_page_positions(0).count() as u32,
             };
             voxelicous_profiler::report_queue_sizes(queues);
+            voxelicous_profiler::report_memory(MemoryStats {
+                gpu_memory_bytes: self.clipmap_renderer.gpu_memory_breakdown().total,
+                clipmap_memory_bytes: 0,
+            });
         }
     }
 
@@ -443,7 +677,9 @@ impl VoxelApp for Viewer {
         let frame_index = frame.frame_index;
         let frame_number = frame.frame_number;
         let capturing = self.screenshot_config.should_capture(frame_number);
-        let mut camera_uniforms = self.camera.uniforms_with_day_phase(self.day_phase);
+        let mut camera_uniforms = self.camera.uniforms();
+        self.time_of_day.apply(&mut camera_uniforms);
+        camera_uniforms.set_time(self.animation_time);
         if self.debug_disable_shadows {
             camera_uniforms.day_night[1] = 1.0;
         }
@@ -484,19 +720,26 @@ impl VoxelApp for Viewer {
         unsafe {
             let mut allocator = ctx.gpu.allocator().lock();
 
-            // Destroy old pipeline
+            // Destroy old pipeline, keeping its output format and stats setting
+            let mut output_format = OutputFormat::default();
+            let mut render_stats = false;
             if let Some(old_pipeline) = self.pipeline.take() {
+                output_format = old_pipeline.output_format().supported_or_rgba8(&ctx.gpu);
+                render_stats = old_pipeline.render_stats_enabled();
                 old_pipeline.destroy(ctx.gpu.device(), &mut allocator)?;
             }
 
             // Create new pipeline with frames_in_flight
-            let new_pipeline = ClipmapRayMarchPipeline::new(
+            let mut new_pipeline = ClipmapRayMarchPipeline::new_with_format(
                 ctx.gpu.device(),
                 &mut allocator,
                 width,
                 height,
                 ctx.frames_in_flight(),
+                WorkgroupSize::for_vendor(ctx.gpu.capabilities().vendor),
+                output_format,
             )?;
+            new_pipeline.set_render_stats_enabled(render_stats);
             self.pipeline = Some(new_pipeline);
         }
 
@@ -518,6 +761,10 @@ impl VoxelApp for Viewer {
     }
 
     fn cleanup(&mut self, ctx: &mut AppContext) {
+        // Flush deferred frees before tearing down the renderers they belong to
+        if let Err(e) = ctx.gpu.drain_all_deferred() {
+            error!("Failed to drain deferred deletions: {e}");
+        }
         let mut allocator = ctx.gpu.allocator().lock();
 
         // Destroy clipmap renderer (frees all clipmap GPU resources)
@@ -540,6 +787,18 @@ impl VoxelApp for Viewer {
 }
 
 impl Viewer {
+    /// Dump a diagnostic snapshot of the world to a timestamped JSON file in
+    /// the working directory.
+    fn write_diagnostics(&self) -> anyhow::Result<PathBuf> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = PathBuf::from(format!("voxelicous-diagnostics-{timestamp}.json"));
+        let json = self.clipmap.diagnostic_snapshot().to_json()?;
+        std::fs::write(&path, json).with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+
     fn try_destroy_aimed_block(&mut self) {
         let origin = self.camera.position;
         let direction = self.camera.direction;
@@ -587,6 +846,9 @@ impl Viewer {
         {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.sync_from_controller").entered();
+            if let Some(budget) = &mut self.upload_budget {
+                budget.begin_frame();
+            }
             self.clipmap_renderer.sync_from_controller(
                 &mut allocator,
                 device,
@@ -594,6 +856,7 @@ impl Viewer {
                 dirty,
                 frame_index,
                 frame_number,
+                self.upload_budget.as_mut(),
             )?;
         }
 
@@ -899,10 +1162,11 @@ impl Viewer {
         let pipeline = self.pipeline.as_ref().expect("Pipeline should exist");
         let (width, height) = pipeline.dimensions();
 
-        match pipeline.read_output() {
+        match pipeline.read_output_raw() {
             Ok(data) => {
                 let output_path = self.screenshot_config.output_path(frame_number);
-                if let Err(e) = save_screenshot(data, width, height, &output_path) {
+                let format = pipeline.output_format();
+                if let Err(e) = save_screenshot(data, format, width, height, &output_path) {
                     error!("Failed to save screenshot: {e}");
                 }
             }
@@ -15,16 +15,31 @@
 //! - `-o, --output <PATTERN>`: Output path pattern (use `{}` for frame number)
 //! - `-f, --frames <FRAMES>`: Frame indices to capture (e.g., "0,10,20" or "0-5")
 //! - `--exit-after`: Exit after capturing all specified frames
+//! - `--camera-path <FILE>`: Drive the camera along scripted keyframes
+//!   (`frame px py pz tx ty tz` per line) instead of input
 //!
 //! ### World options
 //! - `--seed <N>`: World generation seed (default: 42)
 //! - `--max-steps <N>`: Ray march step limit (default: 1024)
+//! - `--base-steps <N>`: Finest-LOD step budget (default: max steps)
+//! - `--lod-step-falloff <F>`: Step budget falloff per LOD voxel size (default: 0)
+//! - `--target-frame-ms <MS>`: Auto-tune the step limit (up to `--max-steps`) to hold this frame time
+//! - `--upload-budget-kb <KB>`: Limit clipmap GPU uploads per frame (default: unlimited)
+//! - `--fog-density <D>`: Exponential distance fog density per block (default: 0, off)
+//! - `--fog-affects-sky`: Fade the sky into the fog near the horizon
+//! - `--wind-strength <S>`: Leaf sway amount, 0-1 (default: 0.3)
+//! - `--wind-speed <S>`: Leaf sway speed in radians per second (default: 1.5)
+//! - `--lod-transitions <D,...>`: Distance where each LOD hands over to the next, finest first (default: LOD coverage)
 //!
 //! ### Debug options
 //! - `--debug-skip-raymarch`: Skip compute ray march pass and clear/present only
 //! - `--debug-disable-shadows`: Disable secondary shadow rays in shader
 //!
 //! ### Other
+//! - `--mouse-smoothing <F>`: Camera look smoothing, 0-0.99 (default: 0, off)
+//! - `--fov <DEG>`: Field of view in degrees (default: 60)
+//! - `--fov-mode <MODE>`: Angle `--fov` sets: `vertical`, `horizontal` or `hor4x3` (default: vertical)
+//! - `--log-file <PATH>`: Also write logs to PATH (rolls over to `PATH.1`)
 //! - `-h, --help`: Print help message
 //!
 //! ## Examples
@@ -41,6 +56,9 @@
 //!
 //! # Capture frames during orbit and exit
 //! cargo run -p voxelicous-viewer -- -S -f 0,50,100,150,200 -o stream_{}.png --exit-after
+//!
+//! # Capture a scripted fly-through
+//! cargo run -p voxelicous-viewer -- -S -f 0-120 -o path_{}.png --exit-after --camera-path path.txt
 //! ```
 //!
 //! ## Environment Variables
@@ -70,6 +88,11 @@ fn main() -> anyhow::Result<()> {
     let config = config;
     #[cfg(not(feature = "profiling-tracy"))]
     let config = config.with_target_fps(TARGET_FPS);
+    let args: Vec<String> = std::env::args().collect();
+    let config = match args.iter().position(|arg| arg == "--log-file") {
+        Some(i) if i + 1 < args.len() => config.with_log_file(&args[i + 1]),
+        _ => config,
+    };
 
     run_app::<Viewer>(config)
 }
@@ -89,16 +112,38 @@ SCREENSHOT OPTIONS:
                             Examples: \"0\" \"0,10,20\" \"0-5\" \"0,5-10,20\"
                             Default: 0
     --exit-after            Exit after capturing all specified frames
+    --camera-path <FILE>    Drive the camera along keyframes in FILE, one
+                            `frame px py pz tx ty tz` per line
 
 WORLD OPTIONS:
     --seed <N>              World generation seed (default: 42)
     --max-steps <N>         Ray march step limit (default: 1024)
+    --base-steps <N>        Finest-LOD step budget (default: max steps)
+    --lod-step-falloff <F>  Step budget falloff per LOD voxel size (default: 0)
+    --target-frame-ms <MS>  Auto-tune the step limit (up to --max-steps) to
+                            hold this frame time (default: off)
+    --upload-budget-kb <KB> Limit clipmap GPU uploads per frame
+                            (default: unlimited)
+    --fog-density <D>       Exponential distance fog density per block
+                            (default: 0, off)
+    --fog-affects-sky       Fade the sky into the fog near the horizon
+    --wind-strength <S>     Leaf sway amount, 0-1 (default: 0.3, 0 = off)
+    --wind-speed <S>        Leaf sway speed in radians per second
+                            (default: 1.5)
+    --lod-transitions <D,...>
+                            Distance where each LOD hands over to the next,
+                            finest first (default: LOD coverage)
 
 DEBUG OPTIONS:
     --debug-skip-raymarch      Skip compute ray march; clear+present only
     --debug-disable-shadows    Disable secondary shadow rays in shader
 
 OTHER:
+    --mouse-smoothing <F>   Camera look smoothing, 0-0.99 (default: 0, off)
+    --fov <DEG>             Field of view in degrees (default: 60)
+    --fov-mode <MODE>       Angle --fov sets: vertical, horizontal or hor4x3
+                            (default: vertical)
+    --log-file <PATH>       Also write logs to PATH (rolls over to PATH.1)
     -h, --help              Print this help message
 
 EXAMPLES:
@@ -111,6 +156,9 @@ EXAMPLES:
     # Capture frames during orbit
     cargo run -p voxelicous-viewer -- -S -f 0,50,100,150,200 -o stream_{{}}.png --exit-after
 
+    # Capture a scripted fly-through
+    cargo run -p voxelicous-viewer -- -S -f 0-120 -o path_{{}}.png --exit-after --camera-path path.txt
+
 ENVIRONMENT VARIABLES:
     RUST_LOG                Set log level (e.g., info, debug, trace)"
     );
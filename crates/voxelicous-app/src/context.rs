@@ -1,12 +1,14 @@
 //! Application context.
 
+use std::cell::Cell;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use ash::vk;
 use voxelicous_gpu::swapchain::Swapchain;
 use voxelicous_gpu::sync::{create_fence, create_semaphore, wait_for_fence};
-use voxelicous_gpu::{GpuContext, SurfaceContext};
+use voxelicous_gpu::{GpuContext, PresentMode, SurfaceContext};
+use winit::dpi::{LogicalSize, PhysicalSize};
 use winit::window::Window;
 
 /// Application context shared across all app methods.
@@ -36,6 +38,32 @@ pub struct AppContext {
     pub(crate) last_frame_time: Instant,
     /// Whether vsync is enabled.
     pub vsync: bool,
+    /// Present mode requested by the app, applied at the next frame.
+    pub(crate) present_mode_request: Cell<Option<PresentMode>>,
+    /// Frame pacing interval (`None` when uncapped).
+    pub(crate) target_frame_time: Option<Duration>,
+}
+
+/// Swapchain and frame settings an [`AppContext`] is created with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ContextSettings {
+    /// Prefer a vsynced present mode.
+    pub vsync: bool,
+    /// Frames in flight, also requested as the swapchain image count
+    /// (`None` uses one frame per swapchain image).
+    pub frames_in_flight: Option<usize>,
+}
+
+impl ContextSettings {
+    /// Swapchain image count to request from the surface.
+    pub fn requested_image_count(self) -> Option<u32> {
+        self.frames_in_flight.and_then(|n| u32::try_from(n).ok())
+    }
+
+    /// Frames in flight for a swapchain with `image_count` images.
+    pub fn frames_for(self, image_count: usize) -> usize {
+        self.frames_in_flight.unwrap_or(image_count)
+    }
 }
 
 /// Per-frame synchronization primitives.
@@ -56,11 +84,14 @@ impl AppContext {
     pub(crate) unsafe fn new(
         window: Arc<Window>,
         gpu: GpuContext,
-        vsync: bool,
+        settings: ContextSettings,
     ) -> anyhow::Result<Self> {
+        let vsync = settings.vsync;
+
         // Create surface
         // SAFETY: Caller guarantees window has valid handles
-        let surface = unsafe { SurfaceContext::from_window(&gpu, window.as_ref())? };
+        let mut surface = unsafe { SurfaceContext::from_window(&gpu, window.as_ref())? };
+        surface.set_requested_image_count(settings.requested_image_count());
 
         // Get window size
         let size = window.inner_size();
@@ -85,8 +116,9 @@ impl AppContext {
         // SAFETY: Device is valid
         let command_pool = unsafe { gpu.device().create_command_pool(&pool_info, None)? };
 
-        // Create per-frame sync data (match swapchain image count)
-        let frames_in_flight = swapchain.images.len();
+        // Create per-frame sync data (default: match swapchain image count)
+        let frames_in_flight = settings.frames_for(swapchain.images.len());
+        gpu.set_deferred_frames_in_flight(frames_in_flight);
         let mut frames = Vec::with_capacity(frames_in_flight);
         for _ in 0..frames_in_flight {
             let alloc_info = vk::CommandBufferAllocateInfo::default()
@@ -106,11 +138,14 @@ impl AppContext {
         }
 
         // Create per-swapchain-image render finished semaphores
-        let mut render_finished_semaphores = Vec::with_capacity(swapchain.images.len());
-        for _ in 0..swapchain.images.len() {
+        let mut render_finished_semaphores = Vec::new();
+        rebuild_per_image(
+            &mut render_finished_semaphores,
+            swapchain.images.len(),
+            |_| {},
             // SAFETY: Device is valid
-            render_finished_semaphores.push(unsafe { create_semaphore(gpu.device())? });
-        }
+            || unsafe { create_semaphore(gpu.device()) },
+        )?;
 
         Ok(Self {
             window,
@@ -124,6 +159,8 @@ impl AppContext {
             frame_count: 0,
             last_frame_time: Instant::now(),
             vsync,
+            present_mode_request: Cell::new(None),
+            target_frame_time: None,
         })
     }
 
@@ -132,21 +169,57 @@ impl AppContext {
         self.swapchain.extent
     }
 
-    /// Get the swapchain width.
+    /// Get the swapchain width in physical pixels.
     pub fn width(&self) -> u32 {
         self.swapchain.extent.width
     }
 
-    /// Get the swapchain height.
+    /// Get the swapchain height in physical pixels.
     pub fn height(&self) -> u32 {
         self.swapchain.extent.height
     }
 
+    /// Get the window's DPI scale factor (physical pixels per logical pixel).
+    pub fn scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+
+    /// Get the swapchain size in logical pixels (physical size / scale factor).
+    pub fn logical_size(&self) -> LogicalSize<f64> {
+        physical_to_logical(self.swapchain.extent, self.scale_factor())
+    }
+
     /// Get the aspect ratio (width / height).
     pub fn aspect_ratio(&self) -> f32 {
         self.swapchain.extent.width as f32 / self.swapchain.extent.height as f32
     }
 
+    /// Set the frame rate cap enforced by the runner (0 = uncapped).
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.target_frame_time = target_frame_time(fps);
+    }
+
+    /// Get the current frame rate cap (0 when uncapped).
+    pub fn target_fps(&self) -> u32 {
+        self.target_frame_time.map_or(0, |interval| {
+            u32::try_from(1_000_000_000 / interval.as_nanos().max(1)).unwrap_or(u32::MAX)
+        })
+    }
+
+    /// Request a swapchain present mode, e.g. to toggle vsync for benchmarking.
+    ///
+    /// The swapchain is recreated before the next frame is acquired. Modes
+    /// the surface does not support fall back to [`PresentMode::Fifo`]; check
+    /// [`Self::present_mode`] afterwards for the mode in use.
+    pub fn set_present_mode(&self, mode: PresentMode) {
+        self.present_mode_request.set(Some(mode));
+    }
+
+    /// Present mode of the current swapchain.
+    pub fn present_mode(&self) -> PresentMode {
+        PresentMode::from_vk(self.swapchain.present_mode).unwrap_or_default()
+    }
+
     /// Get the number of frames in flight.
     pub fn frames_in_flight(&self) -> usize {
         self.frames.len()
@@ -171,6 +244,43 @@ impl AppContext {
         &mut self,
         width: u32,
         height: u32,
+    ) -> anyhow::Result<()> {
+        // SAFETY: Caller guarantees GPU is idle
+        unsafe { self.recreate_swapchain_with_present_mode(width, height, self.present_mode()) }
+    }
+
+    /// Apply a pending [`Self::set_present_mode`] request.
+    ///
+    /// Returns `true` when the swapchain was recreated.
+    ///
+    /// # Safety
+    /// The GPU must be idle.
+    pub(crate) unsafe fn apply_present_mode_request(&mut self) -> anyhow::Result<bool> {
+        let Some(mode) = self.present_mode_request.take() else {
+            return Ok(false);
+        };
+        if mode == self.present_mode() {
+            return Ok(false);
+        }
+        let extent = self.swapchain.extent;
+        // SAFETY: Caller guarantees GPU is idle
+        unsafe { self.recreate_swapchain_with_present_mode(extent.width, extent.height, mode)? };
+        if self.present_mode() != mode {
+            tracing::warn!(
+                "Present mode {mode:?} not supported, using {:?}",
+                self.present_mode()
+            );
+        }
+        Ok(true)
+    }
+
+    /// # Safety
+    /// The GPU must be idle.
+    unsafe fn recreate_swapchain_with_present_mode(
+        &mut self,
+        width: u32,
+        height: u32,
+        present_mode: PresentMode,
     ) -> anyhow::Result<()> {
         // Destroy old swapchain
         // SAFETY: Caller guarantees GPU is idle
@@ -182,14 +292,33 @@ impl AppContext {
         // Create new swapchain
         // SAFETY: GPU context and surface are valid
         self.swapchain = unsafe {
-            self.surface
-                .create_swapchain(&self.gpu, width, height, self.vsync, None)?
+            self.surface.create_swapchain_with_present_mode(
+                &self.gpu,
+                width,
+                height,
+                present_mode,
+                None,
+            )?
         };
+        self.vsync = self.present_mode() == PresentMode::Fifo;
+
+        // The new swapchain may have a different image count.
+        let device = self.gpu.device();
+        rebuild_per_image(
+            &mut self.render_finished_semaphores,
+            self.swapchain.images.len(),
+            // SAFETY: Caller guarantees GPU is idle, so no submission waits
+            // on the old semaphores
+            |sem| unsafe { device.destroy_semaphore(sem, None) },
+            // SAFETY: Device is valid
+            || unsafe { create_semaphore(device) },
+        )?;
 
         tracing::info!(
-            "Swapchain recreated: {}x{}",
+            "Swapchain recreated: {}x{} ({} images)",
             self.swapchain.extent.width,
-            self.swapchain.extent.height
+            self.swapchain.extent.height,
+            self.swapchain.images.len()
         );
 
         Ok(())
@@ -227,3 +356,118 @@ impl AppContext {
         }
     }
 }
+
+/// Replace every entry of `items` with `count` freshly created ones.
+///
+/// Per-swapchain-image objects are indexed by image index, so they are
+/// rebuilt whenever the swapchain is, whatever its new image count.
+fn rebuild_per_image<T, E>(
+    items: &mut Vec<T>,
+    count: usize,
+    mut destroy: impl FnMut(T),
+    mut create: impl FnMut() -> Result<T, E>,
+) -> Result<(), E> {
+    for item in items.drain(..) {
+        destroy(item);
+    }
+    items.reserve(count);
+    for _ in 0..count {
+        items.push(create()?);
+    }
+    Ok(())
+}
+
+fn target_frame_time(fps: u32) -> Option<Duration> {
+    (fps > 0).then(|| Duration::from_nanos(1_000_000_000 / u64::from(fps)))
+}
+
+/// How long to wait after `elapsed` of work to hold the `target` interval.
+///
+/// Zero when uncapped or already behind schedule.
+pub fn frame_pacing_delay(target: Option<Duration>, elapsed: Duration) -> Duration {
+    target.map_or(Duration::ZERO, |target| target.saturating_sub(elapsed))
+}
+
+fn physical_to_logical(extent: vk::Extent2D, scale_factor: f64) -> LogicalSize<f64> {
+    PhysicalSize::new(extent.width, extent.height).to_logical(scale_factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_logical_conversion() {
+        let extent = vk::Extent2D {
+            width: 2560,
+            height: 1440,
+        };
+        let logical = physical_to_logical(extent, 2.0);
+        assert_eq!(logical, LogicalSize::new(1280.0, 720.0));
+
+        let physical: PhysicalSize<u32> = logical.to_physical(2.0);
+        assert_eq!(physical, PhysicalSize::new(2560, 1440));
+
+        let logical = physical_to_logical(extent, 1.5);
+        assert!((logical.width - 2560.0 / 1.5).abs() < 1e-9);
+        assert!((logical.height - 960.0).abs() < 1e-9);
+        assert_eq!(
+            physical_to_logical(extent, 1.0),
+            LogicalSize::new(2560.0, 1440.0)
+        );
+    }
+
+    #[test]
+    fn frame_pacing_delay_holds_target_interval() {
+        let target = target_frame_time(60);
+        assert_eq!(target, Some(Duration::from_nanos(16_666_666)));
+        assert_eq!(target_frame_time(0), None);
+
+        assert_eq!(
+            frame_pacing_delay(target, Duration::from_millis(10)),
+            Duration::from_nanos(6_666_666)
+        );
+        assert_eq!(frame_pacing_delay(target, Duration::ZERO), target.unwrap());
+        // Behind schedule: no sleep.
+        assert_eq!(
+            frame_pacing_delay(target, Duration::from_millis(20)),
+            Duration::ZERO
+        );
+        assert_eq!(frame_pacing_delay(target, target.unwrap()), Duration::ZERO);
+        // Uncapped never sleeps.
+        assert_eq!(
+            frame_pacing_delay(None, Duration::from_millis(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn per_image_objects_follow_the_image_count() {
+        let mut next = 0;
+        let mut destroyed = Vec::new();
+        let mut semaphores = Vec::new();
+        let mut rebuild = |semaphores: &mut Vec<u32>, count| {
+            rebuild_per_image(
+                semaphores,
+                count,
+                |sem| destroyed.push(sem),
+                || {
+                    next += 1;
+                    Ok::<_, ()>(next)
+                },
+            )
+            .unwrap();
+        };
+
+        rebuild(&mut semaphores, 3);
+        assert_eq!(semaphores, [1, 2, 3]);
+        // More images: every index the runner can acquire has a semaphore.
+        rebuild(&mut semaphores, 5);
+        assert_eq!(semaphores, [4, 5, 6, 7, 8]);
+        // Fewer images: no stale entries are left behind.
+        rebuild(&mut semaphores, 2);
+        assert_eq!(semaphores, [9, 10]);
+        drop(rebuild);
+        assert_eq!(destroyed, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
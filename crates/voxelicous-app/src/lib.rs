@@ -40,6 +40,7 @@
 mod app;
 mod context;
 mod frame;
+mod log_file;
 mod runner;
 
 pub use app::VoxelApp;
@@ -48,6 +49,6 @@ pub use frame::FrameContext;
 pub use runner::{run_app, AppConfig};
 
 // Re-export commonly used types for convenience
-pub use voxelicous_gpu::{GpuContext, GpuContextBuilder};
+pub use voxelicous_gpu::{GpuContext, GpuContextBuilder, PresentMode};
 pub use voxelicous_render::Camera;
 pub use winit::event::{DeviceEvent, DeviceId, WindowEvent};
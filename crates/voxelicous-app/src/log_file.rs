@@ -0,0 +1,99 @@
+//! Size-capped log file for the app's tracing output.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Log file that rolls over to `<path>.1` once it reaches a size cap.
+///
+/// Only one previous file is kept, so the logs on disk never exceed twice
+/// the cap.
+#[derive(Debug)]
+pub struct RollingLogFile {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    written: u64,
+}
+
+impl RollingLogFile {
+    /// Open (appending to) the log at `path`, rolling it over whenever it
+    /// grows past `max_bytes`.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes: max_bytes.max(1),
+            file,
+            written,
+        })
+    }
+
+    /// Path of the previous log file.
+    pub fn rolled_path(path: &Path) -> PathBuf {
+        let mut rolled = path.as_os_str().to_owned();
+        rolled.push(".1");
+        PathBuf::from(rolled)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        fs::rename(&self.path, Self::rolled_path(&self.path))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingLogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.roll()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_rolls_over_at_the_size_cap() {
+        let dir = std::env::temp_dir().join(format!("voxelicous-log-{}", std::process::id()));
+        let path = dir.join("app.log");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut log = RollingLogFile::open(&path, 16).unwrap();
+        log.write_all(b"first line\n").unwrap();
+        log.write_all(b"second line\n").unwrap();
+        log.flush().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second line\n");
+        let rolled = RollingLogFile::rolled_path(&path);
+        assert_eq!(fs::read(&rolled).unwrap(), b"first line\n");
+
+        // Reopening appends and keeps counting from the existing size.
+        drop(log);
+        let mut log = RollingLogFile::open(&path, 16).unwrap();
+        log.write_all(b"third\n").unwrap();
+        log.flush().unwrap();
+        assert_eq!(fs::read(&rolled).unwrap(), b"second line\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,46 +1,66 @@
 //! Application runner and event loop.
 
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use ash::vk;
 use tracing::{error, info};
-use tracing_subscriber::EnvFilter;
-#[cfg(feature = "profiling-tracy")]
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use voxelicous_gpu::command::submit_command_buffers;
 use voxelicous_gpu::error::GpuError;
 use voxelicous_gpu::sync::{reset_fence, wait_for_fence};
 use voxelicous_gpu::GpuContextBuilder;
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
+use winit::dpi::LogicalSize;
 use winit::event::{DeviceEvent, DeviceId, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowId};
 
 use crate::app::VoxelApp;
-use crate::context::AppContext;
+use crate::context::{frame_pacing_delay, AppContext, ContextSettings};
 use crate::frame::FrameContext;
+use crate::log_file::RollingLogFile;
 
 #[cfg(feature = "profiling")]
 use voxelicous_profiler::{profile_scope, EventCategory};
 
+/// Remaining pacing wait below which the runner spins instead of sleeping.
+const PACING_SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+/// How long the runner sleeps per frame while the window is minimized.
+const MINIMIZED_POLL_INTERVAL: Duration = Duration::from_millis(16);
+/// Size at which [`AppConfig::log_file`] rolls over to `<path>.1`.
+const LOG_FILE_MAX_BYTES: u64 = 16 * 1024 * 1024;
+/// Log filter used when `RUST_LOG` is not set.
+#[cfg(feature = "profiling-tracy")]
+const DEFAULT_LOG_FILTER: &str = "info,voxelicous_app=trace,voxelicous_world=trace,voxelicous_render=trace,voxelicous_gpu=trace,voxelicous_viewer=trace,voxelicous_editor=trace,voxelicous_benchmark=trace";
+#[cfg(not(feature = "profiling-tracy"))]
+const DEFAULT_LOG_FILTER: &str = "info";
+
 /// Application configuration.
 #[derive(Clone)]
 pub struct AppConfig {
     /// Window title.
     pub title: String,
-    /// Initial window width.
+    /// Initial window width in logical pixels.
     pub width: u32,
-    /// Initial window height.
+    /// Initial window height in logical pixels.
     pub height: u32,
     /// Target frames per second (None for unlimited).
+    ///
+    /// Can be changed at runtime with [`AppContext::set_target_fps`].
     pub target_fps: Option<u32>,
     /// Enable vsync.
     pub vsync: bool,
     /// Enable Vulkan validation layers (default: debug builds only).
     pub validation: bool,
+    /// Frames the CPU may record ahead of the GPU (`None` matches the
+    /// swapchain image count). Also requested as the swapchain image count.
+    pub frames_in_flight: Option<usize>,
+    /// Also write logs to this file, rolling over to `<path>.1` every
+    /// 16 MiB.
+    pub log_file: Option<PathBuf>,
 }
 
 impl Default for AppConfig {
@@ -52,11 +72,16 @@ impl Default for AppConfig {
             target_fps: None,
             vsync: false,
             validation: cfg!(debug_assertions),
+            frames_in_flight: None,
+            log_file: None,
         }
     }
 }
 
 impl AppConfig {
+    /// Largest supported [`Self::frames_in_flight`].
+    pub const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
     /// Create a new config with the given title.
     pub fn new(title: impl Into<String>) -> Self {
         Self {
@@ -65,7 +90,7 @@ impl AppConfig {
         }
     }
 
-    /// Set the window dimensions.
+    /// Set the window dimensions (logical pixels).
     pub fn with_size(mut self, width: u32, height: u32) -> Self {
         self.width = width;
         self.height = height;
@@ -89,6 +114,34 @@ impl AppConfig {
         self.validation = validation;
         self
     }
+
+    /// Set the number of frames in flight (3 for triple buffering).
+    ///
+    /// # Panics
+    /// Panics unless `frames` is in `1..=MAX_FRAMES_IN_FLIGHT`.
+    pub fn with_frames_in_flight(mut self, frames: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_FRAMES_IN_FLIGHT).contains(&frames),
+            "frames in flight must be in 1..={}, got {frames}",
+            Self::MAX_FRAMES_IN_FLIGHT
+        );
+        self.frames_in_flight = Some(frames);
+        self
+    }
+
+    /// Also write logs to `path` (without terminal colors).
+    pub fn with_log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Settings the app context creates its swapchain and frames with.
+    pub(crate) const fn context_settings(&self) -> ContextSettings {
+        ContextSettings {
+            vsync: self.vsync,
+            frames_in_flight: self.frames_in_flight,
+        }
+    }
 }
 
 /// Run a VoxelApp with the given configuration.
@@ -97,28 +150,30 @@ impl AppConfig {
 /// and runs the event loop until the application exits.
 pub fn run_app<A: VoxelApp + 'static>(config: AppConfig) -> anyhow::Result<()> {
     // Initialize logging
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let (file_layer, log_file_error) = match &config.log_file {
+        Some(path) => match RollingLogFile::open(path, LOG_FILE_MAX_BYTES) {
+            Ok(file) => (
+                Some(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))),
+                None,
+            ),
+            Err(e) => (None, Some((path.clone(), e))),
+        },
+        None => (None, None),
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt::layer())
+        .with(file_layer);
+    // Forward spans to the profiler so they show up in the TUI.
+    #[cfg(feature = "profiling")]
+    let subscriber = subscriber.with(voxelicous_profiler::ProfilerLayer);
     #[cfg(feature = "profiling-tracy")]
-    {
-        let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-            EnvFilter::new(
-                "info,voxelicous_app=trace,voxelicous_world=trace,voxelicous_render=trace,voxelicous_gpu=trace,voxelicous_viewer=trace,voxelicous_editor=trace,voxelicous_benchmark=trace",
-            )
-        });
-        let tracy_layer = tracing_tracy::TracyLayer::default();
-
-        tracing_subscriber::registry()
-            .with(env_filter)
-            .with(tracing_subscriber::fmt::layer())
-            .with(tracy_layer)
-            .init();
-    }
-    #[cfg(not(feature = "profiling-tracy"))]
-    {
-        tracing_subscriber::fmt()
-            .with_env_filter(
-                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-            )
-            .init();
+    let subscriber = subscriber.with(tracing_tracy::TracyLayer::default());
+    subscriber.init();
+    if let Some((path, e)) = log_file_error {
+        error!("Failed to open log file {}: {e}", path.display());
     }
 
     // Initialize profiler
@@ -158,7 +213,6 @@ struct AppRunner<A: VoxelApp> {
 struct AppState<A: VoxelApp> {
     ctx: AppContext,
     app: A,
-    target_frame_time: Option<Duration>,
     // FPS tracking
     min_fps: f64,
     max_fps: f64,
@@ -231,6 +285,17 @@ impl<A: VoxelApp + 'static> ApplicationHandler for AppRunner<A> {
                     }
                 }
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // Moving to a monitor with a different DPI changes the physical
+                // size; rebuild at the new resolution.
+                if let Some(state) = &mut self.state {
+                    info!("Scale factor changed to {scale_factor}");
+                    let size = state.ctx.window.inner_size();
+                    if let Err(e) = state.handle_resize(size.width, size.height) {
+                        error!("Resize error: {e}");
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -258,7 +323,7 @@ impl<A: VoxelApp + 'static> AppRunner<A> {
         // Create window
         let window_attrs = Window::default_attributes()
             .with_title(&self.config.title)
-            .with_inner_size(PhysicalSize::new(self.config.width, self.config.height));
+            .with_inner_size(LogicalSize::new(self.config.width, self.config.height));
 
         let window = Arc::new(event_loop.create_window(window_attrs)?);
 
@@ -271,20 +336,15 @@ impl<A: VoxelApp + 'static> AppRunner<A> {
         info!("GPU: {}", gpu.capabilities().summary());
 
         // Create app context
-        let mut ctx = unsafe { AppContext::new(window, gpu, self.config.vsync)? };
+        let mut ctx = unsafe { AppContext::new(window, gpu, self.config.context_settings())? };
+        ctx.set_target_fps(self.config.target_fps.unwrap_or(0));
 
         // Initialize the application
         let app = A::init(&mut ctx)?;
 
-        let target_frame_time = self
-            .config
-            .target_fps
-            .map(|fps| Duration::from_nanos(1_000_000_000 / fps as u64));
-
         Ok(AppState {
             ctx,
             app,
-            target_frame_time,
             min_fps: f64::MAX,
             max_fps: 0.0,
             fps_sum: 0.0,
@@ -309,6 +369,15 @@ impl<A: VoxelApp> AppState<A> {
         )
         .entered();
 
+        // A minimized window has a zero-sized surface; no swapchain can be
+        // built for it, so idle until it is restored.
+        let size = self.ctx.window.inner_size();
+        if !should_render(size.width, size.height) {
+            self.ctx.last_frame_time = Instant::now();
+            thread::sleep(MINIMIZED_POLL_INTERVAL);
+            return Ok(());
+        }
+
         let frame_start = Instant::now();
 
         // Calculate delta time
@@ -345,7 +414,14 @@ impl<A: VoxelApp> AppState<A> {
             self.app.update(&self.ctx, dt);
         }
 
-        let device = self.ctx.gpu.device();
+        // Apply a present mode change requested by the app
+        if self.ctx.present_mode_request.get().is_some() {
+            unsafe {
+                self.ctx.gpu.wait_idle()?;
+                self.ctx.apply_present_mode_request()?;
+            }
+        }
+
         let frame_slot = self.ctx.current_frame_index;
         let frame_fence = self.ctx.frames[frame_slot].in_flight_fence;
         let frame_image_available = self.ctx.frames[frame_slot].image_available;
@@ -364,25 +440,34 @@ impl<A: VoxelApp> AppState<A> {
                 {
                     #[cfg(feature = "profiling-tracy")]
                     let _span = tracing::trace_span!("frame.gpu_sync.wait_fence").entered();
-                    wait_for_fence(device, frame_fence, u64::MAX)?;
+                    wait_for_fence(self.ctx.gpu.device(), frame_fence, u64::MAX)?;
                 }
+                self.ctx.gpu.process_deferred(self.ctx.frame_count)?;
 
-                // Acquire swapchain image
+                // Acquire swapchain image, recreating the swapchain and
+                // retrying once if it went stale.
                 let (image_index, suboptimal) = {
                     #[cfg(feature = "profiling-tracy")]
                     let _span = tracing::trace_span!("frame.gpu_sync.acquire_image").entered();
-                    match self.ctx.swapchain.acquire_next_image(
-                        &self.ctx.surface.swapchain_loader,
-                        frame_image_available,
-                        u64::MAX,
-                    ) {
-                        Ok(v) => v,
-                        Err(GpuError::Vulkan(vk::Result::ERROR_OUT_OF_DATE_KHR)) => {
-                            let size = self.ctx.window.inner_size();
-                            self.handle_resize(size.width.max(1), size.height.max(1))?;
-                            return Ok(());
+                    let mut retried = false;
+                    loop {
+                        match self.ctx.swapchain.acquire_next_image(
+                            &self.ctx.surface.swapchain_loader,
+                            frame_image_available,
+                            u64::MAX,
+                        ) {
+                            Ok((index, suboptimal)) => break (index, suboptimal),
+                            Err(e) => match SwapchainStatus::from_error(&e) {
+                                Some(status) if status.needs_recreate() && !retried => {
+                                    retried = true;
+                                    if !self.recreate_swapchain()? {
+                                        return Ok(());
+                                    }
+                                }
+                                Some(_) => return Ok(()),
+                                None => return Err(e.into()),
+                            },
                         }
-                        Err(e) => return Err(e.into()),
                     }
                 };
 
@@ -392,6 +477,8 @@ impl<A: VoxelApp> AppState<A> {
             }
         };
 
+        let device = self.ctx.gpu.device();
+
         // Render: record command buffer
         {
             #[cfg(feature = "profiling-tracy")]
@@ -498,9 +585,11 @@ impl<A: VoxelApp> AppState<A> {
                     image_index,
                     &[render_finished],
                 )?;
-                if suboptimal || acquire_suboptimal {
-                    let size = self.ctx.window.inner_size();
-                    self.handle_resize(size.width.max(1), size.height.max(1))?;
+                // Present reports out-of-date as suboptimal.
+                if SwapchainStatus::from_suboptimal(suboptimal || acquire_suboptimal)
+                    .needs_recreate()
+                {
+                    self.recreate_swapchain()?;
                     return Ok(());
                 }
             }
@@ -515,12 +604,20 @@ impl<A: VoxelApp> AppState<A> {
         }
 
         // Frame pacing
-        if let Some(target) = self.target_frame_time {
-            let elapsed = frame_start.elapsed();
-            if elapsed < target {
-                #[cfg(feature = "profiling-tracy")]
-                let _span = tracing::trace_span!("frame.pacing").entered();
-                thread::sleep(target - elapsed);
+        let delay = frame_pacing_delay(self.ctx.target_frame_time, frame_start.elapsed());
+        if !delay.is_zero() {
+            #[cfg(feature = "profiling")]
+            profile_scope!(EventCategory::FramePacing);
+            #[cfg(feature = "profiling-tracy")]
+            let _span = tracing::trace_span!("frame.pacing").entered();
+            // Sleep for the bulk of the wait, then spin out the remainder since
+            // OS sleeps tend to overshoot by about a millisecond.
+            let deadline = Instant::now() + delay;
+            if delay > PACING_SPIN_THRESHOLD {
+                thread::sleep(delay.saturating_sub(PACING_SPIN_THRESHOLD));
+            }
+            while Instant::now() < deadline {
+                std::hint::spin_loop();
             }
         }
 
@@ -535,8 +632,20 @@ impl<A: VoxelApp> AppState<A> {
         Ok(())
     }
 
+    /// Rebuild the swapchain at the window's current size.
+    ///
+    /// Returns `false` without recreating while the window is minimized.
+    fn recreate_swapchain(&mut self) -> anyhow::Result<bool> {
+        let size = self.ctx.window.inner_size();
+        if !should_render(size.width, size.height) {
+            return Ok(false);
+        }
+        self.handle_resize(size.width, size.height)?;
+        Ok(true)
+    }
+
     fn handle_resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
-        if width == 0 || height == 0 {
+        if !should_render(width, height) {
             return Ok(());
         }
 
@@ -576,6 +685,11 @@ impl<A: VoxelApp> AppState<A> {
             // Let the app cleanup first
             self.app.cleanup(&mut self.ctx);
 
+            // Free anything the app queued for deferred deletion
+            if let Err(e) = self.ctx.gpu.drain_all_deferred() {
+                error!("Failed to drain deferred deletions: {e}");
+            }
+
             // Then cleanup context resources
             self.ctx.cleanup();
 
@@ -588,3 +702,120 @@ fn is_device_lost_error(err: &anyhow::Error) -> bool {
     err.downcast_ref::<GpuError>()
         .is_some_and(|gpu_err| matches!(gpu_err, GpuError::Vulkan(vk::Result::ERROR_DEVICE_LOST)))
 }
+
+/// Whether a surface of this size can be rendered to (minimized windows
+/// report a zero extent).
+const fn should_render(width: u32, height: u32) -> bool {
+    width > 0 && height > 0
+}
+
+/// Swapchain state reported by an acquire or present.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SwapchainStatus {
+    /// Matches the surface.
+    Optimal,
+    /// Still usable but no longer matches the surface exactly.
+    Suboptimal,
+    /// Unusable; no image was acquired or presented.
+    OutOfDate,
+}
+
+impl SwapchainStatus {
+    const fn from_suboptimal(suboptimal: bool) -> Self {
+        if suboptimal {
+            Self::Suboptimal
+        } else {
+            Self::Optimal
+        }
+    }
+
+    /// Status for an acquire/present error, or `None` for unrelated errors.
+    const fn from_error(err: &GpuError) -> Option<Self> {
+        match err {
+            GpuError::Vulkan(vk::Result::ERROR_OUT_OF_DATE_KHR) => Some(Self::OutOfDate),
+            _ => None,
+        }
+    }
+
+    /// Whether the swapchain should be recreated before the next frame.
+    const fn needs_recreate(self) -> bool {
+        !matches!(self, Self::Optimal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxelicous_gpu::swapchain::select_image_count;
+    use voxelicous_gpu::SurfaceCapabilities;
+
+    use super::*;
+
+    #[test]
+    fn frames_in_flight_is_validated() {
+        assert_eq!(AppConfig::default().frames_in_flight, None);
+        let config = AppConfig::new("test").with_frames_in_flight(3);
+        assert_eq!(config.frames_in_flight, Some(3));
+        for frames in [0, AppConfig::MAX_FRAMES_IN_FLIGHT + 1] {
+            let result =
+                std::panic::catch_unwind(|| AppConfig::default().with_frames_in_flight(frames));
+            assert!(result.is_err(), "{frames} frames should be rejected");
+        }
+    }
+
+    #[test]
+    fn config_reaches_the_swapchain_and_frames() {
+        // The selection `AppContext::new` goes through when it creates the
+        // first swapchain on a surface with these capabilities.
+        let surface = SurfaceCapabilities {
+            capabilities: vk::SurfaceCapabilitiesKHR {
+                min_image_count: 2,
+                max_image_count: 4,
+                ..Default::default()
+            },
+            formats: Vec::new(),
+            present_modes: vec![vk::PresentModeKHR::FIFO, vk::PresentModeKHR::MAILBOX],
+        };
+        let swapchain = |settings: ContextSettings| {
+            (
+                surface.recommended_present_mode(settings.vsync),
+                select_image_count(&surface.capabilities, settings.requested_image_count()),
+            )
+        };
+
+        let defaults = AppConfig::default().context_settings();
+        assert_eq!(swapchain(defaults), (vk::PresentModeKHR::MAILBOX, 3));
+        assert_eq!(defaults.frames_for(3), 3);
+
+        let triple = AppConfig::default()
+            .with_vsync(true)
+            .with_frames_in_flight(3)
+            .context_settings();
+        assert_eq!(swapchain(triple), (vk::PresentModeKHR::FIFO, 3));
+        assert_eq!(triple.frames_for(3), 3);
+
+        // One frame in flight still gets the surface's minimum image count.
+        let single = AppConfig::default()
+            .with_frames_in_flight(1)
+            .context_settings();
+        assert_eq!(swapchain(single), (vk::PresentModeKHR::MAILBOX, 2));
+        assert_eq!(single.frames_for(2), 1);
+    }
+
+    #[test]
+    fn minimized_and_stale_swapchains_are_handled() {
+        assert!(!should_render(0, 0));
+        assert!(!should_render(1280, 0));
+        assert!(should_render(1, 1));
+
+        let out_of_date = GpuError::Vulkan(vk::Result::ERROR_OUT_OF_DATE_KHR);
+        let status = SwapchainStatus::from_error(&out_of_date);
+        assert_eq!(status, Some(SwapchainStatus::OutOfDate));
+        assert!(status.unwrap().needs_recreate());
+        assert!(SwapchainStatus::from_suboptimal(true).needs_recreate());
+        assert!(!SwapchainStatus::from_suboptimal(false).needs_recreate());
+        assert_eq!(
+            SwapchainStatus::from_error(&GpuError::Vulkan(vk::Result::ERROR_DEVICE_LOST)),
+            None
+        );
+    }
+}
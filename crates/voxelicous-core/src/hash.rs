@@ -0,0 +1,68 @@
+//! Stable, platform-independent hashing.
+
+use std::hash::Hasher;
+
+/// 64-bit FNV-1a hasher.
+///
+/// Unlike the standard library's default hasher its output is fixed, so it
+/// suits values that are pinned in tests or derived from names. Feed it
+/// explicit little-endian bytes; the integer `write_*` methods of
+/// [`Hasher`] use native byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    /// FNV-1a 64-bit offset basis.
+    pub const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    /// FNV-1a 64-bit prime.
+    pub const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    /// A hasher that has seen no bytes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    /// Hash `bytes` in one go.
+    #[must_use]
+    pub fn hash_bytes(bytes: &[u8]) -> u64 {
+        let mut hasher = Self::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_fnv1a() {
+        assert_eq!(Fnv1a::hash_bytes(b""), Fnv1a::OFFSET_BASIS);
+        assert_eq!(Fnv1a::hash_bytes(b"a"), 0xAF63_DC4C_8601_EC8C);
+        assert_eq!(Fnv1a::hash_bytes(b"foobar"), 0x8594_4171_F739_67E8);
+
+        let mut split = Fnv1a::new();
+        split.write(b"foo");
+        split.write(b"bar");
+        assert_eq!(split.finish(), Fnv1a::hash_bytes(b"foobar"));
+    }
+}
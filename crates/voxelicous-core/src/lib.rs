@@ -8,12 +8,16 @@
 
 pub mod coords;
 pub mod error;
+pub mod hash;
 pub mod math;
+pub mod seed;
 pub mod types;
 
 pub use coords::{ChunkPos, LocalPos, WorldPos};
 pub use error::{Error, Result};
-pub use types::{BlockId, Material, Voxel};
+pub use hash::Fnv1a;
+pub use seed::MasterSeed;
+pub use types::{BlockId, Material, MaterialRegistry, ToolClass, Voxel};
 
 /// Engine-wide constants
 pub mod constants {
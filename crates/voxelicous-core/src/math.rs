@@ -1,6 +1,6 @@
 //! Math utilities and helpers.
 
-use glam::{Mat4, Vec3, Vec4};
+use glam::{Mat4, Vec2, Vec3, Vec4};
 
 /// Ray for raycasting operations.
 #[derive(Clone, Copy, Debug)]
@@ -207,6 +207,43 @@ impl Frustum {
     }
 }
 
+/// Low-discrepancy Halton sequence, used for sub-pixel jitter patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HaltonSequence {
+    /// Radix of the sequence (should be prime)
+    pub base: u32,
+}
+
+impl HaltonSequence {
+    /// Create a sequence with the given base.
+    #[inline]
+    pub const fn new(base: u32) -> Self {
+        Self { base }
+    }
+
+    /// Value at `index` in `[0, 1)`. Index 0 maps to 0, so patterns usually start at 1.
+    pub fn sample(&self, mut index: u32) -> f32 {
+        let base = self.base.max(2);
+        let mut fraction = 1.0_f64;
+        let mut result = 0.0_f64;
+        while index > 0 {
+            fraction /= f64::from(base);
+            result += fraction * f64::from(index % base);
+            index /= base;
+        }
+        result as f32
+    }
+
+    /// 2D jitter in `[-0.5, 0.5)` pixels from the (2, 3) Halton pair.
+    ///
+    /// The pattern repeats every `period` samples; use a fixed `index` for
+    /// deterministic captures.
+    pub fn jitter_2d(index: u32, period: u32) -> Vec2 {
+        let index = index % period.max(1) + 1;
+        Vec2::new(Self::new(2).sample(index), Self::new(3).sample(index)) - Vec2::splat(0.5)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,4 +281,25 @@ mod tests {
         let ray = Ray::new(Vec3::new(-1.0, 2.0, 0.5), Vec3::X);
         assert!(aabb.intersect_ray(&ray).is_none());
     }
+
+    #[test]
+    fn halton_matches_known_values() {
+        let base2: Vec<f32> = (1..=4).map(|i| HaltonSequence::new(2).sample(i)).collect();
+        assert_eq!(base2, vec![0.5, 0.25, 0.75, 0.125]);
+
+        let base3 = HaltonSequence::new(3);
+        let expected = [1.0 / 3.0, 2.0 / 3.0, 1.0 / 9.0, 4.0 / 9.0];
+        for (i, &value) in expected.iter().enumerate() {
+            assert!((base3.sample(i as u32 + 1) - value).abs() < 1e-6);
+        }
+
+        assert_eq!(
+            HaltonSequence::jitter_2d(0, 8),
+            Vec2::new(0.0, 1.0 / 3.0 - 0.5)
+        );
+        assert_eq!(
+            HaltonSequence::jitter_2d(8, 8),
+            HaltonSequence::jitter_2d(0, 8)
+        );
+    }
 }
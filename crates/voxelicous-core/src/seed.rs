@@ -0,0 +1,140 @@
+//! Deterministic sub-seeds for engine subsystems.
+//!
+//! Everything random in a world derives its seed from one [`MasterSeed`]
+//! through a named domain, so the derivations are listed in one place and
+//! two subsystems never share a seed by accident.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hash::Fnv1a;
+
+/// Domains with pinned offsets from the master seed.
+///
+/// These predate [`MasterSeed`] and keep their original offsets so existing
+/// seeds generate the same worlds. New domains need no entry: their
+/// sub-seed is hashed from the name.
+pub const PINNED_SEED_DOMAINS: &[(&str, u64)] = &[
+    ("terrain.height", 0),
+    ("terrain.detail", 0x9E37_79B9),
+    ("terrain.ridge", 0xC2B2_AE35),
+    ("terrain.temperature", 0xA5A5_5A5A),
+    ("terrain.moisture", 0xD6E8_FEB8),
+    ("terrain.desert", 0x3C6E_F372),
+    ("terrain.mountain_region", 0xE703_7ED1),
+    ("terrain.lake", 0x94D0_49BB),
+    ("terrain.lake_depth", 0xB529_7A4D),
+    ("terrain.snow", 0x27D4_EB2F),
+    ("trees.placement", 0x6C8E_9CF5),
+    ("trees.roll", 0x2F6B_1D37),
+    ("flowers", 0x8B8B_8B8B),
+];
+
+/// Root seed of a world, from which every subsystem seed is derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MasterSeed(pub u64);
+
+impl MasterSeed {
+    /// Wrap a raw seed.
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// The raw seed.
+    #[must_use]
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Sub-seed for a named domain, e.g. `"terrain.height"`.
+    ///
+    /// The result only depends on the master seed and the name, so it is
+    /// stable across runs and platforms. Pinned domains add their offset
+    /// from [`PINNED_SEED_DOMAINS`]. Any other name is hashed and folded
+    /// into the mixed seed, and the result mixed again, so master seeds a
+    /// fixed distance apart do not reappear as each other's sub-seeds in
+    /// other hashed domains.
+    #[must_use]
+    pub fn derive(self, domain: &str) -> u64 {
+        if let Some(&(_, offset)) = PINNED_SEED_DOMAINS
+            .iter()
+            .find(|&&(name, _)| name == domain)
+        {
+            return self.0.wrapping_add(offset);
+        }
+        mix64(mix64(self.0) ^ Fnv1a::hash_bytes(domain.as_bytes()))
+    }
+}
+
+impl From<u64> for MasterSeed {
+    fn from(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+/// `SplitMix64` finalizer, so nearby inputs land far apart.
+const fn mix64(value: u64) -> u64 {
+    let mut v = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    v = (v ^ (v >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    v = (v ^ (v >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    v ^ (v >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn domains_get_distinct_stable_sub_seeds() {
+        let new_domains = ["ores", "structures", "caves", "terrain.heights"];
+        for seed in [0, 42, 12345, u64::MAX] {
+            let master = MasterSeed::new(seed);
+            let sub_seeds: HashSet<u64> = PINNED_SEED_DOMAINS
+                .iter()
+                .map(|&(name, _)| name)
+                .chain(new_domains)
+                .map(|domain| master.derive(domain))
+                .collect();
+            assert_eq!(
+                sub_seeds.len(),
+                PINNED_SEED_DOMAINS.len() + new_domains.len(),
+                "seed {seed}"
+            );
+        }
+
+        // Pinned domains keep their historical offsets.
+        assert_eq!(MasterSeed::new(7).derive("terrain.height"), 7);
+        assert_eq!(MasterSeed::new(7).derive("terrain.detail"), 7 + 0x9E37_79B9);
+        // Hashed domains must not change between runs or releases.
+        assert_eq!(MasterSeed::new(42).derive("ores"), 0x45E4_6613_3AC6_04FD);
+        assert_ne!(
+            MasterSeed::new(42).derive("ores"),
+            MasterSeed::new(43).derive("ores")
+        );
+    }
+
+    #[test]
+    fn master_seeds_do_not_share_sub_seeds_across_domains() {
+        // Pinned offsets cannot avoid this (seed 0's detail noise is seed
+        // 0x9E37_79B9's height noise); hashed domains must.
+        let domains = ["ores", "structures", "caves", "terrain.heights"];
+        assert_ne!(
+            MasterSeed::new(0).derive("ores"),
+            MasterSeed::new(0x9E37_79B9).derive("caves")
+        );
+
+        let seeds = (0..256).chain([0x9E37_79B9, 0xC2B2_AE35, u64::MAX]);
+        let mut sub_seeds = HashSet::new();
+        let mut count = 0;
+        for seed in seeds {
+            for domain in domains {
+                sub_seeds.insert(MasterSeed::new(seed).derive(domain));
+                count += 1;
+            }
+        }
+        assert_eq!(sub_seeds.len(), count);
+    }
+}
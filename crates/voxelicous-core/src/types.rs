@@ -33,6 +33,10 @@ impl BlockId {
     pub const LEAVES: Self = Self(8);
     /// Flower block
     pub const FLOWER: Self = Self(9);
+    /// Ice block (frozen water surface)
+    pub const ICE: Self = Self(10);
+    /// Bedrock block (indestructible world floor)
+    pub const BEDROCK: Self = Self(11);
 
     /// Returns true if this block is air (empty)
     #[inline]
@@ -47,7 +51,7 @@ impl BlockId {
     }
 }
 
-/// Material properties for rendering.
+/// Material properties for rendering and gameplay.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Material {
     /// Base color (RGB, 0-255)
@@ -58,6 +62,29 @@ pub struct Material {
     pub metallic: f32,
     /// Emission strength (0.0 = no emission)
     pub emission: f32,
+    /// Thin geometry (leaves, plants) the ray marcher refines its steps around
+    #[serde(default)]
+    pub needs_fine_steps: bool,
+    /// Alpha-cutout coverage (0.0-1.0); rays pass through the rest of the block
+    #[serde(default)]
+    pub cutout_alpha: Option<f32>,
+    /// Mining hardness (0.0 = instant, infinity = unbreakable)
+    #[serde(default)]
+    pub hardness: f32,
+    /// Tool needed to mine the block at full speed
+    #[serde(default)]
+    pub tool_required: Option<ToolClass>,
+}
+
+/// Class of tool used to mine blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ToolClass {
+    /// Stone and ores
+    Pickaxe,
+    /// Dirt, sand and snow
+    Shovel,
+    /// Logs and planks
+    Axe,
 }
 
 impl Material {
@@ -67,6 +94,10 @@ impl Material {
         roughness: 0.8,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 1.5,
+        tool_required: Some(ToolClass::Pickaxe),
     };
 
     /// Default dirt material
@@ -75,6 +106,10 @@ impl Material {
         roughness: 0.9,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 0.5,
+        tool_required: None,
     };
 
     /// Default grass material
@@ -83,6 +118,10 @@ impl Material {
         roughness: 0.85,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 0.6,
+        tool_required: None,
     };
 
     /// Default snow material
@@ -91,6 +130,10 @@ impl Material {
         roughness: 0.95,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 0.2,
+        tool_required: None,
     };
 
     /// Default sand material
@@ -99,6 +142,10 @@ impl Material {
         roughness: 0.92,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 0.5,
+        tool_required: None,
     };
 
     /// Default water material
@@ -107,6 +154,10 @@ impl Material {
         roughness: 0.4,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: f32::INFINITY,
+        tool_required: None,
     };
 
     /// Default log material
@@ -115,6 +166,10 @@ impl Material {
         roughness: 0.88,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 2.0,
+        tool_required: None,
     };
 
     /// Default leaves material
@@ -123,6 +178,10 @@ impl Material {
         roughness: 0.95,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: true,
+        cutout_alpha: Some(0.65),
+        hardness: 0.0,
+        tool_required: None,
     };
 
     /// Default flower material
@@ -131,9 +190,125 @@ impl Material {
         roughness: 0.8,
         metallic: 0.0,
         emission: 0.0,
+        needs_fine_steps: true,
+        cutout_alpha: Some(0.5),
+        hardness: 0.0,
+        tool_required: None,
+    };
+
+    /// Default ice material
+    pub const ICE: Self = Self {
+        color: [179, 217, 242],
+        roughness: 0.2,
+        metallic: 0.0,
+        emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: 0.5,
+        tool_required: Some(ToolClass::Pickaxe),
+    };
+
+    /// Default bedrock material
+    pub const BEDROCK: Self = Self {
+        color: [46, 46, 50],
+        roughness: 0.95,
+        metallic: 0.0,
+        emission: 0.0,
+        needs_fine_steps: false,
+        cutout_alpha: None,
+        hardness: f32::INFINITY,
+        tool_required: None,
     };
 }
 
+/// Seconds of mining per point of hardness with a suitable tool.
+const MINING_TIME_PER_HARDNESS: f32 = 1.5;
+/// Seconds of mining per point of hardness without the required tool.
+const WRONG_TOOL_MINING_MULTIPLIER: f32 = 5.0;
+
+/// Materials indexed by [`BlockId`].
+///
+/// The default registry holds the built-in block materials. Renderers read
+/// it to build their per-block GPU tables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialRegistry {
+    materials: Vec<Option<Material>>,
+}
+
+impl MaterialRegistry {
+    /// Create an empty registry.
+    pub const fn new() -> Self {
+        Self {
+            materials: Vec::new(),
+        }
+    }
+
+    /// Register (or replace) the material of a block.
+    pub fn register(&mut self, block: BlockId, material: Material) {
+        let index = usize::from(block.0);
+        if index >= self.materials.len() {
+            self.materials.resize(index + 1, None);
+        }
+        self.materials[index] = Some(material);
+    }
+
+    /// Material of a block, if registered.
+    pub fn get(&self, block: BlockId) -> Option<&Material> {
+        self.materials.get(usize::from(block.0))?.as_ref()
+    }
+
+    /// Whether the ray marcher should refine its steps around a block.
+    pub fn needs_fine_steps(&self, block: BlockId) -> bool {
+        self.get(block).is_some_and(|m| m.needs_fine_steps)
+    }
+
+    /// Seconds needed to mine a block with `tool` (`None` for bare hands).
+    ///
+    /// Blocks take `hardness * 1.5` seconds, or five times longer without
+    /// their required tool. Unregistered and unbreakable blocks return
+    /// infinity.
+    pub fn mining_time(&self, block: BlockId, tool: Option<ToolClass>) -> f32 {
+        let Some(material) = self.get(block) else {
+            return f32::INFINITY;
+        };
+        let multiplier = match material.tool_required {
+            Some(required) if tool != Some(required) => WRONG_TOOL_MINING_MULTIPLIER,
+            _ => MINING_TIME_PER_HARDNESS,
+        };
+        material.hardness * multiplier
+    }
+
+    /// Registered materials in block id order.
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &Material)> {
+        self.materials
+            .iter()
+            .enumerate()
+            .filter_map(|(id, m)| Some((BlockId(id as u16), m.as_ref()?)))
+    }
+}
+
+impl Default for MaterialRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        for (block, material) in [
+            (BlockId::STONE, Material::STONE),
+            (BlockId::DIRT, Material::DIRT),
+            (BlockId::GRASS, Material::GRASS),
+            (BlockId::SNOW, Material::SNOW),
+            (BlockId::SAND, Material::SAND),
+            (BlockId::WATER, Material::WATER),
+            (BlockId::LOG, Material::LOG),
+            (BlockId::LEAVES, Material::LEAVES),
+            (BlockId::FLOWER, Material::FLOWER),
+            (BlockId::ICE, Material::ICE),
+            (BlockId::BEDROCK, Material::BEDROCK),
+        ] {
+            registry.register(block, material);
+        }
+        registry
+    }
+}
+
 /// A single voxel with block type and optional metadata.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
 #[repr(C)]
@@ -201,4 +376,51 @@ mod tests {
         let voxel = Voxel::default();
         assert!(voxel.is_air());
     }
+
+    #[test]
+    fn default_registry_marks_thin_blocks_for_fine_steps() {
+        let mut registry = MaterialRegistry::default();
+        assert!(registry.needs_fine_steps(BlockId::LEAVES));
+        assert!(registry.needs_fine_steps(BlockId::FLOWER));
+        assert!(!registry.needs_fine_steps(BlockId::STONE));
+        assert!(!registry.needs_fine_steps(BlockId::AIR));
+        assert!(registry
+            .get(BlockId::LEAVES)
+            .unwrap()
+            .cutout_alpha
+            .is_some());
+        assert!(registry.get(BlockId::STONE).unwrap().cutout_alpha.is_none());
+        assert_eq!(registry.iter().count(), 11);
+
+        registry.register(
+            BlockId(300),
+            Material {
+                needs_fine_steps: true,
+                ..Material::GRASS
+            },
+        );
+        assert!(registry.needs_fine_steps(BlockId(300)));
+        assert!(registry.get(BlockId(299)).is_none());
+    }
+
+    #[test]
+    fn mining_time_follows_hardness_and_tools() {
+        let mut registry = MaterialRegistry::default();
+        let pickaxe = Some(ToolClass::Pickaxe);
+        let stone = registry.mining_time(BlockId::STONE, pickaxe);
+        assert!(stone > registry.mining_time(BlockId::DIRT, pickaxe));
+        assert!(registry.mining_time(BlockId::STONE, None) > stone);
+        assert!(registry.mining_time(BlockId::LEAVES, None).abs() < f32::EPSILON);
+
+        let bedrock = BlockId(200);
+        registry.register(
+            bedrock,
+            Material {
+                hardness: f32::INFINITY,
+                ..Material::STONE
+            },
+        );
+        assert_eq!(registry.mining_time(bedrock, pickaxe), f32::INFINITY);
+        assert_eq!(registry.mining_time(BlockId(201), pickaxe), f32::INFINITY);
+    }
 }
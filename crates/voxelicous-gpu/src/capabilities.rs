@@ -52,6 +52,10 @@ pub struct GpuCapabilities {
     pub supports_descriptor_indexing: bool,
     /// Scalar block layout support
     pub supports_scalar_block_layout: bool,
+    /// Hardware ray tracing (ray tracing pipeline and acceleration structures)
+    pub supports_ray_tracing: bool,
+    /// Storage image writes without a declared format (`shaderStorageImageWriteWithoutFormat`)
+    pub supports_storage_image_write_without_format: bool,
 
     // Memory info
     /// Device-local memory in MB
@@ -80,6 +84,7 @@ impl GpuCapabilities {
         // Get basic properties
         let properties = instance.get_physical_device_properties(physical_device);
         let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+        let features = instance.get_physical_device_features(physical_device);
 
         // Get available extensions
         let extensions = instance
@@ -131,6 +136,11 @@ impl GpuCapabilities {
                 || available_extensions.contains("VK_EXT_descriptor_indexing"),
             supports_scalar_block_layout: has_vulkan_1_3
                 || available_extensions.contains("VK_EXT_scalar_block_layout"),
+            supports_ray_tracing: available_extensions.contains("VK_KHR_ray_tracing_pipeline")
+                && available_extensions.contains("VK_KHR_acceleration_structure"),
+            supports_storage_image_write_without_format: features
+                .shader_storage_image_write_without_format
+                == vk::TRUE,
 
             device_local_memory_mb,
             max_memory_allocation_count: properties.limits.max_memory_allocation_count,
@@ -1,14 +1,18 @@
 //! GPU context management.
 
 use crate::capabilities::GpuCapabilities;
+use crate::deferred::DeferredDeletionQueue;
 use crate::error::{GpuError, Result};
 use crate::instance::{create_instance, select_physical_device};
-use crate::memory::GpuAllocator;
+use crate::memory::{GpuAllocator, GpuBuffer};
 use ash::vk;
 use parking_lot::Mutex;
 use std::ffi::CStr;
 use std::sync::Arc;
 
+/// Frames a deferred buffer is kept before the swapchain reports its real count.
+const DEFAULT_DEFERRED_FRAMES: usize = 2;
+
 /// Main GPU context holding Vulkan resources.
 pub struct GpuContext {
     // Entry must be kept alive for the lifetime of the context
@@ -19,6 +23,7 @@ pub struct GpuContext {
     pub(crate) device: Arc<ash::Device>,
     pub(crate) capabilities: GpuCapabilities,
     pub(crate) allocator: Mutex<GpuAllocator>,
+    pub(crate) deferred: Mutex<DeferredDeletionQueue>,
 
     // Queue families and queues
     pub(crate) graphics_queue_family: u32,
@@ -45,6 +50,20 @@ impl GpuContext {
         &self.capabilities
     }
 
+    /// Check whether `format` can be written as a storage image.
+    ///
+    /// Formats other than the one a shader declares also need
+    /// [`GpuCapabilities::supports_storage_image_write_without_format`].
+    pub fn supports_storage_image_format(&self, format: vk::Format) -> bool {
+        let properties = unsafe {
+            self.instance
+                .get_physical_device_format_properties(self.physical_device, format)
+        };
+        properties
+            .optimal_tiling_features
+            .contains(vk::FormatFeatureFlags::STORAGE_IMAGE)
+    }
+
     /// Get the graphics queue.
     pub fn graphics_queue(&self) -> vk::Queue {
         self.graphics_queue
@@ -85,6 +104,38 @@ impl GpuContext {
         &self.allocator
     }
 
+    /// Queue a buffer to be freed once no in-flight frame can reference it.
+    pub fn defer_free(&self, buffer: GpuBuffer, frame_number: u64) {
+        self.deferred.lock().queue(buffer, frame_number);
+    }
+
+    /// Free deferred buffers whose frames have completed.
+    pub fn process_deferred(&self, frame_number: u64) -> Result<()> {
+        let mut allocator = self.allocator.lock();
+        self.deferred.lock().process(&mut allocator, frame_number)
+    }
+
+    /// Set how many frames deferred buffers are kept before being freed.
+    pub fn set_deferred_frames_in_flight(&self, frames_in_flight: usize) {
+        self.deferred.lock().set_frames_in_flight(frames_in_flight);
+    }
+
+    /// Number of buffers waiting in the deferred deletion queue.
+    pub fn deferred_count(&self) -> usize {
+        self.deferred.lock().pending_count()
+    }
+
+    /// Wait for the device to go idle, then free every deferred buffer.
+    ///
+    /// Call this on shutdown before destroying renderers so nothing queued
+    /// is still referenced by an in-flight frame. Must not be called while
+    /// holding the [`allocator`](Self::allocator) lock.
+    pub fn drain_all_deferred(&self) -> Result<()> {
+        self.wait_idle()?;
+        let mut allocator = self.allocator.lock();
+        self.deferred.lock().flush(&mut allocator)
+    }
+
     /// Wait for device to be idle.
     #[cfg_attr(
         feature = "profiling-tracy",
@@ -101,7 +152,9 @@ impl GpuContext {
 impl Drop for GpuContext {
     fn drop(&mut self) {
         unsafe {
-            let _ = self.device.device_wait_idle();
+            if let Err(e) = self.drain_all_deferred() {
+                tracing::error!("Failed to drain deferred deletions: {e}");
+            }
 
             // Shutdown allocator BEFORE destroying device
             // This frees all VkDeviceMemory allocations
@@ -173,7 +226,7 @@ impl GpuContextBuilder {
 
         // Create logical device
         let (device, graphics_queue, compute_queue, transfer_queue) =
-            unsafe { create_device(&instance, physical_device, &queue_families)? };
+            unsafe { create_device(&instance, physical_device, &queue_families, &capabilities)? };
 
         let device = Arc::new(device);
 
@@ -187,6 +240,7 @@ impl GpuContextBuilder {
             device,
             capabilities,
             allocator: Mutex::new(allocator),
+            deferred: Mutex::new(DeferredDeletionQueue::new(DEFAULT_DEFERRED_FRAMES)),
             graphics_queue_family: queue_families.graphics,
             compute_queue_family: queue_families.compute,
             transfer_queue_family: queue_families.transfer,
@@ -278,6 +332,7 @@ unsafe fn create_device(
     instance: &ash::Instance,
     physical_device: vk::PhysicalDevice,
     queue_families: &QueueFamilyIndices,
+    capabilities: &GpuCapabilities,
 ) -> Result<(ash::Device, vk::Queue, vk::Queue, vk::Queue)> {
     // Collect unique queue families
     let mut unique_families = std::collections::HashSet::new();
@@ -314,8 +369,13 @@ unsafe fn create_device(
         .runtime_descriptor_array(true)
         .shader_sampled_image_array_non_uniform_indexing(true);
 
-    // Enable base features
-    let features = vk::PhysicalDeviceFeatures::default().shader_int64(true);
+    // Enable base features; format-less storage image writes only where supported,
+    // otherwise renderers fall back to RGBA8 output
+    let features = vk::PhysicalDeviceFeatures::default()
+        .shader_int64(true)
+        .shader_storage_image_write_without_format(
+            capabilities.supports_storage_image_write_without_format,
+        );
 
     // Chain features together
     let mut features2 = vk::PhysicalDeviceFeatures2::default()
@@ -340,3 +400,41 @@ unsafe fn create_device(
 
     Ok((device, graphics_queue, compute_queue, transfer_queue))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_gpu;
+    use gpu_allocator::MemoryLocation;
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn drain_frees_all_deferred_buffers() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+
+        let baseline = gpu.allocator().lock().tracked_bytes();
+        for (frame, size) in [(0, 256), (0, 4096), (1, 1 << 20), (3, 64)] {
+            let buffer = gpu
+                .allocator()
+                .lock()
+                .create_buffer(
+                    size,
+                    vk::BufferUsageFlags::STORAGE_BUFFER,
+                    MemoryLocation::CpuToGpu,
+                    "deferred_test",
+                )
+                .expect("create buffer");
+            gpu.defer_free(buffer, frame);
+        }
+        assert_eq!(gpu.deferred_count(), 4);
+        assert!(gpu.allocator().lock().tracked_bytes() > baseline);
+
+        // Only buffers older than the in-flight window are freed per frame.
+        gpu.process_deferred(3).expect("process");
+        assert_eq!(gpu.deferred_count(), 2);
+
+        gpu.drain_all_deferred().expect("drain");
+        assert_eq!(gpu.deferred_count(), 0);
+        assert_eq!(gpu.allocator().lock().tracked_bytes(), baseline);
+    }
+}
@@ -19,6 +19,8 @@ pub mod pipeline;
 pub mod surface;
 pub mod swapchain;
 pub mod sync;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use capabilities::{GpuCapabilities, GpuVendor};
 pub use context::{GpuContext, GpuContextBuilder};
@@ -28,7 +30,8 @@ pub use descriptors::{
     DescriptorSetLayoutBuilder,
 };
 pub use error::{GpuError, Result};
-pub use memory::{GpuAllocator, GpuBuffer, GpuImage};
+pub use memory::{DefragStats, GpuAllocator, GpuBuffer, GpuImage};
 pub use pipeline::{ComputePipeline, GraphicsPipeline, GraphicsPipelineConfig};
 pub use surface::{SurfaceCapabilities, SurfaceContext};
+pub use swapchain::PresentMode;
 pub use sync::{create_fence, create_semaphore, FrameSync, FrameSyncManager};
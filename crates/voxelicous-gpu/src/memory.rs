@@ -12,6 +12,8 @@ use std::sync::Arc;
 pub struct GpuAllocator {
     allocator: Option<Allocator>,
     device: Arc<ash::Device>,
+    /// Bytes held by live buffer and image allocations.
+    tracked_bytes: u64,
 }
 
 impl GpuAllocator {
@@ -44,9 +46,18 @@ impl GpuAllocator {
         Ok(Self {
             allocator: Some(allocator),
             device,
+            tracked_bytes: 0,
         })
     }
 
+    /// Bytes currently held by buffers and images from this allocator.
+    ///
+    /// Counts the size of each live allocation, so it returns to its previous
+    /// value once every resource created since then has been freed.
+    pub fn tracked_bytes(&self) -> u64 {
+        self.tracked_bytes
+    }
+
     /// Allocate a buffer.
     pub fn create_buffer(
         &mut self,
@@ -86,17 +97,21 @@ impl GpuAllocator {
                 .bind_buffer_memory(buffer, allocation.memory(), allocation.offset())
                 .map_err(GpuError::from)?;
         }
+        self.tracked_bytes += allocation.size();
 
         Ok(GpuBuffer {
             buffer,
             allocation: Some(allocation),
             size,
+            usage,
+            location,
         })
     }
 
     /// Free a buffer allocation.
     pub fn free_buffer(&mut self, buffer: &mut GpuBuffer) -> Result<()> {
         if let Some(allocation) = buffer.allocation.take() {
+            self.tracked_bytes = self.tracked_bytes.saturating_sub(allocation.size());
             self.allocator
                 .as_mut()
                 .ok_or_else(|| GpuError::InvalidState("Allocator not initialized".to_string()))?
@@ -145,6 +160,7 @@ impl GpuAllocator {
                 .bind_image_memory(image, allocation.memory(), allocation.offset())
                 .map_err(GpuError::from)?;
         }
+        self.tracked_bytes += allocation.size();
 
         Ok(GpuImage {
             image,
@@ -157,6 +173,7 @@ impl GpuAllocator {
     /// Free an image allocation.
     pub fn free_image(&mut self, image: &mut GpuImage) -> Result<()> {
         if let Some(allocation) = image.allocation.take() {
+            self.tracked_bytes = self.tracked_bytes.saturating_sub(allocation.size());
             self.allocator
                 .as_mut()
                 .ok_or_else(|| GpuError::InvalidState("Allocator not initialized".to_string()))?
@@ -173,7 +190,126 @@ impl GpuAllocator {
     }
 }
 
+/// Result of a [`GpuAllocator::defragment`] pass.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DefragStats {
+    /// Bytes copied into new allocations.
+    pub moved_bytes: u64,
+    /// Indices (into the slice passed to `defragment`) of buffers that now
+    /// live in a new `vk::Buffer`; their device addresses have changed.
+    pub moved: Vec<usize>,
+    /// [`GpuAllocator::fragmentation`] before the pass.
+    pub fragmentation_before: f64,
+    /// [`GpuAllocator::fragmentation`] after the pass.
+    pub fragmentation_after: f64,
+}
+
 impl GpuAllocator {
+    /// Fraction (0.0-1.0) of free memory outside each block's largest hole.
+    ///
+    /// 0.0 means every block's free space is one contiguous range; values
+    /// near 1.0 mean it is scattered across many small holes.
+    pub fn fragmentation(&self) -> f64 {
+        let Some(allocator) = self.allocator.as_ref() else {
+            return 0.0;
+        };
+        let report = allocator.generate_report();
+        let mut free_total = 0u64;
+        let mut largest_total = 0u64;
+        for block in &report.blocks {
+            let mut ranges: Vec<(u64, u64)> = report.allocations[block.allocations.clone()]
+                .iter()
+                .map(|allocation| (allocation.offset, allocation.size))
+                .collect();
+            ranges.sort_unstable();
+            let mut cursor = 0u64;
+            let mut largest = 0u64;
+            let mut free = 0u64;
+            for (offset, size) in ranges.into_iter().chain([(block.size, 0)]) {
+                let hole = offset.saturating_sub(cursor);
+                free += hole;
+                largest = largest.max(hole);
+                cursor = cursor.max(offset + size);
+            }
+            free_total += free;
+            largest_total += largest;
+        }
+        if free_total == 0 {
+            return 0.0;
+        }
+        1.0 - largest_total as f64 / free_total as f64
+    }
+
+    /// Move host-visible buffers toward the start of their memory blocks.
+    ///
+    /// Relocates at most `budget_bytes` of buffer data per call, copying
+    /// contents through the mapped pointers. Only `buffers` are candidates,
+    /// so callers opt out by leaving buffers out; moved ones get a new
+    /// `vk::Buffer` and device address and are listed in
+    /// [`DefragStats::moved`]. A buffer only moves when it lands earlier in
+    /// the same memory block. Device-local buffers are skipped.
+    ///
+    /// The old buffers are freed immediately, so none of `buffers` may be
+    /// in use by the GPU (wait for their frame's fence first).
+    pub fn defragment(
+        &mut self,
+        buffers: &mut [&mut GpuBuffer],
+        budget_bytes: u64,
+    ) -> Result<DefragStats> {
+        let mut stats = DefragStats {
+            fragmentation_before: self.fragmentation(),
+            ..DefragStats::default()
+        };
+
+        // Buffers furthest into their blocks leave the biggest holes behind.
+        let mut order: Vec<usize> = (0..buffers.len())
+            .filter(|&i| buffers[i].mapped_ptr().is_some())
+            .collect();
+        order.sort_by_key(|&i| {
+            std::cmp::Reverse(buffers[i].allocation.as_ref().map_or(0, Allocation::offset))
+        });
+
+        for index in order {
+            let buffer = &mut *buffers[index];
+            if stats.moved_bytes + buffer.size > budget_bytes {
+                continue;
+            }
+            let Some((old_memory, old_offset)) = buffer
+                .allocation
+                .as_ref()
+                .map(|a| (unsafe { a.memory() }, a.offset()))
+            else {
+                continue;
+            };
+
+            let mut moved =
+                self.create_buffer(buffer.size, buffer.usage, buffer.location, "defragment")?;
+            let improves = moved
+                .allocation
+                .as_ref()
+                .is_some_and(|a| unsafe { a.memory() } == old_memory && a.offset() < old_offset);
+            let (Some(src), Some(dst)) = (buffer.mapped_ptr(), moved.mapped_ptr()) else {
+                self.free_buffer(&mut moved)?;
+                continue;
+            };
+            if !improves {
+                self.free_buffer(&mut moved)?;
+                continue;
+            }
+
+            unsafe {
+                std::ptr::copy_nonoverlapping(src, dst, buffer.size as usize);
+            }
+            std::mem::swap(buffer, &mut moved);
+            self.free_buffer(&mut moved)?;
+            stats.moved_bytes += buffer.size;
+            stats.moved.push(index);
+        }
+
+        stats.fragmentation_after = self.fragmentation();
+        Ok(stats)
+    }
+
     /// Shutdown the allocator, freeing all GPU memory.
     ///
     /// This must be called before the Vulkan device is destroyed.
@@ -199,6 +335,10 @@ pub struct GpuBuffer {
     pub buffer: vk::Buffer,
     pub allocation: Option<Allocation>,
     pub size: u64,
+    /// Usage the buffer was created with.
+    pub usage: vk::BufferUsageFlags,
+    /// Memory location the buffer was allocated from.
+    pub location: MemoryLocation,
 }
 
 impl GpuBuffer {
@@ -274,3 +414,54 @@ pub struct GpuImage {
     pub format: vk::Format,
     pub extent: vk::Extent3D,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_gpu;
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn defragment_fills_holes_and_keeps_contents() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        const SIZE: u64 = 64 * 1024;
+        let mut allocator = gpu.allocator().lock();
+
+        // Fill a run of buffers, then free every other one to punch holes.
+        let mut kept = Vec::new();
+        for value in 0..16u8 {
+            let mut buffer = allocator
+                .create_buffer(
+                    SIZE,
+                    vk::BufferUsageFlags::STORAGE_BUFFER,
+                    MemoryLocation::CpuToGpu,
+                    "defrag_test",
+                )
+                .expect("create buffer");
+            buffer.write(&vec![value; SIZE as usize]).unwrap();
+            if value % 2 == 0 {
+                allocator.free_buffer(&mut buffer).unwrap();
+            } else {
+                kept.push((value, buffer));
+            }
+        }
+        let fragmented = allocator.fragmentation();
+        assert!(fragmented > 0.0);
+
+        let mut buffers: Vec<&mut GpuBuffer> = kept.iter_mut().map(|(_, b)| b).collect();
+        let stats = allocator.defragment(&mut buffers, SIZE).unwrap();
+        assert_eq!(stats.moved.len(), 1, "budget allows a single buffer");
+        assert_eq!(stats.moved_bytes, SIZE);
+
+        let stats = allocator.defragment(&mut buffers, u64::MAX).unwrap();
+        assert!(!stats.moved.is_empty());
+        assert!(allocator.fragmentation() < fragmented);
+
+        for (value, buffer) in &mut kept {
+            let ptr = buffer.mapped_ptr().unwrap();
+            let bytes = unsafe { std::slice::from_raw_parts(ptr, SIZE as usize) };
+            assert!(bytes.iter().all(|b| b == value), "buffer {value} corrupted");
+            allocator.free_buffer(buffer).unwrap();
+        }
+    }
+}
@@ -5,7 +5,10 @@
 
 use crate::context::GpuContext;
 use crate::error::{GpuError, Result};
-use crate::swapchain::{calculate_extent, select_present_mode, select_surface_format, Swapchain};
+use crate::swapchain::{
+    calculate_extent, select_present_mode, select_requested_present_mode, select_surface_format,
+    PresentMode, Swapchain,
+};
 use ash::vk;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 
@@ -19,6 +22,8 @@ pub struct SurfaceContext {
     pub surface_loader: ash::khr::surface::Instance,
     /// Swapchain extension loader.
     pub swapchain_loader: ash::khr::swapchain::Device,
+    /// Swapchain image count requested by the app (`None` for the default).
+    requested_image_count: Option<u32>,
     /// The Vulkan entry point (kept alive for surface_loader lifetime).
     #[allow(dead_code)]
     entry: ash::Entry,
@@ -59,10 +64,25 @@ impl SurfaceContext {
             surface,
             surface_loader,
             swapchain_loader,
+            requested_image_count: None,
             entry,
         })
     }
 
+    /// Swapchain image count requested for new swapchains.
+    pub fn requested_image_count(&self) -> Option<u32> {
+        self.requested_image_count
+    }
+
+    /// Request an image count for swapchains created from now on.
+    ///
+    /// The surface's limits still apply; see
+    /// [`select_image_count`](crate::swapchain::select_image_count). `None`
+    /// restores the default of one more than the surface minimum.
+    pub fn set_requested_image_count(&mut self, count: Option<u32>) {
+        self.requested_image_count = count;
+    }
+
     /// Query surface capabilities.
     pub fn capabilities(&self, gpu: &GpuContext) -> Result<SurfaceCapabilities> {
         unsafe {
@@ -99,9 +119,40 @@ impl SurfaceContext {
         old_swapchain: Option<vk::SwapchainKHR>,
     ) -> Result<Swapchain> {
         let caps = self.capabilities(gpu)?;
+        let present_mode = select_present_mode(&caps.present_modes, vsync);
+        self.create_swapchain_for_caps(gpu, &caps, width, height, present_mode, old_swapchain)
+    }
 
+    /// Create a swapchain with a specific present mode.
+    ///
+    /// Falls back to FIFO when the surface does not support `present_mode`;
+    /// the mode actually used is stored on the returned swapchain.
+    ///
+    /// # Safety
+    /// The GPU context must be valid.
+    pub unsafe fn create_swapchain_with_present_mode(
+        &self,
+        gpu: &GpuContext,
+        width: u32,
+        height: u32,
+        present_mode: PresentMode,
+        old_swapchain: Option<vk::SwapchainKHR>,
+    ) -> Result<Swapchain> {
+        let caps = self.capabilities(gpu)?;
+        let present_mode = caps.present_mode_for(present_mode).to_vk();
+        self.create_swapchain_for_caps(gpu, &caps, width, height, present_mode, old_swapchain)
+    }
+
+    unsafe fn create_swapchain_for_caps(
+        &self,
+        gpu: &GpuContext,
+        caps: &SurfaceCapabilities,
+        width: u32,
+        height: u32,
+        present_mode: vk::PresentModeKHR,
+        old_swapchain: Option<vk::SwapchainKHR>,
+    ) -> Result<Swapchain> {
         let surface_format = select_surface_format(&caps.formats);
-        let present_mode = select_present_mode(&caps.present_modes, vsync);
         let extent = calculate_extent(&caps.capabilities, width, height);
 
         Swapchain::new(
@@ -114,6 +165,7 @@ impl SurfaceContext {
             extent,
             old_swapchain,
             gpu.graphics_queue_family(),
+            self.requested_image_count,
         )
     }
 
@@ -165,4 +217,9 @@ impl SurfaceCapabilities {
     pub fn recommended_present_mode(&self, vsync: bool) -> vk::PresentModeKHR {
         select_present_mode(&self.present_modes, vsync)
     }
+
+    /// Get `requested` if supported, otherwise FIFO.
+    pub fn present_mode_for(&self, requested: PresentMode) -> PresentMode {
+        select_requested_present_mode(&self.present_modes, requested)
+    }
 }
@@ -10,6 +10,7 @@ pub struct Swapchain {
     pub image_views: Vec<vk::ImageView>,
     pub format: vk::Format,
     pub extent: vk::Extent2D,
+    pub present_mode: vk::PresentModeKHR,
 }
 
 impl Swapchain {
@@ -28,14 +29,9 @@ impl Swapchain {
         extent: vk::Extent2D,
         old_swapchain: Option<vk::SwapchainKHR>,
         graphics_queue_family: u32,
+        requested_image_count: Option<u32>,
     ) -> Result<Self> {
-        // Determine image count
-        let mut image_count = surface_capabilities.min_image_count + 1;
-        if surface_capabilities.max_image_count > 0
-            && image_count > surface_capabilities.max_image_count
-        {
-            image_count = surface_capabilities.max_image_count;
-        }
+        let image_count = select_image_count(surface_capabilities, requested_image_count);
 
         let queue_families = [graphics_queue_family];
         let create_info = vk::SwapchainCreateInfoKHR::default()
@@ -89,6 +85,7 @@ impl Swapchain {
             image_views,
             format: surface_format.format,
             extent,
+            present_mode,
         })
     }
 
@@ -209,6 +206,51 @@ pub fn select_present_mode(available: &[vk::PresentModeKHR], vsync: bool) -> vk:
     }
 }
 
+/// Swapchain presentation mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for vertical blank (vsync). Always supported.
+    #[default]
+    Fifo,
+    /// Replace the queued image on each present; no tearing, no blocking.
+    Mailbox,
+    /// Present without waiting; may tear.
+    Immediate,
+}
+
+impl PresentMode {
+    /// Vulkan present mode for this mode.
+    pub const fn to_vk(self) -> vk::PresentModeKHR {
+        match self {
+            Self::Fifo => vk::PresentModeKHR::FIFO,
+            Self::Mailbox => vk::PresentModeKHR::MAILBOX,
+            Self::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        }
+    }
+
+    /// Mode for a Vulkan present mode, if it is one of the supported ones.
+    pub fn from_vk(mode: vk::PresentModeKHR) -> Option<Self> {
+        match mode {
+            vk::PresentModeKHR::FIFO => Some(Self::Fifo),
+            vk::PresentModeKHR::MAILBOX => Some(Self::Mailbox),
+            vk::PresentModeKHR::IMMEDIATE => Some(Self::Immediate),
+            _ => None,
+        }
+    }
+}
+
+/// Select `requested` if the surface supports it, otherwise FIFO.
+pub fn select_requested_present_mode(
+    available: &[vk::PresentModeKHR],
+    requested: PresentMode,
+) -> PresentMode {
+    if available.contains(&requested.to_vk()) {
+        requested
+    } else {
+        PresentMode::Fifo
+    }
+}
+
 /// Calculate swapchain extent.
 pub fn calculate_extent(
     capabilities: &vk::SurfaceCapabilitiesKHR,
@@ -230,3 +272,82 @@ pub fn calculate_extent(
         }
     }
 }
+
+/// Number of swapchain images to request.
+///
+/// Without a request this is one more than the surface minimum. Requests are
+/// raised to the surface minimum and capped at its maximum (if any).
+pub fn select_image_count(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+    requested: Option<u32>,
+) -> u32 {
+    let count = requested.map_or(capabilities.min_image_count + 1, |requested| {
+        requested.max(capabilities.min_image_count)
+    });
+    if capabilities.max_image_count > 0 {
+        count.min(capabilities.max_image_count)
+    } else {
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_count_follows_request_within_surface_limits() {
+        let caps = vk::SurfaceCapabilitiesKHR {
+            min_image_count: 2,
+            max_image_count: 3,
+            ..Default::default()
+        };
+        assert_eq!(select_image_count(&caps, None), 3);
+        assert_eq!(select_image_count(&caps, Some(1)), 2);
+        assert_eq!(select_image_count(&caps, Some(3)), 3);
+        assert_eq!(select_image_count(&caps, Some(8)), 3);
+
+        // A maximum of 0 means unbounded.
+        let unbounded = vk::SurfaceCapabilitiesKHR {
+            max_image_count: 0,
+            ..caps
+        };
+        assert_eq!(select_image_count(&unbounded, Some(8)), 8);
+    }
+
+    #[test]
+    fn requested_present_mode_falls_back_to_fifo() {
+        let all = [
+            vk::PresentModeKHR::FIFO,
+            vk::PresentModeKHR::MAILBOX,
+            vk::PresentModeKHR::IMMEDIATE,
+        ];
+        for mode in [
+            PresentMode::Fifo,
+            PresentMode::Mailbox,
+            PresentMode::Immediate,
+        ] {
+            assert_eq!(select_requested_present_mode(&all, mode), mode);
+            assert_eq!(PresentMode::from_vk(mode.to_vk()), Some(mode));
+        }
+
+        let fifo_only = [vk::PresentModeKHR::FIFO];
+        assert_eq!(
+            select_requested_present_mode(&fifo_only, PresentMode::Mailbox),
+            PresentMode::Fifo
+        );
+        assert_eq!(
+            select_requested_present_mode(&fifo_only, PresentMode::Immediate),
+            PresentMode::Fifo
+        );
+        let no_mailbox = [vk::PresentModeKHR::IMMEDIATE, vk::PresentModeKHR::FIFO];
+        assert_eq!(
+            select_requested_present_mode(&no_mailbox, PresentMode::Mailbox),
+            PresentMode::Fifo
+        );
+        assert_eq!(
+            select_requested_present_mode(&no_mailbox, PresentMode::Immediate),
+            PresentMode::Immediate
+        );
+    }
+}
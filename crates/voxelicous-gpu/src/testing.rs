@@ -0,0 +1,12 @@
+//! Helpers for tests that need a real GPU.
+//!
+//! Enabled by the `testing` feature. Tests that use [`test_gpu`] are marked
+//! `#[ignore = "needs a Vulkan device"]` and run with
+//! `cargo test -- --ignored` on a machine with one.
+
+use crate::context::{GpuContext, GpuContextBuilder};
+
+/// Create a context without validation, or `None` without a Vulkan device.
+pub fn test_gpu() -> Option<GpuContext> {
+    GpuContextBuilder::new().validation(false).build().ok()
+}
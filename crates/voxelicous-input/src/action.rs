@@ -17,6 +17,9 @@ pub enum InputBinding {
     KeyWithModifiers(KeyCode, Modifiers),
     /// A mouse button.
     Mouse(MouseButton),
+    /// The key that types a character on the current keyboard layout,
+    /// matched case-insensitively (e.g. `'z'` is `KeyW` on AZERTY).
+    Character(char),
 }
 
 impl InputBinding {
@@ -37,6 +40,12 @@ impl InputBinding {
     pub const fn mouse(button: MouseButton) -> Self {
         Self::Mouse(button)
     }
+
+    /// Create a layout-dependent character binding.
+    #[must_use]
+    pub const fn character(c: char) -> Self {
+        Self::Character(c)
+    }
 }
 
 impl From<KeyCode> for InputBinding {
@@ -45,6 +54,12 @@ impl From<KeyCode> for InputBinding {
     }
 }
 
+impl From<char> for InputBinding {
+    fn from(c: char) -> Self {
+        Self::Character(c)
+    }
+}
+
 impl From<MouseButton> for InputBinding {
     fn from(button: MouseButton) -> Self {
         Self::Mouse(button)
@@ -136,24 +151,39 @@ impl ActionMap {
     }
 
     /// Update action states based on keyboard and mouse state.
+    ///
+    /// When several bindings share a key, only the one with the most specific
+    /// satisfied modifier mask fires, so holding Ctrl+S triggers a `Ctrl+S`
+    /// binding but not a plain `S` binding.
     pub fn update(&mut self, keyboard: &KeyboardState, mouse: &MouseState) {
+        let chord_masks = self.active_chord_masks(keyboard.modifiers().without_locks());
+        let chord_matches = |key: &KeyCode, modifiers: Modifiers| {
+            chord_masks.get(key).copied().unwrap_or_default() == modifiers
+        };
+
         for action in self.actions.values_mut() {
             // Check if any binding is pressed
             let any_pressed = action.bindings.iter().any(|binding| match binding {
-                InputBinding::Key(key) => keyboard.is_pressed(*key),
+                InputBinding::Key(key) => {
+                    keyboard.is_pressed(*key) && chord_matches(key, Modifiers::empty())
+                }
                 InputBinding::KeyWithModifiers(key, modifiers) => {
-                    keyboard.is_pressed(*key) && keyboard.modifiers().contains(*modifiers)
+                    keyboard.is_pressed(*key) && chord_matches(key, *modifiers)
                 }
                 InputBinding::Mouse(button) => mouse.is_pressed(*button),
+                InputBinding::Character(c) => keyboard.is_char_pressed(*c),
             });
 
             // Check if any binding was just pressed
             let any_just_pressed = action.bindings.iter().any(|binding| match binding {
-                InputBinding::Key(key) => keyboard.is_just_pressed(*key),
+                InputBinding::Key(key) => {
+                    keyboard.is_just_pressed(*key) && chord_matches(key, Modifiers::empty())
+                }
                 InputBinding::KeyWithModifiers(key, modifiers) => {
-                    keyboard.is_just_pressed(*key) && keyboard.modifiers().contains(*modifiers)
+                    keyboard.is_just_pressed(*key) && chord_matches(key, *modifiers)
                 }
                 InputBinding::Mouse(button) => mouse.is_just_pressed(*button),
+                InputBinding::Character(c) => keyboard.is_char_just_pressed(*c),
             });
 
             // Check if any binding was just released (and none are still pressed)
@@ -163,6 +193,7 @@ impl ActionMap {
                         keyboard.is_just_released(*key)
                     }
                     InputBinding::Mouse(button) => mouse.is_just_released(*button),
+                    InputBinding::Character(c) => keyboard.is_char_just_released(*c),
                 });
 
             // Update action state
@@ -178,6 +209,27 @@ impl ActionMap {
         }
     }
 
+    /// For each chord key, find the most specific modifier mask satisfied by `held`.
+    fn active_chord_masks(&self, held: Modifiers) -> HashMap<KeyCode, Modifiers> {
+        let mut masks: HashMap<KeyCode, Modifiers> = HashMap::new();
+        for binding in self.actions.values().flat_map(|a| &a.bindings) {
+            let InputBinding::KeyWithModifiers(key, required) = *binding else {
+                continue;
+            };
+            if !held.contains(required) {
+                continue;
+            }
+            let best = masks.entry(key).or_default();
+            let more_specific = required.bits().count_ones() > best.bits().count_ones()
+                || (required.bits().count_ones() == best.bits().count_ones()
+                    && required.bits() > best.bits());
+            if more_specific {
+                *best = required;
+            }
+        }
+        masks
+    }
+
     /// Returns `true` if the action is currently pressed.
     #[must_use]
     pub fn is_pressed(&self, action: &str) -> bool {
@@ -288,4 +340,93 @@ mod tests {
         assert_eq!(bindings.len(), 1);
         assert_eq!(bindings[0], InputBinding::Key(KeyCode::KeyB));
     }
+
+    #[test]
+    fn chord_suppresses_plain_key_binding() {
+        let mut actions = ActionMap::builder()
+            .bind("move_back", KeyCode::KeyS)
+            .bind(
+                "save",
+                InputBinding::key_with_modifiers(KeyCode::KeyS, Modifiers::CTRL),
+            )
+            .build();
+        let mut keyboard = KeyboardState::new();
+        let mouse = MouseState::new();
+
+        // Ctrl+S fires only the chord.
+        keyboard.set_modifiers(Modifiers::CTRL);
+        keyboard.press(KeyCode::KeyS);
+        actions.update(&keyboard, &mouse);
+        assert!(actions.is_just_pressed("save"));
+        assert!(actions.is_pressed("save"));
+        assert!(!actions.is_just_pressed("move_back"));
+        assert!(!actions.is_pressed("move_back"));
+
+        keyboard.release(KeyCode::KeyS);
+        actions.update(&keyboard, &mouse);
+        keyboard.end_frame();
+        actions.end_frame();
+
+        // Plain S fires only the plain binding.
+        keyboard.set_modifiers(Modifiers::empty());
+        keyboard.press(KeyCode::KeyS);
+        actions.update(&keyboard, &mouse);
+        assert!(actions.is_just_pressed("move_back"));
+        assert!(!actions.is_pressed("save"));
+    }
+
+    #[test]
+    fn character_binding_follows_the_layout() {
+        use winit::event::ElementState;
+        use winit::keyboard::{Key, PhysicalKey};
+
+        let mut actions = ActionMap::builder()
+            .bind("forward_physical", KeyCode::KeyW)
+            .bind("forward_azerty", 'z')
+            .bind("forward_qwerty", InputBinding::character('w'))
+            .build();
+        let mut keyboard = KeyboardState::new();
+        let mouse = MouseState::new();
+
+        // AZERTY: the key in the QWERTY W position types 'z'.
+        let key_w = PhysicalKey::Code(KeyCode::KeyW);
+        keyboard.process_key(key_w, &Key::Character("z".into()), ElementState::Pressed);
+        actions.update(&keyboard, &mouse);
+        assert!(actions.is_just_pressed("forward_physical"));
+        assert!(actions.is_just_pressed("forward_azerty"));
+        assert!(!actions.is_pressed("forward_qwerty"));
+        keyboard.end_frame();
+        actions.end_frame();
+
+        // Shift changes the logical key to 'Z'; releasing still lifts 'z'.
+        keyboard.process_key(key_w, &Key::Character("Z".into()), ElementState::Released);
+        actions.update(&keyboard, &mouse);
+        assert!(actions.is_just_released("forward_azerty"));
+        assert!(actions.is_just_released("forward_physical"));
+        keyboard.end_frame();
+        actions.end_frame();
+
+        // Dead keys press the physical key but no character.
+        let dead = Key::Dead(Some('^'));
+        let key_bracket = PhysicalKey::Code(KeyCode::BracketLeft);
+        keyboard.process_key(key_bracket, &dead, ElementState::Pressed);
+        assert!(keyboard.is_pressed(KeyCode::BracketLeft));
+        assert!(!keyboard.is_char_pressed('^'));
+        keyboard.process_key(key_bracket, &dead, ElementState::Released);
+        assert!(!keyboard.is_pressed(KeyCode::BracketLeft));
+    }
+
+    #[test]
+    fn unrelated_modifiers_keep_plain_binding() {
+        let mut actions = ActionMap::builder()
+            .bind("move_forward", KeyCode::KeyW)
+            .build();
+        let mut keyboard = KeyboardState::new();
+        let mouse = MouseState::new();
+
+        keyboard.set_modifiers(Modifiers::SHIFT);
+        keyboard.press(KeyCode::KeyW);
+        actions.update(&keyboard, &mouse);
+        assert!(actions.is_pressed("move_forward"));
+    }
 }
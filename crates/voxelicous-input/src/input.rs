@@ -34,7 +34,7 @@ use crate::mouse::{CursorMode, MouseButton, MouseState};
 ///     self.input.end_frame();
 /// }
 /// ```
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct InputManager {
     /// Keyboard input state.
     keyboard: KeyboardState,
@@ -42,6 +42,20 @@ pub struct InputManager {
     mouse: MouseState,
     /// Action mappings.
     actions: ActionMap,
+    /// Scale applied to raw mouse motion by [`Self::look_delta`].
+    look_sensitivity: f32,
+    /// Whether [`Self::look_delta`] flips the Y axis.
+    invert_y: bool,
+    /// Weight of the previous frame in the smoothed look motion; `0.0` is off.
+    mouse_smoothing: f32,
+    /// Raw mouse motion averaged over frames by [`Self::update`].
+    smoothed_motion: Vec2,
+}
+
+impl Default for InputManager {
+    fn default() -> Self {
+        Self::with_actions(ActionMap::default())
+    }
 }
 
 impl InputManager {
@@ -58,6 +72,10 @@ impl InputManager {
             keyboard: KeyboardState::new(),
             mouse: MouseState::new(),
             actions,
+            look_sensitivity: 1.0,
+            invert_y: false,
+            mouse_smoothing: 0.0,
+            smoothed_motion: Vec2::ZERO,
         }
     }
 
@@ -88,8 +106,7 @@ impl InputManager {
                 true
             }
             WindowEvent::ModifiersChanged(modifiers) => {
-                self.keyboard
-                    .set_modifiers(Modifiers::from(modifiers.state()));
+                self.keyboard.set_modifier_state(modifiers.state());
                 true
             }
             WindowEvent::CursorMoved { position, .. } => {
@@ -122,6 +139,9 @@ impl InputManager {
     /// **IMPORTANT**: Call this at the START of your update loop, before querying actions.
     pub fn update(&mut self) {
         self.actions.update(&self.keyboard, &self.mouse);
+        self.smoothed_motion = self
+            .smoothed_motion
+            .lerp(self.mouse.raw_delta(), 1.0 - self.mouse_smoothing);
     }
 
     /// Called at the end of each frame to transition button states and reset deltas.
@@ -180,6 +200,67 @@ impl InputManager {
         self.mouse.raw_delta()
     }
 
+    /// Get raw mouse motion scaled by the look sensitivity, with Y flipped if inverted.
+    ///
+    /// With [mouse smoothing](Self::set_mouse_smoothing) on, the motion is the
+    /// smoothed value from the last [`Self::update`]. Use this for camera look;
+    /// [`Self::mouse_raw_delta`] stays unadjusted.
+    #[must_use]
+    pub fn look_delta(&self) -> Vec2 {
+        let motion = if self.mouse_smoothing > 0.0 {
+            self.smoothed_motion
+        } else {
+            self.mouse.raw_delta()
+        };
+        let delta = motion * self.look_sensitivity;
+        if self.invert_y {
+            Vec2::new(delta.x, -delta.y)
+        } else {
+            delta
+        }
+    }
+
+    /// Get the look sensitivity (scale applied to raw mouse motion).
+    #[must_use]
+    pub const fn look_sensitivity(&self) -> f32 {
+        self.look_sensitivity
+    }
+
+    /// Set the look sensitivity (scale applied to raw mouse motion).
+    pub fn set_look_sensitivity(&mut self, sensitivity: f32) {
+        self.look_sensitivity = sensitivity;
+    }
+
+    /// Returns `true` if look Y is inverted.
+    #[must_use]
+    pub const fn invert_y(&self) -> bool {
+        self.invert_y
+    }
+
+    /// Set whether look Y is inverted.
+    pub fn set_invert_y(&mut self, invert: bool) {
+        self.invert_y = invert;
+    }
+
+    /// Get the look smoothing factor.
+    #[must_use]
+    pub const fn mouse_smoothing(&self) -> f32 {
+        self.mouse_smoothing
+    }
+
+    /// Set how much [`Self::look_delta`] is smoothed across frames.
+    ///
+    /// Each [`Self::update`] keeps `factor` of the previous smoothed motion
+    /// and takes the rest from this frame's raw motion (an exponential moving
+    /// average). `0.0` passes raw motion through; the factor is clamped to
+    /// `[0, 0.99]` so the look never freezes.
+    pub fn set_mouse_smoothing(&mut self, factor: f32) {
+        self.mouse_smoothing = factor.clamp(0.0, 0.99);
+        if self.mouse_smoothing <= 0.0 {
+            self.smoothed_motion = Vec2::ZERO;
+        }
+    }
+
     /// Get scroll wheel delta.
     #[must_use]
     pub const fn scroll_delta(&self) -> Vec2 {
@@ -247,11 +328,15 @@ impl InputManager {
     pub fn clear(&mut self) {
         self.keyboard.clear();
         self.mouse.clear();
+        self.smoothed_motion = Vec2::ZERO;
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use winit::event::ElementState;
+    use winit::keyboard::{Key, ModifiersState, NamedKey, PhysicalKey};
+
     use super::*;
 
     #[test]
@@ -268,4 +353,98 @@ mod tests {
         let input = InputManager::with_actions(actions);
         assert!(!input.is_action_pressed("test"));
     }
+
+    #[test]
+    fn look_delta_applies_sensitivity_and_invert() {
+        let mut input = InputManager::new();
+        input.process_device_event(&DeviceEvent::MouseMotion {
+            delta: (10.0, -4.0),
+        });
+        assert_eq!(input.look_delta(), Vec2::new(10.0, -4.0));
+
+        input.set_look_sensitivity(0.5);
+        assert_eq!(input.look_delta(), Vec2::new(5.0, -2.0));
+
+        input.set_invert_y(true);
+        assert_eq!(input.look_delta(), Vec2::new(5.0, 2.0));
+        assert_eq!(input.mouse_raw_delta(), Vec2::new(10.0, -4.0));
+    }
+
+    #[test]
+    fn mouse_smoothing_averages_look_motion() {
+        let frame = |input: &mut InputManager, dx: f32| {
+            input.process_device_event(&DeviceEvent::MouseMotion {
+                delta: (f64::from(dx), 0.0),
+            });
+            input.update();
+            let look = input.look_delta();
+            assert_eq!(input.mouse_raw_delta(), Vec2::new(dx, 0.0));
+            input.end_frame();
+            look
+        };
+        let raw = [10.0, 10.0, 0.0, 0.0, -20.0];
+
+        // Off by default: look follows the raw motion exactly.
+        let mut input = InputManager::new();
+        assert!(input.mouse_smoothing().abs() < f32::EPSILON);
+        for dx in raw {
+            assert_eq!(frame(&mut input, dx), Vec2::new(dx, 0.0));
+        }
+
+        let mut input = InputManager::new();
+        input.set_mouse_smoothing(0.5);
+        let looks: Vec<Vec2> = raw.iter().map(|&dx| frame(&mut input, dx)).collect();
+        let expected = [5.0, 7.5, 3.75, 1.875, -9.0625].map(|x| Vec2::new(x, 0.0));
+        assert_eq!(looks, expected);
+
+        // Stopping the mouse decays the look motion toward zero.
+        input.set_mouse_smoothing(0.9);
+        let mut last = frame(&mut input, 0.0).length();
+        for _ in 0..50 {
+            let next = frame(&mut input, 0.0).length();
+            assert!(next < last);
+            last = next;
+        }
+        assert!(last < 0.1);
+
+        input.set_mouse_smoothing(2.0);
+        assert!((input.mouse_smoothing() - 0.99).abs() < f32::EPSILON);
+        input.set_mouse_smoothing(0.0);
+        assert_eq!(frame(&mut input, 3.0), Vec2::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn modifiers_changed_keeps_lock_toggles() {
+        let mut input = InputManager::new();
+        let caps = PhysicalKey::Code(KeyCode::CapsLock);
+        let caps_key = Key::Named(NamedKey::CapsLock);
+        input
+            .keyboard
+            .process_key(caps, &caps_key, ElementState::Pressed);
+        input
+            .keyboard
+            .process_key(caps, &caps_key, ElementState::Released);
+
+        let shift = winit::event::Modifiers::from(ModifiersState::SHIFT);
+        assert!(input.process_window_event(&WindowEvent::ModifiersChanged(shift)));
+        let mods = input.modifiers();
+        assert!(mods.caps_lock());
+        assert!(mods.shift());
+        assert!(!mods.ctrl());
+        assert!(!mods.num_lock());
+
+        // Pressing again turns it off; auto-repeat does not flip it back on.
+        input
+            .keyboard
+            .process_key(caps, &caps_key, ElementState::Pressed);
+        input
+            .keyboard
+            .process_key(caps, &caps_key, ElementState::Pressed);
+        let ctrl = winit::event::Modifiers::from(ModifiersState::CONTROL);
+        input.process_window_event(&WindowEvent::ModifiersChanged(ctrl));
+        let mods = input.modifiers();
+        assert!(!mods.caps_lock());
+        assert!(!mods.shift());
+        assert!(mods.ctrl());
+    }
 }
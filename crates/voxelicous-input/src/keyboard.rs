@@ -2,19 +2,24 @@
 
 use hashbrown::HashMap;
 use winit::event::{ElementState, KeyEvent};
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{Key, KeyCode, ModifiersState, PhysicalKey};
 
 use crate::button_state::ButtonState;
 use crate::modifiers::Modifiers;
 
 /// Keyboard input state.
 ///
-/// Tracks the state of all keyboard keys and modifier keys.
+/// Tracks the state of all keyboard keys and modifier keys, plus the
+/// layout-dependent characters those keys produce.
 #[derive(Debug, Default)]
 pub struct KeyboardState {
     /// State of individual keys by key code.
     keys: HashMap<KeyCode, ButtonState>,
-    /// Current modifier key state.
+    /// State of typed characters, lowercased.
+    chars: HashMap<char, ButtonState>,
+    /// Character each held key produced when it was pressed.
+    key_chars: HashMap<KeyCode, char>,
+    /// Current modifier key state, including lock toggles.
     modifiers: Modifiers,
 }
 
@@ -27,14 +32,52 @@ impl KeyboardState {
 
     /// Process a key event.
     pub fn process_key_event(&mut self, event: &KeyEvent) {
-        let PhysicalKey::Code(key_code) = event.physical_key else {
+        self.process_key(event.physical_key, &event.logical_key, event.state);
+    }
+
+    /// Process a key from its physical and logical parts.
+    ///
+    /// Same as [`Self::process_key_event`], for synthetic input (winit's
+    /// `KeyEvent` cannot be built outside winit). A logical key that is a
+    /// single character also presses that character until the physical key
+    /// is released, even if the layout or Shift changes meanwhile. Dead keys
+    /// and multi-character text only update the physical key. Pressing Caps
+    /// Lock, Num Lock or Scroll Lock toggles its flag in [`Self::modifiers`];
+    /// the flags start off, as winit cannot report the initial lock state.
+    pub fn process_key(&mut self, physical: PhysicalKey, logical: &Key, state: ElementState) {
+        let PhysicalKey::Code(key_code) = physical else {
             return;
         };
 
-        let state = self.keys.entry(key_code).or_default();
-        match event.state {
-            ElementState::Pressed => state.press(),
-            ElementState::Released => state.release(),
+        let key_state = self.keys.entry(key_code).or_default();
+        match state {
+            ElementState::Pressed => {
+                if !key_state.is_pressed() {
+                    self.modifiers.toggle(lock_flag(key_code));
+                }
+                key_state.press();
+                if let Some(c) = single_char(logical) {
+                    if let Some(previous) = self.key_chars.insert(key_code, c) {
+                        if previous != c {
+                            self.release_char(previous);
+                        }
+                    }
+                    self.chars.entry(c).or_default().press();
+                }
+            }
+            ElementState::Released => {
+                key_state.release();
+                if let Some(c) = self.key_chars.remove(&key_code) {
+                    self.release_char(c);
+                }
+            }
+        }
+    }
+
+    /// Release `c` unless another held key still produces it.
+    fn release_char(&mut self, c: char) {
+        if !self.key_chars.values().any(|&held| held == c) {
+            self.chars.entry(c).or_default().release();
         }
     }
 
@@ -43,6 +86,11 @@ impl KeyboardState {
         self.modifiers = modifiers;
     }
 
+    /// Update the held modifier keys from winit, keeping lock toggles.
+    pub fn set_modifier_state(&mut self, state: ModifiersState) {
+        self.modifiers = Modifiers::from(state) | self.modifiers.intersection(Modifiers::LOCKS);
+    }
+
     /// Returns `true` if the key is currently pressed.
     #[must_use]
     pub fn is_pressed(&self, key: KeyCode) -> bool {
@@ -61,6 +109,30 @@ impl KeyboardState {
         self.keys.get(&key).is_some_and(|s| s.is_just_released())
     }
 
+    /// Returns `true` if a held key produced the character (case-insensitive).
+    #[must_use]
+    pub fn is_char_pressed(&self, c: char) -> bool {
+        self.chars
+            .get(&lowercase(c))
+            .is_some_and(|s| s.is_pressed())
+    }
+
+    /// Returns `true` if the character was just pressed this frame.
+    #[must_use]
+    pub fn is_char_just_pressed(&self, c: char) -> bool {
+        self.chars
+            .get(&lowercase(c))
+            .is_some_and(|s| s.is_just_pressed())
+    }
+
+    /// Returns `true` if the character was just released this frame.
+    #[must_use]
+    pub fn is_char_just_released(&self, c: char) -> bool {
+        self.chars
+            .get(&lowercase(c))
+            .is_some_and(|s| s.is_just_released())
+    }
+
     /// Get the current modifier key state.
     #[must_use]
     pub const fn modifiers(&self) -> Modifiers {
@@ -69,18 +141,56 @@ impl KeyboardState {
 
     /// Called at end of frame to transition button states.
     pub fn end_frame(&mut self) {
-        for state in self.keys.values_mut() {
+        for state in self.keys.values_mut().chain(self.chars.values_mut()) {
             state.end_frame();
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn press(&mut self, key: KeyCode) {
+        self.keys.entry(key).or_default().press();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn release(&mut self, key: KeyCode) {
+        self.keys.entry(key).or_default().release();
+    }
+
     /// Clear all key states.
     pub fn clear(&mut self) {
         self.keys.clear();
+        self.chars.clear();
+        self.key_chars.clear();
         self.modifiers = Modifiers::empty();
     }
 }
 
+/// The lock flag a key toggles, or no flags for other keys.
+const fn lock_flag(key: KeyCode) -> Modifiers {
+    match key {
+        KeyCode::CapsLock => Modifiers::CAPS_LOCK,
+        KeyCode::NumLock => Modifiers::NUM_LOCK,
+        KeyCode::ScrollLock => Modifiers::SCROLL_LOCK,
+        _ => Modifiers::empty(),
+    }
+}
+
+/// The lowercased character of a logical key that types exactly one.
+fn single_char(key: &Key) -> Option<char> {
+    let Key::Character(text) = key else {
+        return None;
+    };
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(lowercase(c)),
+        _ => None,
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,7 +6,8 @@ use winit::keyboard::ModifiersState;
 bitflags! {
     /// Modifier key flags.
     ///
-    /// Represents the state of modifier keys (Shift, Ctrl, Alt, Super).
+    /// Represents the state of modifier keys (Shift, Ctrl, Alt, Super) and
+    /// lock key toggles (Caps Lock, Num Lock, Scroll Lock).
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
     pub struct Modifiers: u8 {
         /// Shift key is pressed.
@@ -17,6 +18,14 @@ bitflags! {
         const ALT   = 0b0000_0100;
         /// Super/Windows/Command key is pressed.
         const SUPER = 0b0000_1000;
+        /// Caps Lock is on.
+        const CAPS_LOCK   = 0b0001_0000;
+        /// Num Lock is on.
+        const NUM_LOCK    = 0b0010_0000;
+        /// Scroll Lock is on.
+        const SCROLL_LOCK = 0b0100_0000;
+        /// All lock key toggles.
+        const LOCKS = Self::CAPS_LOCK.bits() | Self::NUM_LOCK.bits() | Self::SCROLL_LOCK.bits();
     }
 }
 
@@ -48,8 +57,38 @@ impl Modifiers {
     pub const fn super_key(self) -> bool {
         self.contains(Self::SUPER)
     }
+
+    /// Returns `true` if Caps Lock is on.
+    #[inline]
+    #[must_use]
+    pub const fn caps_lock(self) -> bool {
+        self.contains(Self::CAPS_LOCK)
+    }
+
+    /// Returns `true` if Num Lock is on.
+    #[inline]
+    #[must_use]
+    pub const fn num_lock(self) -> bool {
+        self.contains(Self::NUM_LOCK)
+    }
+
+    /// Returns `true` if Scroll Lock is on.
+    #[inline]
+    #[must_use]
+    pub const fn scroll_lock(self) -> bool {
+        self.contains(Self::SCROLL_LOCK)
+    }
+
+    /// The held modifier keys, without lock toggles.
+    #[inline]
+    #[must_use]
+    pub const fn without_locks(self) -> Self {
+        self.difference(Self::LOCKS)
+    }
 }
 
+/// Winit does not report lock keys in [`ModifiersState`], so the result
+/// never has lock flags set.
 impl From<ModifiersState> for Modifiers {
     fn from(state: ModifiersState) -> Self {
         let mut modifiers = Self::empty();
@@ -80,6 +119,16 @@ mod tests {
         assert!(mods.ctrl());
         assert!(!mods.alt());
         assert!(!mods.super_key());
+        assert!(!mods.caps_lock());
+    }
+
+    #[test]
+    fn lock_flags_are_separate_from_held_keys() {
+        let mods = Modifiers::CTRL | Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK;
+        assert!(mods.caps_lock());
+        assert!(mods.num_lock());
+        assert!(!mods.scroll_lock());
+        assert_eq!(mods.without_locks(), Modifiers::CTRL);
     }
 
     #[test]
@@ -2,6 +2,12 @@
 
 use glam::Vec3;
 
+pub mod raycast;
+pub mod trigger;
+
+pub use raycast::{raycast, raycast_batch, raycast_with_occupancy};
+pub use trigger::trigger_blocks_in_box;
+
 /// Ray for collision detection.
 #[derive(Debug, Clone, Copy)]
 pub struct Ray {
@@ -0,0 +1,294 @@
+//! Voxel grid raycasts.
+
+use glam::{IVec3, Vec3};
+use rayon::prelude::*;
+use voxelicous_voxel::BRICK_SIZE;
+
+use crate::{Ray, RaycastHit};
+
+/// Most voxels a single [`raycast`] visits before giving up.
+///
+/// A ray crosses at most `3 * (max_dist + 1)` cells, so this only cuts off
+/// casts longer than about 5000 blocks.
+pub const MAX_RAYCAST_STEPS: u32 = 16_384;
+
+/// Cast a ray through the unit voxel grid.
+///
+/// `is_solid` reports whether the block at a position is solid. Returns the
+/// first solid block within `max_dist` of the origin. A ray starting inside a
+/// solid block hits it at distance `0.0` with a zero normal.
+///
+/// Misses when `max_dist` or the ray is not finite, when `max_dist` is
+/// negative, after [`MAX_RAYCAST_STEPS`] voxels, or at the edge of the
+/// `i32` grid.
+pub fn raycast<F>(is_solid: F, ray: &Ray, max_dist: f32) -> Option<RaycastHit>
+where
+    F: Fn([i32; 3]) -> bool,
+{
+    raycast_with_occupancy(is_solid, |_| None, ray, max_dist)
+}
+
+/// [`raycast`] that skips empty space using brick occupancy.
+///
+/// `brick_occupancy` returns the 4x4x4 cell mask of the brick containing a
+/// position, as `ClipmapStreamingController::brick_occupancy` does for LOD0,
+/// or `None` when it is unknown. Every cell holding a block `is_solid`
+/// reports as solid must have its bit set. `is_solid` is only called in
+/// occupied cells and bricks of unknown occupancy, and `brick_occupancy`
+/// once per brick the ray enters; the hit is the same as [`raycast`]'s.
+pub fn raycast_with_occupancy<F, B>(
+    is_solid: F,
+    brick_occupancy: B,
+    ray: &Ray,
+    max_dist: f32,
+) -> Option<RaycastHit>
+where
+    F: Fn([i32; 3]) -> bool,
+    B: Fn([i32; 3]) -> Option<u64>,
+{
+    if !max_dist.is_finite() || max_dist < 0.0 || !ray.origin.is_finite() {
+        return None;
+    }
+    let dir = ray.direction.normalize_or_zero();
+    if dir == Vec3::ZERO {
+        return None;
+    }
+
+    let step = IVec3::new(axis_step(dir.x), axis_step(dir.y), axis_step(dir.z));
+    let mut voxel = ray.origin.floor().as_ivec3();
+    let next_boundary = voxel.as_vec3() + step.max(IVec3::ZERO).as_vec3();
+    let mut t_max = Vec3::select(
+        dir.cmpeq(Vec3::ZERO),
+        Vec3::INFINITY,
+        (next_boundary - ray.origin) / dir,
+    );
+    let t_delta = (1.0 / dir).abs();
+
+    let brick_size = BRICK_SIZE as i32;
+    let mut brick = (
+        voxel.div_euclid(IVec3::splat(brick_size)),
+        brick_occupancy(voxel.to_array()),
+    );
+
+    let mut t = 0.0;
+    let mut normal = Vec3::ZERO;
+    for _ in 0..MAX_RAYCAST_STEPS {
+        let brick_coord = voxel.div_euclid(IVec3::splat(brick_size));
+        if brick_coord != brick.0 {
+            brick = (brick_coord, brick_occupancy(voxel.to_array()));
+        }
+        if cell_may_be_solid(brick.1, voxel) && is_solid(voxel.to_array()) {
+            return Some(RaycastHit {
+                position: ray.origin + dir * t,
+                normal,
+                distance: t,
+                block_position: voxel.to_array(),
+            });
+        }
+
+        let axis = if t_max.x <= t_max.y && t_max.x <= t_max.z {
+            0
+        } else if t_max.y <= t_max.z {
+            1
+        } else {
+            2
+        };
+        t = t_max[axis];
+        if t > max_dist {
+            return None;
+        }
+        voxel[axis] = voxel[axis].checked_add(step[axis])?;
+        t_max[axis] += t_delta[axis];
+        normal = Vec3::ZERO;
+        normal[axis] = -step[axis] as f32;
+    }
+    None
+}
+
+/// Cast many rays in parallel, e.g. for AI line-of-sight or lighting probes.
+///
+/// Results are in the same order as `rays`. `is_solid` is shared by all
+/// worker threads, so any occupancy cache behind it is shared too.
+///
+/// Empty bricks and cells are skipped as in [`raycast_with_occupancy`];
+/// pass `|_| None` for `brick_occupancy` to test every voxel.
+pub fn raycast_batch<F, B>(
+    is_solid: F,
+    brick_occupancy: B,
+    rays: &[Ray],
+    max_dist: f32,
+) -> Vec<Option<RaycastHit>>
+where
+    F: Fn([i32; 3]) -> bool + Sync,
+    B: Fn([i32; 3]) -> Option<u64> + Sync,
+{
+    rays.par_iter()
+        .map(|ray| raycast_with_occupancy(&is_solid, &brick_occupancy, ray, max_dist))
+        .collect()
+}
+
+/// Whether the 2x2x2-voxel cell of `voxel` may hold a solid block, given the
+/// occupancy mask of its brick.
+fn cell_may_be_solid(occupancy: Option<u64>, voxel: IVec3) -> bool {
+    let Some(mask) = occupancy else {
+        return true;
+    };
+    let cell = voxel.rem_euclid(IVec3::splat(BRICK_SIZE as i32)) / 2;
+    mask >> (cell.x + cell.y * 4 + cell.z * 16) & 1 == 1
+}
+
+fn axis_step(d: f32) -> i32 {
+    if d > 0.0 {
+        1
+    } else if d < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downward_ray_grid_hits_flat_ground() {
+        // Solid ground up to and including y = 10.
+        let ground = |p: [i32; 3]| p[1] <= 10;
+        let rays: Vec<Ray> = (-8..8)
+            .flat_map(|x| (-8..8).map(move |z| (x, z)))
+            .map(|(x, z)| Ray {
+                origin: Vec3::new(x as f32 + 0.5, 50.0, z as f32 + 0.25),
+                direction: Vec3::NEG_Y,
+            })
+            .collect();
+
+        let hits = raycast_batch(ground, |_| None, &rays, 100.0);
+        assert_eq!(hits.len(), rays.len());
+        for (ray, hit) in rays.iter().zip(&hits) {
+            let hit = hit.expect("ray should hit the ground");
+            assert_eq!(
+                hit.block_position,
+                [ray.origin.x.floor() as i32, 10, ray.origin.z.floor() as i32]
+            );
+            assert!((hit.position.y - 11.0).abs() < 1e-5);
+            assert!((hit.distance - 39.0).abs() < 1e-5);
+            assert_eq!(hit.normal, Vec3::Y);
+        }
+
+        // Out of range and upward rays miss.
+        assert!(raycast_batch(ground, |_| None, &rays[..4], 20.0)
+            .iter()
+            .all(Option::is_none));
+        let up = Ray {
+            origin: Vec3::new(0.5, 50.0, 0.5),
+            direction: Vec3::Y,
+        };
+        assert!(raycast(ground, &up, 100.0).is_none());
+    }
+
+    #[test]
+    fn downward_rays_hit_golden_fixture_surfaces() {
+        use voxelicous_voxel::testing::{golden_volume, GoldenKind};
+
+        const DEPTH: u32 = 4;
+        let size = 1i32 << DEPTH;
+        for kind in GoldenKind::ALL {
+            let volume = golden_volume(kind, DEPTH);
+            let index = |x: i32, y: i32, z: i32| (x + y * size + z * size * size) as usize;
+            let solid = |p: [i32; 3]| {
+                p.iter().all(|c| (0..size).contains(c)) && !volume[index(p[0], p[1], p[2])].is_air()
+            };
+            for z in 0..size {
+                for x in 0..size {
+                    let ray = Ray {
+                        origin: Vec3::new(x as f32 + 0.5, size as f32 + 4.0, z as f32 + 0.5),
+                        direction: Vec3::NEG_Y,
+                    };
+                    let top = (0..size).rev().find(|&y| solid([x, y, z]));
+                    let hit = raycast(solid, &ray, 64.0);
+                    assert_eq!(
+                        hit.map(|hit| hit.block_position),
+                        top.map(|y| [x, y, z]),
+                        "{kind:?} column ({x}, {z})"
+                    );
+                    if let Some(hit) = hit {
+                        assert_eq!(hit.normal, Vec3::Y, "{kind:?} column ({x}, {z})");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unbounded_rays_terminate() {
+        let down = Ray {
+            origin: Vec3::new(0.5, 50.0, 0.5),
+            direction: Vec3::NEG_Y,
+        };
+        let ground = |p: [i32; 3]| p[1] <= 10;
+        for max_dist in [f32::INFINITY, f32::NAN, -1.0] {
+            assert!(raycast(ground, &down, max_dist).is_none(), "{max_dist}");
+        }
+
+        // Empty space stops at the step cap, and the grid edge never
+        // overflows.
+        let empty = |_: [i32; 3]| false;
+        assert!(raycast(empty, &down, f32::MAX).is_none());
+        let edge = Ray {
+            origin: Vec3::new(0.5, 3.0e9, 0.5),
+            direction: Vec3::Y,
+        };
+        assert!(raycast(empty, &edge, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn occupancy_skips_empty_cells_with_the_same_hits() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Sparse pillars over flat ground, with the occupancy masks a
+        // clipmap would report for them.
+        let solid = |p: [i32; 3]| p[1] <= 2 || (p[0] % 13 == 0 && p[2] % 11 == 0 && p[1] < 40);
+        let occupancy = |p: [i32; 3]| {
+            let min = IVec3::from_array(p).div_euclid(IVec3::splat(8)) * 8;
+            let mut mask = 0u64;
+            for bit in 0..64 {
+                let cell = min + IVec3::new(bit % 4, bit / 4 % 4, bit / 16) * 2;
+                let hit = (0..8).any(|i| {
+                    let voxel = cell + IVec3::new(i & 1, i >> 1 & 1, i >> 2);
+                    solid(voxel.to_array())
+                });
+                mask |= u64::from(hit) << bit;
+            }
+            Some(mask)
+        };
+        let rays: Vec<Ray> = (0..64)
+            .map(|i| Ray {
+                origin: Vec3::new(6.5, 30.0, 5.5),
+                direction: Vec3::new((i % 8) as f32 - 3.5, -2.0, (i / 8) as f32 - 3.5),
+            })
+            .collect();
+
+        let visits = AtomicUsize::new(0);
+        let counted = |p: [i32; 3]| {
+            visits.fetch_add(1, Ordering::Relaxed);
+            solid(p)
+        };
+        let plain = raycast_batch(counted, |_| None, &rays, 200.0);
+        let plain_visits = visits.swap(0, Ordering::Relaxed);
+        let skipping = raycast_batch(counted, occupancy, &rays, 200.0);
+        let skipping_visits = visits.load(Ordering::Relaxed);
+
+        for (plain, skipping) in plain.iter().zip(&skipping) {
+            let plain = plain.expect("every ray reaches the ground");
+            let skipping = skipping.expect("every ray reaches the ground");
+            assert_eq!(skipping.block_position, plain.block_position);
+            assert_eq!(skipping.normal, plain.normal);
+            assert_eq!(skipping.distance, plain.distance);
+        }
+        assert!(
+            skipping_visits * 4 < plain_visits,
+            "{skipping_visits} vs {plain_visits} voxels"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Trigger volumes: blocks an entity overlaps without colliding.
+
+use glam::Vec3;
+use voxelicous_core::BlockId;
+
+/// Voxels overlapping the box `min..max` whose block matches `predicate`.
+///
+/// `world_fn` returns the block at integer voxel coordinates, e.g.
+/// `ClipmapStreamingController::block_at_world`. A voxel counts when its unit
+/// cube overlaps the box interior; faces that only touch do not. Results are
+/// ordered by Y, then Z, then X, so game code can react to "entered lava" or
+/// "touched water" without treating those blocks as solid.
+pub fn trigger_blocks_in_box<F, P>(
+    world_fn: F,
+    min: Vec3,
+    max: Vec3,
+    predicate: P,
+) -> Vec<([i64; 3], BlockId)>
+where
+    F: Fn(i64, i64, i64) -> BlockId,
+    P: Fn(BlockId) -> bool,
+{
+    let (lo, hi) = (min.min(max), min.max(max));
+    let first = lo.floor().as_i64vec3();
+    let last = hi.ceil().as_i64vec3() - 1;
+
+    let mut matches = Vec::new();
+    for y in first.y..=last.y {
+        for z in first.z..=last.z {
+            for x in first.x..=last.x {
+                let block = world_fn(x, y, z);
+                if predicate(block) {
+                    matches.push(([x, y, z], block));
+                }
+            }
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_in_a_pool_reports_only_water() {
+        // Stone floor at y <= 0, water above it up to y = 2, air higher.
+        let world = |x: i64, y: i64, _z: i64| match y {
+            ..=0 => BlockId::STONE,
+            1..=2 if x < 4 => BlockId::WATER,
+            _ => BlockId::AIR,
+        };
+        let is_water = |block: BlockId| block == BlockId::WATER;
+
+        // Entity spanning the floor, the pool and the air above it.
+        let hits = trigger_blocks_in_box(
+            world,
+            Vec3::new(2.2, 0.5, 0.2),
+            Vec3::new(4.8, 3.5, 0.8),
+            is_water,
+        );
+        let expected: Vec<_> = [[2, 1, 0], [3, 1, 0], [2, 2, 0], [3, 2, 0]]
+            .into_iter()
+            .map(|p| (p, BlockId::WATER))
+            .collect();
+        assert_eq!(hits, expected);
+
+        // Standing exactly on the pool surface only touches the water.
+        let on_top = trigger_blocks_in_box(
+            world,
+            Vec3::new(0.2, 3.0, 0.2),
+            Vec3::new(0.8, 4.8, 0.8),
+            is_water,
+        );
+        assert!(on_top.is_empty());
+
+        // Stone is reported only when the predicate asks for it.
+        let floor = trigger_blocks_in_box(
+            world,
+            Vec3::new(0.2, -0.5, 0.2),
+            Vec3::new(0.8, 1.5, 0.8),
+            |block| block == BlockId::STONE,
+        );
+        assert_eq!(
+            floor,
+            vec![([0, -1, 0], BlockId::STONE), ([0, 0, 0], BlockId::STONE)]
+        );
+    }
+}
@@ -3,13 +3,18 @@
 use std::collections::HashMap;
 
 use crate::events::{
-    CategoryStats, EventCategory, MemoryStats, ProfilerSnapshot, QueueSizes, TimingEvent,
+    CategoryStats, EventCategory, FrameTimeHistogram, MemoryStats, ProfilerSnapshot, QueueSizes,
+    TimingEvent,
 };
 use crate::ring_buffer::RingBuffer;
+use crate::tracing_bridge::span_name;
 
 /// Number of recent samples to keep for percentile calculations.
 const SAMPLE_HISTORY_SIZE: usize = 100;
 
+/// Number of slowest per-page events kept for the snapshot.
+const SLOWEST_PAGE_EVENTS: usize = 8;
+
 /// Collects and aggregates profiling events.
 pub struct Collector {
     /// Ring buffer for incoming events.
@@ -28,6 +33,10 @@ pub struct Collector {
     fps: f32,
     /// Current frame time in ms.
     frame_time_ms: f32,
+    /// Frame time distribution.
+    histogram: FrameTimeHistogram,
+    /// Slowest page build/encode events, slowest first.
+    slowest_pages: Vec<TimingEvent>,
 }
 
 impl Default for Collector {
@@ -49,6 +58,8 @@ impl Collector {
             frame_number: 0,
             fps: 0.0,
             frame_time_ms: 0.0,
+            histogram: FrameTimeHistogram::default(),
+            slowest_pages: Vec::with_capacity(SLOWEST_PAGE_EVENTS + 1),
         }
     }
 
@@ -78,6 +89,12 @@ impl Collector {
         self.frame_number = frame_number;
         self.fps = fps;
         self.frame_time_ms = frame_time_ms;
+        self.histogram.record(frame_time_ms);
+    }
+
+    /// Replace the frame time histogram with one using the given bucket edges (ms).
+    pub fn configure_histogram(&mut self, buckets: &[f32]) {
+        self.histogram = FrameTimeHistogram::new(buckets);
     }
 
     /// Process all pending events and update statistics.
@@ -86,6 +103,13 @@ impl Collector {
         let events = self.buffer.drain();
 
         for event in events {
+            if matches!(
+                event.category,
+                EventCategory::ClipmapPageBuild | EventCategory::ClipmapEncode
+            ) {
+                self.track_slow_page(event);
+            }
+
             // Update stats
             let stats = self
                 .stats
@@ -117,6 +141,18 @@ impl Collector {
     pub fn reset(&mut self) {
         self.stats.clear();
         self.samples.clear();
+        self.histogram.reset();
+        self.slowest_pages.clear();
+    }
+
+    fn track_slow_page(&mut self, event: TimingEvent) {
+        let index = self
+            .slowest_pages
+            .partition_point(|slow| slow.duration_ns >= event.duration_ns);
+        if index < SLOWEST_PAGE_EVENTS {
+            self.slowest_pages.insert(index, event);
+            self.slowest_pages.truncate(SLOWEST_PAGE_EVENTS);
+        }
     }
 
     /// Get a snapshot of current profiling data.
@@ -132,13 +168,21 @@ impl Collector {
             EventCategory::FrameRender => 3,
             EventCategory::GpuSubmit => 4,
             EventCategory::FramePresent => 5,
+            EventCategory::FramePacing => 6,
             EventCategory::ClipmapPageBuild => 10,
             EventCategory::ClipmapEncode => 11,
             EventCategory::GpuClipmapUpload => 12,
             EventCategory::GpuClipmapUnload => 13,
             EventCategory::ClipmapUpdate => 14,
-            EventCategory::Custom(id) => 100 + id as i32,
+            EventCategory::Custom(id) => 100 + i64::from(id),
         });
+        let custom_names = categories
+            .iter()
+            .filter_map(|stats| match stats.category {
+                EventCategory::Custom(id) => span_name(id).map(|name| (id, name.to_string())),
+                _ => None,
+            })
+            .collect();
 
         ProfilerSnapshot {
             frame_number: self.frame_number,
@@ -147,6 +191,9 @@ impl Collector {
             categories,
             queues: self.queues,
             memory: self.memory,
+            frame_time_histogram: self.histogram.clone(),
+            slowest_page_events: self.slowest_pages.clone(),
+            custom_names,
         }
     }
 
@@ -208,4 +255,61 @@ mod tests {
 
         assert!(collector.get_stats(EventCategory::Frame).is_none());
     }
+
+    #[test]
+    fn slowest_page_events_keep_their_coordinates() {
+        let mut collector = Collector::new();
+        for i in 0u16..20 {
+            collector.record(TimingEvent::with_context(
+                EventCategory::ClipmapPageBuild,
+                u64::from(i) * 1_000,
+                [i32::from(i), -1, 2],
+            ));
+        }
+        collector.record(TimingEvent::with_context(
+            EventCategory::ClipmapEncode,
+            15_500,
+            [7, 7, 7],
+        ));
+        collector.record_duration(EventCategory::Frame, 1_000_000);
+        collector.flush();
+
+        let slowest = collector.snapshot().slowest_page_events;
+        assert_eq!(slowest.len(), SLOWEST_PAGE_EVENTS);
+        assert_eq!(slowest[0].context, [19, -1, 2]);
+        assert_eq!(slowest[0].duration_ns, 19_000);
+        assert!(slowest
+            .windows(2)
+            .all(|pair| pair[0].duration_ns >= pair[1].duration_ns));
+        assert!(slowest.iter().any(|event| event.context == [7, 7, 7]));
+        assert!(slowest
+            .iter()
+            .all(|event| event.category != EventCategory::Frame));
+
+        collector.reset();
+        assert!(collector.snapshot().slowest_page_events.is_empty());
+    }
+
+    #[test]
+    fn frame_times_land_in_histogram_buckets() {
+        let mut collector = Collector::new();
+        collector.configure_histogram(&[16.6, 8.3, 33.3]);
+
+        for (frame, ms) in [4.0, 8.3, 8.4, 16.6, 16.7, 20.0, 33.3, 40.0, 250.0]
+            .into_iter()
+            .enumerate()
+        {
+            collector.set_frame_info(frame as u64, 0.0, ms);
+        }
+
+        let histogram = collector.snapshot().frame_time_histogram;
+        assert_eq!(histogram.edges_ms, vec![8.3, 16.6, 33.3]);
+        assert_eq!(histogram.counts, vec![2, 2, 3, 2]);
+        assert_eq!(histogram.overflow(), 2);
+        assert_eq!(histogram.count_above(16.6), 5);
+        assert_eq!(histogram.total(), 9);
+
+        collector.reset();
+        assert_eq!(collector.snapshot().frame_time_histogram.total(), 0);
+    }
 }
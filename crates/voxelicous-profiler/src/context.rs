@@ -108,6 +108,16 @@ pub fn report_memory(memory: MemoryStats) {
     }
 }
 
+/// Set the frame time histogram bucket edges in milliseconds.
+///
+/// Frames slower than the last edge are counted in an overflow bucket.
+/// Existing counts are discarded.
+pub fn configure_histogram(buckets: &[f32]) {
+    if let Some(ctx) = PROFILER.lock().as_mut() {
+        ctx.collector.configure_histogram(buckets);
+    }
+}
+
 /// Mark the end of a frame, flushing events and sending to clients.
 pub fn end_frame(frame_number: u64, fps: f32, frame_time_ms: f32) {
     let mut guard = PROFILER.lock();
@@ -18,6 +18,8 @@ pub enum EventCategory {
     GpuSync = 4,
     /// GPU queue submit.
     GpuSubmit = 5,
+    /// Frame pacing wait (sleep/spin to hold the target FPS).
+    FramePacing = 6,
     /// Clipmap page generation.
     ClipmapPageBuild = 10,
     /// Clipmap page/brick encoding work.
@@ -43,6 +45,7 @@ impl EventCategory {
             Self::FramePresent => "Present",
             Self::GpuSync => "GPU Sync",
             Self::GpuSubmit => "GPU Submit",
+            Self::FramePacing => "Pacing",
             Self::ClipmapPageBuild => "Page Build",
             Self::ClipmapEncode => "Encode",
             Self::GpuClipmapUpload => "GPU Upload",
@@ -202,6 +205,75 @@ pub struct MemoryStats {
     pub clipmap_memory_bytes: u64,
 }
 
+/// Default frame time bucket edges in milliseconds (120/60/30/20 FPS).
+pub const DEFAULT_HISTOGRAM_EDGES_MS: [f32; 4] = [8.3, 16.6, 33.3, 50.0];
+
+/// Histogram of frame times.
+///
+/// Bucket `i` counts frames with `edges_ms[i - 1] < frame_time <= edges_ms[i]`;
+/// one extra overflow bucket at the end counts frames slower than the last edge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameTimeHistogram {
+    /// Ascending bucket upper edges in milliseconds.
+    pub edges_ms: Vec<f32>,
+    /// Frame counts per bucket (`edges_ms.len() + 1` entries).
+    pub counts: Vec<u64>,
+}
+
+impl Default for FrameTimeHistogram {
+    fn default() -> Self {
+        Self::new(&DEFAULT_HISTOGRAM_EDGES_MS)
+    }
+}
+
+impl FrameTimeHistogram {
+    /// Create an empty histogram; edges are sorted and non-finite ones dropped.
+    #[must_use]
+    pub fn new(edges_ms: &[f32]) -> Self {
+        let mut edges_ms: Vec<f32> = edges_ms.iter().copied().filter(|e| e.is_finite()).collect();
+        edges_ms.sort_by(f32::total_cmp);
+        edges_ms.dedup();
+        let counts = vec![0; edges_ms.len() + 1];
+        Self { edges_ms, counts }
+    }
+
+    /// Bucket index for a frame time.
+    #[must_use]
+    pub fn bucket_index(&self, frame_time_ms: f32) -> usize {
+        self.edges_ms.partition_point(|&edge| edge < frame_time_ms)
+    }
+
+    /// Record one frame.
+    pub fn record(&mut self, frame_time_ms: f32) {
+        let index = self.bucket_index(frame_time_ms);
+        self.counts[index] += 1;
+    }
+
+    /// Frames slower than the last edge.
+    #[must_use]
+    pub fn overflow(&self) -> u64 {
+        self.counts.last().copied().unwrap_or(0)
+    }
+
+    /// Frames slower than `edge_ms`, which should be one of the bucket edges.
+    #[must_use]
+    pub fn count_above(&self, edge_ms: f32) -> u64 {
+        let first = self.edges_ms.partition_point(|&edge| edge <= edge_ms);
+        self.counts[first..].iter().sum()
+    }
+
+    /// Total recorded frames.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Clear all counts, keeping the edges.
+    pub fn reset(&mut self) {
+        self.counts.fill(0);
+    }
+}
+
 /// Complete profiler snapshot sent to TUI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfilerSnapshot {
@@ -217,6 +289,29 @@ pub struct ProfilerSnapshot {
     pub queues: QueueSizes,
     /// Memory stats.
     pub memory: MemoryStats,
+    /// Frame time histogram since the last reset.
+    pub frame_time_histogram: FrameTimeHistogram,
+    /// Slowest page build/encode events since the last reset, slowest first.
+    ///
+    /// Each event's context holds the page coordinate it was recorded for.
+    pub slowest_page_events: Vec<TimingEvent>,
+    /// Span names of the custom categories in `categories` that came from
+    /// the tracing bridge, by custom id.
+    pub custom_names: Vec<(u32, String)>,
+}
+
+impl ProfilerSnapshot {
+    /// Display name of `category`, using span names for bridged custom
+    /// categories.
+    #[must_use]
+    pub fn category_name(&self, category: EventCategory) -> &str {
+        if let EventCategory::Custom(id) = category {
+            if let Some((_, name)) = self.custom_names.iter().find(|(custom, _)| *custom == id) {
+                return name;
+            }
+        }
+        category.name()
+    }
 }
 
 impl Default for ProfilerSnapshot {
@@ -228,6 +323,9 @@ impl Default for ProfilerSnapshot {
             categories: Vec::new(),
             queues: QueueSizes::default(),
             memory: MemoryStats::default(),
+            frame_time_histogram: FrameTimeHistogram::default(),
+            slowest_page_events: Vec::new(),
+            custom_names: Vec::new(),
         }
     }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use crate::events::ProfilerSnapshot;
 
 /// Protocol version for compatibility checking.
-pub const PROTOCOL_VERSION: u8 = 1;
+pub const PROTOCOL_VERSION: u8 = 4;
 
 /// Message from server to client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +32,10 @@
 //! ```ignore
 //! voxelicous_profiler::end_frame(frame_number, fps, frame_time_ms);
 //! ```
+//!
+//! Existing `tracing` spans can be forwarded as well by adding
+//! [`ProfilerLayer`] to the subscriber (or calling [`install_tracing_bridge`]
+//! when there is none); each span name becomes its own category.
 
 mod collector;
 mod context;
@@ -39,17 +43,20 @@ mod events;
 pub mod ipc;
 mod macros;
 mod ring_buffer;
+mod tracing_bridge;
 
 // Re-export public API
 pub use context::{
-    end_frame, init, init_with_port, is_initialized, record, record_duration,
+    configure_histogram, end_frame, init, init_with_port, is_initialized, record, record_duration,
     record_duration_with_context, report_memory, report_queue_sizes, reset, shutdown, snapshot,
     DEFAULT_PORT,
 };
 pub use events::{
-    CategoryStats, EventCategory, MemoryStats, ProfilerSnapshot, QueueSizes, TimingEvent,
+    CategoryStats, EventCategory, FrameTimeHistogram, MemoryStats, ProfilerSnapshot, QueueSizes,
+    TimingEvent, DEFAULT_HISTOGRAM_EDGES_MS,
 };
 pub use macros::ScopeGuard;
+pub use tracing_bridge::{install_tracing_bridge, span_category, span_name, ProfilerLayer};
 
 // Re-export protocol types for the TUI client
 pub use ipc::protocol::{ClientMessage, ServerMessage, PROTOCOL_VERSION};
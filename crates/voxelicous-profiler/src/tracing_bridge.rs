@@ -0,0 +1,129 @@
+//! Forward `tracing` spans into profiler events.
+//!
+//! [`ProfilerLayer`] times every span between enter and exit and records it
+//! under a [`EventCategory::Custom`] category derived from the span name, so
+//! `#[instrument]`ed functions show up in the TUI without a manual
+//! `profile_scope!`. Events are only kept while the profiler is
+//! initialized.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use tracing::span;
+use tracing::subscriber::Interest;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::context::record_duration;
+use crate::events::EventCategory;
+
+/// Span names by custom category id, filled as span callsites register.
+static SPAN_NAMES: Mutex<BTreeMap<u32, &'static str>> = Mutex::new(BTreeMap::new());
+
+/// Time at which a span was last entered.
+struct EnteredAt(Instant);
+
+/// `tracing` layer that records span timings as profiler events.
+///
+/// Each enter/exit pair of a span is one event. Add it to an existing
+/// subscriber, or use [`install_tracing_bridge`] when the application has
+/// none.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfilerLayer;
+
+impl<S> Layer<S> for ProfilerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_span() {
+            if let EventCategory::Custom(id) = span_category(metadata.name()) {
+                SPAN_NAMES.lock().insert(id, metadata.name());
+            }
+        }
+        Interest::always()
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().replace(EnteredAt(Instant::now()));
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(EnteredAt(start)) = span.extensions_mut().remove::<EnteredAt>() else {
+            return;
+        };
+        let duration_ns = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        record_duration(span_category(span.name()), duration_ns);
+    }
+}
+
+/// Install a global subscriber that only feeds the profiler.
+///
+/// Returns `false` when a global subscriber is already set; applications
+/// with their own subscriber should add [`ProfilerLayer`] to it instead.
+#[must_use]
+pub fn install_tracing_bridge() -> bool {
+    tracing_subscriber::registry()
+        .with(ProfilerLayer)
+        .try_init()
+        .is_ok()
+}
+
+/// Profiler category for spans named `name`.
+///
+/// A stable hash of the name, so the same span lands in the same category
+/// across runs.
+#[must_use]
+pub fn span_category(name: &str) -> EventCategory {
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    EventCategory::Custom(hash)
+}
+
+/// Span name recorded for a custom category id, if the bridge has seen it.
+#[must_use]
+pub fn span_name(id: u32) -> Option<&'static str> {
+    SPAN_NAMES.lock().get(&id).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tracing::instrument]
+    fn bridged_work() -> u32 {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        7
+    }
+
+    #[test]
+    fn instrumented_function_produces_a_profiler_event() {
+        crate::init_with_port(0);
+        let subscriber = tracing_subscriber::registry().with(ProfilerLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            assert_eq!(bridged_work(), 7);
+            assert_eq!(bridged_work(), 7);
+        });
+        crate::end_frame(0, 0.0, 0.0);
+
+        let category = span_category("bridged_work");
+        let snapshot = crate::snapshot();
+        let stats = snapshot
+            .categories
+            .iter()
+            .find(|stats| stats.category == category)
+            .expect("bridged span was not recorded");
+        assert!(stats.count >= 2);
+        assert!(stats.min_ns >= 1_000_000);
+        assert_eq!(snapshot.category_name(category), "bridged_work");
+    }
+}
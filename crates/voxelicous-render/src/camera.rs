@@ -1,7 +1,24 @@
 //! Camera and view management.
 
-use glam::{Mat4, Vec3};
-use voxelicous_core::math::Frustum;
+use ash::vk;
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use voxelicous_core::math::{Frustum, Ray};
+
+use crate::sky::SkyConfig;
+
+/// Which angle [`Camera::fov`] fixes as the aspect ratio changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FovMode {
+    /// `fov` is the vertical angle; wider screens see more to the sides
+    /// (Hor+).
+    #[default]
+    Vertical,
+    /// `fov` is the horizontal angle; wider screens see less vertically.
+    Horizontal,
+    /// `fov` is the horizontal angle a 4:3 screen would have. The vertical
+    /// angle follows from it and stays fixed, so wider screens are Hor+.
+    Hor4x3,
+}
 
 /// Camera for rendering.
 #[derive(Debug, Clone)]
@@ -9,10 +26,18 @@ pub struct Camera {
     pub position: Vec3,
     pub direction: Vec3,
     pub up: Vec3,
+    /// Field of view in radians, measured as [`Self::fov_mode`] says.
     pub fov: f32,
+    /// Angle [`Self::fov`] fixes (see [`Camera::set_fov_mode`]).
+    pub fov_mode: FovMode,
     pub aspect: f32,
     pub near: f32,
     pub far: f32,
+    /// Sub-pixel projection offset in NDC (see [`Camera::set_jitter`]).
+    pub jitter: Vec2,
+    /// Map the far plane to depth 0 and the near plane to 1 (see
+    /// [`Camera::set_reverse_z`]).
+    pub reverse_z: bool,
 }
 
 impl Default for Camera {
@@ -22,9 +47,12 @@ impl Default for Camera {
             direction: Vec3::NEG_Z,
             up: Vec3::Y,
             fov: std::f32::consts::FRAC_PI_4,
+            fov_mode: FovMode::Vertical,
             aspect: 16.0 / 9.0,
             near: 0.1,
             far: 1000.0,
+            jitter: Vec2::ZERO,
+            reverse_z: false,
         }
     }
 }
@@ -46,9 +74,12 @@ impl Camera {
             direction,
             up,
             fov,
+            fov_mode: FovMode::Vertical,
             aspect,
             near,
             far,
+            jitter: Vec2::ZERO,
+            reverse_z: false,
         }
     }
 
@@ -67,6 +98,83 @@ impl Camera {
         self.aspect = aspect;
     }
 
+    /// Choose which angle [`Self::fov`] fixes.
+    pub fn set_fov_mode(&mut self, mode: FovMode) {
+        self.fov_mode = mode;
+    }
+
+    /// Angle [`Self::fov`] fixes.
+    pub const fn fov_mode(&self) -> FovMode {
+        self.fov_mode
+    }
+
+    /// Vertical field of view in radians, from [`Self::fov`] and the aspect.
+    pub fn vertical_fov(&self) -> f32 {
+        let half_tan = (self.fov * 0.5).tan();
+        match self.fov_mode {
+            FovMode::Vertical => self.fov,
+            FovMode::Horizontal => 2.0 * (half_tan / self.aspect).atan(),
+            FovMode::Hor4x3 => 2.0 * (half_tan * 0.75).atan(),
+        }
+    }
+
+    /// Horizontal field of view in radians, from [`Self::fov`] and the aspect.
+    pub fn horizontal_fov(&self) -> f32 {
+        2.0 * ((self.vertical_fov() * 0.5).tan() * self.aspect).atan()
+    }
+
+    /// Shift the projection by `offset` in NDC.
+    ///
+    /// One pixel spans `2.0 / width` (or `2.0 / height`) NDC units, so a
+    /// sub-pixel jitter `j` in pixels is `2.0 * j / resolution`. Pass
+    /// `Vec2::ZERO` to disable.
+    pub fn set_jitter(&mut self, offset: Vec2) {
+        self.jitter = offset;
+    }
+
+    /// Use a reverse-Z projection.
+    ///
+    /// Floating-point depth is most precise near 0, so putting the far plane
+    /// there keeps distant geometry from z-fighting. Depth tests must then
+    /// use [`Self::depth_compare_op`] and clear to [`Self::depth_clear_value`].
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+    }
+
+    /// Depth comparison that keeps the nearer fragment.
+    pub const fn depth_compare_op(&self) -> vk::CompareOp {
+        if self.reverse_z {
+            vk::CompareOp::GREATER
+        } else {
+            vk::CompareOp::LESS
+        }
+    }
+
+    /// Depth buffer clear value (the far plane).
+    pub const fn depth_clear_value(&self) -> f32 {
+        if self.reverse_z {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Set the jitter from sample `index` of the (2, 3) Halton pattern.
+    pub fn set_halton_jitter(&mut self, index: u32, period: u32, width: u32, height: u32) {
+        let pixel = voxelicous_core::math::HaltonSequence::jitter_2d(index, period);
+        let resolution = Vec2::new(width.max(1) as f32, height.max(1) as f32);
+        self.set_jitter(pixel * 2.0 / resolution);
+    }
+
+    /// World-space ray through an NDC position, matching the ray march shader.
+    pub fn ray_through(&self, ndc: Vec2) -> Ray {
+        let clip = Vec4::new(ndc.x, -ndc.y, 1.0, 1.0);
+        let view = self.inverse_projection_matrix() * clip;
+        let view_dir = (view.truncate() / view.w).extend(0.0);
+        let direction = (self.inverse_view_matrix() * view_dir).truncate();
+        Ray::new(self.position, direction)
+    }
+
     /// Get camera uniforms for GPU.
     pub fn uniforms(&self) -> CameraUniforms {
         self.uniforms_with_day_phase(0.25)
@@ -88,7 +196,17 @@ impl Camera {
     }
 
     pub fn projection_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far)
+        let fov_y = self.vertical_fov();
+        let projection = if self.reverse_z {
+            Mat4::perspective_rh(fov_y, self.aspect, self.far, self.near)
+        } else {
+            Mat4::perspective_rh(fov_y, self.aspect, self.near, self.far)
+        };
+        if self.jitter == Vec2::ZERO {
+            projection
+        } else {
+            Mat4::from_translation(self.jitter.extend(0.0)) * projection
+        }
     }
 
     pub fn inverse_view_matrix(&self) -> Mat4 {
@@ -105,7 +223,17 @@ impl Camera {
     }
 
     /// Extract frustum planes from the current camera state.
+    ///
+    /// Always built from the standard projection: plane extraction expects
+    /// the near plane at the low end of depth, and both bound the same volume.
     pub fn frustum(&self) -> Frustum {
+        if self.reverse_z {
+            let standard = Self {
+                reverse_z: false,
+                ..self.clone()
+            };
+            return standard.frustum();
+        }
         Frustum::from_view_projection(self.view_projection_matrix())
     }
 }
@@ -120,12 +248,31 @@ pub struct CameraUniforms {
     pub inverse_projection: [[f32; 4]; 4],
     pub position: [f32; 4],
     pub direction: [f32; 4],
+    /// Day phase (x), shadows disabled when non-zero (y) and animation time
+    /// in seconds (z).
     pub day_night: [f32; 4],
+    /// Sky horizon color (rgb) and haze (w).
+    pub sky_horizon: [f32; 4],
+    /// Sky zenith color (rgb).
+    pub sky_zenith: [f32; 4],
+}
+
+impl CameraUniforms {
+    /// Set the animation time in seconds, e.g. for leaf wind.
+    pub fn set_time(&mut self, seconds: f32) {
+        self.day_night[2] = seconds;
+    }
+
+    /// Replace the sky parameters.
+    pub fn set_sky(&mut self, sky: &SkyConfig) {
+        self.sky_horizon = sky.horizon.extend(sky.haze).to_array();
+        self.sky_zenith = sky.zenith.extend(0.0).to_array();
+    }
 }
 
 impl From<&Camera> for CameraUniforms {
     fn from(camera: &Camera) -> Self {
-        Self {
+        let mut uniforms = Self {
             view: camera.view_matrix().to_cols_array_2d(),
             projection: camera.projection_matrix().to_cols_array_2d(),
             inverse_view: camera.inverse_view_matrix().to_cols_array_2d(),
@@ -138,6 +285,176 @@ impl From<&Camera> for CameraUniforms {
                 0.0,
             ],
             day_night: [0.25, 0.0, 0.0, 0.0],
+            sky_horizon: [0.0; 4],
+            sky_zenith: [0.0; 4],
+        };
+        uniforms.set_sky(&SkyConfig::default());
+        uniforms
+    }
+}
+
+/// Two-eye setup for stereo (VR) rendering.
+///
+/// The eyes sit [`Self::ipd`] apart along the camera's right axis, centred
+/// on the camera, and each uses its own projection since headsets usually
+/// report asymmetric frusta per eye.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StereoConfig {
+    /// Interpupillary distance in world units.
+    pub ipd: f32,
+    /// Projection of the left eye (view 0).
+    pub left_proj: Mat4,
+    /// Projection of the right eye (view 1).
+    pub right_proj: Mat4,
+}
+
+impl StereoConfig {
+    /// Both eyes sharing `camera`'s projection.
+    pub fn symmetric(ipd: f32, camera: &Camera) -> Self {
+        let projection = camera.projection_matrix();
+        Self {
+            ipd,
+            left_proj: projection,
+            right_proj: projection,
         }
     }
+
+    /// Per-eye uniforms derived from a mono `camera`, left eye first.
+    ///
+    /// Everything but the view, projection and position (sky, day phase) is
+    /// copied from `camera`, so build these after applying time of day.
+    pub fn eye_uniforms(&self, camera: &CameraUniforms) -> [CameraUniforms; 2] {
+        let view = Mat4::from_cols_array_2d(&camera.view);
+        let inverse_view = Mat4::from_cols_array_2d(&camera.inverse_view);
+        let half_ipd = self.ipd * 0.5;
+        let eye = |offset: f32, projection: Mat4| {
+            let local = Vec3::new(offset, 0.0, 0.0);
+            let eye_inverse_view = inverse_view * Mat4::from_translation(local);
+            let position = eye_inverse_view.transform_point3(Vec3::ZERO);
+            CameraUniforms {
+                view: (Mat4::from_translation(-local) * view).to_cols_array_2d(),
+                projection: projection.to_cols_array_2d(),
+                inverse_view: eye_inverse_view.to_cols_array_2d(),
+                inverse_projection: projection.inverse().to_cols_array_2d(),
+                position: position.extend(1.0).to_array(),
+                ..*camera
+            }
+        };
+        [
+            eye(-half_ipd, self.left_proj),
+            eye(half_ipd, self.right_proj),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_shifts_rays_by_sub_pixel_offset() {
+        let mut camera = Camera::default();
+        let center = camera.ray_through(Vec2::ZERO).direction;
+
+        // Half a pixel right and a quarter pixel down on a 1280x720 target.
+        let jitter = Vec2::new(2.0 * 0.5 / 1280.0, 2.0 * 0.25 / 720.0);
+        camera.set_jitter(jitter);
+        let jittered = camera.ray_through(Vec2::ZERO).direction;
+        assert!(jittered.abs_diff_eq(center, 1e-2));
+        assert!(!jittered.abs_diff_eq(center, 1e-6));
+
+        // Shifting the projection by `j` moves every ray to the unjittered
+        // ray at `ndc - j` (the shader flips Y when building clip space).
+        camera.set_jitter(Vec2::ZERO);
+        let expected = camera.ray_through(Vec2::new(-jitter.x, jitter.y)).direction;
+        assert!(jittered.abs_diff_eq(expected, 1e-5));
+
+        // Halton jitter index 0 is (1/2, 1/3) - 0.5 pixels.
+        camera.set_halton_jitter(0, 8, 1280, 720);
+        let pixel = Vec2::new(0.0, 1.0 / 3.0 - 0.5);
+        assert!(camera
+            .jitter
+            .abs_diff_eq(pixel * 2.0 / Vec2::new(1280.0, 720.0), 1e-7));
+    }
+
+    #[test]
+    fn fov_modes_follow_the_aspect_ratio() {
+        // Angle between the forward ray and the ray at the screen edge.
+        let edge_angles = |camera: &Camera| {
+            let forward = camera.ray_through(Vec2::ZERO).direction;
+            let right = camera.ray_through(Vec2::new(1.0, 0.0)).direction;
+            let top = camera.ray_through(Vec2::new(0.0, -1.0)).direction;
+            (forward.angle_between(right), forward.angle_between(top))
+        };
+        let fov = 70f32.to_radians();
+        let mut camera = Camera {
+            fov,
+            aspect: 1.0,
+            ..Camera::default()
+        };
+
+        // A square screen sees `fov` both ways in every mode.
+        for mode in [FovMode::Vertical, FovMode::Horizontal] {
+            camera.set_fov_mode(mode);
+            let (half_h, half_v) = edge_angles(&camera);
+            assert!((half_h - fov * 0.5).abs() < 1e-4, "{mode:?}");
+            assert!((half_v - fov * 0.5).abs() < 1e-4, "{mode:?}");
+        }
+
+        // 21:9 in Vertical mode keeps the vertical angle and widens the
+        // horizontal one: tan(h / 2) = tan(v / 2) * aspect.
+        camera.set_fov_mode(FovMode::Vertical);
+        camera.set_aspect(21.0 / 9.0);
+        let (half_h, half_v) = edge_angles(&camera);
+        assert!((half_v - fov * 0.5).abs() < 1e-4);
+        let expected_h = ((fov * 0.5).tan() * 21.0 / 9.0).atan();
+        assert!((half_h - expected_h).abs() < 1e-4);
+        assert!((camera.horizontal_fov() - expected_h * 2.0).abs() < 1e-5);
+
+        // Horizontal mode keeps the horizontal angle instead.
+        camera.set_fov_mode(FovMode::Horizontal);
+        let (half_h, half_v) = edge_angles(&camera);
+        assert!((half_h - fov * 0.5).abs() < 1e-4);
+        assert!(half_v < fov * 0.5);
+
+        // Hor4x3 matches Horizontal at 4:3 and keeps that vertical angle
+        // on wider screens.
+        camera.set_aspect(4.0 / 3.0);
+        let at_4x3 = camera.vertical_fov();
+        camera.set_fov_mode(FovMode::Hor4x3);
+        assert!((camera.vertical_fov() - at_4x3).abs() < 1e-6);
+        camera.set_aspect(21.0 / 9.0);
+        let (half_h, half_v) = edge_angles(&camera);
+        assert!((half_v - at_4x3 * 0.5).abs() < 1e-4);
+        assert!(half_h > fov * 0.5);
+    }
+
+    #[test]
+    fn reverse_z_inverts_depth() {
+        let depth = |camera: &Camera, distance: f32| {
+            let point = camera.position + camera.direction * distance;
+            let clip = camera.view_projection_matrix() * point.extend(1.0);
+            clip.z / clip.w
+        };
+
+        let mut camera = Camera::default();
+        let (near, far) = (camera.near * 1.5, camera.far * 0.9);
+        assert!(depth(&camera, near) < 0.5);
+        assert!(depth(&camera, far) > 0.99);
+
+        let standard_ray = camera.ray_through(Vec2::new(0.3, -0.2)).direction;
+        camera.set_reverse_z(true);
+        assert!(depth(&camera, near) > 0.5);
+        assert!(depth(&camera, far) < 0.01);
+        assert!((depth(&camera, camera.near) - 1.0).abs() < 1e-5);
+        assert!(depth(&camera, camera.far).abs() < 1e-5);
+        assert_eq!(camera.depth_compare_op(), vk::CompareOp::GREATER);
+        assert_eq!(camera.depth_clear_value(), 0.0);
+
+        // Rays only depend on the view direction, not the depth mapping.
+        let reversed_ray = camera.ray_through(Vec2::new(0.3, -0.2)).direction;
+        assert!(reversed_ray
+            .normalize()
+            .abs_diff_eq(standard_ray.normalize(), 1e-5));
+    }
 }
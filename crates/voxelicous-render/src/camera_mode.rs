@@ -0,0 +1,138 @@
+//! Switching between manual free-fly and an automatic orbit.
+
+use glam::Vec3;
+
+use crate::camera::Camera;
+
+/// Who moves the camera.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CameraMode {
+    /// Driven by input through a [`FlyCamController`](crate::FlyCamController).
+    #[default]
+    FreeFly,
+    /// Circles `center` at a fixed height, always looking at it.
+    Orbit {
+        /// Point the camera circles around.
+        center: Vec3,
+        /// Horizontal distance from `center`.
+        radius: f32,
+        /// Turn rate in radians per second; positive speeds turn the view
+        /// the way a positive fly-camera yaw does.
+        speed: f32,
+    },
+}
+
+impl CameraMode {
+    /// Orbit around the point `camera` looks at, `radius` away horizontally.
+    ///
+    /// The camera keeps its height, so the orbit starts where it is.
+    #[must_use]
+    pub fn orbit_in_front(camera: &Camera, radius: f32, speed: f32) -> Self {
+        let direction = camera.direction.normalize_or_zero();
+        let horizontal = Vec3::new(direction.x, 0.0, direction.z).length();
+        // Follow the look ray to `radius` horizontally; looking straight
+        // up or down, orbit around a point level with the camera.
+        let center = if horizontal > 1e-3 {
+            camera.position + direction * (radius / horizontal)
+        } else {
+            camera.position + Vec3::Z * radius
+        };
+        Self::Orbit {
+            center,
+            radius,
+            speed,
+        }
+    }
+
+    /// Returns `true` for [`Self::Orbit`].
+    #[must_use]
+    pub const fn is_orbit(&self) -> bool {
+        matches!(self, Self::Orbit { .. })
+    }
+
+    /// Advance an orbit by `dt` seconds; free-fly leaves `camera` alone.
+    ///
+    /// The camera turns by `speed * dt` around the vertical axis through
+    /// `center`, snaps to `radius` and looks at `center`.
+    pub fn update(&self, camera: &mut Camera, dt: f32) {
+        let Self::Orbit {
+            center,
+            radius,
+            speed,
+        } = *self
+        else {
+            return;
+        };
+        let offset = camera.position - center;
+        let angle = offset.x.atan2(offset.z) + speed * dt;
+        let (sin, cos) = angle.sin_cos();
+        camera.position = center + Vec3::new(sin * radius, offset.y, cos * radius);
+        camera.direction = (center - camera.position).normalize_or(camera.direction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn orbit_turns_by_speed_times_dt_at_a_fixed_radius() {
+        let center = Vec3::new(10.0, 64.0, -5.0);
+        let mut camera = Camera::new(
+            center + Vec3::new(0.0, 20.0, 30.0),
+            center,
+            Vec3::Y,
+            1.0,
+            1.0,
+            0.1,
+            1000.0,
+        );
+        let mode = CameraMode::Orbit {
+            center,
+            radius: 30.0,
+            speed: 0.5,
+        };
+
+        // 0.5 rad/s for pi seconds is a quarter turn, from +Z to +X.
+        mode.update(&mut camera, FRAC_PI_2 / 0.5);
+        let expected = center + Vec3::new(30.0, 20.0, 0.0);
+        assert!((camera.position - expected).length() < 1e-3, "{camera:?}");
+        assert!((camera.direction - (center - expected).normalize()).length() < 1e-5);
+
+        // A small step turns by speed * dt and keeps the radius and height.
+        let before = camera.position - center;
+        mode.update(&mut camera, 0.1);
+        let after = camera.position - center;
+        let turned = after.x.atan2(after.z) - before.x.atan2(before.z);
+        assert!((turned - 0.05).abs() < 1e-5, "{turned}");
+        assert!((Vec3::new(after.x, 0.0, after.z).length() - 30.0).abs() < 1e-4);
+        assert!((after.y - 20.0).abs() < 1e-4);
+
+        // Free-fly never moves the camera on its own.
+        let still = camera.position;
+        CameraMode::FreeFly.update(&mut camera, 1.0);
+        assert_eq!(camera.position, still);
+    }
+
+    #[test]
+    fn orbit_in_front_centers_on_the_look_ray() {
+        let camera = Camera::new(
+            Vec3::new(0.0, 10.0, 0.0),
+            Vec3::new(3.0, 6.0, 0.0),
+            Vec3::Y,
+            1.0,
+            1.0,
+            0.1,
+            1000.0,
+        );
+        let CameraMode::Orbit { center, .. } = CameraMode::orbit_in_front(&camera, 6.0, 1.0) else {
+            panic!("expected an orbit");
+        };
+        assert!(
+            (center - Vec3::new(6.0, 2.0, 0.0)).length() < 1e-4,
+            "{center}"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Scripted camera paths for reproducible captures.
+//!
+//! A [`CameraPath`] pins the camera to keyframes indexed by frame number,
+//! so screenshot runs of a moving camera land on the same viewpoints on
+//! every machine regardless of frame time.
+
+use std::path::Path;
+
+use glam::Vec3;
+
+use crate::camera::Camera;
+
+/// Camera pose at one frame of a [`CameraPath`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    /// Frame number the pose applies to.
+    pub frame: u64,
+    /// Camera position.
+    pub position: Vec3,
+    /// Point the camera looks at.
+    pub target: Vec3,
+}
+
+/// Keyframed camera path, linearly interpolated between keyframes.
+///
+/// Before the first keyframe the camera holds the first pose, after the
+/// last it holds the last one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    /// Create a path from keyframes in any order.
+    ///
+    /// Later keyframes replace earlier ones on the same frame.
+    pub fn new(keyframes: impl IntoIterator<Item = CameraKeyframe>) -> Self {
+        let mut keyframes: Vec<_> = keyframes.into_iter().collect();
+        keyframes.sort_by_key(|key| key.frame);
+        let mut deduped: Vec<CameraKeyframe> = Vec::with_capacity(keyframes.len());
+        for key in keyframes {
+            match deduped.last_mut() {
+                Some(last) if last.frame == key.frame => *last = key,
+                _ => deduped.push(key),
+            }
+        }
+        Self { keyframes: deduped }
+    }
+
+    /// Parse a path with one keyframe per line.
+    ///
+    /// Each line is `frame px py pz tx ty tz`, separated by whitespace or
+    /// commas. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, CameraPathError> {
+        let mut keyframes = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || CameraPathError::InvalidLine(index + 1);
+            let fields: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|field| !field.is_empty())
+                .collect();
+            let [frame, rest @ ..] = fields.as_slice() else {
+                return Err(invalid());
+            };
+            let frame = frame.parse::<u64>().map_err(|_| invalid())?;
+            let coords = rest
+                .iter()
+                .map(|field| field.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            let [px, py, pz, tx, ty, tz] = coords.as_slice() else {
+                return Err(invalid());
+            };
+            keyframes.push(CameraKeyframe {
+                frame,
+                position: Vec3::new(*px, *py, *pz),
+                target: Vec3::new(*tx, *ty, *tz),
+            });
+        }
+        if keyframes.is_empty() {
+            return Err(CameraPathError::Empty);
+        }
+        Ok(Self::new(keyframes))
+    }
+
+    /// Read and parse a path file (see [`Self::parse`]).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CameraPathError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CameraPathError::ReadFailed(e.to_string()))?;
+        Self::parse(&text)
+    }
+
+    /// Keyframes sorted by frame.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Frame of the last keyframe, or `None` for an empty path.
+    pub fn last_frame(&self) -> Option<u64> {
+        self.keyframes.last().map(|key| key.frame)
+    }
+
+    /// Interpolated `(position, target)` at `frame`, or `None` for an empty
+    /// path.
+    pub fn pose_at(&self, frame: u64) -> Option<(Vec3, Vec3)> {
+        let next = self.keyframes.partition_point(|key| key.frame <= frame);
+        let (a, b) = match (
+            next.checked_sub(1).map(|i| &self.keyframes[i]),
+            self.keyframes.get(next),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(key), None) | (None, Some(key)) => return Some((key.position, key.target)),
+            (None, None) => return None,
+        };
+        let t = ((frame - a.frame) as f64 / (b.frame - a.frame) as f64) as f32;
+        Some((a.position.lerp(b.position, t), a.target.lerp(b.target, t)))
+    }
+
+    /// Move `camera` to its pose at `frame`.
+    ///
+    /// Returns `false` (leaving the camera alone) for an empty path.
+    pub fn apply(&self, camera: &mut Camera, frame: u64) -> bool {
+        let Some((position, target)) = self.pose_at(frame) else {
+            return false;
+        };
+        camera.set_position(position);
+        if target != position {
+            camera.look_at(target);
+        }
+        true
+    }
+}
+
+/// Errors from loading a [`CameraPath`].
+#[derive(Debug)]
+pub enum CameraPathError {
+    /// The path file could not be read.
+    ReadFailed(String),
+    /// A line was not `frame px py pz tx ty tz` (1-based line number).
+    InvalidLine(usize),
+    /// The path has no keyframes.
+    Empty,
+}
+
+impl std::fmt::Display for CameraPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadFailed(e) => write!(f, "Failed to read camera path: {e}"),
+            Self::InvalidLine(line) => write!(f, "Invalid camera path keyframe on line {line}"),
+            Self::Empty => write!(f, "Camera path has no keyframes"),
+        }
+    }
+}
+
+impl std::error::Error for CameraPathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_frames_follow_the_path() {
+        let path = CameraPath::parse(
+            "# frame  position        target\n\
+             0        0 100 0         0 100 10\n\
+             \n\
+             10,      20, 100, 0,     20, 100, 10\n",
+        )
+        .unwrap();
+        assert_eq!(path.last_frame(), Some(10));
+
+        let mut camera = Camera::default();
+        let uniforms: Vec<_> = [0, 5, 10]
+            .into_iter()
+            .map(|frame| {
+                assert!(path.apply(&mut camera, frame));
+                camera.uniforms()
+            })
+            .collect();
+
+        // The camera slides along +X while looking down +Z the whole time.
+        for (uniforms, x) in uniforms.iter().zip([0.0, 10.0, 20.0]) {
+            assert_eq!(uniforms.position[..3], [x, 100.0, 0.0]);
+            let direction = Vec3::from_slice(&uniforms.direction[..3]);
+            assert!((direction - Vec3::Z).length() < 1e-6);
+        }
+        assert_ne!(uniforms[0].view, uniforms[1].view);
+        assert_ne!(uniforms[1].view, uniforms[2].view);
+
+        // Past the end the camera holds the last pose.
+        path.apply(&mut camera, 50);
+        assert_eq!(camera.uniforms().view, uniforms[2].view);
+    }
+
+    #[test]
+    fn malformed_paths_are_rejected() {
+        assert!(matches!(
+            CameraPath::parse("0 1 2 3 4 5 6\n1 2 3\n"),
+            Err(CameraPathError::InvalidLine(2))
+        ));
+        assert!(matches!(
+            CameraPath::parse("# nothing\n"),
+            Err(CameraPathError::Empty)
+        ));
+        assert!(!CameraPath::default().apply(&mut Camera::default(), 0));
+    }
+}
@@ -2,17 +2,397 @@
 //!
 //! Uses the `ray_march_clipmap.comp` shader to render clipmap voxel data.
 
+use std::cell::Cell;
+
 use ash::vk;
 use gpu_allocator::MemoryLocation;
 use voxelicous_gpu::descriptors::{DescriptorPool, DescriptorSetLayoutBuilder};
 use voxelicous_gpu::error::{GpuError, Result};
 use voxelicous_gpu::memory::{GpuAllocator, GpuBuffer, GpuImage};
 use voxelicous_gpu::pipeline::ComputePipeline;
+use voxelicous_gpu::{GpuContext, GpuVendor};
 
 use crate::camera::CameraUniforms;
 use crate::clipmap_render::{ClipmapRenderPushConstants, ClipmapRenderer};
 use crate::debug::DebugMode;
 
+/// Workgroup size variant of `ray_march_clipmap.comp`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WorkgroupSize {
+    /// 8x8 invocations (64 threads).
+    #[default]
+    Size8,
+    /// 16x16 invocations (256 threads).
+    Size16,
+}
+
+impl WorkgroupSize {
+    /// All compiled variants.
+    pub const ALL: [Self; 2] = [Self::Size8, Self::Size16];
+
+    /// Workgroup edge length in pixels.
+    pub const fn edge(self) -> u32 {
+        match self {
+            Self::Size8 => 8,
+            Self::Size16 => 16,
+        }
+    }
+
+    /// Heuristic default for a GPU vendor.
+    ///
+    /// NVIDIA schedules 16x16 well; AMD's 64-wide waves and Intel/Apple's
+    /// smaller EUs are happiest with 8x8.
+    pub const fn for_vendor(vendor: GpuVendor) -> Self {
+        match vendor {
+            GpuVendor::Nvidia => Self::Size16,
+            GpuVendor::Amd | GpuVendor::Intel | GpuVendor::Apple | GpuVendor::Other(_) => {
+                Self::Size8
+            }
+        }
+    }
+
+    /// Workgroup counts needed to cover `width`x`height` pixels.
+    pub const fn group_count(self, width: u32, height: u32) -> (u32, u32) {
+        (width.div_ceil(self.edge()), height.div_ceil(self.edge()))
+    }
+}
+
+/// Pixel format of the pipeline's output image.
+///
+/// Any of these can be blitted straight to the swapchain. Formats other than
+/// `Rgba8Unorm` are written as a format-less storage image, which needs
+/// `shaderStorageImageWriteWithoutFormat` and storage image support for the
+/// format; [`Self::supported_or_rgba8`] checks both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// `R8G8B8A8_UNORM` (default).
+    #[default]
+    Rgba8Unorm,
+    /// `R16G16B16A16_SFLOAT`, for HDR compositing.
+    Rgba16Float,
+    /// `B8G8R8A8_UNORM`, the byte order most video encoders expect.
+    Bgra8Unorm,
+}
+
+impl OutputFormat {
+    /// Vulkan format of the output image.
+    pub const fn vk_format(self) -> vk::Format {
+        match self {
+            Self::Rgba8Unorm => vk::Format::R8G8B8A8_UNORM,
+            Self::Rgba16Float => vk::Format::R16G16B16A16_SFLOAT,
+            Self::Bgra8Unorm => vk::Format::B8G8R8A8_UNORM,
+        }
+    }
+
+    /// Whether the shaders must write this format without declaring it.
+    pub const fn needs_formatless_writes(self) -> bool {
+        !matches!(self, Self::Rgba8Unorm)
+    }
+
+    /// This format if `gpu` can render to it, otherwise `Rgba8Unorm`.
+    pub fn supported_or_rgba8(self, gpu: &GpuContext) -> Self {
+        let supported = !self.needs_formatless_writes()
+            || (gpu
+                .capabilities()
+                .supports_storage_image_write_without_format
+                && gpu.supports_storage_image_format(self.vk_format()));
+        if supported {
+            self
+        } else {
+            tracing::warn!("{self:?} output is not supported by this GPU, using RGBA8");
+            Self::Rgba8Unorm
+        }
+    }
+
+    /// Bytes per pixel in the output image and readback buffer.
+    pub const fn bytes_per_pixel(self) -> u32 {
+        match self {
+            Self::Rgba8Unorm | Self::Bgra8Unorm => 4,
+            Self::Rgba16Float => 8,
+        }
+    }
+
+    /// Readback buffer size for a `width`x`height` image.
+    pub const fn readback_size(self, width: u32, height: u32) -> u64 {
+        width as u64 * height as u64 * self.bytes_per_pixel() as u64
+    }
+
+    /// Convert raw readback bytes in this format to RGBA8.
+    ///
+    /// Half-float color is tonemapped with an ACES filmic curve before it is
+    /// quantized, so highlights above 1.0 roll off instead of clipping.
+    /// Alpha is clamped.
+    pub fn decode_rgba8(self, mut data: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::Rgba8Unorm => data,
+            Self::Bgra8Unorm => {
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+                data
+            }
+            Self::Rgba16Float => data
+                .chunks_exact(2)
+                .enumerate()
+                .map(|(channel, half)| {
+                    let value = f16_to_f32(u16::from_le_bytes([half[0], half[1]]));
+                    let value = if channel % 4 == 3 {
+                        value
+                    } else {
+                        tonemap_aces(value)
+                    };
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Map a linear HDR color channel to `0..=1` with Narkowicz's fit of the
+/// ACES filmic curve.
+fn tonemap_aces(x: f32) -> f32 {
+    let x = x.max(0.0);
+    (x * (2.51 * x + 0.03) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+}
+
+/// Decode an IEEE 754 half-precision float.
+fn f16_to_f32(bits: u16) -> f32 {
+    let negative = bits & 0x8000 != 0;
+    let exponent = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => f32::from(bits & 0x3ff) * 2f32.powi(-24),
+        0x1f => f32::from_bits(0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(((exponent + 112) << 23) | (mantissa << 13)),
+    };
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Frame-time driven auto-tuner for the ray march step budget.
+///
+/// Feed it each frame's time (e.g. the profiler's `frame_time_ms`) and pass
+/// [`Self::steps`] to [`ClipmapRayMarchPipeline::record`]. Frame times are
+/// smoothed, and the budget only moves once the smoothed time leaves a
+/// band around the target: it drops by an eighth when over budget and grows
+/// by a sixteenth when under, so quality recovers slower than it sheds and
+/// the budget does not oscillate. The budget always stays within the bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaxStepsTuner {
+    target_frame_ms: f32,
+    min_steps: u32,
+    max_steps: u32,
+    steps: u32,
+    smoothed_ms: Option<f32>,
+}
+
+impl MaxStepsTuner {
+    /// Fraction the smoothed frame time may stray from the target before
+    /// the budget changes.
+    pub const TOLERANCE: f32 = 0.1;
+    /// Weight of the newest frame in the smoothed frame time.
+    const SMOOTHING: f32 = 0.25;
+
+    /// Tune between `min_steps` and `max_steps` (in either order, at least
+    /// one) for `target_frame_ms`, starting at the full budget.
+    pub fn new(target_frame_ms: f32, min_steps: u32, max_steps: u32) -> Self {
+        let (min_steps, max_steps) = ordered_bounds(min_steps, max_steps);
+        Self {
+            target_frame_ms: target_frame_ms.max(0.0),
+            min_steps,
+            max_steps,
+            steps: max_steps,
+            smoothed_ms: None,
+        }
+    }
+
+    /// Current step budget.
+    pub const fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Frame time the tuner aims for, in milliseconds.
+    pub const fn target_frame_ms(&self) -> f32 {
+        self.target_frame_ms
+    }
+
+    /// Set the frame time to aim for, in milliseconds.
+    pub fn set_target_frame_ms(&mut self, target_frame_ms: f32) {
+        self.target_frame_ms = target_frame_ms.max(0.0);
+    }
+
+    /// Lowest and highest step budget.
+    pub const fn bounds(&self) -> (u32, u32) {
+        (self.min_steps, self.max_steps)
+    }
+
+    /// Set the step budget bounds (in either order, at least one), clamping
+    /// the current budget into them.
+    pub fn set_bounds(&mut self, min_steps: u32, max_steps: u32) {
+        (self.min_steps, self.max_steps) = ordered_bounds(min_steps, max_steps);
+        self.steps = self.steps.clamp(self.min_steps, self.max_steps);
+    }
+
+    /// Record one frame's time and return the budget for the next frame.
+    ///
+    /// Non-positive or non-finite times (e.g. before the profiler has seen
+    /// a frame) are ignored.
+    pub fn update(&mut self, frame_time_ms: f32) -> u32 {
+        if !frame_time_ms.is_finite() || frame_time_ms <= 0.0 {
+            return self.steps;
+        }
+        let smoothed = self.smoothed_ms.map_or(frame_time_ms, |previous| {
+            previous + (frame_time_ms - previous) * Self::SMOOTHING
+        });
+        self.smoothed_ms = Some(smoothed);
+
+        if smoothed > self.target_frame_ms * (1.0 + Self::TOLERANCE) {
+            self.steps = self.steps.saturating_sub((self.steps / 8).max(1));
+        } else if smoothed < self.target_frame_ms * (1.0 - Self::TOLERANCE) {
+            self.steps = self.steps.saturating_add((self.steps / 16).max(1));
+        }
+        self.steps = self.steps.clamp(self.min_steps, self.max_steps);
+        self.steps
+    }
+}
+
+fn ordered_bounds(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b).max(1), a.max(b).max(1))
+}
+
+/// One ray march dispatch covering part of the output image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DispatchTile {
+    /// Pixel offset of the tile's top-left corner.
+    offset: [u32; 2],
+    /// Workgroup counts along X and Y.
+    workgroups: [u32; 2],
+}
+
+/// Split a `width`x`height` image into dispatch tiles.
+///
+/// Tile sizes are rounded up to the workgroup size so tiles never overlap;
+/// a zero tile size dispatches the whole image at once.
+fn dispatch_tiles(
+    width: u32,
+    height: u32,
+    tile_size: (u32, u32),
+    workgroup: WorkgroupSize,
+) -> Vec<DispatchTile> {
+    let tile_w = if tile_size.0 == 0 {
+        width.max(1)
+    } else {
+        tile_size.0
+    }
+    .next_multiple_of(workgroup.edge());
+    let tile_h = if tile_size.1 == 0 {
+        height.max(1)
+    } else {
+        tile_size.1
+    }
+    .next_multiple_of(workgroup.edge());
+
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(tile_h as usize) {
+        for x in (0..width).step_by(tile_w as usize) {
+            let (groups_x, groups_y) =
+                workgroup.group_count(tile_w.min(width - x), tile_h.min(height - y));
+            tiles.push(DispatchTile {
+                offset: [x, y],
+                workgroups: [groups_x, groups_y],
+            });
+        }
+    }
+    tiles
+}
+
+/// Most views one dispatch can trace; the camera uniform buffer holds one
+/// [`CameraUniforms`] per view.
+const MAX_VIEWS: usize = 2;
+
+/// Output size for two `eye_width`x`eye_height` eyes side by side.
+pub const fn side_by_side_extent(eye_width: u32, eye_height: u32) -> (u32, u32) {
+    (eye_width * 2, eye_height)
+}
+
+/// Dispatches covering `views` side-by-side `width`x`height` views.
+///
+/// Each view's tiles are offset into its own column of the output and
+/// tagged with the view index for the `view_index` push constant.
+fn view_dispatches(
+    width: u32,
+    height: u32,
+    views: u32,
+    tile_size: (u32, u32),
+    workgroup: WorkgroupSize,
+) -> Vec<(u32, DispatchTile)> {
+    let tiles = dispatch_tiles(width, height, tile_size, workgroup);
+    (0..views)
+        .flat_map(|view| {
+            tiles.iter().map(move |tile| {
+                let mut tile = *tile;
+                tile.offset[0] += view * width;
+                (view, tile)
+            })
+        })
+        .collect()
+}
+
+/// Descriptor binding of the render stats counters in `ray_march_clipmap.comp`.
+const RENDER_STATS_BINDING: u32 = 2;
+
+/// Primary ray counters for one frame, summed over every view and tile.
+///
+/// Ray and hit counts are 32-bit on the GPU, enough for one ray per pixel
+/// up to about four billion pixels. Steps easily pass `u32::MAX` at 4K, so
+/// the shader carries them into a second word.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Primary rays traced, one per pixel.
+    pub rays: u32,
+    /// Primary rays that hit a voxel.
+    pub hits: u32,
+    /// Traversal steps taken by all primary rays.
+    pub steps: u64,
+}
+
+impl RenderStats {
+    /// Size of the GPU counter buffer: rays, hits and the low and high
+    /// step words.
+    pub const BUFFER_SIZE: u64 = 16;
+
+    fn from_counters(counters: [u32; 4]) -> Self {
+        Self {
+            rays: counters[0],
+            hits: counters[1],
+            steps: u64::from(counters[3]) << 32 | u64::from(counters[2]),
+        }
+    }
+
+    /// Fraction of rays that hit; `0.0` without rays.
+    #[must_use]
+    pub fn hit_rate(&self) -> f32 {
+        if self.rays == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.rays as f32
+        }
+    }
+
+    /// Mean traversal steps per ray; `0.0` without rays.
+    #[must_use]
+    pub fn steps_per_ray(&self) -> f32 {
+        if self.rays == 0 {
+            0.0
+        } else {
+            self.steps as f32 / self.rays as f32
+        }
+    }
+}
+
 /// Clipmap ray marching compute pipeline.
 pub struct ClipmapRayMarchPipeline {
     ray_march_pipeline: ComputePipeline,
@@ -27,8 +407,20 @@ pub struct ClipmapRayMarchPipeline {
     output_image: GpuImage,
     output_image_view: vk::ImageView,
     readback_buffer: GpuBuffer,
+    /// Per-frame [`RenderStats`] counters, written by the shader.
+    stats_buffers: Vec<GpuBuffer>,
+    render_stats_enabled: bool,
+    /// Frames recorded with stats whose counters have not been read yet.
+    stats_pending: Vec<Cell<bool>>,
+    /// Counters of the last finished frame, until taken.
+    latest_stats: Cell<Option<RenderStats>>,
     width: u32,
     height: u32,
+    /// Views traced side by side: 1 for mono, 2 for stereo.
+    views: u32,
+    tile_size: (u32, u32),
+    workgroup: WorkgroupSize,
+    output_format: OutputFormat,
 }
 
 impl ClipmapRayMarchPipeline {
@@ -42,10 +434,61 @@ impl ClipmapRayMarchPipeline {
         width: u32,
         height: u32,
         frames_in_flight: usize,
+    ) -> Result<Self> {
+        Self::new_with_workgroup(
+            device,
+            allocator,
+            width,
+            height,
+            frames_in_flight,
+            WorkgroupSize::default(),
+        )
+    }
+
+    /// Create a clipmap ray marching pipeline using a specific shader workgroup size.
+    ///
+    /// # Safety
+    /// The Vulkan device must be valid.
+    pub unsafe fn new_with_workgroup(
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+        width: u32,
+        height: u32,
+        frames_in_flight: usize,
+        workgroup: WorkgroupSize,
+    ) -> Result<Self> {
+        Self::new_with_format(
+            device,
+            allocator,
+            width,
+            height,
+            frames_in_flight,
+            workgroup,
+            OutputFormat::default(),
+        )
+    }
+
+    /// Create a clipmap ray marching pipeline with a specific workgroup size
+    /// and output image format.
+    ///
+    /// Pass the format through [`OutputFormat::supported_or_rgba8`] first
+    /// unless it is known to be supported.
+    ///
+    /// # Safety
+    /// The Vulkan device must be valid.
+    pub unsafe fn new_with_format(
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+        width: u32,
+        height: u32,
+        frames_in_flight: usize,
+        workgroup: WorkgroupSize,
+        output_format: OutputFormat,
     ) -> Result<Self> {
         let descriptor_set_layout = DescriptorSetLayoutBuilder::new()
             .uniform_buffer(0, vk::ShaderStageFlags::COMPUTE)
             .storage_image(1, vk::ShaderStageFlags::COMPUTE)
+            .storage_buffer(RENDER_STATS_BINDING, vk::ShaderStageFlags::COMPUTE)
             .build(device)?;
 
         let push_constant_range = vk::PushConstantRange::default()
@@ -53,7 +496,12 @@ impl ClipmapRayMarchPipeline {
             .offset(0)
             .size(ClipmapRenderPushConstants::SIZE);
 
-        let shader_code = voxelicous_shaders::ray_march_clipmap_shader();
+        let formatless = output_format.needs_formatless_writes();
+        let shader_code =
+            voxelicous_shaders::ray_march_clipmap_shader_for_output(workgroup.edge(), formatless)
+                .ok_or_else(|| {
+                GpuError::Other(format!("No ray march shader for {workgroup:?} workgroups"))
+            })?;
         let ray_march_pipeline = ComputePipeline::new(
             device,
             shader_code,
@@ -65,7 +513,11 @@ impl ClipmapRayMarchPipeline {
             .storage_image(0, vk::ShaderStageFlags::COMPUTE)
             .build(device)?;
 
-        let crosshair_shader_code = voxelicous_shaders::crosshair_overlay_shader();
+        let crosshair_shader_code = if formatless {
+            voxelicous_shaders::crosshair_overlay_formatless_shader()
+        } else {
+            voxelicous_shaders::crosshair_overlay_shader()
+        };
         let crosshair_pipeline = ComputePipeline::new(
             device,
             crosshair_shader_code,
@@ -76,7 +528,7 @@ impl ClipmapRayMarchPipeline {
         let mut camera_buffers = Vec::with_capacity(frames_in_flight);
         for i in 0..frames_in_flight {
             let buffer = allocator.create_buffer(
-                std::mem::size_of::<CameraUniforms>() as u64,
+                (std::mem::size_of::<CameraUniforms>() * MAX_VIEWS) as u64,
                 vk::BufferUsageFlags::UNIFORM_BUFFER,
                 MemoryLocation::CpuToGpu,
                 &format!("clipmap_camera_uniforms_{i}"),
@@ -86,7 +538,7 @@ impl ClipmapRayMarchPipeline {
 
         let image_info = vk::ImageCreateInfo::default()
             .image_type(vk::ImageType::TYPE_2D)
-            .format(vk::Format::R8G8B8A8_UNORM)
+            .format(output_format.vk_format())
             .extent(vk::Extent3D {
                 width,
                 height,
@@ -105,7 +557,7 @@ impl ClipmapRayMarchPipeline {
         let view_info = vk::ImageViewCreateInfo::default()
             .image(output_image.image)
             .view_type(vk::ImageViewType::TYPE_2D)
-            .format(vk::Format::R8G8B8A8_UNORM)
+            .format(output_format.vk_format())
             .subresource_range(vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 base_mip_level: 0,
@@ -119,12 +571,24 @@ impl ClipmapRayMarchPipeline {
             .map_err(|e| GpuError::Other(format!("Failed to create image view: {e}")))?;
 
         let readback_buffer = allocator.create_buffer(
-            (width * height * 4) as u64,
+            output_format.readback_size(width, height),
             vk::BufferUsageFlags::TRANSFER_DST,
             MemoryLocation::GpuToCpu,
             "clipmap_readback",
         )?;
 
+        let mut stats_buffers = Vec::with_capacity(frames_in_flight);
+        for i in 0..frames_in_flight {
+            let buffer = allocator.create_buffer(
+                RenderStats::BUFFER_SIZE,
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+                MemoryLocation::GpuToCpu,
+                &format!("clipmap_render_stats_{i}"),
+            )?;
+            buffer.write(&[0u32; 4])?;
+            stats_buffers.push(buffer);
+        }
+
         let pool_sizes = [
             vk::DescriptorPoolSize::default()
                 .ty(vk::DescriptorType::UNIFORM_BUFFER)
@@ -132,6 +596,9 @@ impl ClipmapRayMarchPipeline {
             vk::DescriptorPoolSize::default()
                 .ty(vk::DescriptorType::STORAGE_IMAGE)
                 .descriptor_count(frames_in_flight as u32),
+            vk::DescriptorPoolSize::default()
+                .ty(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(frames_in_flight as u32),
         ];
 
         let descriptor_pool = DescriptorPool::new(device, frames_in_flight as u32, &pool_sizes)?;
@@ -159,7 +626,11 @@ impl ClipmapRayMarchPipeline {
             let buffer_info = vk::DescriptorBufferInfo::default()
                 .buffer(camera_buffers[i].buffer)
                 .offset(0)
-                .range(std::mem::size_of::<CameraUniforms>() as u64);
+                .range((std::mem::size_of::<CameraUniforms>() * MAX_VIEWS) as u64);
+            let stats_info = vk::DescriptorBufferInfo::default()
+                .buffer(stats_buffers[i].buffer)
+                .offset(0)
+                .range(RenderStats::BUFFER_SIZE);
 
             let writes = [
                 vk::WriteDescriptorSet::default()
@@ -172,6 +643,11 @@ impl ClipmapRayMarchPipeline {
                     .dst_binding(1)
                     .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
                     .image_info(std::slice::from_ref(&image_info_desc)),
+                vk::WriteDescriptorSet::default()
+                    .dst_set(descriptor_set)
+                    .dst_binding(RENDER_STATS_BINDING)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(std::slice::from_ref(&stats_info)),
             ];
 
             device.update_descriptor_sets(&writes, &[]);
@@ -199,13 +675,123 @@ impl ClipmapRayMarchPipeline {
             output_image,
             output_image_view,
             readback_buffer,
+            stats_buffers,
+            render_stats_enabled: false,
+            stats_pending: (0..frames_in_flight).map(|_| Cell::new(false)).collect(),
+            latest_stats: Cell::new(None),
             width,
             height,
+            views: 1,
+            tile_size: (0, 0),
+            workgroup,
+            output_format,
         })
     }
 
+    /// Create a stereo pipeline tracing two eyes side by side.
+    ///
+    /// The output is [`side_by_side_extent`] of the eye size: the left eye
+    /// fills the left half. Record frames with [`Self::record_stereo`].
+    ///
+    /// # Safety
+    /// The Vulkan device must be valid.
+    pub unsafe fn new_stereo(
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+        eye_width: u32,
+        eye_height: u32,
+        frames_in_flight: usize,
+        workgroup: WorkgroupSize,
+        output_format: OutputFormat,
+    ) -> Result<Self> {
+        let (width, height) = side_by_side_extent(eye_width, eye_height);
+        let mut pipeline = Self::new_with_format(
+            device,
+            allocator,
+            width,
+            height,
+            frames_in_flight,
+            workgroup,
+            output_format,
+        )?;
+        pipeline.views = 2;
+        Ok(pipeline)
+    }
+
+    /// Whether this pipeline traces two eyes (see [`Self::new_stereo`]).
+    pub fn is_stereo(&self) -> bool {
+        self.views > 1
+    }
+
+    /// Size of one view: half the output width in stereo.
+    pub fn eye_dimensions(&self) -> (u32, u32) {
+        (self.width / self.views, self.height)
+    }
+
+    /// Split the ray march into screen tiles of the given pixel size.
+    ///
+    /// Each tile is a separate dispatch with its own `tile_offset` push
+    /// constant. Sizes round up to the workgroup size; `(0, 0)` disables tiling.
+    pub fn set_tile_size(&mut self, tile_size: (u32, u32)) {
+        self.tile_size = tile_size;
+    }
+
+    /// Current tile size (`(0, 0)` when untiled).
+    pub fn tile_size(&self) -> (u32, u32) {
+        self.tile_size
+    }
+
+    /// Shader workgroup size this pipeline dispatches with.
+    pub fn workgroup(&self) -> WorkgroupSize {
+        self.workgroup
+    }
+
+    /// Pixel format of the output image.
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// Turn per-frame [`RenderStats`] collection on or off.
+    ///
+    /// Counting costs a few atomics per pixel, so it is off by default.
+    pub fn set_render_stats_enabled(&mut self, enabled: bool) {
+        self.render_stats_enabled = enabled;
+        for pending in &self.stats_pending {
+            pending.set(false);
+        }
+        self.latest_stats.set(None);
+    }
+
+    /// Whether [`RenderStats`] are being collected.
+    pub fn render_stats_enabled(&self) -> bool {
+        self.render_stats_enabled
+    }
+
+    /// Take the counters of the latest finished frame.
+    ///
+    /// A frame's counters are read back when its frame slot is recorded
+    /// again, after the slot's fence has been waited on, so they trail by
+    /// `frames_in_flight` frames. Returns `None` when collection is off or
+    /// no new frame has finished since the last call.
+    pub fn take_render_stats(&self) -> Option<RenderStats> {
+        self.latest_stats.take()
+    }
+
+    /// Read the counters a frame slot last wrote.
+    ///
+    /// Only meaningful once the slot's GPU work has finished.
+    pub fn read_render_stats(&self, frame_index: usize) -> Result<RenderStats> {
+        let ptr = self.stats_buffers[frame_index]
+            .mapped_ptr()
+            .ok_or_else(|| GpuError::InvalidState("Stats buffer not mapped".to_string()))?;
+        let counters = unsafe { std::ptr::read_unaligned(ptr.cast::<[u32; 4]>()) };
+        Ok(RenderStats::from_counters(counters))
+    }
+
     /// Record clipmap ray marching dispatch commands.
     ///
+    /// A stereo pipeline traces `camera` for both eyes.
+    ///
     /// # Safety
     /// Command buffer must be in recording state.
     pub unsafe fn record(
@@ -218,7 +804,68 @@ impl ClipmapRayMarchPipeline {
         frame_index: usize,
         debug_mode: DebugMode,
     ) -> Result<()> {
-        self.camera_buffers[frame_index].write(std::slice::from_ref(camera))?;
+        self.record_views(
+            device,
+            cmd,
+            &[*camera; MAX_VIEWS],
+            renderer,
+            max_steps,
+            frame_index,
+            debug_mode,
+        )
+    }
+
+    /// Record both eyes of a stereo pipeline, left eye first (see
+    /// [`crate::StereoConfig::eye_uniforms`]).
+    ///
+    /// # Safety
+    /// Command buffer must be in recording state.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn record_stereo(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        eyes: &[CameraUniforms; 2],
+        renderer: &ClipmapRenderer,
+        max_steps: u32,
+        frame_index: usize,
+        debug_mode: DebugMode,
+    ) -> Result<()> {
+        if !self.is_stereo() {
+            return Err(GpuError::InvalidState(
+                "record_stereo needs a pipeline created with new_stereo".to_string(),
+            ));
+        }
+        self.record_views(
+            device,
+            cmd,
+            eyes,
+            renderer,
+            max_steps,
+            frame_index,
+            debug_mode,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn record_views(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        cameras: &[CameraUniforms; MAX_VIEWS],
+        renderer: &ClipmapRenderer,
+        max_steps: u32,
+        frame_index: usize,
+        debug_mode: DebugMode,
+    ) -> Result<()> {
+        self.camera_buffers[frame_index].write(cameras)?;
+        if self.render_stats_enabled {
+            if self.stats_pending[frame_index].replace(true) {
+                self.latest_stats
+                    .set(Some(self.read_render_stats(frame_index)?));
+            }
+            self.record_stats_reset(device, cmd, frame_index);
+        }
 
         let barrier = vk::ImageMemoryBarrier2::default()
             .src_stage_mask(vk::PipelineStageFlags2::TOP_OF_PIPE)
@@ -255,20 +902,49 @@ impl ClipmapRayMarchPipeline {
             &[],
         );
 
-        let push_constants =
-            renderer.push_constants(self.width, self.height, max_steps, frame_index, debug_mode);
+        let (eye_width, eye_height) = self.eye_dimensions();
+        let mut push_constants =
+            renderer.push_constants(eye_width, eye_height, max_steps, frame_index, debug_mode);
 
-        device.cmd_push_constants(
-            cmd,
-            self.ray_march_pipeline.layout,
-            vk::ShaderStageFlags::COMPUTE,
-            0,
-            bytemuck::bytes_of(&push_constants),
-        );
+        for (view, tile) in view_dispatches(
+            eye_width,
+            eye_height,
+            self.views,
+            self.tile_size,
+            self.workgroup,
+        ) {
+            push_constants.view_index = view;
+            push_constants.collect_stats = u32::from(self.render_stats_enabled);
+            push_constants.tile_offset = tile.offset;
+            device.cmd_push_constants(
+                cmd,
+                self.ray_march_pipeline.layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                bytemuck::bytes_of(&push_constants),
+            );
+            device.cmd_dispatch(cmd, tile.workgroups[0], tile.workgroups[1], 1);
+        }
 
-        let workgroup_x = (self.width + 7) / 8;
-        let workgroup_y = (self.height + 7) / 8;
-        device.cmd_dispatch(cmd, workgroup_x, workgroup_y, 1);
+        if self.render_stats_enabled {
+            let stats_barrier = vk::BufferMemoryBarrier2::default()
+                .src_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER)
+                .src_access_mask(vk::AccessFlags2::SHADER_STORAGE_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags2::HOST)
+                .dst_access_mask(vk::AccessFlags2::HOST_READ)
+                .buffer(self.stats_buffers[frame_index].buffer)
+                .offset(0)
+                .size(vk::WHOLE_SIZE);
+            let stats_dependency = vk::DependencyInfo::default()
+                .buffer_memory_barriers(std::slice::from_ref(&stats_barrier));
+            device.cmd_pipeline_barrier2(cmd, &stats_dependency);
+        }
+
+        // The crosshair marks the image centre, which is the seam between
+        // the eyes in stereo.
+        if self.is_stereo() {
+            return Ok(());
+        }
 
         let overlay_barrier = vk::ImageMemoryBarrier2::default()
             .src_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER)
@@ -309,6 +985,30 @@ impl ClipmapRayMarchPipeline {
         Ok(())
     }
 
+    /// Zero a frame slot's stats counters ahead of its dispatches.
+    unsafe fn record_stats_reset(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        frame_index: usize,
+    ) {
+        let buffer = self.stats_buffers[frame_index].buffer;
+        device.cmd_fill_buffer(cmd, buffer, 0, vk::WHOLE_SIZE, 0);
+        let barrier = vk::BufferMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+            .src_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::COMPUTE_SHADER)
+            .dst_access_mask(
+                vk::AccessFlags2::SHADER_STORAGE_READ | vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            )
+            .buffer(buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE);
+        let dependency =
+            vk::DependencyInfo::default().buffer_memory_barriers(std::slice::from_ref(&barrier));
+        device.cmd_pipeline_barrier2(cmd, &dependency);
+    }
+
     /// Record commands to copy the output image to the readback buffer.
     pub unsafe fn record_readback_from_transfer_src(
         &self,
@@ -341,14 +1041,20 @@ impl ClipmapRayMarchPipeline {
         );
     }
 
-    /// Read the rendered image from the readback buffer.
+    /// Read the rendered image from the readback buffer as RGBA8.
     pub fn read_output(&self) -> Result<Vec<u8>> {
+        self.read_output_raw()
+            .map(|data| self.output_format.decode_rgba8(data))
+    }
+
+    /// Read the readback buffer in the pipeline's [`OutputFormat`].
+    pub fn read_output_raw(&self) -> Result<Vec<u8>> {
         let ptr = self
             .readback_buffer
             .mapped_ptr()
             .ok_or_else(|| GpuError::InvalidState("Readback buffer not mapped".to_string()))?;
 
-        let size = (self.width * self.height * 4) as usize;
+        let size = self.output_format.readback_size(self.width, self.height) as usize;
         let mut data = vec![0u8; size];
 
         unsafe {
@@ -383,6 +1089,9 @@ impl ClipmapRayMarchPipeline {
             allocator.free_buffer(camera_buffer)?;
         }
         allocator.free_buffer(&mut self.readback_buffer)?;
+        for stats_buffer in &mut self.stats_buffers {
+            allocator.free_buffer(stats_buffer)?;
+        }
         self.crosshair_descriptor_pool.destroy(device);
         device.destroy_descriptor_set_layout(self.crosshair_descriptor_set_layout, None);
         self.crosshair_pipeline.destroy(device);
@@ -392,3 +1101,278 @@ impl ClipmapRayMarchPipeline {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use voxelicous_gpu::testing::test_gpu;
+
+    use super::*;
+
+    #[test]
+    fn step_tuner_trades_quality_for_frame_time_within_bounds() {
+        let mut tuner = MaxStepsTuner::new(16.0, 256, 1024);
+        assert_eq!(tuner.steps(), 1024);
+
+        // Over budget: the step count falls, but not below the minimum.
+        let first = tuner.update(30.0);
+        assert!(first < 1024);
+        for _ in 0..100 {
+            tuner.update(30.0);
+        }
+        assert_eq!(tuner.steps(), 256);
+
+        // Unknown frame times leave the budget alone.
+        assert_eq!(tuner.update(0.0), 256);
+
+        // Under budget: it climbs back, but not above the maximum.
+        let mut previous = tuner.steps();
+        for _ in 0..10 {
+            tuner.update(5.0);
+        }
+        assert!(tuner.steps() > previous);
+        for _ in 0..200 {
+            tuner.update(5.0);
+        }
+        assert_eq!(tuner.steps(), 1024);
+
+        // Bounds clamp the current budget.
+        tuner.set_bounds(800, 100);
+        assert_eq!((tuner.bounds(), tuner.steps()), ((100, 800), 800));
+
+        // Within the tolerance band it holds steady.
+        for _ in 0..5 {
+            tuner.update(30.0);
+        }
+        for _ in 0..20 {
+            tuner.update(16.5);
+        }
+        previous = tuner.steps();
+        assert!(previous > 100 && previous < 800, "{previous}");
+        for _ in 0..50 {
+            tuner.update(16.5);
+        }
+        assert_eq!(tuner.steps(), previous);
+    }
+
+    #[test]
+    fn render_stats_ratios() {
+        let stats = RenderStats {
+            rays: 200,
+            hits: 50,
+            steps: 3000,
+        };
+        assert!((stats.hit_rate() - 0.25).abs() < f32::EPSILON);
+        assert!((stats.steps_per_ray() - 15.0).abs() < f32::EPSILON);
+        assert!(RenderStats::default().steps_per_ray().abs() < f32::EPSILON);
+
+        // Steps carry into the high word instead of wrapping.
+        let stats = RenderStats::from_counters([3840 * 2160, 0, u32::MAX, 2]);
+        assert_eq!(stats.steps, 3 * (1 << 32) - 1);
+        assert!(stats.steps_per_ray() > 1000.0);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn render_stats_buffers_are_bound_and_read_zero_before_dispatch() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let device = gpu.device();
+        let mut allocator = gpu.allocator().lock();
+        let mut pipeline =
+            unsafe { ClipmapRayMarchPipeline::new(device, &mut allocator, 64, 32, 2) }.unwrap();
+
+        assert_eq!(pipeline.stats_buffers.len(), 2);
+        for (frame, buffer) in pipeline.stats_buffers.iter().enumerate() {
+            assert_eq!(buffer.size, RenderStats::BUFFER_SIZE);
+            assert!(buffer.usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER));
+            assert_eq!(
+                pipeline.read_render_stats(frame).unwrap(),
+                RenderStats::default()
+            );
+        }
+        assert!(!pipeline.render_stats_enabled());
+        assert_eq!(pipeline.take_render_stats(), None);
+        pipeline.set_render_stats_enabled(true);
+        assert_eq!(pipeline.take_render_stats(), None);
+
+        unsafe { pipeline.destroy(device, &mut allocator) }.unwrap();
+    }
+
+    #[test]
+    fn readback_stride_follows_output_format() {
+        assert_eq!(
+            OutputFormat::Rgba16Float.readback_size(1920, 1080),
+            1920 * 1080 * 8
+        );
+        assert_eq!(
+            OutputFormat::Rgba8Unorm.readback_size(1920, 1080),
+            1920 * 1080 * 4
+        );
+        assert_eq!(
+            OutputFormat::Bgra8Unorm.readback_size(1920, 1080),
+            1920 * 1080 * 4
+        );
+        assert_eq!(
+            OutputFormat::default().vk_format(),
+            vk::Format::R8G8B8A8_UNORM
+        );
+    }
+
+    #[test]
+    fn output_formats_decode_to_rgba8() {
+        let rgba = vec![10, 20, 30, 255];
+        assert_eq!(OutputFormat::Rgba8Unorm.decode_rgba8(rgba.clone()), rgba);
+        assert_eq!(
+            OutputFormat::Bgra8Unorm.decode_rgba8(vec![30, 20, 10, 255]),
+            rgba
+        );
+
+        // 0.0, 0.5, 1.0, 2.0 as little-endian halves. Color is tonemapped,
+        // alpha only clamped.
+        let halves: Vec<u8> = [0x0000u16, 0x3800, 0x3C00, 0x4000]
+            .iter()
+            .flat_map(|h| h.to_le_bytes())
+            .collect();
+        assert_eq!(
+            OutputFormat::Rgba16Float.decode_rgba8(halves),
+            vec![0, 157, 205, 255]
+        );
+
+        // Highlights roll off instead of clipping at 1.0.
+        assert!(tonemap_aces(2.0) < tonemap_aces(4.0));
+        assert!(tonemap_aces(4.0) < 1.0);
+        assert!(tonemap_aces(-1.0).abs() < f32::EPSILON);
+        assert!((f16_to_f32(0x0001) - 2f32.powi(-24)).abs() < f32::EPSILON);
+        assert!((f16_to_f32(0xC000) + 2.0).abs() < f32::EPSILON);
+    }
+
+    fn assert_covers_exactly_once(width: u32, height: u32, tile_size: (u32, u32)) -> usize {
+        assert_covers_exactly_once_with(width, height, tile_size, WorkgroupSize::Size8)
+    }
+
+    fn assert_covers_exactly_once_with(
+        width: u32,
+        height: u32,
+        tile_size: (u32, u32),
+        workgroup: WorkgroupSize,
+    ) -> usize {
+        let edge = workgroup.edge();
+        let tiles = dispatch_tiles(width, height, tile_size, workgroup);
+        let mut hits = vec![0u8; (width * height) as usize];
+        for tile in &tiles {
+            let x_end = (tile.offset[0] + tile.workgroups[0] * edge).min(width);
+            let y_end = (tile.offset[1] + tile.workgroups[1] * edge).min(height);
+            for y in tile.offset[1]..y_end {
+                for x in tile.offset[0]..x_end {
+                    hits[(y * width + x) as usize] += 1;
+                }
+            }
+        }
+        assert!(
+            hits.iter().all(|&h| h == 1),
+            "{width}x{height} tiled by {tile_size:?} does not cover every pixel once"
+        );
+        tiles.len()
+    }
+
+    #[test]
+    fn dispatch_tiles_cover_image_once() {
+        assert_eq!(assert_covers_exactly_once(1280, 720, (256, 256)), 5 * 3);
+        assert_eq!(assert_covers_exactly_once(1920, 1080, (512, 128)), 4 * 9);
+        // Tile sizes off the workgroup grid round up instead of overlapping.
+        assert_eq!(assert_covers_exactly_once(100, 60, (30, 30)), 4 * 2);
+        assert_eq!(assert_covers_exactly_once(37, 23, (1, 1)), 5 * 3);
+    }
+
+    #[test]
+    fn untiled_dispatch_is_single_full_frame() {
+        assert_eq!(assert_covers_exactly_once(1280, 720, (0, 0)), 1);
+        assert_eq!(
+            dispatch_tiles(1280, 720, (0, 0), WorkgroupSize::Size8),
+            vec![DispatchTile {
+                offset: [0, 0],
+                workgroups: [160, 90],
+            }]
+        );
+    }
+
+    #[test]
+    fn every_workgroup_size_covers_image() {
+        for workgroup in WorkgroupSize::ALL {
+            let edge = workgroup.edge();
+            assert!(voxelicous_shaders::RAY_MARCH_WORKGROUP_SIZES.contains(&edge));
+            for (width, height) in [(1280, 720), (1921, 1081), (7, 5), (16, 16)] {
+                let (gx, gy) = workgroup.group_count(width, height);
+                assert!(gx * edge >= width && (gx - 1) * edge < width);
+                assert!(gy * edge >= height && (gy - 1) * edge < height);
+                assert_eq!(
+                    assert_covers_exactly_once_with(width, height, (0, 0), workgroup),
+                    1
+                );
+                assert_covers_exactly_once_with(width, height, (100, 60), workgroup);
+            }
+        }
+        assert_eq!(WorkgroupSize::Size16.group_count(1280, 720), (80, 45));
+        assert_eq!(
+            WorkgroupSize::for_vendor(GpuVendor::Amd),
+            WorkgroupSize::Size8
+        );
+    }
+
+    #[test]
+    fn stereo_traces_two_eyes_side_by_side() {
+        use crate::camera::{Camera, StereoConfig};
+        use glam::Vec3;
+
+        let (eye_width, eye_height) = (1001, 720);
+        let (width, height) = side_by_side_extent(eye_width, eye_height);
+        assert_eq!((width, height), (2 * eye_width, eye_height));
+
+        // Each view's tiles stay in its own half: only the owning view's
+        // pixels pass the shader's bounds check. Check the top tile row.
+        let edge = WorkgroupSize::Size8.edge();
+        let mut owner = vec![u32::MAX; width as usize];
+        let dispatches =
+            view_dispatches(eye_width, eye_height, 2, (256, 256), WorkgroupSize::Size8);
+        for (view, tile) in dispatches
+            .into_iter()
+            .filter(|(_, tile)| tile.offset[1] == 0)
+        {
+            let x_end = tile.offset[0] + tile.workgroups[0] * edge;
+            for x in tile.offset[0]..x_end {
+                if (view * eye_width..(view + 1) * eye_width).contains(&x) {
+                    assert_eq!(owner[x as usize], u32::MAX, "x = {x} traced twice");
+                    owner[x as usize] = view;
+                }
+            }
+        }
+        assert!(owner[..eye_width as usize].iter().all(|&view| view == 0));
+        assert!(owner[eye_width as usize..].iter().all(|&view| view == 1));
+
+        // The eyes straddle the camera, one IPD apart along its right axis.
+        let camera = Camera::new(
+            Vec3::new(4.0, 70.0, -3.0),
+            Vec3::new(10.0, 70.0, 5.0),
+            Vec3::Y,
+            1.2,
+            1.0,
+            0.1,
+            500.0,
+        );
+        let stereo = StereoConfig::symmetric(0.064, &camera);
+        let [left, right] = stereo.eye_uniforms(&camera.uniforms());
+        let origin = |eye: &CameraUniforms| Vec3::from_slice(&eye.position);
+        let right_axis = camera.direction.cross(camera.up).normalize();
+        let baseline = origin(&right) - origin(&left);
+        assert!((baseline.length() - stereo.ipd).abs() < 1e-5);
+        assert!(baseline.normalize().abs_diff_eq(right_axis, 1e-4));
+        assert!(((origin(&left) + origin(&right)) * 0.5).abs_diff_eq(camera.position, 1e-4));
+
+        // Each eye's view matrix agrees with its reported position.
+        for eye in [left, right] {
+            let view = glam::Mat4::from_cols_array_2d(&eye.view);
+            assert!(view
+                .transform_point3(origin(&eye))
+                .abs_diff_eq(Vec3::ZERO, 1e-4));
+        }
+    }
+}
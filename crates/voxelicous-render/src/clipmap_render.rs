@@ -1,21 +1,40 @@
 //! Clipmap GPU resources and upload helpers.
 
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
 use ash::vk;
 use bytemuck::{Pod, Zeroable};
+use glam::{IVec3, Vec3};
 use gpu_allocator::MemoryLocation;
+use voxelicous_core::types::MaterialRegistry;
 use voxelicous_gpu::error::Result;
 use voxelicous_gpu::memory::{GpuAllocator, GpuBuffer};
 use voxelicous_voxel::{
-    BrickHeader, BrickId, ClipmapVoxelStore, CLIPMAP_LOD_COUNT, CLIPMAP_PAGE_GRID, PAGE_BRICKS,
-    PALETTE16_STRIDE, PALETTE32_STRIDE, RAW16_STRIDE,
+    BrickHeader, BrickId, ClipmapVoxelStore, CLIPMAP_LOD_COUNT, CLIPMAP_PAGE_GRID, LIGHT_STRIDE,
+    NORMAL_STRIDE, PAGE_BRICKS, PALETTE16_STRIDE, PALETTE32_STRIDE, RAW16_STRIDE,
 };
 use voxelicous_world::{ClipmapDirtyState, ClipmapStreamingController};
 
-use crate::debug::DebugMode;
+use crate::debug::{DebugMode, DebugPalette};
+use crate::upload_budget::UploadBudget;
 
 const INVALID_PAGE_COORD: [i32; 4] = [i32::MIN, i32::MIN, i32::MIN, 0];
 const INIT_CHUNK_U32: usize = 16 * 1024;
 const INIT_CHUNK_COORD: usize = 4 * 1024;
+/// Granularity of partial clipmap info writes, in bytes.
+const INFO_WRITE_GRANULE: usize = 8;
+/// Above this many separate ranges a changed info block is rewritten whole.
+const INFO_MAX_PARTIAL_WRITES: usize = 8;
+
+/// Number of block ids covered by the GPU material table.
+pub const MATERIAL_TABLE_LEN: usize = 256;
+/// Material flag: refine ray steps around this block (thin geometry).
+pub const MATERIAL_FLAG_FINE_STEPS: u32 = 1 << 0;
+/// Material flag: rays pass through a dithered `1 - alpha` of the block.
+pub const MATERIAL_FLAG_ALPHA_CUTOUT: u32 = 1 << 1;
+/// Default sub-step length near fine-step materials, in voxels.
+pub const DEFAULT_FINE_STEP_SCALE: f32 = 0.25;
 
 /// GPU-side clipmap info shared with the shader (buffer reference).
 #[repr(C)]
@@ -28,31 +47,277 @@ pub struct GpuClipmapInfo {
     pub palette16_addr: u64,
     pub palette32_addr: u64,
     pub raw16_addr: u64,
-    pub _pad0: [u64; 2],
+    pub normal_addr: u64,
+    /// Per-voxel block light, [`LIGHT_STRIDE`] bytes per lit brick.
+    pub light_addr: u64,
+    /// Per-block materials, [`MATERIAL_TABLE_LEN`] [`GpuMaterial`]s.
+    pub material_addr: u64,
+    /// Debug visualization colors, one [`DebugPalette`].
+    pub debug_palette_addr: u64,
     pub origin: [[i32; 4]; CLIPMAP_LOD_COUNT],
     pub voxel_size: [[u32; 4]; CLIPMAP_LOD_COUNT],
     pub lod_aabb_min: [[f32; 4]; CLIPMAP_LOD_COUNT],
     pub lod_aabb_max: [[f32; 4]; CLIPMAP_LOD_COUNT],
+    /// `.x`: distance from the camera, in world units, where the next LOD
+    /// takes over; `0.0` switches at the LOD's coverage boundary.
+    pub lod_transition: [[f32; 4]; CLIPMAP_LOD_COUNT],
 }
 
 impl GpuClipmapInfo {
     pub const SIZE: usize = std::mem::size_of::<Self>();
 }
 
+/// Rejected [`ClipmapRenderer::set_lod_transition_distances`] input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LodTransitionError {
+    /// More distances than LODs with a coarser LOD to hand over to.
+    TooMany(usize),
+    /// The distance for this LOD is not a positive finite number.
+    NotPositive(usize),
+    /// The distance for this LOD is not past the previous LOD's.
+    NotIncreasing(usize),
+}
+
+impl std::fmt::Display for LodTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooMany(count) => write!(
+                f,
+                "{count} LOD transition distances given, at most {} allowed",
+                CLIPMAP_LOD_COUNT - 1
+            ),
+            Self::NotPositive(lod) => {
+                write!(f, "LOD {lod} transition distance must be positive")
+            }
+            Self::NotIncreasing(lod) => write!(
+                f,
+                "LOD {lod} transition distance must be greater than LOD {}'s",
+                lod - 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LodTransitionError {}
+
+/// GPU-side material table entry, indexed by block id.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct GpuMaterial {
+    /// `MATERIAL_FLAG_*` bits.
+    pub flags: u32,
+    /// Coverage of alpha-cutout blocks; `1.0` otherwise.
+    pub alpha: f32,
+}
+
 /// Push constants for clipmap ray marching.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct ClipmapRenderPushConstants {
     pub screen_size: [u32; 2],
     pub max_steps: u32,
-    pub _pad0: u32,
+    pub base_steps: u32,
     pub clipmap_info_address: u64,
     pub debug_mode: u32,
-    pub _pad1: u32,
+    pub lod_step_falloff: f32,
+    /// Pixel offset of the dispatched tile within the output image.
+    pub tile_offset: [u32; 2],
+    /// Sub-step length (in voxels) used near fine-step materials on coarse
+    /// LODs; `0.0` disables the refinement.
+    pub fine_step_scale: f32,
+    /// Exponential fog density per world unit; `0.0` disables fog.
+    pub fog_density: f32,
+    /// Distance before fog starts, in world units.
+    pub fog_start: f32,
+    /// Non-zero to blend the sky toward the fog color near the horizon.
+    pub fog_affects_sky: u32,
+    /// Eye being traced: selects the camera uniforms and the output half
+    /// in side-by-side stereo. Always `0` for mono output.
+    pub view_index: u32,
+    /// Leaf sway amount; `0.0` disables wind.
+    pub wind_strength: f32,
+    /// Leaf sway wave speed in radians per second.
+    pub wind_speed: f32,
+    /// Non-zero to count rays into the pipeline's [`RenderStats`] buffer.
+    ///
+    /// [`RenderStats`]: crate::RenderStats
+    pub collect_stats: u32,
 }
 
 impl ClipmapRenderPushConstants {
     pub const SIZE: u32 = std::mem::size_of::<Self>() as u32;
+
+    /// Per-interval step budget the shader uses for a LOD.
+    ///
+    /// Mirrors `lod_step_budget` in the shader: `base_steps` is divided by
+    /// `(voxel_size / finest_voxel_size) ^ lod_step_falloff` and clamped to
+    /// `[1, max_steps]`. A falloff of zero gives every LOD the same budget.
+    #[must_use]
+    pub fn steps_for_lod(&self, voxel_size: u32, finest_voxel_size: u32) -> u32 {
+        let ratio = voxel_size.max(1) as f32 / finest_voxel_size.max(1) as f32;
+        let scaled = self.base_steps as f32 / ratio.powf(self.lod_step_falloff);
+        (scaled as u32).clamp(1, self.max_steps.max(1))
+    }
+}
+
+/// Distance fog applied by the ray march shader.
+///
+/// Terrain fades toward the sky's horizon color with distance. With
+/// [`Self::affects_sky`] the sky also fades to that color just above the
+/// horizon, so distant terrain blends into the sky instead of ending at a
+/// hard line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FogSettings {
+    /// Distance before fog starts, in world units.
+    pub start: f32,
+    /// Exponential density per world unit past `start`; `0.0` disables fog.
+    pub density: f32,
+    /// Blend the sky toward the fog color near the horizon.
+    pub affects_sky: bool,
+}
+
+impl FogSettings {
+    /// Height of the sky band (in ray direction `y`) that fades into fog.
+    ///
+    /// Mirrors `FOG_SKY_BAND` in the shader.
+    pub const SKY_BAND: f32 = 0.2;
+
+    /// Fog blend factor for terrain `distance` units away.
+    ///
+    /// Mirrors `fog_factor` in the shader.
+    #[must_use]
+    pub fn terrain_factor(&self, distance: f32) -> f32 {
+        if self.density <= 0.0 {
+            return 0.0;
+        }
+        1.0 - (-self.density * (distance - self.start).max(0.0)).exp()
+    }
+
+    /// Fog blend factor for a sky pixel whose ray direction has height
+    /// `dir_y`.
+    ///
+    /// Mirrors `sky_fog_factor` in the shader: full fog at the horizon,
+    /// fading out over [`Self::SKY_BAND`], and none unless fog is on and
+    /// [`Self::affects_sky`] is set.
+    #[must_use]
+    pub fn sky_factor(&self, dir_y: f32) -> f32 {
+        if self.density <= 0.0 || !self.affects_sky {
+            return 0.0;
+        }
+        let t = (dir_y.max(0.0) / Self::SKY_BAND).min(1.0);
+        1.0 - t * t * (3.0 - 2.0 * t)
+    }
+}
+
+/// Leaf sway animated by the ray march shader.
+///
+/// Leaf voxels shift brightness and tilt their shading normals with a wave
+/// over world position and the animation time in the camera uniforms (see
+/// [`crate::CameraUniforms::set_time`]). Other blocks are unaffected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WindSettings {
+    /// Sway amount, from `0.0` (off) to `1.0` (gusty).
+    pub strength: f32,
+    /// Wave speed in radians per second.
+    pub speed: f32,
+}
+
+impl WindSettings {
+    /// Brightness swing of leaves at full strength.
+    ///
+    /// Mirrors `LEAF_WIND_TINT` in the shader.
+    pub const LEAF_TINT: f32 = 0.18;
+
+    /// Tilt of leaf shading normals at full strength.
+    ///
+    /// Mirrors `LEAF_WIND_BEND` in the shader.
+    pub const LEAF_BEND: f32 = 0.35;
+
+    /// Strength clamped to `0.0..=1.0` and a non-negative speed.
+    pub(crate) fn clamped(self) -> Self {
+        Self {
+            strength: self.strength.clamp(0.0, 1.0),
+            speed: self.speed.max(0.0),
+        }
+    }
+
+    /// Wave phase of the leaf voxel at `voxel`, `time` seconds in.
+    ///
+    /// Mirrors `leaf_wind_phase` in the shader.
+    #[must_use]
+    pub fn leaf_phase(&self, voxel: IVec3, time: f32) -> f32 {
+        let voxel = voxel.as_vec3();
+        voxel.x * 0.37 + voxel.y * 0.51 + voxel.z * 0.23 + time * self.speed
+    }
+
+    /// Brightness multiplier of the leaf voxel at `voxel`, `time` seconds in.
+    #[must_use]
+    pub fn leaf_tint(&self, voxel: IVec3, time: f32) -> f32 {
+        if self.strength <= 0.0 {
+            return 1.0;
+        }
+        1.0 + self.strength * Self::LEAF_TINT * self.leaf_phase(voxel, time).sin()
+    }
+
+    /// Shading normal of the leaf voxel at `voxel` whose face normal is
+    /// `normal`, `time` seconds in.
+    ///
+    /// Mirrors the normal bend in the shader.
+    #[must_use]
+    pub fn leaf_normal(&self, normal: Vec3, voxel: IVec3, time: f32) -> Vec3 {
+        if self.strength <= 0.0 {
+            return normal;
+        }
+        let phase = self.leaf_phase(voxel, time);
+        let bend = Vec3::new(phase.sin(), 0.0, (phase * 0.7).cos());
+        (normal + bend * (self.strength * Self::LEAF_BEND)).normalize()
+    }
+}
+
+/// Initial pool capacities (in entries) reserved per frame on first sync.
+///
+/// Pools still grow past these sizes when needed; reserving up front just
+/// avoids the reallocation (and full re-upload) while the world streams in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolReserve {
+    /// Brick header entries.
+    pub headers: usize,
+    /// Palette16 brick entries.
+    pub palette16: usize,
+    /// Palette32 brick entries.
+    pub palette32: usize,
+    /// Raw16 brick entries.
+    pub raw16: usize,
+    /// Per-voxel normal entries.
+    pub normals: usize,
+    /// Per-voxel block-light entries.
+    pub lights: usize,
+}
+
+impl PoolReserve {
+    const fn header_bytes(&self) -> u64 {
+        (self.headers * std::mem::size_of::<BrickHeader>()) as u64
+    }
+
+    const fn palette16_bytes(&self) -> u64 {
+        (self.palette16 * PALETTE16_STRIDE) as u64
+    }
+
+    const fn palette32_bytes(&self) -> u64 {
+        (self.palette32 * PALETTE32_STRIDE) as u64
+    }
+
+    const fn raw16_bytes(&self) -> u64 {
+        (self.raw16 * RAW16_STRIDE) as u64
+    }
+
+    const fn normal_bytes(&self) -> u64 {
+        (self.normals * NORMAL_STRIDE) as u64
+    }
+
+    const fn light_bytes(&self) -> u64 {
+        (self.lights * LIGHT_STRIDE) as u64
+    }
 }
 
 struct FrameBuffers {
@@ -63,7 +328,19 @@ struct FrameBuffers {
     palette16_buffer: Option<GpuBuffer>,
     palette32_buffer: Option<GpuBuffer>,
     raw16_buffer: Option<GpuBuffer>,
+    normal_buffer: Option<GpuBuffer>,
+    light_buffer: Option<GpuBuffer>,
     clipmap_info_buffer: Option<GpuBuffer>,
+    material_buffer: Option<GpuBuffer>,
+    /// Material table revision last written to `material_buffer`.
+    material_revision: u64,
+    debug_palette_buffer: Option<GpuBuffer>,
+    /// Palette revision last written to `debug_palette_buffer`.
+    debug_palette_revision: u64,
+    /// Info last written to `clipmap_info_buffer`, for incremental updates.
+    last_info: Option<GpuClipmapInfo>,
+    /// Page coordinates currently in each LOD's page tables, keyed by slot.
+    resident_pages: Vec<HashMap<usize, [i32; 3]>>,
 }
 
 impl FrameBuffers {
@@ -83,27 +360,194 @@ impl FrameBuffers {
             palette16_buffer: None,
             palette32_buffer: None,
             raw16_buffer: None,
+            normal_buffer: None,
+            light_buffer: None,
             clipmap_info_buffer: None,
+            material_buffer: None,
+            material_revision: 0,
+            debug_palette_buffer: None,
+            debug_palette_revision: 0,
+            last_info: None,
+            resident_pages: vec![HashMap::new(); CLIPMAP_LOD_COUNT],
         }
     }
+
+    fn free(&mut self, allocator: &mut GpuAllocator) -> Result<()> {
+        for buffer in &mut self.page_brick_buffers {
+            if let Some(mut buf) = buffer.take() {
+                allocator.free_buffer(&mut buf)?;
+            }
+        }
+        for buffer in &mut self.page_occ_buffers {
+            if let Some(mut buf) = buffer.take() {
+                allocator.free_buffer(&mut buf)?;
+            }
+        }
+        for buffer in &mut self.page_coord_buffers {
+            if let Some(mut buf) = buffer.take() {
+                allocator.free_buffer(&mut buf)?;
+            }
+        }
+        if let Some(mut buf) = self.clipmap_info_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.material_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.debug_palette_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.brick_header_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.palette16_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.palette32_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.raw16_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.normal_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        if let Some(mut buf) = self.light_buffer.take() {
+            allocator.free_buffer(&mut buf)?;
+        }
+        Ok(())
+    }
+
+    fn memory(&self) -> GpuMemBreakdown {
+        let size = |buffer: &Option<GpuBuffer>| buffer.as_ref().map_or(0, |b| b.size);
+        let page_tables = self
+            .page_brick_buffers
+            .iter()
+            .chain(&self.page_occ_buffers)
+            .chain(&self.page_coord_buffers)
+            .map(size)
+            .sum();
+        let pools = [
+            &self.brick_header_buffer,
+            &self.palette16_buffer,
+            &self.palette32_buffer,
+            &self.raw16_buffer,
+            &self.normal_buffer,
+            &self.light_buffer,
+        ]
+        .into_iter()
+        .map(size)
+        .sum();
+        GpuMemBreakdown::new(
+            page_tables,
+            pools,
+            size(&self.clipmap_info_buffer)
+                + size(&self.material_buffer)
+                + size(&self.debug_palette_buffer),
+        )
+    }
+}
+
+/// Record the page coordinate uploaded into a page table slot.
+fn track_page(resident: &mut HashMap<usize, [i32; 3]>, page_index: usize, coord: [i32; 4]) {
+    if coord == INVALID_PAGE_COORD {
+        resident.remove(&page_index);
+    } else {
+        resident.insert(page_index, [coord[0], coord[1], coord[2]]);
+    }
+}
+
+/// GPU memory held by a [`ClipmapRenderer`], in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GpuMemBreakdown {
+    /// Per-LOD page brick, occupancy and coordinate tables.
+    pub page_tables: u64,
+    /// Brick headers and voxel payload pools.
+    pub pools: u64,
+    /// Clipmap info blocks, material tables and debug palettes.
+    pub info_buffer: u64,
+    /// Sum of all of the above.
+    pub total: u64,
+}
+
+impl GpuMemBreakdown {
+    const fn new(page_tables: u64, pools: u64, info_buffer: u64) -> Self {
+        Self {
+            page_tables,
+            pools,
+            info_buffer,
+            total: page_tables + pools + info_buffer,
+        }
+    }
+
+    const fn add(self, other: Self) -> Self {
+        Self::new(
+            self.page_tables + other.page_tables,
+            self.pools + other.pools,
+            self.info_buffer + other.info_buffer,
+        )
+    }
+}
+
+/// Dirty indices waiting for upload, in first-dirtied order.
+///
+/// An index dirtied again before it is uploaded keeps its place instead of
+/// being queued twice; the upload reads current data either way.
+#[derive(Clone)]
+struct DirtyQueue<T> {
+    items: Vec<T>,
+    queued: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash> DirtyQueue<T> {
+    fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            queued: HashSet::new(),
+        }
+    }
+
+    fn extend_from_slice(&mut self, src: &[T]) {
+        for &item in src {
+            if self.queued.insert(item) {
+                self.items.push(item);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        self.items
+    }
 }
 
 struct PendingDirtyState {
-    dirty_pages: Vec<Vec<usize>>,
-    dirty_headers: Vec<BrickId>,
-    dirty_palette16_entries: Vec<u32>,
-    dirty_palette32_entries: Vec<u32>,
-    dirty_raw16_entries: Vec<u32>,
+    dirty_pages: Vec<DirtyQueue<usize>>,
+    dirty_headers: DirtyQueue<BrickId>,
+    dirty_palette16_entries: DirtyQueue<u32>,
+    dirty_palette32_entries: DirtyQueue<u32>,
+    dirty_raw16_entries: DirtyQueue<u32>,
+    dirty_normal_entries: DirtyQueue<u32>,
+    dirty_light_entries: DirtyQueue<u32>,
 }
 
 impl PendingDirtyState {
     fn new() -> Self {
         Self {
-            dirty_pages: vec![Vec::new(); CLIPMAP_LOD_COUNT],
-            dirty_headers: Vec::new(),
-            dirty_palette16_entries: Vec::new(),
-            dirty_palette32_entries: Vec::new(),
-            dirty_raw16_entries: Vec::new(),
+            dirty_pages: vec![DirtyQueue::new(); CLIPMAP_LOD_COUNT],
+            dirty_headers: DirtyQueue::new(),
+            dirty_palette16_entries: DirtyQueue::new(),
+            dirty_palette32_entries: DirtyQueue::new(),
+            dirty_raw16_entries: DirtyQueue::new(),
+            dirty_normal_entries: DirtyQueue::new(),
+            dirty_light_entries: DirtyQueue::new(),
         }
     }
 
@@ -120,7 +564,123 @@ impl PendingDirtyState {
             .extend_from_slice(&dirty.dirty_palette32_entries);
         self.dirty_raw16_entries
             .extend_from_slice(&dirty.dirty_raw16_entries);
+        self.dirty_normal_entries
+            .extend_from_slice(&dirty.dirty_normal_entries);
+        self.dirty_light_entries
+            .extend_from_slice(&dirty.dirty_light_entries);
     }
+
+    fn len(&self) -> usize {
+        self.dirty_pages.iter().map(DirtyQueue::len).sum::<usize>()
+            + self.dirty_headers.len()
+            + self.dirty_palette16_entries.len()
+            + self.dirty_palette32_entries.len()
+            + self.dirty_raw16_entries.len()
+            + self.dirty_normal_entries.len()
+            + self.dirty_light_entries.len()
+    }
+
+    /// Split off the entries `fits` allows, leaving the remainder pending.
+    ///
+    /// `fits(available, item_bytes)` returns how many of the next
+    /// `available` entries (each `item_bytes` large) to take. Entries are
+    /// drained in dependency order (pool entries, then brick headers, then
+    /// page tables) so a page is never uploaded before the bricks it
+    /// references. Draining stops at the first category left partly
+    /// pending, even if later, smaller entries would still fit.
+    fn take_with(&mut self, mut fits: impl FnMut(usize, u64) -> usize) -> Self {
+        let mut taken = Self::new();
+
+        let pools = [
+            (
+                &mut self.dirty_palette16_entries,
+                &mut taken.dirty_palette16_entries,
+                PALETTE16_STRIDE,
+            ),
+            (
+                &mut self.dirty_palette32_entries,
+                &mut taken.dirty_palette32_entries,
+                PALETTE32_STRIDE,
+            ),
+            (
+                &mut self.dirty_raw16_entries,
+                &mut taken.dirty_raw16_entries,
+                RAW16_STRIDE,
+            ),
+            (
+                &mut self.dirty_normal_entries,
+                &mut taken.dirty_normal_entries,
+                NORMAL_STRIDE,
+            ),
+            (
+                &mut self.dirty_light_entries,
+                &mut taken.dirty_light_entries,
+                LIGHT_STRIDE,
+            ),
+        ];
+        let mut complete = true;
+        for (src, dst, stride) in pools {
+            complete = drain_front(src, dst, stride as u64, &mut fits);
+            if !complete {
+                break;
+            }
+        }
+        if complete
+            && drain_front(
+                &mut self.dirty_headers,
+                &mut taken.dirty_headers,
+                std::mem::size_of::<BrickHeader>() as u64,
+                &mut fits,
+            )
+        {
+            for lod in 0..CLIPMAP_LOD_COUNT {
+                if !drain_front(
+                    &mut self.dirty_pages[lod],
+                    &mut taken.dirty_pages[lod],
+                    PAGE_TABLE_ENTRY_BYTES,
+                    &mut fits,
+                ) {
+                    break;
+                }
+            }
+        }
+
+        taken
+    }
+
+    /// Whether any brick pool entry is still waiting for upload.
+    fn has_pending_pool_entries(&self) -> bool {
+        !(self.dirty_palette16_entries.is_empty()
+            && self.dirty_palette32_entries.is_empty()
+            && self.dirty_raw16_entries.is_empty()
+            && self.dirty_normal_entries.is_empty()
+            && self.dirty_light_entries.is_empty())
+    }
+}
+
+/// Bytes written per dirty page: brick indices, occupancy and coordinate.
+const PAGE_TABLE_ENTRY_BYTES: u64 =
+    ((PAGE_BRICKS + 2) * std::mem::size_of::<u32>() + std::mem::size_of::<[i32; 4]>()) as u64;
+
+/// Move the entries `fits` allows from the front of `src` to `dst`.
+///
+/// Returns `true` when `src` was emptied.
+fn drain_front<T: Copy + Eq + Hash>(
+    src: &mut DirtyQueue<T>,
+    dst: &mut DirtyQueue<T>,
+    item_bytes: u64,
+    fits: &mut impl FnMut(usize, u64) -> usize,
+) -> bool {
+    if src.is_empty() {
+        return true;
+    }
+    let count = fits(src.len(), item_bytes).min(src.len());
+    for item in src.items.drain(..count) {
+        src.queued.remove(&item);
+        dst.queued.insert(item);
+        dst.items.push(item);
+    }
+    src.is_empty()
 }
 
 /// GPU resources for clipmap rendering.
@@ -128,25 +688,252 @@ pub struct ClipmapRenderer {
     frame_buffers: Vec<FrameBuffers>,
     pending_dirty_per_frame: Vec<PendingDirtyState>,
     clipmap_info_addresses: Vec<vk::DeviceAddress>,
+    upload_budget: Option<usize>,
+    pool_reserve: PoolReserve,
+    base_steps: Option<u32>,
+    lod_step_falloff: f32,
+    fine_step_scale: f32,
+    fog: FogSettings,
+    wind: WindSettings,
+    /// Distance where each LOD hands over to the next; `0.0` = coverage.
+    lod_transitions: [f32; CLIPMAP_LOD_COUNT],
+    materials: Vec<GpuMaterial>,
+    /// Bumped whenever `materials` changes; frames re-upload on mismatch.
+    material_revision: u64,
+    debug_palette: DebugPalette,
+    /// Bumped whenever `debug_palette` changes.
+    debug_palette_revision: u64,
+    info_writes: u64,
 }
 
 impl ClipmapRenderer {
     /// Create a new clipmap renderer.
     pub fn new(frames_in_flight: usize) -> Self {
+        Self::with_reserve(frames_in_flight, PoolReserve::default())
+    }
+
+    /// Create a clipmap renderer that pre-reserves pool buffers.
+    pub fn with_reserve(frames_in_flight: usize, reserve: PoolReserve) -> Self {
         Self {
             frame_buffers: (0..frames_in_flight).map(|_| FrameBuffers::new()).collect(),
             pending_dirty_per_frame: (0..frames_in_flight)
                 .map(|_| PendingDirtyState::new())
                 .collect(),
             clipmap_info_addresses: vec![0; frames_in_flight],
+            upload_budget: None,
+            pool_reserve: reserve,
+            base_steps: None,
+            lod_step_falloff: 0.0,
+            fine_step_scale: DEFAULT_FINE_STEP_SCALE,
+            fog: FogSettings::default(),
+            wind: WindSettings::default(),
+            lod_transitions: [0.0; CLIPMAP_LOD_COUNT],
+            materials: gpu_materials(&MaterialRegistry::default()),
+            material_revision: 1,
+            debug_palette: DebugPalette::default(),
+            debug_palette_revision: 1,
+            info_writes: 0,
+        }
+    }
+
+    /// Number of per-frame buffer sets, one per frame in flight.
+    pub fn frames_in_flight(&self) -> usize {
+        self.frame_buffers.len()
+    }
+
+    /// Limit how many dirty pages/entries are uploaded per sync call.
+    ///
+    /// Anything over the budget stays pending and is uploaded on later
+    /// frames. `None` uploads everything at once.
+    pub fn set_upload_budget(&mut self, budget: Option<usize>) {
+        self.upload_budget = budget;
+    }
+
+    /// Current per-sync upload budget.
+    pub fn upload_budget(&self) -> Option<usize> {
+        self.upload_budget
+    }
+
+    /// Configure how the ray march step budget scales across LODs.
+    ///
+    /// `base_steps` is the per-interval budget at the finest LOD (`None` uses
+    /// the frame's `max_steps`). Each coarser LOD divides it by its voxel size
+    /// ratio raised to `falloff`; `0.0` keeps stepping uniform.
+    pub fn set_lod_step_scaling(&mut self, base_steps: Option<u32>, falloff: f32) {
+        self.base_steps = base_steps;
+        self.lod_step_falloff = falloff.max(0.0);
+    }
+
+    /// Configured finest-LOD step budget.
+    pub fn base_steps(&self) -> Option<u32> {
+        self.base_steps
+    }
+
+    /// Configured per-LOD step falloff exponent.
+    pub fn lod_step_falloff(&self) -> f32 {
+        self.lod_step_falloff
+    }
+
+    /// Set the sub-step length, in voxels, used near fine-step materials.
+    ///
+    /// Clamped to `[0, 1]`; `0.0` turns the refinement off.
+    pub fn set_fine_step_scale(&mut self, scale: f32) {
+        self.fine_step_scale = scale.clamp(0.0, 1.0);
+    }
+
+    /// Configured fine-step sub-step length.
+    pub fn fine_step_scale(&self) -> f32 {
+        self.fine_step_scale
+    }
+
+    /// Set the distance fog.
+    pub fn set_fog(&mut self, fog: FogSettings) {
+        self.fog = FogSettings {
+            start: fog.start.max(0.0),
+            density: fog.density.max(0.0),
+            ..fog
+        };
+    }
+
+    /// Current distance fog.
+    pub fn fog(&self) -> FogSettings {
+        self.fog
+    }
+
+    /// Set the leaf wind animation.
+    pub fn set_wind(&mut self, wind: WindSettings) {
+        self.wind = wind.clamped();
+    }
+
+    /// Current leaf wind animation.
+    pub fn wind(&self) -> WindSettings {
+        self.wind
+    }
+
+    /// Set where each LOD hands over to the next, in world units from the
+    /// camera, finest LOD first.
+    ///
+    /// Distances must be positive and increasing. LODs past the end of
+    /// `distances` (and every LOD for an empty slice) switch at their
+    /// coverage boundary; a distance beyond a LOD's coverage has no effect.
+    ///
+    /// The streaming controller stitches coarse pages to the finer LOD at
+    /// the coverage-boundary handover only. A distance inside a LOD's
+    /// coverage makes the handover follow the camera, so that seam is not
+    /// stitched.
+    pub fn set_lod_transition_distances(
+        &mut self,
+        distances: &[f32],
+    ) -> std::result::Result<(), LodTransitionError> {
+        if distances.len() >= CLIPMAP_LOD_COUNT {
+            return Err(LodTransitionError::TooMany(distances.len()));
+        }
+        for (lod, &distance) in distances.iter().enumerate() {
+            if !distance.is_finite() || distance <= 0.0 {
+                return Err(LodTransitionError::NotPositive(lod));
+            }
+            if lod > 0 && distance <= distances[lod - 1] {
+                return Err(LodTransitionError::NotIncreasing(lod));
+            }
         }
+        self.lod_transitions = [0.0; CLIPMAP_LOD_COUNT];
+        self.lod_transitions[..distances.len()].copy_from_slice(distances);
+        Ok(())
+    }
+
+    /// Per-LOD transition distances; `0.0` switches at the coverage boundary.
+    pub fn lod_transition_distances(&self) -> [f32; CLIPMAP_LOD_COUNT] {
+        self.lod_transitions
+    }
+
+    /// Set whether the sky fades into the fog near the horizon.
+    pub fn set_fog_affects_sky(&mut self, affects_sky: bool) {
+        self.fog.affects_sky = affects_sky;
+    }
+
+    /// Rebuild the GPU material table from a registry.
+    ///
+    /// Each frame re-uploads the table on its next sync.
+    pub fn set_materials(&mut self, registry: &MaterialRegistry) {
+        let materials = gpu_materials(registry);
+        if materials != self.materials {
+            self.materials = materials;
+            self.material_revision += 1;
+        }
+    }
+
+    /// Per-block materials as uploaded to the GPU.
+    pub fn materials(&self) -> &[GpuMaterial] {
+        &self.materials
+    }
+
+    /// Set the colors used by the debug visualizations.
+    ///
+    /// Each frame re-uploads the palette on its next sync.
+    pub fn set_debug_palette(&mut self, palette: DebugPalette) {
+        if palette != self.debug_palette {
+            self.debug_palette = palette;
+            self.debug_palette_revision += 1;
+        }
+    }
+
+    /// Debug visualization colors as uploaded to the GPU.
+    pub fn debug_palette(&self) -> &DebugPalette {
+        &self.debug_palette
+    }
+
+    /// Pool capacities reserved on first sync.
+    pub fn pool_reserve(&self) -> PoolReserve {
+        self.pool_reserve
+    }
+
+    /// Total clipmap info buffer writes issued so far.
+    ///
+    /// Only changed slots are written, so this stays flat while the clipmap
+    /// is static.
+    pub fn info_writes(&self) -> u64 {
+        self.info_writes
+    }
+
+    /// Pages present in a frame's GPU page tables, as `(lod, page_coord)`.
+    ///
+    /// Compare against the controller's resident pages to spot pages that
+    /// were built on the CPU but never reached the GPU (or vice versa).
+    pub fn gpu_page_positions(
+        &self,
+        frame_index: usize,
+    ) -> impl Iterator<Item = (usize, [i32; 3])> + '_ {
+        self.frame_buffers[frame_index]
+            .resident_pages
+            .iter()
+            .enumerate()
+            .flat_map(|(lod, pages)| pages.values().map(move |&coord| (lod, coord)))
+    }
+
+    /// GPU buffer memory across all frames in flight.
+    pub fn gpu_memory_breakdown(&self) -> GpuMemBreakdown {
+        self.frame_buffers
+            .iter()
+            .map(FrameBuffers::memory)
+            .fold(GpuMemBreakdown::default(), GpuMemBreakdown::add)
     }
 
-    /// Ensure all GPU buffers exist and are large enough.
+    /// Number of dirty pages/entries still waiting to be uploaded for a frame.
+    pub fn pending_upload_count(&self, frame_index: usize) -> usize {
+        self.pending_dirty_per_frame[frame_index].len()
+    }
+
+    /// Ensure all GPU buffers exist and are large enough, then upload the
+    /// frame's pending dirty state.
+    ///
+    /// Uploads are limited by [`Self::upload_budget`] and, when given, by
+    /// `shared_budget`, which other renderers may draw from in the same
+    /// frame. Full re-uploads after a buffer grows are not charged.
     #[cfg_attr(
         feature = "profiling-tracy",
         tracing::instrument(level = "trace", skip_all)
     )]
+    #[allow(clippy::too_many_arguments)]
     pub fn sync_from_controller(
         &mut self,
         allocator: &mut GpuAllocator,
@@ -155,6 +942,7 @@ impl ClipmapRenderer {
         dirty: ClipmapDirtyState,
         frame_index: usize,
         _frame_number: u64,
+        shared_budget: Option<&mut UploadBudget>,
     ) -> Result<()> {
         self.broadcast_dirty(&dirty);
 
@@ -167,10 +955,12 @@ impl ClipmapRenderer {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.ensure_info_buffers").entered();
             self.ensure_info_buffer(allocator, device, frame_index)?;
+            self.ensure_material_buffer(allocator, frame_index)?;
+            self.ensure_debug_palette_buffer(allocator, frame_index)?;
         }
 
         let store = controller.store();
-        let pending = self.take_pending_dirty(frame_index);
+        let pending = self.take_pending_dirty(frame_index, shared_budget);
 
         let header_realloc = {
             #[cfg(feature = "profiling-tracy")]
@@ -184,6 +974,7 @@ impl ClipmapRenderer {
                 allocator,
                 &mut self.frame_buffers[frame_index].palette16_buffer,
                 store.palette16_pool().len() as u64,
+                self.pool_reserve.palette16_bytes(),
                 PALETTE16_STRIDE as u64,
                 "clipmap_palette16",
             )?
@@ -195,6 +986,7 @@ impl ClipmapRenderer {
                 allocator,
                 &mut self.frame_buffers[frame_index].palette32_buffer,
                 store.palette32_pool().len() as u64,
+                self.pool_reserve.palette32_bytes(),
                 PALETTE32_STRIDE as u64,
                 "clipmap_palette32",
             )?
@@ -206,20 +998,58 @@ impl ClipmapRenderer {
                 allocator,
                 &mut self.frame_buffers[frame_index].raw16_buffer,
                 store.raw16_pool().len() as u64,
+                self.pool_reserve.raw16_bytes(),
                 RAW16_STRIDE as u64,
                 "clipmap_raw16",
             )?
         };
+        let normal_realloc = {
+            #[cfg(feature = "profiling-tracy")]
+            let _span = tracing::trace_span!("clipmap_sync.ensure_normal_buffer").entered();
+            Self::ensure_pool_buffer(
+                allocator,
+                &mut self.frame_buffers[frame_index].normal_buffer,
+                store.normal_pool().len() as u64,
+                self.pool_reserve.normal_bytes(),
+                NORMAL_STRIDE as u64,
+                "clipmap_normals",
+            )?
+        };
+        let light_realloc = {
+            #[cfg(feature = "profiling-tracy")]
+            let _span = tracing::trace_span!("clipmap_sync.ensure_light_buffer").entered();
+            Self::ensure_pool_buffer(
+                allocator,
+                &mut self.frame_buffers[frame_index].light_buffer,
+                store.light_pool().len() as u64,
+                self.pool_reserve.light_bytes(),
+                LIGHT_STRIDE as u64,
+                "clipmap_light",
+            )?
+        };
 
         {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.upload_page_tables").entered();
-            self.upload_page_tables(controller, frame_index, pending.dirty_pages)?;
+            self.upload_page_tables(
+                controller,
+                frame_index,
+                pending
+                    .dirty_pages
+                    .into_iter()
+                    .map(DirtyQueue::into_vec)
+                    .collect(),
+            )?;
         }
         {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.upload_brick_headers").entered();
-            self.upload_brick_headers(store, frame_index, pending.dirty_headers, header_realloc)?;
+            self.upload_brick_headers(
+                store,
+                frame_index,
+                pending.dirty_headers.into_vec(),
+                header_realloc,
+            )?;
         }
         {
             #[cfg(feature = "profiling-tracy")]
@@ -231,7 +1061,7 @@ impl ClipmapRenderer {
                     .palette16_buffer
                     .as_ref()
                     .unwrap(),
-                pending.dirty_palette16_entries,
+                pending.dirty_palette16_entries.into_vec(),
                 pal16_realloc,
             )?;
         }
@@ -245,7 +1075,7 @@ impl ClipmapRenderer {
                     .palette32_buffer
                     .as_ref()
                     .unwrap(),
-                pending.dirty_palette32_entries,
+                pending.dirty_palette32_entries.into_vec(),
                 pal32_realloc,
             )?;
         }
@@ -259,20 +1089,54 @@ impl ClipmapRenderer {
                     .raw16_buffer
                     .as_ref()
                     .unwrap(),
-                pending.dirty_raw16_entries,
+                pending.dirty_raw16_entries.into_vec(),
                 raw_realloc,
             )?;
         }
+        {
+            #[cfg(feature = "profiling-tracy")]
+            let _span = tracing::trace_span!("clipmap_sync.upload_normal_entries").entered();
+            self.upload_pool_entries(
+                store.normal_pool(),
+                NORMAL_STRIDE,
+                self.frame_buffers[frame_index]
+                    .normal_buffer
+                    .as_ref()
+                    .unwrap(),
+                pending.dirty_normal_entries.into_vec(),
+                normal_realloc,
+            )?;
+        }
+        {
+            #[cfg(feature = "profiling-tracy")]
+            let _span = tracing::trace_span!("clipmap_sync.upload_light_entries").entered();
+            self.upload_pool_entries(
+                store.light_pool(),
+                LIGHT_STRIDE,
+                self.frame_buffers[frame_index]
+                    .light_buffer
+                    .as_ref()
+                    .unwrap(),
+                pending.dirty_light_entries.into_vec(),
+                light_realloc,
+            )?;
+        }
 
         let info = {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.build_gpu_info").entered();
             self.build_gpu_info(device, controller, frame_index)
         };
-        if let Some(info_buffer) = &self.frame_buffers[frame_index].clipmap_info_buffer {
+        let frame = &mut self.frame_buffers[frame_index];
+        if let Some(info_buffer) = &frame.clipmap_info_buffer {
             #[cfg(feature = "profiling-tracy")]
             let _span = tracing::trace_span!("clipmap_sync.write_gpu_info").entered();
-            info_buffer.write(std::slice::from_ref(&info))?;
+            let bytes = bytemuck::bytes_of(&info);
+            for range in info_write_ranges(frame.last_info.as_ref(), &info) {
+                info_buffer.write_bytes(range.start as u64, &bytes[range])?;
+                self.info_writes += 1;
+            }
+            frame.last_info = Some(info);
             self.clipmap_info_addresses[frame_index] = info_buffer.device_address(device);
         }
 
@@ -291,10 +1155,19 @@ impl ClipmapRenderer {
         ClipmapRenderPushConstants {
             screen_size: [screen_width, screen_height],
             max_steps,
-            _pad0: 0,
+            base_steps: self.base_steps.unwrap_or(max_steps),
             clipmap_info_address: self.clipmap_info_addresses[frame_index],
             debug_mode: debug_mode.as_u32(),
-            _pad1: 0,
+            lod_step_falloff: self.lod_step_falloff,
+            tile_offset: [0, 0],
+            fine_step_scale: self.fine_step_scale,
+            fog_density: self.fog.density,
+            fog_start: self.fog.start,
+            fog_affects_sky: u32::from(self.fog.affects_sky),
+            view_index: 0,
+            wind_strength: self.wind.strength,
+            wind_speed: self.wind.speed,
+            collect_stats: 0,
         }
     }
 
@@ -307,39 +1180,28 @@ impl ClipmapRenderer {
         Ok(())
     }
 
+    /// Drop all uploaded GPU state so the next syncs rebuild it from scratch.
+    ///
+    /// Use this when the renderer starts drawing a different controller, e.g.
+    /// after switching worlds, together with
+    /// [`ClipmapStreamingController::mark_all_pages_dirty`] on the new one.
+    /// Buffers are freed immediately, so the device must be idle.
+    pub fn invalidate(&mut self, allocator: &mut GpuAllocator) -> Result<()> {
+        for frame in &mut self.frame_buffers {
+            frame.free(allocator)?;
+            *frame = FrameBuffers::new();
+        }
+        for pending in &mut self.pending_dirty_per_frame {
+            *pending = PendingDirtyState::new();
+        }
+        self.clipmap_info_addresses.fill(0);
+        Ok(())
+    }
+
     /// Destroy all GPU resources.
     pub fn destroy(mut self, allocator: &mut GpuAllocator) -> Result<()> {
         for frame in &mut self.frame_buffers {
-            for buffer in &mut frame.page_brick_buffers {
-                if let Some(mut buf) = buffer.take() {
-                    allocator.free_buffer(&mut buf)?;
-                }
-            }
-            for buffer in &mut frame.page_occ_buffers {
-                if let Some(mut buf) = buffer.take() {
-                    allocator.free_buffer(&mut buf)?;
-                }
-            }
-            for buffer in &mut frame.page_coord_buffers {
-                if let Some(mut buf) = buffer.take() {
-                    allocator.free_buffer(&mut buf)?;
-                }
-            }
-            if let Some(mut buf) = frame.clipmap_info_buffer.take() {
-                allocator.free_buffer(&mut buf)?;
-            }
-            if let Some(mut buf) = frame.brick_header_buffer.take() {
-                allocator.free_buffer(&mut buf)?;
-            }
-            if let Some(mut buf) = frame.palette16_buffer.take() {
-                allocator.free_buffer(&mut buf)?;
-            }
-            if let Some(mut buf) = frame.palette32_buffer.take() {
-                allocator.free_buffer(&mut buf)?;
-            }
-            if let Some(mut buf) = frame.raw16_buffer.take() {
-                allocator.free_buffer(&mut buf)?;
-            }
+            frame.free(allocator)?;
         }
 
         Ok(())
@@ -351,11 +1213,24 @@ impl ClipmapRenderer {
         }
     }
 
-    fn take_pending_dirty(&mut self, frame_index: usize) -> PendingDirtyState {
-        std::mem::replace(
-            &mut self.pending_dirty_per_frame[frame_index],
-            PendingDirtyState::new(),
-        )
+    fn take_pending_dirty(
+        &mut self,
+        frame_index: usize,
+        mut shared_budget: Option<&mut UploadBudget>,
+    ) -> PendingDirtyState {
+        let pending = &mut self.pending_dirty_per_frame[frame_index];
+        if self.upload_budget.is_none() && shared_budget.is_none() {
+            return std::mem::replace(pending, PendingDirtyState::new());
+        }
+        let mut entries = self.upload_budget.unwrap_or(usize::MAX);
+        pending.take_with(|available, item_bytes| {
+            let mut count = available.min(entries);
+            if let Some(budget) = shared_budget.as_deref_mut() {
+                count = budget.take(count, item_bytes);
+            }
+            entries -= count;
+            count
+        })
     }
 
     fn ensure_page_buffers(
@@ -403,6 +1278,7 @@ impl ClipmapRenderer {
                 )?;
                 Self::initialize_page_coord_buffer(&mut buffer, coord_count)?;
                 frame.page_coord_buffers[lod] = Some(buffer);
+                frame.resident_pages[lod].clear();
             }
         }
 
@@ -416,6 +1292,7 @@ impl ClipmapRenderer {
             if let Some(mut buffer) = frame.page_coord_buffers[lod].take() {
                 allocator.free_buffer(&mut buffer)?;
             }
+            frame.resident_pages[lod].clear();
         }
 
         Ok(())
@@ -439,75 +1316,107 @@ impl ClipmapRenderer {
             )?;
             self.clipmap_info_addresses[frame_index] = buffer.device_address(device);
             self.frame_buffers[frame_index].clipmap_info_buffer = Some(buffer);
+            self.frame_buffers[frame_index].last_info = None;
         }
 
         Ok(())
     }
 
-    fn ensure_brick_header_buffer(
+    fn ensure_material_buffer(
         &mut self,
         allocator: &mut GpuAllocator,
         frame_index: usize,
-        headers: &[BrickHeader],
-    ) -> Result<bool> {
-        let required = (headers.len() * std::mem::size_of::<BrickHeader>()) as u64;
-        let usage =
-            vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS;
-        let buffer = &mut self.frame_buffers[frame_index].brick_header_buffer;
-
-        if buffer.as_ref().is_none_or(|b| b.size < required) {
-            let current_size = buffer.as_ref().map_or(0, |b| b.size);
-            if let Some(mut old) = buffer.take() {
-                allocator.free_buffer(&mut old)?;
+    ) -> Result<()> {
+        let frame = &mut self.frame_buffers[frame_index];
+        if frame.material_buffer.is_none() {
+            frame.material_buffer = Some(allocator.create_buffer(
+                (MATERIAL_TABLE_LEN * std::mem::size_of::<GpuMaterial>()) as u64,
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS,
+                MemoryLocation::CpuToGpu,
+                &format!("clipmap_materials_{frame_index}"),
+            )?);
+            frame.material_revision = 0;
+        }
+        if frame.material_revision != self.material_revision {
+            if let Some(buffer) = &frame.material_buffer {
+                buffer.write(&self.materials)?;
             }
-            let min_size = std::mem::size_of::<BrickHeader>() as u64;
-            let required_size = required.max(min_size);
-            let size = if current_size == 0 {
-                required_size
-            } else {
-                current_size.saturating_mul(2).max(required_size)
-            };
-            let new_buffer = allocator.create_buffer(
-                size,
-                usage,
+            frame.material_revision = self.material_revision;
+        }
+
+        Ok(())
+    }
+
+    fn ensure_debug_palette_buffer(
+        &mut self,
+        allocator: &mut GpuAllocator,
+        frame_index: usize,
+    ) -> Result<()> {
+        let frame = &mut self.frame_buffers[frame_index];
+        if frame.debug_palette_buffer.is_none() {
+            frame.debug_palette_buffer = Some(allocator.create_buffer(
+                std::mem::size_of::<DebugPalette>() as u64,
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS,
                 MemoryLocation::CpuToGpu,
-                &format!("clipmap_brick_headers_f{frame_index}"),
-            )?;
-            *buffer = Some(new_buffer);
-            return Ok(true);
+                &format!("clipmap_debug_palette_{frame_index}"),
+            )?);
+            frame.debug_palette_revision = 0;
         }
+        if frame.debug_palette_revision != self.debug_palette_revision {
+            if let Some(buffer) = &frame.debug_palette_buffer {
+                buffer.write(std::slice::from_ref(&self.debug_palette))?;
+            }
+            frame.debug_palette_revision = self.debug_palette_revision;
+        }
+
+        Ok(())
+    }
 
-        Ok(false)
+    fn ensure_brick_header_buffer(
+        &mut self,
+        allocator: &mut GpuAllocator,
+        frame_index: usize,
+        headers: &[BrickHeader],
+    ) -> Result<bool> {
+        let required = (headers.len() * std::mem::size_of::<BrickHeader>())
+            .max(std::mem::size_of::<BrickHeader>()) as u64;
+        let name = format!("clipmap_brick_headers_f{frame_index}");
+        Self::ensure_pool_buffer(
+            allocator,
+            &mut self.frame_buffers[frame_index].brick_header_buffer,
+            required,
+            self.pool_reserve.header_bytes(),
+            std::mem::size_of::<BrickHeader>() as u64,
+            &name,
+        )
     }
 
     fn ensure_pool_buffer(
         allocator: &mut GpuAllocator,
         buffer: &mut Option<GpuBuffer>,
         pool_size: u64,
+        reserved: u64,
         stride: u64,
         name: &str,
     ) -> Result<bool> {
-        let required = pool_size.max(stride);
+        let current_size = buffer.as_ref().map(|b| b.size);
+        let Some(size) = pool_buffer_size(current_size, pool_size.max(stride), reserved) else {
+            return Ok(false);
+        };
+        if current_size.is_some() {
+            tracing::warn!(
+                "{name}: pool buffer reallocation (potential frame spike), {} -> {size} bytes",
+                current_size.unwrap_or(0)
+            );
+        }
+
         let usage =
             vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS;
-
-        if buffer.as_ref().is_none_or(|b| b.size < required) {
-            let current_size = buffer.as_ref().map_or(0, |b| b.size);
-            if let Some(mut old) = buffer.take() {
-                allocator.free_buffer(&mut old)?;
-            }
-            let grow_size = if current_size == 0 {
-                required
-            } else {
-                current_size.saturating_mul(2).max(required)
-            };
-            let new_buffer =
-                allocator.create_buffer(grow_size, usage, MemoryLocation::CpuToGpu, name)?;
-            *buffer = Some(new_buffer);
-            return Ok(true);
+        if let Some(mut old) = buffer.take() {
+            allocator.free_buffer(&mut old)?;
         }
-
-        Ok(false)
+        *buffer = Some(allocator.create_buffer(size, usage, MemoryLocation::CpuToGpu, name)?);
+        Ok(true)
     }
 
     #[cfg_attr(
@@ -515,13 +1424,13 @@ impl ClipmapRenderer {
         tracing::instrument(level = "trace", skip_all)
     )]
     fn upload_page_tables(
-        &self,
+        &mut self,
         controller: &ClipmapStreamingController,
         frame_index: usize,
         dirty_pages: Vec<Vec<usize>>,
     ) -> Result<()> {
         let page_count = CLIPMAP_PAGE_GRID * CLIPMAP_PAGE_GRID * CLIPMAP_PAGE_GRID;
-        let frame = &self.frame_buffers[frame_index];
+        let frame = &mut self.frame_buffers[frame_index];
 
         for lod in 0..CLIPMAP_LOD_COUNT {
             let Some(brick_buffer) = &frame.page_brick_buffers[lod] else {
@@ -566,6 +1475,11 @@ impl ClipmapRenderer {
 
                 let coord_offset = (page_index * std::mem::size_of::<[i32; 4]>()) as u64;
                 coord_buffer.write_range(coord_offset, &page_coords[page_index])?;
+                track_page(
+                    &mut frame.resident_pages[lod],
+                    page_index,
+                    page_coords[page_index],
+                );
             }
         }
 
@@ -688,10 +1602,13 @@ impl ClipmapRenderer {
             } else {
                 0
             };
-            let coverage = if renderable {
-                controller.lod_coverage(lod) as f32
+            let (coverage, coverage_y) = if renderable {
+                (
+                    controller.lod_coverage(lod) as f32,
+                    controller.lod_coverage_y(lod) as f32,
+                )
             } else {
-                0.0
+                (0.0, 0.0)
             };
 
             info.origin[lod] = [origin.x as i32, origin.y as i32, origin.z as i32, 0];
@@ -699,10 +1616,11 @@ impl ClipmapRenderer {
             info.lod_aabb_min[lod] = [origin.x as f32, origin.y as f32, origin.z as f32, 0.0];
             info.lod_aabb_max[lod] = [
                 origin.x as f32 + coverage,
-                origin.y as f32 + coverage,
+                origin.y as f32 + coverage_y,
                 origin.z as f32 + coverage,
                 0.0,
             ];
+            info.lod_transition[lod] = [self.lod_transitions[lod], 0.0, 0.0, 0.0];
         }
 
         if let Some(buffer) = &frame.brick_header_buffer {
@@ -717,6 +1635,18 @@ impl ClipmapRenderer {
         if let Some(buffer) = &frame.raw16_buffer {
             info.raw16_addr = buffer.device_address(device);
         }
+        if let Some(buffer) = &frame.normal_buffer {
+            info.normal_addr = buffer.device_address(device);
+        }
+        if let Some(buffer) = &frame.light_buffer {
+            info.light_addr = buffer.device_address(device);
+        }
+        if let Some(buffer) = &frame.material_buffer {
+            info.material_addr = buffer.device_address(device);
+        }
+        if let Some(buffer) = &frame.debug_palette_buffer {
+            info.debug_palette_addr = buffer.device_address(device);
+        }
 
         info
     }
@@ -756,17 +1686,726 @@ impl ClipmapRenderer {
     }
 }
 
+/// Per-block GPU material table for a registry.
+///
+/// Blocks past [`MATERIAL_TABLE_LEN`] are not representable and are ignored.
+fn gpu_materials(registry: &MaterialRegistry) -> Vec<GpuMaterial> {
+    let opaque = GpuMaterial {
+        flags: 0,
+        alpha: 1.0,
+    };
+    let mut materials = vec![opaque; MATERIAL_TABLE_LEN];
+    for (block, material) in registry.iter() {
+        if let Some(slot) = materials.get_mut(usize::from(block.0)) {
+            if material.needs_fine_steps {
+                slot.flags |= MATERIAL_FLAG_FINE_STEPS;
+            }
+            if let Some(alpha) = material.cutout_alpha {
+                slot.flags |= MATERIAL_FLAG_ALPHA_CUTOUT;
+                slot.alpha = alpha.clamp(0.0, 1.0);
+            }
+        }
+    }
+    materials
+}
+
+/// New byte size for a pool buffer, or `None` when the current one fits.
+///
+/// First allocations honour the reservation; later growth doubles.
+fn pool_buffer_size(current_size: Option<u64>, required: u64, reserved: u64) -> Option<u64> {
+    match current_size {
+        Some(size) if size >= required => None,
+        Some(size) => Some(size.saturating_mul(2).max(required)),
+        None => Some(required.max(reserved)),
+    }
+}
+
+/// Byte ranges of `next` that differ from the info already on the GPU.
+///
+/// Differences are found per 8-byte granule and merged into contiguous
+/// ranges. A missing `previous`, or changes scattered over too many ranges,
+/// rewrites the whole block in one go.
+fn info_write_ranges(
+    previous: Option<&GpuClipmapInfo>,
+    next: &GpuClipmapInfo,
+) -> Vec<std::ops::Range<usize>> {
+    const WHOLE: std::ops::Range<usize> = 0..GpuClipmapInfo::SIZE;
+
+    let Some(previous) = previous else {
+        return vec![WHOLE];
+    };
+    let old = bytemuck::bytes_of(previous);
+    let new = bytemuck::bytes_of(next);
+
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (granule, (old, new)) in old
+        .chunks(INFO_WRITE_GRANULE)
+        .zip(new.chunks(INFO_WRITE_GRANULE))
+        .enumerate()
+    {
+        if old == new {
+            continue;
+        }
+        let start = granule * INFO_WRITE_GRANULE;
+        let end = start + new.len();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+
+    if ranges.len() > INFO_MAX_PARTIAL_WRITES {
+        return vec![WHOLE];
+    }
+    ranges
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use glam::Vec3;
+    use voxelicous_core::types::{BlockId, Material};
+    use voxelicous_gpu::testing::test_gpu;
+    use voxelicous_gpu::GpuContext;
+    use voxelicous_world::TerrainGenerator;
+
     use super::*;
 
+    fn fake_buffer(size: u64) -> Option<GpuBuffer> {
+        Some(GpuBuffer {
+            buffer: vk::Buffer::null(),
+            allocation: None,
+            size,
+            usage: vk::BufferUsageFlags::STORAGE_BUFFER,
+            location: MemoryLocation::CpuToGpu,
+        })
+    }
+
+    #[test]
+    fn one_buffer_set_per_frame_in_flight() {
+        for frames in 1..=3 {
+            let renderer = ClipmapRenderer::new(frames);
+            assert_eq!(renderer.frames_in_flight(), frames);
+            assert_eq!(renderer.pending_dirty_per_frame.len(), frames);
+            assert_eq!(renderer.clipmap_info_addresses.len(), frames);
+        }
+    }
+
+    #[test]
+    fn gpu_page_positions_follow_page_table_uploads() {
+        let mut renderer = ClipmapRenderer::new(2);
+        let resident = &mut renderer.frame_buffers[0].resident_pages;
+        track_page(&mut resident[0], 17, [4, -2, 9, 0]);
+        track_page(&mut resident[2], 3, [1, 0, -1, 0]);
+        track_page(&mut resident[0], 40, [5, -2, 9, 0]);
+        // Slot 40 is evicted again.
+        track_page(&mut resident[0], 40, INVALID_PAGE_COORD);
+
+        let mut positions: Vec<_> = renderer.gpu_page_positions(0).collect();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![(0, [4, -2, 9]), (2, [1, 0, -1])]);
+        assert_eq!(renderer.gpu_page_positions(1).count(), 0);
+    }
+
+    #[test]
+    fn memory_breakdown_sums_buffer_sizes() {
+        let mut renderer = ClipmapRenderer::new(2);
+        for (i, frame) in renderer.frame_buffers.iter_mut().enumerate() {
+            let scale = i as u64 + 1;
+            frame.page_brick_buffers[0] = fake_buffer(1000 * scale);
+            frame.page_occ_buffers[0] = fake_buffer(200 * scale);
+            frame.page_coord_buffers[1] = fake_buffer(30 * scale);
+            frame.brick_header_buffer = fake_buffer(4000 * scale);
+            frame.raw16_buffer = fake_buffer(500 * scale);
+            frame.clipmap_info_buffer = fake_buffer(GpuClipmapInfo::SIZE as u64);
+        }
+
+        let memory = renderer.gpu_memory_breakdown();
+        assert_eq!(memory.page_tables, 1230 * 3);
+        assert_eq!(memory.pools, 4500 * 3);
+        assert_eq!(memory.info_buffer, 2 * GpuClipmapInfo::SIZE as u64);
+        assert_eq!(
+            memory.total,
+            memory.page_tables + memory.pools + memory.info_buffer
+        );
+    }
+
+    #[test]
+    fn info_updates_write_only_changed_slots() {
+        let whole = 0..GpuClipmapInfo::SIZE;
+        let mut info = GpuClipmapInfo::zeroed();
+        assert_eq!(info_write_ranges(None, &info), std::slice::from_ref(&whole));
+        assert!(info_write_ranges(Some(&info), &info).is_empty());
+
+        // One reallocated pool buffer touches a single 8-byte slot.
+        let previous = info;
+        info.palette16_addr = 0xDEAD_0000;
+        let ranges = info_write_ranges(Some(&previous), &info);
+        let offset = std::mem::offset_of!(GpuClipmapInfo, palette16_addr);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], offset..offset + 8);
+
+        // Moving one LOD only rewrites that LOD's origin and bounds.
+        let previous = info;
+        info.origin[2] = [64, 0, -32, 0];
+        info.lod_aabb_min[2] = [64.0, 0.0, -32.0, 0.0];
+        info.lod_aabb_max[2] = [320.0, 256.0, 224.0, 0.0];
+        let ranges = info_write_ranges(Some(&previous), &info);
+        assert_eq!(ranges.len(), 3);
+        let written: usize = ranges.iter().map(ExactSizeIterator::len).sum();
+        assert!(written <= 3 * 16, "wrote {written} bytes");
+
+        // Widely scattered changes fall back to a single full write.
+        let previous = info;
+        for lod in 0..CLIPMAP_LOD_COUNT {
+            info.origin[lod][0] = 1;
+            info.voxel_size[lod] = [1 << lod, 0, 0, 0];
+        }
+        assert_eq!(
+            info_write_ranges(Some(&previous), &info),
+            std::slice::from_ref(&whole)
+        );
+    }
+
     #[test]
     fn gpu_clipmap_info_size() {
-        assert_eq!(GpuClipmapInfo::SIZE, 576);
+        assert_eq!(GpuClipmapInfo::SIZE, 688);
     }
 
     #[test]
     fn push_constants_size() {
-        assert_eq!(ClipmapRenderPushConstants::SIZE, 32);
+        assert_eq!(ClipmapRenderPushConstants::SIZE, 72);
+    }
+
+    #[test]
+    fn fog_coupled_sky_meets_the_terrain_at_the_horizon() {
+        let mut renderer = ClipmapRenderer::new(1);
+        let pc = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert_eq!((pc.fog_density, pc.fog_affects_sky), (0.0, 0));
+
+        renderer.set_fog(FogSettings {
+            start: 64.0,
+            density: 0.01,
+            affects_sky: false,
+        });
+        renderer.set_fog_affects_sky(true);
+        let pc = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert_eq!(
+            (pc.fog_start, pc.fog_density, pc.fog_affects_sky),
+            (64.0, 0.01, 1)
+        );
+
+        // Fog share of each pixel in a column sweeping up across the horizon:
+        // far terrain below, sky above.
+        let column = |fog: &FogSettings| -> Vec<f32> {
+            (-8..=8)
+                .map(|row| {
+                    let dir_y = row as f32 * 0.025;
+                    if dir_y < 0.0 {
+                        fog.terrain_factor(80.0 / -dir_y)
+                    } else {
+                        fog.sky_factor(dir_y)
+                    }
+                })
+                .collect()
+        };
+        let max_jump = |column: &[f32]| {
+            column
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .fold(0.0, f32::max)
+        };
+
+        let coupled = renderer.fog();
+        let coupled_column = column(&coupled);
+        assert!(coupled_column[8] > 0.99);
+        assert!(coupled_column[16] < 0.01);
+        assert!(max_jump(&coupled_column) < 0.4);
+
+        let uncoupled = FogSettings {
+            affects_sky: false,
+            ..coupled
+        };
+        assert!(max_jump(&column(&uncoupled)) > 0.95);
+    }
+
+    #[test]
+    fn push_constants_carry_lod_step_scaling() {
+        let mut renderer = ClipmapRenderer::new(1);
+        let defaults = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert_eq!(defaults.base_steps, 512);
+        assert!(defaults.lod_step_falloff.abs() < f32::EPSILON);
+
+        renderer.set_lod_step_scaling(Some(256), 1.0);
+        let pc = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert_eq!(pc.max_steps, 512);
+        assert_eq!(pc.base_steps, 256);
+        assert!((pc.lod_step_falloff - 1.0).abs() < f32::EPSILON);
+        assert_eq!(pc.steps_for_lod(1, 1), 256);
+        assert_eq!(pc.steps_for_lod(2, 1), 128);
+        assert_eq!(pc.steps_for_lod(32, 1), 8);
+    }
+
+    /// Upload a frame's material table and read it back.
+    fn uploaded_materials(gpu: &GpuContext, renderer: &mut ClipmapRenderer) -> Vec<GpuMaterial> {
+        let mut allocator = gpu.allocator().lock();
+        renderer.ensure_material_buffer(&mut allocator, 0).unwrap();
+        let buffer = renderer.frame_buffers[0].material_buffer.as_ref().unwrap();
+        let uploaded = unsafe {
+            std::slice::from_raw_parts(
+                buffer.mapped_ptr().unwrap().cast::<GpuMaterial>(),
+                MATERIAL_TABLE_LEN,
+            )
+        }
+        .to_vec();
+        renderer.invalidate(&mut allocator).unwrap();
+        uploaded
+    }
+
+    fn custom_debug_palette() -> DebugPalette {
+        let mut ramp = DebugPalette::default().ramp;
+        ramp[3] = [0.25, 0.5, 0.75, 0.0];
+        DebugPalette {
+            ramp,
+            lod_boundary: [0.0, 0.6, 1.0, 0.0],
+            ..DebugPalette::default()
+        }
+    }
+
+    #[test]
+    fn custom_debug_palette_replaces_the_default() {
+        let mut renderer = ClipmapRenderer::new(1);
+        assert_eq!(renderer.debug_palette(), &DebugPalette::default());
+        renderer.set_debug_palette(custom_debug_palette());
+        assert_eq!(renderer.debug_palette().lod_boundary, [0.0, 0.6, 1.0, 0.0]);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn custom_debug_palette_reaches_gpu_buffer() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let mut renderer = ClipmapRenderer::new(1);
+        let palette = custom_debug_palette();
+        renderer.set_debug_palette(palette);
+
+        let mut allocator = gpu.allocator().lock();
+        renderer
+            .ensure_debug_palette_buffer(&mut allocator, 0)
+            .unwrap();
+        let buffer = renderer.frame_buffers[0]
+            .debug_palette_buffer
+            .as_ref()
+            .unwrap();
+        let uploaded = unsafe { *buffer.mapped_ptr().unwrap().cast::<DebugPalette>() };
+        assert_eq!(uploaded, palette);
+        renderer.invalidate(&mut allocator).unwrap();
+    }
+
+    #[test]
+    fn leaves_request_fine_steps() {
+        let leaves = usize::from(BlockId::LEAVES.0);
+        let stone = usize::from(BlockId::STONE.0);
+        let mut renderer = ClipmapRenderer::new(1);
+        let materials = renderer.materials();
+        assert_eq!(materials.len(), MATERIAL_TABLE_LEN);
+        assert_ne!(materials[leaves].flags & MATERIAL_FLAG_FINE_STEPS, 0);
+        assert_eq!(materials[stone].flags & MATERIAL_FLAG_FINE_STEPS, 0);
+
+        let pc = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert!((pc.fine_step_scale - DEFAULT_FINE_STEP_SCALE).abs() < f32::EPSILON);
+        renderer.set_fine_step_scale(0.5);
+        let pc = renderer.push_constants(1280, 720, 512, 0, DebugMode::None);
+        assert!((pc.fine_step_scale - 0.5).abs() < f32::EPSILON);
+        renderer.set_fine_step_scale(4.0);
+        assert!((renderer.fine_step_scale() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn leaves_fine_step_flag_reaches_material_buffer() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let leaves = usize::from(BlockId::LEAVES.0);
+        let stone = usize::from(BlockId::STONE.0);
+        let mut renderer = ClipmapRenderer::new(1);
+        let uploaded = uploaded_materials(&gpu, &mut renderer);
+        assert_ne!(uploaded[leaves].flags & MATERIAL_FLAG_FINE_STEPS, 0);
+        assert_eq!(uploaded[stone].flags, 0);
+
+        // Dropping the flag re-uploads on the next sync.
+        let mut registry = MaterialRegistry::default();
+        registry.register(BlockId::LEAVES, Material::GRASS);
+        renderer.set_materials(&registry);
+        let uploaded = uploaded_materials(&gpu, &mut renderer);
+        assert_eq!(uploaded[leaves].flags, 0);
+    }
+
+    fn thin_leaf_registry() -> MaterialRegistry {
+        let mut registry = MaterialRegistry::default();
+        registry.register(
+            BlockId::LEAVES,
+            Material {
+                cutout_alpha: Some(0.3),
+                ..Material::LEAVES
+            },
+        );
+        registry
+    }
+
+    #[test]
+    fn leaf_cutout_alpha_follows_the_material() {
+        let leaves = usize::from(BlockId::LEAVES.0);
+        let mut renderer = ClipmapRenderer::new(1);
+        let leaf = renderer.materials()[leaves];
+        assert_ne!(leaf.flags & MATERIAL_FLAG_ALPHA_CUTOUT, 0);
+        assert!(leaf.alpha > 0.0 && leaf.alpha < 1.0);
+        let stone = renderer.materials()[usize::from(BlockId::STONE.0)];
+        assert_eq!(stone.flags & MATERIAL_FLAG_ALPHA_CUTOUT, 0);
+        assert!((stone.alpha - 1.0).abs() < f32::EPSILON);
+
+        renderer.set_materials(&thin_leaf_registry());
+        assert!((renderer.materials()[leaves].alpha - 0.3).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn leaf_cutout_alpha_reaches_material_buffer() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let leaves = usize::from(BlockId::LEAVES.0);
+        let mut renderer = ClipmapRenderer::new(1);
+        renderer.set_materials(&thin_leaf_registry());
+        let uploaded = uploaded_materials(&gpu, &mut renderer);
+        assert_eq!(uploaded[leaves], renderer.materials()[leaves]);
+    }
+
+    #[test]
+    fn zero_falloff_keeps_uniform_stepping() {
+        let mut renderer = ClipmapRenderer::new(1);
+        renderer.set_lod_step_scaling(Some(300), 0.0);
+        let pc = renderer.push_constants(1280, 720, 1024, 0, DebugMode::None);
+        for lod in 0..CLIPMAP_LOD_COUNT {
+            assert_eq!(pc.steps_for_lod(1 << lod, 1), 300);
+        }
+    }
+
+    #[test]
+    fn lod_transition_distances_must_increase() {
+        let mut renderer = ClipmapRenderer::new(1);
+        assert_eq!(
+            renderer.set_lod_transition_distances(&[64.0, 64.0]),
+            Err(LodTransitionError::NotIncreasing(1))
+        );
+        assert_eq!(
+            renderer.set_lod_transition_distances(&[64.0, 160.0, 96.0]),
+            Err(LodTransitionError::NotIncreasing(2))
+        );
+        assert_eq!(
+            renderer.set_lod_transition_distances(&[0.0]),
+            Err(LodTransitionError::NotPositive(0))
+        );
+        assert_eq!(
+            renderer.set_lod_transition_distances(&[f32::NAN]),
+            Err(LodTransitionError::NotPositive(0))
+        );
+        assert_eq!(
+            renderer.set_lod_transition_distances(&[1.0; CLIPMAP_LOD_COUNT]),
+            Err(LodTransitionError::TooMany(CLIPMAP_LOD_COUNT))
+        );
+        assert_eq!(
+            renderer.lod_transition_distances(),
+            [0.0; CLIPMAP_LOD_COUNT]
+        );
+
+        renderer
+            .set_lod_transition_distances(&[64.0, 160.0, 400.0])
+            .unwrap();
+        let expected = [64.0, 160.0, 400.0, 0.0, 0.0, 0.0];
+        assert_eq!(renderer.lod_transition_distances(), expected);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn lod_transition_distances_upload_in_order() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let mut renderer = ClipmapRenderer::new(1);
+        renderer
+            .set_lod_transition_distances(&[64.0, 160.0, 400.0])
+            .unwrap();
+        let expected = renderer.lod_transition_distances();
+        let controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        let info = renderer.build_gpu_info(gpu.device(), &controller, 0);
+        let uploaded = info.lod_transition.map(|transition| transition[0]);
+        assert_eq!(uploaded, expected);
+    }
+
+    #[test]
+    fn pool_buffer_size_follows_the_reserve() {
+        // With a reserve the first buffer takes the reserved size, and later
+        // syncs reuse it while the pool fits.
+        let first = pool_buffer_size(None, 1000, 4096);
+        assert_eq!(first, Some(4096));
+        assert_eq!(pool_buffer_size(first, 4000, 4096), None);
+        assert_eq!(pool_buffer_size(first, 5000, 4096), Some(8192));
+
+        // Without a reserve the first buffer is exact-fit and growth doubles.
+        assert_eq!(pool_buffer_size(None, 1000, 0), Some(1000));
+        assert_eq!(pool_buffer_size(Some(1000), 1500, 0), Some(2000));
+        assert_eq!(pool_buffer_size(Some(1000), 5000, 0), Some(5000));
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn pool_reserve_avoids_first_sync_reallocation() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(42));
+        controller.set_visible_page_grid(8);
+        let stream_to = |controller: &mut ClipmapStreamingController, x: f32| {
+            for _ in 0..2000 {
+                controller.update(Vec3::new(x, 60.0, 0.0));
+                if controller.lod_ready(0) {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            assert!(controller.lod_ready(0));
+        };
+
+        let reserve = PoolReserve {
+            headers: 1 << 16,
+            palette16: 1 << 15,
+            palette32: 1 << 14,
+            raw16: 1 << 13,
+            normals: 1 << 13,
+            lights: 1 << 12,
+        };
+        let mut renderer = ClipmapRenderer::with_reserve(1, reserve);
+        assert_eq!(renderer.pool_reserve(), reserve);
+        let pools = |renderer: &ClipmapRenderer| {
+            let frame = &renderer.frame_buffers[0];
+            [
+                &frame.brick_header_buffer,
+                &frame.palette16_buffer,
+                &frame.palette32_buffer,
+                &frame.raw16_buffer,
+                &frame.normal_buffer,
+                &frame.light_buffer,
+            ]
+            .map(|buffer| {
+                let buffer = buffer.as_ref().expect("pool buffer allocated");
+                (buffer.buffer, buffer.size)
+            })
+        };
+        let reserved = [
+            reserve.header_bytes(),
+            reserve.palette16_bytes(),
+            reserve.palette32_bytes(),
+            reserve.raw16_bytes(),
+            reserve.normal_bytes(),
+            reserve.light_bytes(),
+        ];
+
+        let mut allocator = gpu.allocator().lock();
+        let mut first = None;
+        for x in [0.0, 96.0] {
+            stream_to(&mut controller, x);
+            let dirty = controller.take_dirty_state();
+            renderer
+                .sync_from_controller(&mut allocator, gpu.device(), &controller, dirty, 0, 0, None)
+                .unwrap();
+            match first {
+                // The first sync allocates every pool at its reserved size.
+                None => {
+                    assert_eq!(pools(&renderer).map(|(_, size)| size), reserved);
+                    first = Some(pools(&renderer));
+                }
+                // Streaming more of the world fills the same buffers.
+                Some(first) => assert_eq!(pools(&renderer), first),
+            }
+        }
+
+        renderer.destroy(&mut allocator).unwrap();
+    }
+
+    #[test]
+    fn upload_budget_limits_pending_dirty_per_sync() {
+        let mut renderer = ClipmapRenderer::new(2);
+        renderer.set_upload_budget(Some(64));
+
+        let mut dirty = ClipmapDirtyState {
+            dirty_pages: vec![Vec::new(); CLIPMAP_LOD_COUNT],
+            ..Default::default()
+        };
+        dirty.dirty_pages[0] = (0..200).collect();
+        dirty.dirty_headers = (0..50).map(BrickId).collect();
+        dirty.dirty_palette16_entries = (0..30).collect();
+        renderer.broadcast_dirty(&dirty);
+        assert_eq!(renderer.pending_upload_count(0), 280);
+
+        let first = renderer.take_pending_dirty(0, None);
+        assert_eq!(first.len(), 64);
+        // Pool entries and headers drain before page tables.
+        assert_eq!(first.dirty_palette16_entries.len(), 30);
+        assert_eq!(first.dirty_headers.len(), 34);
+        assert!(first.dirty_pages[0].is_empty());
+        assert_eq!(renderer.pending_upload_count(0), 216);
+        // Other frames keep their own full copy.
+        assert_eq!(renderer.pending_upload_count(1), 280);
+
+        let mut uploaded = first.len();
+        while renderer.pending_upload_count(0) > 0 {
+            let batch = renderer.take_pending_dirty(0, None);
+            assert!(batch.len() <= 64);
+            uploaded += batch.len();
+        }
+        assert_eq!(uploaded, 280);
+    }
+
+    #[test]
+    fn shared_upload_budget_bounds_bytes_across_renderers() {
+        let mut dirty = ClipmapDirtyState {
+            dirty_pages: vec![Vec::new(); CLIPMAP_LOD_COUNT],
+            ..Default::default()
+        };
+        dirty.dirty_pages[0] = (0..100).collect();
+        dirty.dirty_headers = (0..100).map(BrickId).collect();
+        dirty.dirty_palette16_entries = (0..20).collect();
+
+        let mut near = ClipmapRenderer::new(1);
+        let mut far = ClipmapRenderer::new(1);
+        near.broadcast_dirty(&dirty);
+        far.broadcast_dirty(&dirty);
+        let total = near.pending_upload_count(0) + far.pending_upload_count(0);
+
+        let upload_bytes = |mut taken: PendingDirtyState| {
+            let mut bytes = 0;
+            taken.take_with(|count, item_bytes| {
+                bytes += count as u64 * item_bytes;
+                count
+            });
+            bytes
+        };
+
+        let budget_bytes = 16 * 1024;
+        let mut budget = UploadBudget::new(budget_bytes);
+        let mut uploaded = 0;
+        let mut frames = 0;
+        while near.pending_upload_count(0) + far.pending_upload_count(0) > 0 {
+            budget.begin_frame();
+            let a = near.take_pending_dirty(0, Some(&mut budget));
+            let b = far.take_pending_dirty(0, Some(&mut budget));
+            for (renderer, taken) in [(&near, &a), (&far, &b)] {
+                if renderer.pending_dirty_per_frame[0].has_pending_pool_entries() {
+                    assert!(taken.dirty_headers.is_empty());
+                    assert!(taken.dirty_pages.iter().all(DirtyQueue::is_empty));
+                }
+            }
+            if frames == 0 {
+                // The first renderer drains the budget; the second defers.
+                assert!(a.len() > 0);
+                assert!(far.pending_upload_count(0) > 0);
+            }
+            uploaded += a.len() + b.len();
+            let frame_bytes = upload_bytes(a) + upload_bytes(b);
+            assert!(
+                frame_bytes <= budget_bytes,
+                "{frame_bytes} bytes in one frame"
+            );
+            assert_eq!(frame_bytes, budget.used());
+            frames += 1;
+            assert!(frames < 1000, "uploads stalled");
+        }
+        assert_eq!(uploaded, total);
+        assert!(frames > 2);
+    }
+
+    #[test]
+    fn byte_budget_never_uploads_headers_ahead_of_pool_entries() {
+        fn take(pending: &mut PendingDirtyState, mut bytes: u64) -> PendingDirtyState {
+            pending.take_with(|available, item_bytes| {
+                let count = available.min((bytes / item_bytes) as usize);
+                bytes -= count as u64 * item_bytes;
+                count
+            })
+        }
+
+        let mut pending = PendingDirtyState::new();
+        pending.dirty_raw16_entries.extend_from_slice(&[0, 1, 2]);
+        pending.dirty_normal_entries.extend_from_slice(&[0]);
+        pending
+            .dirty_headers
+            .extend_from_slice(&[BrickId(1), BrickId(2)]);
+        pending.dirty_pages[0].extend_from_slice(&[0]);
+
+        // Room for two raw16 entries plus change: a header and a page table
+        // entry would fit in the leftover bytes, but must wait.
+        let first = take(
+            &mut pending,
+            2 * RAW16_STRIDE as u64 + PAGE_TABLE_ENTRY_BYTES + 64,
+        );
+        assert_eq!(first.dirty_raw16_entries.len(), 2);
+        assert!(pending.has_pending_pool_entries());
+        assert!(first.dirty_normal_entries.is_empty());
+        assert!(first.dirty_headers.is_empty());
+        assert!(first.dirty_pages[0].is_empty());
+
+        // Once the pools are flushed, headers and pages follow.
+        let rest = take(&mut pending, u64::MAX);
+        assert!(!pending.has_pending_pool_entries());
+        assert_eq!(rest.dirty_raw16_entries.len(), 1);
+        assert_eq!(rest.dirty_headers.len(), 2);
+        assert_eq!(rest.dirty_pages[0].len(), 1);
+    }
+
+    #[test]
+    fn no_upload_budget_takes_everything() {
+        let mut renderer = ClipmapRenderer::new(1);
+        let mut dirty = ClipmapDirtyState {
+            dirty_pages: vec![Vec::new(); CLIPMAP_LOD_COUNT],
+            ..Default::default()
+        };
+        dirty.dirty_pages[1] = (0..500).collect();
+        renderer.broadcast_dirty(&dirty);
+
+        assert_eq!(renderer.take_pending_dirty(0, None).len(), 500);
+        assert_eq!(renderer.pending_upload_count(0), 0);
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn redirtied_entries_stay_pending_once() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        let mut renderer = ClipmapRenderer::new(1);
+        // A zero budget keeps everything pending, as when uploads lag behind.
+        renderer.set_upload_budget(Some(0));
+
+        let dirty = || {
+            let mut dirty = ClipmapDirtyState {
+                dirty_pages: vec![Vec::new(); CLIPMAP_LOD_COUNT],
+                ..Default::default()
+            };
+            dirty.dirty_pages[0] = vec![3, 5, 3, 8];
+            dirty.dirty_pages[1] = vec![3];
+            dirty.dirty_headers = vec![BrickId(1), BrickId(2)];
+            dirty.dirty_palette16_entries = vec![4, 4];
+            dirty
+        };
+        let mut allocator = gpu.allocator().lock();
+        for _ in 0..3 {
+            renderer
+                .sync_from_controller(
+                    &mut allocator,
+                    gpu.device(),
+                    &controller,
+                    dirty(),
+                    0,
+                    0,
+                    None,
+                )
+                .unwrap();
+            // Pages 3, 5, 8 at LOD 0, page 3 at LOD 1, two headers, one entry.
+            assert_eq!(renderer.pending_upload_count(0), 7);
+        }
+        renderer.destroy(&mut allocator).unwrap();
     }
 }
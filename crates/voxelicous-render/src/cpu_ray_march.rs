@@ -0,0 +1,430 @@
+//! CPU reference of the clipmap ray marcher.
+//!
+//! [`ray_march_cpu`] walks the same dense LOD0 page table and brick pools as
+//! `ray_march_clipmap.wgsl`, step for step, so traversal bugs show up in unit
+//! tests and GPU output can be compared against it without a device.
+
+use glam::{IVec3, Vec3};
+use voxelicous_core::math::Ray;
+use voxelicous_core::BlockId;
+use voxelicous_voxel::{
+    BrickId, GpuBrickPools, BRICK_SIZE, CLIPMAP_PAGE_GRID, PAGE_BRICKS, PAGE_BRICKS_PER_AXIS,
+    PAGE_VOXELS_PER_AXIS,
+};
+use voxelicous_world::ClipmapStreamingController;
+
+/// Ray marching limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RayMarchConfig {
+    /// Maximum number of voxels visited before giving up.
+    pub max_steps: u32,
+}
+
+impl Default for RayMarchConfig {
+    fn default() -> Self {
+        Self { max_steps: 512 }
+    }
+}
+
+/// First solid voxel along a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Point where the ray enters the hit voxel.
+    pub position: Vec3,
+    /// Distance from the ray origin to `position`.
+    pub distance: f32,
+    /// World voxel coordinate of the hit.
+    pub voxel: IVec3,
+    /// Normal of the face the ray entered through.
+    pub normal: Vec3,
+    /// Block stored at the hit voxel.
+    pub block: BlockId,
+}
+
+/// LOD0 of a clipmap, laid out like the wgpu backend's buffers.
+pub struct CpuClipmapView<'a> {
+    origin_page: IVec3,
+    /// Visible pages along each axis.
+    page_grid: IVec3,
+    pages: Vec<u32>,
+    pools: GpuBrickPools<'a>,
+}
+
+impl<'a> CpuClipmapView<'a> {
+    /// Snapshot the visible LOD0 page table of `clipmap`.
+    ///
+    /// Brick pools are borrowed, so the view must be rebuilt after streaming
+    /// or edits to see the changes.
+    pub fn new(clipmap: &'a ClipmapStreamingController) -> Self {
+        let (origin_page, pages) = lod0_page_table(clipmap);
+        let (grid_xz, grid_y) = (
+            clipmap.visible_page_grid_xz() as i32,
+            clipmap.visible_page_grid_y() as i32,
+        );
+        Self {
+            origin_page: IVec3::from_array(origin_page),
+            page_grid: IVec3::new(grid_xz, grid_y, grid_xz),
+            pages,
+            pools: clipmap.store().gpu_pools(),
+        }
+    }
+
+    /// Block at a world voxel, or air outside the visible LOD0 region.
+    pub fn block_at(&self, voxel: IVec3) -> BlockId {
+        let page_voxels = PAGE_VOXELS_PER_AXIS as i32;
+        let local = voxel - self.origin_page * page_voxels;
+        if local.min_element() < 0 || local.cmpge(self.page_grid * page_voxels).any() {
+            return BlockId::AIR;
+        }
+
+        let page = local / page_voxels;
+        let in_page = local % page_voxels;
+        let brick = in_page / BRICK_SIZE as i32;
+        let bricks_per_axis = PAGE_BRICKS_PER_AXIS as i32;
+        let grid = self.page_grid;
+        let page_index = (page.x + (page.y + page.z * grid.y) * grid.x) as usize;
+        let brick_index =
+            (brick.x + (brick.y + brick.z * bricks_per_axis) * bricks_per_axis) as usize;
+        let id = self.pages[page_index * PAGE_BRICKS + brick_index];
+        if id == 0 {
+            return BlockId::AIR;
+        }
+
+        let v = (in_page % BRICK_SIZE as i32).as_uvec3();
+        self.pools
+            .block_at(BrickId(id), v.x as usize, v.y as usize, v.z as usize)
+            .map_or(BlockId::AIR, BlockId)
+    }
+}
+
+/// March `ray` through LOD0 and return the first solid voxel.
+///
+/// Mirrors the WGSL `trace`: the ray is clipped to the visible region, then
+/// walked one voxel at a time for at most `config.max_steps` voxels.
+pub fn ray_march_cpu(
+    view: &CpuClipmapView<'_>,
+    ray: &Ray,
+    config: &RayMarchConfig,
+) -> Option<RayHit> {
+    let origin = ray.origin;
+    let dir = ray.direction;
+    let safe_dir = Vec3::select(dir.abs().cmplt(Vec3::splat(1e-8)), Vec3::splat(1e-8), dir);
+    let inv_dir = safe_dir.recip();
+    let page_voxels = PAGE_VOXELS_PER_AXIS as i32;
+    let box_min = (view.origin_page * page_voxels).as_vec3();
+    let box_max = box_min + (view.page_grid * page_voxels).as_vec3();
+    let t0 = (box_min - origin) * inv_dir;
+    let t1 = (box_max - origin) * inv_dir;
+    let t_near = t0.min(t1);
+    let t_far = t0.max(t1);
+    let t_enter = t_near.max_element().max(0.0);
+    let t_exit = t_far.min_element();
+    if t_enter >= t_exit {
+        return None;
+    }
+
+    let start_t = t_enter + 1e-4;
+    let start = origin + dir * start_t;
+    let step = safe_dir.signum().as_ivec3();
+    let step_f = step.as_vec3();
+    let delta = inv_dir.abs();
+    let mut voxel = start.floor().as_ivec3();
+    let voxel_f = voxel.as_vec3();
+    let mut side = Vec3::select(
+        step.cmpgt(IVec3::ZERO),
+        voxel_f + 1.0 - start,
+        voxel_f - start,
+    ) * inv_dir;
+    let mut normal = if t_near.x >= t_near.y && t_near.x >= t_near.z {
+        Vec3::new(-step_f.x, 0.0, 0.0)
+    } else if t_near.y >= t_near.z {
+        Vec3::new(0.0, -step_f.y, 0.0)
+    } else {
+        Vec3::new(0.0, 0.0, -step_f.z)
+    };
+
+    let span = t_exit - t_enter;
+    // Distance walked from `start`; zero until the first voxel boundary.
+    let mut t = 0.0;
+    for _ in 0..config.max_steps {
+        let block = view.block_at(voxel);
+        if !block.is_air() {
+            let distance = if t > 0.0 { start_t + t } else { t_enter };
+            return Some(RayHit {
+                position: origin + dir * distance,
+                distance,
+                voxel,
+                normal,
+                block,
+            });
+        }
+
+        if side.x < side.y && side.x < side.z {
+            voxel.x += step.x;
+            t = side.x;
+            side.x += delta.x;
+            normal = Vec3::new(-step_f.x, 0.0, 0.0);
+        } else if side.y < side.z {
+            voxel.y += step.y;
+            t = side.y;
+            side.y += delta.y;
+            normal = Vec3::new(0.0, -step_f.y, 0.0);
+        } else {
+            voxel.z += step.z;
+            t = side.z;
+            side.z += delta.z;
+            normal = Vec3::new(0.0, 0.0, -step_f.z);
+        }
+        if t > span {
+            break;
+        }
+    }
+    None
+}
+
+/// Dense LOD0 page table of the visible grid, 64 brick ids per page.
+///
+/// Entry `x + (y + z * grid_y) * grid` holds the page at `origin_page + (x, y, z)`,
+/// where `grid` is the X/Z page grid and `grid_y` the Y one;
+/// slots whose toroidal page does not match (not loaded yet) stay empty.
+pub(crate) fn lod0_page_table(clipmap: &ClipmapStreamingController) -> ([i32; 3], Vec<u32>) {
+    let page_size = PAGE_VOXELS_PER_AXIS as i64 * clipmap.lod_voxel_size(0);
+    let origin = clipmap.lod_origin(0);
+    let origin_page = [
+        origin.x.div_euclid(page_size) as i32,
+        origin.y.div_euclid(page_size) as i32,
+        origin.z.div_euclid(page_size) as i32,
+    ];
+
+    let (grid, grid_y) = (
+        clipmap.visible_page_grid_xz(),
+        clipmap.visible_page_grid_y(),
+    );
+    let coords = clipmap.page_coords(0);
+    let bricks = clipmap.page_brick_indices(0);
+    let ring = CLIPMAP_PAGE_GRID as i32;
+    let mut pages = vec![0; grid * grid_y * grid * PAGE_BRICKS];
+    for z in 0..grid {
+        for y in 0..grid_y {
+            for x in 0..grid {
+                let page = [
+                    origin_page[0] + x as i32,
+                    origin_page[1] + y as i32,
+                    origin_page[2] + z as i32,
+                ];
+                let slot = page[0].rem_euclid(ring) as usize
+                    + page[1].rem_euclid(ring) as usize * CLIPMAP_PAGE_GRID
+                    + page[2].rem_euclid(ring) as usize * CLIPMAP_PAGE_GRID * CLIPMAP_PAGE_GRID;
+                if coords[slot][..3] != page {
+                    continue;
+                }
+                let dense = (x + (y + z * grid_y) * grid) * PAGE_BRICKS;
+                pages[dense..dense + PAGE_BRICKS]
+                    .copy_from_slice(&bricks[slot * PAGE_BRICKS..(slot + 1) * PAGE_BRICKS]);
+            }
+        }
+    }
+    (origin_page, pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use voxelicous_voxel::WorldCoord;
+    use voxelicous_world::{TerrainConfig, TerrainGenerator};
+
+    use super::*;
+    use crate::WindSettings;
+
+    const GROUND: i32 = 70;
+
+    /// Flat world at `GROUND` with LOD0 loaded around `(0.5, 80, 0.5)`.
+    fn flat_clipmap() -> ClipmapStreamingController {
+        let generator =
+            TerrainGenerator::with_height_fn(TerrainConfig::default(), Box::new(|_, _| GROUND));
+        let mut clipmap = ClipmapStreamingController::new(generator);
+        clipmap.set_visible_page_grid(4);
+        assert!(clipmap.generate_initial_pages(Vec3::new(0.5, 80.0, 0.5)) > 0);
+        clipmap
+    }
+
+    fn block_at(clipmap: &ClipmapStreamingController, v: IVec3) -> BlockId {
+        clipmap.block_at_world(v.x.into(), v.y.into(), v.z.into())
+    }
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-3),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn rays_hit_the_faces_of_a_floating_cube() {
+        let mut clipmap = flat_clipmap();
+        let (min, max) = (IVec3::new(4, 100, 6), IVec3::new(6, 102, 8));
+        clipmap.edit_box(
+            WorldCoord {
+                x: min.x.into(),
+                y: min.y.into(),
+                z: min.z.into(),
+            },
+            WorldCoord {
+                x: max.x.into(),
+                y: max.y.into(),
+                z: max.z.into(),
+            },
+            BlockId::STONE,
+        );
+        let view = CpuClipmapView::new(&clipmap);
+        let config = RayMarchConfig::default();
+        let center = (min + max).as_vec3() * 0.5 + 0.5;
+
+        // Fire at the middle of each face from 10 voxels away.
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z, -Vec3::X, -Vec3::Y, -Vec3::Z] {
+            let face = center + axis * 1.5;
+            let ray = Ray::new(face + axis * 10.0, -axis);
+            let hit = ray_march_cpu(&view, &ray, &config)
+                .unwrap_or_else(|| panic!("ray along {} missed", -axis));
+            assert_close(hit.position, face);
+            assert_close(hit.normal, axis);
+            assert!((hit.distance - 10.0).abs() < 1e-3, "{}", hit.distance);
+            assert_eq!(hit.voxel, (center + axis).floor().as_ivec3());
+            assert_eq!(hit.block, BlockId::STONE);
+        }
+
+        // A diagonal ray lands on the corner voxel's top face.
+        let target = Vec3::new(4.25, 103.0, 6.75);
+        let dir = Vec3::new(-1.0, -2.0, 1.0).normalize();
+        let hit = ray_march_cpu(&view, &Ray::new(target - dir * 6.0, dir), &config).unwrap();
+        assert_close(hit.position, target);
+        assert_close(hit.normal, Vec3::Y);
+        assert_eq!(hit.voxel, IVec3::new(4, 102, 6));
+    }
+
+    #[test]
+    fn leaf_wind_only_animates_leaf_pixels() {
+        let mut clipmap = flat_clipmap();
+        let coord = |v: IVec3| WorldCoord {
+            x: v.x.into(),
+            y: v.y.into(),
+            z: v.z.into(),
+        };
+        let trunk_top = GROUND + 5;
+        clipmap.edit_box(
+            coord(IVec3::new(2, GROUND + 1, 2)),
+            coord(IVec3::new(2, trunk_top, 2)),
+            BlockId::LOG,
+        );
+        clipmap.edit_box(
+            coord(IVec3::new(0, trunk_top + 1, 0)),
+            coord(IVec3::new(4, trunk_top + 3, 4)),
+            BlockId::LEAVES,
+        );
+        let view = CpuClipmapView::new(&clipmap);
+        let config = RayMarchConfig::default();
+        let wind = WindSettings {
+            strength: 0.5,
+            speed: 2.0,
+        };
+
+        // Look down at the tree from the side so trunk, canopy and ground
+        // all show up, shading each pixel like the GPU march: a light term
+        // per face, with leaves tinted and their normals bent by the wind.
+        let eye = Vec3::new(2.5, GROUND as f32 + 14.0, -12.0);
+        let forward = (Vec3::new(2.5, GROUND as f32 + 3.0, 2.5) - eye).normalize();
+        let right = forward.cross(Vec3::Y).normalize();
+        let up = right.cross(forward);
+        let size = 32;
+        let frame = |time: f32| -> Vec<(BlockId, f32)> {
+            let mut pixels = Vec::new();
+            for py in 0..size {
+                for px in 0..size {
+                    let u = (px as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                    let v = (py as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                    let dir = (forward + right * u * 0.6 - up * v * 0.6).normalize();
+                    let Some(hit) = ray_march_cpu(&view, &Ray::new(eye, dir), &config) else {
+                        continue;
+                    };
+                    let brightness = if hit.block == BlockId::LEAVES {
+                        let normal = wind.leaf_normal(hit.normal, hit.voxel, time);
+                        (0.6 + 0.4 * normal.y) * wind.leaf_tint(hit.voxel, time)
+                    } else {
+                        0.6 + 0.4 * hit.normal.y
+                    };
+                    pixels.push((hit.block, brightness));
+                }
+            }
+            pixels
+        };
+
+        let (first, second) = (frame(0.0), frame(1.3));
+        assert_eq!(first.len(), second.len());
+        let mut leaves = 0;
+        let mut others = 0;
+        let mut changed_leaves = 0;
+        for ((block, a), (_, b)) in first.iter().zip(&second) {
+            if *block == BlockId::LEAVES {
+                leaves += 1;
+                changed_leaves += usize::from((a - b).abs() > 1e-4);
+            } else {
+                others += 1;
+                assert_eq!(a.to_bits(), b.to_bits(), "{block:?} pixel moved");
+            }
+        }
+        assert!(
+            leaves > 0 && others > 0,
+            "{leaves} leaf / {others} other pixels"
+        );
+        assert!(
+            changed_leaves * 2 > leaves,
+            "{changed_leaves}/{leaves} leaves swayed"
+        );
+
+        // Without wind the leaves hold still too.
+        let calm = WindSettings::default();
+        assert!((calm.leaf_tint(IVec3::new(1, 76, 3), 5.0) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn vertical_rays_stop_at_the_first_solid_block() {
+        let clipmap = flat_clipmap();
+        let view = CpuClipmapView::new(&clipmap);
+        let config = RayMarchConfig::default();
+
+        for (x, z) in [(0, 0), (-17, 9), (23, -30), (40, 41)] {
+            let top = (GROUND..120)
+                .rev()
+                .map(|y| IVec3::new(x, y, z))
+                .find(|v| !block_at(&clipmap, *v).is_air())
+                .unwrap();
+            let origin = Vec3::new(0.5, 125.5, 0.5) + IVec3::new(x, 0, z).as_vec3();
+            let hit = ray_march_cpu(&view, &Ray::new(origin, -Vec3::Y), &config).unwrap();
+            assert_eq!(hit.voxel, top);
+            assert_close(hit.normal, Vec3::Y);
+            assert_close(
+                hit.position,
+                origin.with_y(0.0) + (top + IVec3::Y).as_vec3() * Vec3::Y,
+            );
+            assert_eq!(hit.block, block_at(&clipmap, top));
+        }
+    }
+
+    #[test]
+    fn rays_that_leave_the_region_miss() {
+        let clipmap = flat_clipmap();
+        let view = CpuClipmapView::new(&clipmap);
+        let config = RayMarchConfig::default();
+
+        let up = Ray::new(Vec3::new(0.5, 110.5, 0.5), Vec3::Y);
+        assert_eq!(ray_march_cpu(&view, &up, &config), None);
+
+        // Pointing away from the visible region entirely.
+        let away = Ray::new(Vec3::new(10_000.0, 80.0, 0.0), Vec3::X);
+        assert_eq!(ray_march_cpu(&view, &away, &config), None);
+
+        // Too few steps to reach the ground.
+        let down = Ray::new(Vec3::new(0.5, 110.5, 0.5), -Vec3::Y);
+        let short = RayMarchConfig { max_steps: 4 };
+        assert_eq!(ray_march_cpu(&view, &down, &short), None);
+    }
+}
@@ -3,31 +3,64 @@
 //! Provides toggleable debug visualization modes for ray marching, including
 //! heatmaps and clipmap boundary visualization, controlled by hotkeys.
 
+use bytemuck::{Pod, Zeroable};
+
+/// Number of evenly spaced stops in [`DebugPalette::ramp`].
+pub const DEBUG_RAMP_STOPS: usize = 16;
+/// Number of evenly spaced stops in [`DebugPalette::heat`].
+pub const DEBUG_HEAT_STOPS: usize = 4;
+
 /// Debug visualization mode for rendering.
 ///
-/// Press F3 to cycle through modes in the viewer.
-#[repr(u32)]
+/// Press F3 to cycle through modes in the viewer. Shader values are noted on
+/// each variant.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DebugMode {
-    /// Normal rendering (default).
+    /// Normal rendering (default), `0`.
     #[default]
-    None = 0,
-    /// Heatmap showing computational cost per pixel (traversal steps).
-    TraversalSteps = 1,
-    /// Heatmap showing LOD index reached.
-    NodeDepth = 2,
-    /// Heatmap showing distance from camera.
-    Distance = 3,
-    /// Surface normals as RGB.
-    Normals = 4,
-    /// Biome classification view.
-    Biomes = 5,
-    /// Red overlay on clipmap LOD boundaries.
-    ChunkBoundaries = 6,
+    None,
+    /// Heatmap showing computational cost per pixel (traversal steps), `1`.
+    TraversalSteps,
+    /// Heatmap showing LOD index reached, `2`.
+    NodeDepth,
+    /// Heatmap showing distance from camera, `3`.
+    Distance,
+    /// Surface normals as RGB, `4`.
+    Normals,
+    /// Biome classification view, `5`.
+    Biomes,
+    /// Red overlay on clipmap LOD boundaries, `6`.
+    ChunkBoundaries,
+    /// Traversal steps (normalized to `max_steps`) as a heat ramp over the shaded scene, `7`.
+    StepHeat,
+    /// Arbitrary shader value, for experimental visualizations that have no
+    /// named variant yet. The shader renders normally for values it does not
+    /// handle.
+    Custom(u32),
 }
 
 impl DebugMode {
+    /// Named modes in cycling order.
+    const NAMED: [Self; 8] = [
+        Self::None,
+        Self::TraversalSteps,
+        Self::NodeDepth,
+        Self::Distance,
+        Self::Normals,
+        Self::Biomes,
+        Self::ChunkBoundaries,
+        Self::StepHeat,
+    ];
+
+    /// All named modes, in the order [`Self::next`] cycles through them.
+    #[must_use]
+    pub const fn all() -> &'static [Self] {
+        &Self::NAMED
+    }
+
     /// Cycle to the next debug mode.
+    ///
+    /// A custom mode returns to [`Self::None`].
     #[must_use]
     pub fn next(self) -> Self {
         match self {
@@ -37,13 +70,170 @@ impl DebugMode {
             Self::Distance => Self::Normals,
             Self::Normals => Self::Biomes,
             Self::Biomes => Self::ChunkBoundaries,
-            Self::ChunkBoundaries => Self::None,
+            Self::ChunkBoundaries => Self::StepHeat,
+            Self::StepHeat | Self::Custom(_) => Self::None,
         }
     }
 
     /// Get the mode as a u32 for shader push constants.
     #[must_use]
     pub const fn as_u32(self) -> u32 {
-        self as u32
+        match self {
+            Self::None => 0,
+            Self::TraversalSteps => 1,
+            Self::NodeDepth => 2,
+            Self::Distance => 3,
+            Self::Normals => 4,
+            Self::Biomes => 5,
+            Self::ChunkBoundaries => 6,
+            Self::StepHeat => 7,
+            Self::Custom(value) => value,
+        }
+    }
+}
+
+/// Colors used by the debug visualizations, uploaded to the GPU.
+///
+/// Ramps are interpolated linearly between evenly spaced stops. Colors are
+/// RGB with an unused `w`. The default reproduces the built-in shader colors
+/// (the turbo ramp is sampled at its stops), so only palettes for e.g.
+/// colorblind-friendly or presentation views need to be supplied.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct DebugPalette {
+    /// Heatmap ramp for the traversal step, LOD and distance views.
+    pub ramp: [[f32; 4]; DEBUG_RAMP_STOPS],
+    /// Ramp for the step heat overlay.
+    pub heat: [[f32; 4]; DEBUG_HEAT_STOPS],
+    /// Normal view color for a zero normal.
+    pub normal_bias: [f32; 4],
+    /// Normal view color added per unit of the normal's X, Y and Z.
+    pub normal_axes: [[f32; 4]; 3],
+    /// Distance view color for rays that hit nothing.
+    pub miss: [f32; 4],
+    /// Overlay color on clipmap LOD boundaries.
+    pub lod_boundary: [f32; 4],
+}
+
+impl Default for DebugPalette {
+    fn default() -> Self {
+        let mut ramp = [[0.0; 4]; DEBUG_RAMP_STOPS];
+        for (i, stop) in ramp.iter_mut().enumerate() {
+            *stop = turbo(i as f32 / (DEBUG_RAMP_STOPS - 1) as f32);
+        }
+        Self {
+            ramp,
+            heat: [
+                [0.0, 0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0, 0.0],
+                [1.0, 1.0, 1.0, 0.0],
+            ],
+            normal_bias: [0.5, 0.5, 0.5, 0.0],
+            normal_axes: [
+                [0.5, 0.0, 0.0, 0.0],
+                [0.0, 0.5, 0.0, 0.0],
+                [0.0, 0.0, 0.5, 0.0],
+            ],
+            miss: [0.1, 0.1, 0.2, 0.0],
+            lod_boundary: [1.0, 0.2, 0.2, 0.0],
+        }
+    }
+}
+
+/// Polynomial turbo colormap, as in the ray march shader.
+fn turbo(t: f32) -> [f32; 4] {
+    const RED: [f32; 6] = [
+        0.135_721_38,
+        4.615_392_6,
+        -42.660_32,
+        132.131_08,
+        -152.942_4,
+        59.286_38,
+    ];
+    const GREEN: [f32; 6] = [
+        0.091_402_61,
+        2.194_188_4,
+        4.842_966_6,
+        -14.185_033,
+        4.277_299,
+        2.829_566,
+    ];
+    const BLUE: [f32; 6] = [
+        0.106_673_3,
+        12.641_946,
+        -60.582_05,
+        110.362_77,
+        -89.903_11,
+        27.348_25,
+    ];
+    let t = t.clamp(0.0, 1.0);
+    let eval = |c: &[f32; 6]| {
+        c.iter()
+            .rev()
+            .fold(0.0_f32, |acc, &k| acc.mul_add(t, k))
+            .clamp(0.0, 1.0)
+    };
+    [eval(&RED), eval(&GREEN), eval(&BLUE), 0.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_palette_matches_builtin_shader_colors() {
+        let palette = DebugPalette::default();
+        let close = |a: [f32; 4], b: [f32; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-4);
+
+        // Turbo endpoints: dark blue-ish at 0, dark red at 1.
+        assert!(close(
+            palette.ramp[0],
+            [0.135_721_38, 0.091_402_61, 0.106_673_3]
+        ));
+        let end = palette.ramp[DEBUG_RAMP_STOPS - 1];
+        assert!(end[0] > 0.4 && end[1] < 0.1 && end[2] < 0.1, "{end:?}");
+
+        // Black -> red -> yellow -> white heat ramp.
+        assert!(close(palette.heat[1], [1.0, 0.0, 0.0]));
+        assert!(close(palette.heat[2], [1.0, 1.0, 0.0]));
+        assert!(close(palette.miss, [0.1, 0.1, 0.2]));
+        assert!(close(palette.lod_boundary, [1.0, 0.2, 0.2]));
+
+        // `n * 0.5 + 0.5` for an up-facing normal.
+        let up: Vec<f32> = (0..3)
+            .map(|i| palette.normal_bias[i] + palette.normal_axes[1][i])
+            .collect();
+        assert_eq!(up, [0.5, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn step_heat_has_distinct_shader_value() {
+        let mut seen = Vec::new();
+        let mut mode = DebugMode::None;
+        loop {
+            assert!(!seen.contains(&mode.as_u32()), "{mode:?} reuses a value");
+            seen.push(mode.as_u32());
+            mode = mode.next();
+            if mode == DebugMode::None {
+                break;
+            }
+        }
+        assert!(seen.contains(&DebugMode::StepHeat.as_u32()));
+        assert_eq!(DebugMode::StepHeat.as_u32(), 7);
+    }
+
+    #[test]
+    fn custom_modes_pass_values_through() {
+        assert_eq!(DebugMode::Custom(42).as_u32(), 42);
+        assert_eq!(DebugMode::Custom(42).next(), DebugMode::None);
+
+        let values: Vec<u32> = DebugMode::all().iter().map(|m| m.as_u32()).collect();
+        assert_eq!(values, (0..8).collect::<Vec<_>>());
+        assert_eq!(DebugMode::Normals.as_u32(), 4);
+        assert_eq!(DebugMode::ChunkBoundaries.as_u32(), 6);
+        for pair in DebugMode::all().windows(2) {
+            assert_eq!(pair[0].next(), pair[1]);
+        }
     }
 }
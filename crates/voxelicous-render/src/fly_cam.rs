@@ -0,0 +1,262 @@
+//! Free-flying camera controller driven by input actions.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use glam::{Vec2, Vec3};
+use voxelicous_input::{CursorMode, InputManager};
+
+use crate::camera::Camera;
+
+/// Movement and look input for one [`FlyCamController::update`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlyCamInput {
+    /// Desired movement along the camera's right (`x`), world up (`y`) and
+    /// horizontal forward (`z`) axes. Normalized by the controller.
+    pub movement: Vec3,
+    /// Multiply the move speed by the sprint multiplier.
+    pub sprint: bool,
+    /// Look delta in radians: `x` turns right, `y` pitches down.
+    pub look: Vec2,
+}
+
+impl FlyCamInput {
+    /// Read the standard movement actions from `input`.
+    ///
+    /// Uses `move_forward`, `move_back`, `move_left`, `move_right`,
+    /// `move_up`, `move_down` and `sprint`. Look comes from
+    /// [`InputManager::look_delta`] and only applies while the cursor is
+    /// locked.
+    #[must_use]
+    pub fn from_input(input: &InputManager) -> Self {
+        let axis = |positive: &str, negative: &str| {
+            f32::from(u8::from(input.is_action_pressed(positive)))
+                - f32::from(u8::from(input.is_action_pressed(negative)))
+        };
+        let look = if input.cursor_mode() == CursorMode::Locked {
+            input.look_delta()
+        } else {
+            Vec2::ZERO
+        };
+        Self {
+            movement: Vec3::new(
+                axis("move_right", "move_left"),
+                axis("move_up", "move_down"),
+                axis("move_forward", "move_back"),
+            ),
+            sprint: input.is_action_pressed("sprint"),
+            look,
+        }
+    }
+}
+
+/// Free-flying camera with yaw/pitch look and optional velocity smoothing.
+///
+/// Yaw `0` looks down +Z and pitch `0` is level. Movement is relative to
+/// the horizontal look direction, so looking up or down does not change
+/// the flying height; `y` movement goes straight up or down.
+#[derive(Debug, Clone)]
+pub struct FlyCamController {
+    yaw: f32,
+    pitch: f32,
+    move_speed: f32,
+    sprint_multiplier: f32,
+    smoothing: f32,
+    pitch_limit: f32,
+    yaw_range: Option<(f32, f32)>,
+    velocity: Vec3,
+}
+
+impl FlyCamController {
+    /// Default move speed in units per second.
+    pub const DEFAULT_MOVE_SPEED: f32 = 30.0;
+    /// Default sprint multiplier.
+    pub const DEFAULT_SPRINT_MULTIPLIER: f32 = 2.5;
+    /// Default pitch limit, just short of straight up or down.
+    pub const DEFAULT_PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+
+    /// Create a controller looking where `camera` looks.
+    pub fn new(camera: &Camera) -> Self {
+        let dir = camera.direction.normalize_or_zero();
+        Self {
+            yaw: dir.x.atan2(dir.z),
+            pitch: (-dir.y).clamp(-1.0, 1.0).asin(),
+            move_speed: Self::DEFAULT_MOVE_SPEED,
+            sprint_multiplier: Self::DEFAULT_SPRINT_MULTIPLIER,
+            smoothing: 0.0,
+            pitch_limit: Self::DEFAULT_PITCH_LIMIT,
+            yaw_range: None,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Yaw in radians.
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Pitch in radians (positive looks down).
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Move speed in units per second.
+    pub fn move_speed(&self) -> f32 {
+        self.move_speed
+    }
+
+    /// Set the move speed in units per second.
+    pub fn set_move_speed(&mut self, speed: f32) {
+        self.move_speed = speed.max(0.0);
+    }
+
+    /// Speed multiplier while sprinting.
+    pub fn sprint_multiplier(&self) -> f32 {
+        self.sprint_multiplier
+    }
+
+    /// Set the speed multiplier while sprinting.
+    pub fn set_sprint_multiplier(&mut self, multiplier: f32) {
+        self.sprint_multiplier = multiplier.max(0.0);
+    }
+
+    /// Velocity smoothing time constant in seconds.
+    pub fn smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    /// Set how long the velocity takes to approach its target, in seconds.
+    ///
+    /// `0.0` (the default) moves at full speed immediately; larger values
+    /// ease in and out of movement.
+    pub fn set_smoothing(&mut self, seconds: f32) {
+        self.smoothing = seconds.max(0.0);
+    }
+
+    /// Set the largest pitch in radians, clamped below straight up/down.
+    pub fn set_pitch_limit(&mut self, limit: f32) {
+        self.pitch_limit = limit.clamp(0.0, Self::DEFAULT_PITCH_LIMIT);
+        self.pitch = self.pitch.clamp(-self.pitch_limit, self.pitch_limit);
+    }
+
+    /// Restrict yaw to `min..=max` radians, or lift the restriction.
+    pub fn set_yaw_range(&mut self, range: Option<(f32, f32)>) {
+        self.yaw_range = range.map(|(a, b)| (a.min(b), a.max(b)));
+        self.yaw = self.clamp_yaw(self.yaw);
+    }
+
+    /// Current velocity in units per second.
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Apply one frame of input to `camera`'s direction and position.
+    pub fn update(&mut self, camera: &mut Camera, input: &FlyCamInput, dt: f32) {
+        self.yaw = self.clamp_yaw(self.yaw - input.look.x);
+        self.pitch = (self.pitch + input.look.y).clamp(-self.pitch_limit, self.pitch_limit);
+
+        let direction = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            -self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        )
+        .normalize();
+        camera.direction = direction;
+
+        let forward = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let wish =
+            (right * input.movement.x + Vec3::Y * input.movement.y + forward * input.movement.z)
+                .normalize_or_zero();
+        let speed = if input.sprint {
+            self.move_speed * self.sprint_multiplier
+        } else {
+            self.move_speed
+        };
+        let target = wish * speed;
+
+        self.velocity = if self.smoothing > 0.0 && dt > 0.0 {
+            let blend = 1.0 - (-dt / self.smoothing).exp();
+            self.velocity.lerp(target, blend)
+        } else {
+            target
+        };
+        camera.position += self.velocity * dt.max(0.0);
+    }
+
+    fn clamp_yaw(&self, yaw: f32) -> f32 {
+        match self.yaw_range {
+            Some((min, max)) => yaw.clamp(min, max),
+            None => (yaw + PI).rem_euclid(2.0 * PI) - PI,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera_looking_down_z() -> Camera {
+        Camera::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(1.0, 2.0, 4.0),
+            Vec3::Y,
+            1.0,
+            1.0,
+            0.1,
+            100.0,
+        )
+    }
+
+    #[test]
+    fn move_forward_advances_by_speed_times_dt() {
+        let mut camera = camera_looking_down_z();
+        let start = camera.position;
+        let mut controller = FlyCamController::new(&camera);
+        controller.set_move_speed(12.0);
+        let forward = FlyCamInput {
+            movement: Vec3::Z,
+            ..FlyCamInput::default()
+        };
+
+        controller.update(&mut camera, &forward, 0.25);
+        assert!((camera.position - (start + Vec3::Z * 3.0)).length() < 1e-5);
+        assert!((camera.direction - Vec3::Z).length() < 1e-5);
+
+        // Sprinting scales the same step.
+        let sprint = FlyCamInput {
+            sprint: true,
+            ..forward
+        };
+        let before = camera.position;
+        controller.update(&mut camera, &sprint, 0.25);
+        let step = 3.0 * FlyCamController::DEFAULT_SPRINT_MULTIPLIER;
+        assert!((camera.position - (before + Vec3::Z * step)).length() < 1e-4);
+
+        // With smoothing, releasing the keys eases out instead of stopping.
+        controller.set_smoothing(0.5);
+        controller.update(&mut camera, &FlyCamInput::default(), 0.1);
+        let coasting = controller.velocity().z;
+        assert!(coasting > 0.0 && coasting < 12.0 * step / 3.0, "{coasting}");
+    }
+
+    #[test]
+    fn look_is_clamped() {
+        let mut camera = camera_looking_down_z();
+        let mut controller = FlyCamController::new(&camera);
+        let look_down = FlyCamInput {
+            look: Vec2::new(0.0, 10.0),
+            ..FlyCamInput::default()
+        };
+        controller.update(&mut camera, &look_down, 0.0);
+        assert_eq!(controller.pitch(), FlyCamController::DEFAULT_PITCH_LIMIT);
+        assert!(camera.direction.y < -0.99);
+
+        controller.set_yaw_range(Some((-0.5, 0.5)));
+        let turn = FlyCamInput {
+            look: Vec2::new(2.0, 0.0),
+            ..FlyCamInput::default()
+        };
+        controller.update(&mut camera, &turn, 0.0);
+        assert_eq!(controller.yaw(), -0.5);
+    }
+}
@@ -2,19 +2,61 @@
 //!
 //! This crate provides:
 //! - Compute shader ray marching
+//! - A CPU reference ray marcher for testing traversal
+//! - Switchable render paths
 //! - Clipmap world rendering
 //! - Post-processing effects
 //! - Camera and view management
+//! - Scripted camera paths for reproducible captures
+//! - A free-flying camera controller and an automatic orbit mode
+//! - Sky colors and per-biome blending
+//! - Day/night cycle
 //! - Screenshot capture utilities
+//! - A top-down shaded relief preview of generated terrain
+//! - A portable wgpu ray marching backend (feature `wgpu`)
 
 pub mod camera;
+pub mod camera_mode;
+pub mod camera_path;
 pub mod clipmap_ray_march_pipeline;
 pub mod clipmap_render;
+pub mod cpu_ray_march;
 pub mod debug;
+pub mod fly_cam;
+pub mod render_backend;
+pub mod render_graph;
 pub mod screenshot;
+pub mod sky;
+pub mod time_of_day;
+pub mod upload_budget;
+#[cfg(feature = "wgpu")]
+pub mod wgpu_backend;
+pub mod worldgen_preview;
 
-pub use camera::{Camera, CameraUniforms};
-pub use clipmap_ray_march_pipeline::ClipmapRayMarchPipeline;
-pub use clipmap_render::{ClipmapRenderPushConstants, ClipmapRenderer, GpuClipmapInfo};
-pub use debug::DebugMode;
+pub use camera::{Camera, CameraUniforms, FovMode, StereoConfig};
+pub use camera_mode::CameraMode;
+pub use camera_path::{CameraKeyframe, CameraPath, CameraPathError};
+pub use clipmap_ray_march_pipeline::{
+    side_by_side_extent, ClipmapRayMarchPipeline, MaxStepsTuner, OutputFormat, RenderStats,
+    WorkgroupSize,
+};
+pub use clipmap_render::{
+    ClipmapRenderPushConstants, ClipmapRenderer, FogSettings, GpuClipmapInfo, GpuMaterial,
+    GpuMemBreakdown, LodTransitionError, PoolReserve, WindSettings,
+};
+pub use cpu_ray_march::{ray_march_cpu, CpuClipmapView, RayHit, RayMarchConfig};
+pub use debug::{DebugMode, DebugPalette, DEBUG_HEAT_STOPS, DEBUG_RAMP_STOPS};
+pub use fly_cam::{FlyCamController, FlyCamInput};
+pub use render_backend::{AppRenderBackend, RenderPath, RenderPipeline};
+pub use render_graph::{
+    BufferAccess, BufferHandle, ImageAccess, ImageHandle, PassBarriers, PassBuilder, RenderGraph,
+};
 pub use screenshot::{parse_frame_indices, save_screenshot, ScreenshotConfig, ScreenshotError};
+pub use sky::{SkyBlend, SkyConfig};
+pub use time_of_day::TimeOfDay;
+pub use upload_budget::UploadBudget;
+#[cfg(feature = "wgpu")]
+pub use wgpu_backend::{
+    WgpuBackendError, WgpuClipmapRenderer, WgpuRayMarchParams, WGPU_OUTPUT_FORMAT,
+};
+pub use worldgen_preview::{shaded_relief, PreviewRegion, WorldGenPreview};
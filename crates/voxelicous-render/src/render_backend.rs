@@ -0,0 +1,311 @@
+//! Switchable render paths behind a common pipeline interface.
+
+use ash::vk;
+use voxelicous_gpu::capabilities::GpuCapabilities;
+use voxelicous_gpu::error::{GpuError, Result};
+use voxelicous_gpu::memory::{GpuAllocator, GpuImage};
+
+use crate::camera::CameraUniforms;
+use crate::clipmap_ray_march_pipeline::ClipmapRayMarchPipeline;
+use crate::clipmap_render::ClipmapRenderer;
+use crate::debug::DebugMode;
+
+/// How the world is traced each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderPath {
+    /// Compute shader ray marching through the clipmap.
+    #[default]
+    ComputeRayMarching,
+    /// Vulkan ray tracing pipelines (`VK_KHR_ray_tracing_pipeline`).
+    HardwareRayTracing,
+}
+
+impl RenderPath {
+    /// Whether a device can run this path.
+    pub fn is_supported(self, capabilities: &GpuCapabilities) -> bool {
+        match self {
+            Self::ComputeRayMarching => true,
+            Self::HardwareRayTracing => capabilities.supports_ray_tracing,
+        }
+    }
+}
+
+/// Interface shared by every render path's pipeline.
+///
+/// Pipelines read world data from the [`ClipmapRenderer`] passed to
+/// [`Self::record`], so uploaded pages survive a path switch.
+pub trait RenderPipeline {
+    /// Path this pipeline implements.
+    fn path(&self) -> RenderPath;
+
+    /// Record the frame's trace into `cmd`.
+    ///
+    /// # Safety
+    /// Command buffer must be in recording state.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn record(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        camera: &CameraUniforms,
+        renderer: &ClipmapRenderer,
+        max_steps: u32,
+        frame_index: usize,
+        debug_mode: DebugMode,
+    ) -> Result<()>;
+
+    /// Image the trace is written to.
+    fn output_image(&self) -> &GpuImage;
+
+    /// Output dimensions.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// Destroy the pipeline's GPU resources.
+    ///
+    /// # Safety
+    /// The device must be idle.
+    unsafe fn destroy(
+        self: Box<Self>,
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+    ) -> Result<()>;
+}
+
+impl RenderPipeline for ClipmapRayMarchPipeline {
+    fn path(&self) -> RenderPath {
+        RenderPath::ComputeRayMarching
+    }
+
+    unsafe fn record(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        camera: &CameraUniforms,
+        renderer: &ClipmapRenderer,
+        max_steps: u32,
+        frame_index: usize,
+        debug_mode: DebugMode,
+    ) -> Result<()> {
+        Self::record(
+            self,
+            device,
+            cmd,
+            camera,
+            renderer,
+            max_steps,
+            frame_index,
+            debug_mode,
+        )
+    }
+
+    fn output_image(&self) -> &GpuImage {
+        Self::output_image(self)
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        Self::dimensions(self)
+    }
+
+    unsafe fn destroy(
+        self: Box<Self>,
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+    ) -> Result<()> {
+        Self::destroy(*self, device, allocator)
+    }
+}
+
+/// The app's active render pipeline, swappable between render paths.
+///
+/// A switch is requested with [`Self::request_path`] at any time and
+/// performed by [`Self::swap`] at a frame boundary, once the app has built
+/// the new path's pipeline.
+pub struct AppRenderBackend {
+    pipeline: Box<dyn RenderPipeline>,
+    hardware_ray_tracing: bool,
+    pending: Option<RenderPath>,
+}
+
+impl AppRenderBackend {
+    /// Wrap the initial pipeline for a device.
+    pub fn new(pipeline: Box<dyn RenderPipeline>, capabilities: &GpuCapabilities) -> Self {
+        Self {
+            pipeline,
+            hardware_ray_tracing: RenderPath::HardwareRayTracing.is_supported(capabilities),
+            pending: None,
+        }
+    }
+
+    /// Path of the active pipeline.
+    pub fn active_path(&self) -> RenderPath {
+        self.pipeline.path()
+    }
+
+    /// The active pipeline.
+    pub fn pipeline(&self) -> &dyn RenderPipeline {
+        self.pipeline.as_ref()
+    }
+
+    /// Ask to switch paths at the next frame boundary.
+    ///
+    /// Fails without changing anything when the device cannot run `path`.
+    /// Requesting the active path cancels a pending switch.
+    pub fn request_path(&mut self, path: RenderPath) -> Result<()> {
+        if path == RenderPath::HardwareRayTracing && !self.hardware_ray_tracing {
+            return Err(GpuError::ExtensionNotSupported(
+                "VK_KHR_ray_tracing_pipeline".to_string(),
+            ));
+        }
+        self.pending = (path != self.active_path()).then_some(path);
+        Ok(())
+    }
+
+    /// Path waiting to be swapped in, if any.
+    pub fn pending_path(&self) -> Option<RenderPath> {
+        self.pending
+    }
+
+    /// Replace the active pipeline, waiting for in-flight frames first.
+    ///
+    /// The old pipeline is destroyed and any pending request is cleared.
+    ///
+    /// # Safety
+    /// Must be called between frames, outside command buffer recording.
+    pub unsafe fn swap(
+        &mut self,
+        device: &ash::Device,
+        allocator: &mut GpuAllocator,
+        pipeline: Box<dyn RenderPipeline>,
+    ) -> Result<()> {
+        device.device_wait_idle()?;
+        let old = std::mem::replace(&mut self.pipeline, pipeline);
+        self.pending = None;
+        old.destroy(device, allocator)
+    }
+
+    /// Destroy the active pipeline.
+    ///
+    /// # Safety
+    /// The device must be idle.
+    pub unsafe fn destroy(self, device: &ash::Device, allocator: &mut GpuAllocator) -> Result<()> {
+        self.pipeline.destroy(device, allocator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use voxelicous_gpu::capabilities::GpuVendor;
+
+    use super::*;
+
+    struct FakePipeline {
+        path: RenderPath,
+        image: GpuImage,
+    }
+
+    impl FakePipeline {
+        fn boxed(path: RenderPath) -> Box<dyn RenderPipeline> {
+            Box::new(Self {
+                path,
+                image: GpuImage {
+                    image: vk::Image::null(),
+                    allocation: None,
+                    format: vk::Format::R8G8B8A8_UNORM,
+                    extent: vk::Extent3D::default(),
+                },
+            })
+        }
+    }
+
+    impl RenderPipeline for FakePipeline {
+        fn path(&self) -> RenderPath {
+            self.path
+        }
+
+        unsafe fn record(
+            &self,
+            _device: &ash::Device,
+            _cmd: vk::CommandBuffer,
+            _camera: &CameraUniforms,
+            _renderer: &ClipmapRenderer,
+            _max_steps: u32,
+            _frame_index: usize,
+            _debug_mode: DebugMode,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn output_image(&self) -> &GpuImage {
+            &self.image
+        }
+
+        fn dimensions(&self) -> (u32, u32) {
+            (0, 0)
+        }
+
+        unsafe fn destroy(
+            self: Box<Self>,
+            _device: &ash::Device,
+            _allocator: &mut GpuAllocator,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capabilities(ray_tracing: bool) -> GpuCapabilities {
+        GpuCapabilities {
+            vendor: GpuVendor::Other(0),
+            device_name: "test".to_string(),
+            api_version: vk::API_VERSION_1_3,
+            driver_version: 0,
+            supports_dynamic_rendering: true,
+            supports_synchronization2: true,
+            supports_buffer_device_address: true,
+            supports_descriptor_indexing: true,
+            supports_scalar_block_layout: true,
+            supports_ray_tracing: ray_tracing,
+            supports_storage_image_write_without_format: true,
+            device_local_memory_mb: 4096,
+            max_memory_allocation_count: 4096,
+            max_compute_workgroup_size: [1024; 3],
+            max_compute_workgroup_invocations: 1024,
+            max_compute_shared_memory_size: 32768,
+            available_extensions: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn switching_to_hardware_rt_requires_support() {
+        let mut backend = AppRenderBackend::new(
+            FakePipeline::boxed(RenderPath::ComputeRayMarching),
+            &capabilities(false),
+        );
+        assert_eq!(backend.active_path(), RenderPath::ComputeRayMarching);
+        assert!(matches!(
+            backend.request_path(RenderPath::HardwareRayTracing),
+            Err(GpuError::ExtensionNotSupported(_))
+        ));
+        assert_eq!(backend.pending_path(), None);
+        backend
+            .request_path(RenderPath::ComputeRayMarching)
+            .unwrap();
+        assert_eq!(backend.pending_path(), None);
+
+        let mut backend = AppRenderBackend::new(
+            FakePipeline::boxed(RenderPath::ComputeRayMarching),
+            &capabilities(true),
+        );
+        backend
+            .request_path(RenderPath::HardwareRayTracing)
+            .unwrap();
+        assert_eq!(backend.pending_path(), Some(RenderPath::HardwareRayTracing));
+        assert_eq!(backend.active_path(), RenderPath::ComputeRayMarching);
+        // Asking for the active path again cancels the switch.
+        backend
+            .request_path(RenderPath::ComputeRayMarching)
+            .unwrap();
+        assert_eq!(backend.pending_path(), None);
+    }
+}
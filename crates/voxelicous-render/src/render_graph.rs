@@ -0,0 +1,444 @@
+//! Lightweight frame graph that derives pipeline barriers from pass usage.
+//!
+//! Passes declare how they touch each image and buffer; the graph tracks
+//! every resource's last use and inserts the `ImageMemoryBarrier2` /
+//! `BufferMemoryBarrier2` needed before each pass. A viewer frame becomes:
+//!
+//! ```ignore
+//! let mut graph = RenderGraph::new();
+//! let output = graph.import_image(pipeline.output_image().image, None);
+//! let swapchain = graph.import_image(frame.swapchain_image, None);
+//! graph
+//!     .add_pass("ray_march")
+//!     .image(output, ImageAccess::ComputeWrite)
+//!     .record(|device, cmd| unsafe { /* dispatch */ });
+//! graph
+//!     .add_pass("blit")
+//!     .image(output, ImageAccess::TransferRead)
+//!     .image(swapchain, ImageAccess::TransferWrite)
+//!     .record(|device, cmd| unsafe { /* blit */ });
+//! graph.add_pass("present").image(swapchain, ImageAccess::Present);
+//! unsafe { graph.execute(device, cmd) };
+//! ```
+//!
+//! Passes run in the order they were added; the graph does not reorder or
+//! cull them. Images are tracked as a whole (all mips and layers of the
+//! color aspect) and buffers over their whole range.
+
+use ash::vk;
+
+/// How a pass uses an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageAccess {
+    /// Storage image written by a compute shader.
+    ComputeWrite,
+    /// Storage image read by a compute shader.
+    ComputeRead,
+    /// Storage image read and written by a compute shader.
+    ComputeReadWrite,
+    /// Source of a copy or blit.
+    TransferRead,
+    /// Destination of a copy, blit or clear.
+    TransferWrite,
+    /// Handed to the presentation engine.
+    Present,
+}
+
+impl ImageAccess {
+    /// Pipeline stage the access happens in.
+    pub const fn stage(self) -> vk::PipelineStageFlags2 {
+        match self {
+            Self::ComputeWrite | Self::ComputeRead | Self::ComputeReadWrite => {
+                vk::PipelineStageFlags2::COMPUTE_SHADER
+            }
+            Self::TransferRead | Self::TransferWrite => vk::PipelineStageFlags2::TRANSFER,
+            Self::Present => vk::PipelineStageFlags2::BOTTOM_OF_PIPE,
+        }
+    }
+
+    /// Memory access mask of the access.
+    pub const fn access(self) -> vk::AccessFlags2 {
+        match self {
+            Self::ComputeWrite => vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            Self::ComputeRead => vk::AccessFlags2::SHADER_STORAGE_READ,
+            Self::ComputeReadWrite => vk::AccessFlags2::from_raw(
+                vk::AccessFlags2::SHADER_STORAGE_READ.as_raw()
+                    | vk::AccessFlags2::SHADER_STORAGE_WRITE.as_raw(),
+            ),
+            Self::TransferRead => vk::AccessFlags2::TRANSFER_READ,
+            Self::TransferWrite => vk::AccessFlags2::TRANSFER_WRITE,
+            Self::Present => vk::AccessFlags2::NONE,
+        }
+    }
+
+    /// Layout the image must be in for the access.
+    pub const fn layout(self) -> vk::ImageLayout {
+        match self {
+            Self::ComputeWrite | Self::ComputeRead | Self::ComputeReadWrite => {
+                vk::ImageLayout::GENERAL
+            }
+            Self::TransferRead => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            Self::TransferWrite => vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            Self::Present => vk::ImageLayout::PRESENT_SRC_KHR,
+        }
+    }
+
+    /// Whether the access writes the image.
+    pub const fn is_write(self) -> bool {
+        matches!(
+            self,
+            Self::ComputeWrite | Self::ComputeReadWrite | Self::TransferWrite
+        )
+    }
+}
+
+/// How a pass uses a buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BufferAccess {
+    /// Storage buffer written by a compute shader.
+    ComputeWrite,
+    /// Storage or uniform buffer read by a compute shader.
+    ComputeRead,
+    /// Source of a copy.
+    TransferRead,
+    /// Destination of a copy (e.g. an image readback).
+    TransferWrite,
+    /// Read through a host mapping after the submission completes.
+    HostRead,
+}
+
+impl BufferAccess {
+    /// Pipeline stage the access happens in.
+    pub const fn stage(self) -> vk::PipelineStageFlags2 {
+        match self {
+            Self::ComputeWrite | Self::ComputeRead => vk::PipelineStageFlags2::COMPUTE_SHADER,
+            Self::TransferRead | Self::TransferWrite => vk::PipelineStageFlags2::TRANSFER,
+            Self::HostRead => vk::PipelineStageFlags2::HOST,
+        }
+    }
+
+    /// Memory access mask of the access.
+    pub const fn access(self) -> vk::AccessFlags2 {
+        match self {
+            Self::ComputeWrite => vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            Self::ComputeRead => vk::AccessFlags2::SHADER_READ,
+            Self::TransferRead => vk::AccessFlags2::TRANSFER_READ,
+            Self::TransferWrite => vk::AccessFlags2::TRANSFER_WRITE,
+            Self::HostRead => vk::AccessFlags2::HOST_READ,
+        }
+    }
+
+    /// Whether the access writes the buffer.
+    pub const fn is_write(self) -> bool {
+        matches!(self, Self::ComputeWrite | Self::TransferWrite)
+    }
+}
+
+/// Image registered with [`RenderGraph::import_image`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageHandle(usize);
+
+/// Buffer registered with [`RenderGraph::import_buffer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BufferHandle(usize);
+
+/// Callback recording a pass's commands.
+type RecordFn<'a> = Box<dyn FnOnce(&ash::Device, vk::CommandBuffer) + 'a>;
+
+struct Pass<'a> {
+    name: String,
+    images: Vec<(ImageHandle, ImageAccess)>,
+    buffers: Vec<(BufferHandle, BufferAccess)>,
+    record: Option<RecordFn<'a>>,
+}
+
+/// Barriers recorded before one pass, from [`RenderGraph::compile`].
+#[derive(Clone, Debug, Default)]
+pub struct PassBarriers {
+    /// Name the pass was added with.
+    pub pass: String,
+    pub image_barriers: Vec<vk::ImageMemoryBarrier2<'static>>,
+    pub buffer_barriers: Vec<vk::BufferMemoryBarrier2<'static>>,
+}
+
+impl PassBarriers {
+    /// Whether the pass needs no barrier.
+    pub fn is_empty(&self) -> bool {
+        self.image_barriers.is_empty() && self.buffer_barriers.is_empty()
+    }
+}
+
+/// Ordered list of passes with automatic barrier insertion.
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    /// Each image and its usage before the first pass.
+    images: Vec<(vk::Image, Option<ImageAccess>)>,
+    buffers: Vec<vk::Buffer>,
+    passes: Vec<Pass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Create an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track an image.
+    ///
+    /// `previous` is how the image was last used before this graph, e.g.
+    /// `Some(ImageAccess::Present)` for an image kept across frames; `None`
+    /// treats its contents as undefined, so the first transition discards
+    /// them.
+    pub fn import_image(&mut self, image: vk::Image, previous: Option<ImageAccess>) -> ImageHandle {
+        self.images.push((image, previous));
+        ImageHandle(self.images.len() - 1)
+    }
+
+    /// Track a buffer. Earlier submissions are assumed to be complete.
+    pub fn import_buffer(&mut self, buffer: vk::Buffer) -> BufferHandle {
+        self.buffers.push(buffer);
+        BufferHandle(self.buffers.len() - 1)
+    }
+
+    /// Append a pass; declare its resources on the returned builder.
+    ///
+    /// A pass without [`PassBuilder::record`] only transitions its
+    /// resources, as for [`ImageAccess::Present`].
+    pub fn add_pass(&mut self, name: impl Into<String>) -> PassBuilder<'_, 'a> {
+        self.passes.push(Pass {
+            name: name.into(),
+            images: Vec::new(),
+            buffers: Vec::new(),
+            record: None,
+        });
+        let index = self.passes.len() - 1;
+        PassBuilder { graph: self, index }
+    }
+
+    /// Number of passes added.
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Barriers each pass needs, in pass order.
+    ///
+    /// An image gets a barrier when its layout changes or when either side
+    /// of the dependency writes it; read-after-read in the same layout needs
+    /// none. Buffers follow the same rule without layouts, and are not
+    /// synchronized against work before the graph.
+    pub fn compile(&self) -> Vec<PassBarriers> {
+        let mut image_state: Vec<Option<ImageAccess>> =
+            self.images.iter().map(|&(_, previous)| previous).collect();
+        let mut buffer_state: Vec<Option<BufferAccess>> = vec![None; self.buffers.len()];
+
+        self.passes
+            .iter()
+            .map(|pass| {
+                let mut barriers = PassBarriers {
+                    pass: pass.name.clone(),
+                    ..PassBarriers::default()
+                };
+                for &(ImageHandle(index), access) in &pass.images {
+                    let previous = image_state[index].replace(access);
+                    if let Some(barrier) = image_barrier(self.images[index].0, previous, access) {
+                        barriers.image_barriers.push(barrier);
+                    }
+                }
+                for &(BufferHandle(index), access) in &pass.buffers {
+                    let previous = buffer_state[index].replace(access);
+                    if let Some(previous) = previous {
+                        if previous.is_write() || access.is_write() {
+                            barriers.buffer_barriers.push(buffer_barrier(
+                                self.buffers[index],
+                                previous,
+                                access,
+                            ));
+                        }
+                    }
+                }
+                barriers
+            })
+            .collect()
+    }
+
+    /// Final usage of an image after every pass, if any pass or the import
+    /// declared one.
+    pub fn final_image_access(&self, image: ImageHandle) -> Option<ImageAccess> {
+        self.passes
+            .iter()
+            .rev()
+            .flat_map(|pass| pass.images.iter().rev())
+            .find(|&&(handle, _)| handle == image)
+            .map(|&(_, access)| access)
+            .or(self.images[image.0].1)
+    }
+
+    /// Record every pass into `cmd`, each preceded by its barriers.
+    ///
+    /// # Safety
+    /// Command buffer must be in recording state, and every imported
+    /// resource must outlive its execution.
+    pub unsafe fn execute(self, device: &ash::Device, cmd: vk::CommandBuffer) {
+        let barriers = self.compile();
+        for (pass, barriers) in self.passes.into_iter().zip(barriers) {
+            if !barriers.is_empty() {
+                let dependency_info = vk::DependencyInfo::default()
+                    .image_memory_barriers(&barriers.image_barriers)
+                    .buffer_memory_barriers(&barriers.buffer_barriers);
+                device.cmd_pipeline_barrier2(cmd, &dependency_info);
+            }
+            if let Some(record) = pass.record {
+                record(device, cmd);
+            }
+        }
+    }
+}
+
+/// Declares the resources of a pass added with [`RenderGraph::add_pass`].
+pub struct PassBuilder<'g, 'a> {
+    graph: &'g mut RenderGraph<'a>,
+    index: usize,
+}
+
+impl<'a> PassBuilder<'_, 'a> {
+    /// Use an image in this pass.
+    pub fn image(&mut self, image: ImageHandle, access: ImageAccess) -> &mut Self {
+        self.graph.passes[self.index].images.push((image, access));
+        self
+    }
+
+    /// Use a buffer in this pass.
+    pub fn buffer(&mut self, buffer: BufferHandle, access: BufferAccess) -> &mut Self {
+        self.graph.passes[self.index].buffers.push((buffer, access));
+        self
+    }
+
+    /// Set the callback recording the pass's commands.
+    pub fn record(&mut self, record: impl FnOnce(&ash::Device, vk::CommandBuffer) + 'a) {
+        self.graph.passes[self.index].record = Some(Box::new(record));
+    }
+}
+
+fn image_barrier(
+    image: vk::Image,
+    previous: Option<ImageAccess>,
+    next: ImageAccess,
+) -> Option<vk::ImageMemoryBarrier2<'static>> {
+    let (src_stage, src_access, old_layout) = match previous {
+        Some(previous) => {
+            if previous.layout() == next.layout() && !previous.is_write() && !next.is_write() {
+                return None;
+            }
+            (previous.stage(), previous.access(), previous.layout())
+        }
+        None => (
+            vk::PipelineStageFlags2::TOP_OF_PIPE,
+            vk::AccessFlags2::NONE,
+            vk::ImageLayout::UNDEFINED,
+        ),
+    };
+    Some(
+        vk::ImageMemoryBarrier2::default()
+            .src_stage_mask(src_stage)
+            .src_access_mask(src_access)
+            .dst_stage_mask(next.stage())
+            .dst_access_mask(next.access())
+            .old_layout(old_layout)
+            .new_layout(next.layout())
+            .image(image)
+            .subresource_range(vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: 0,
+                level_count: vk::REMAINING_MIP_LEVELS,
+                base_array_layer: 0,
+                layer_count: vk::REMAINING_ARRAY_LAYERS,
+            }),
+    )
+}
+
+fn buffer_barrier(
+    buffer: vk::Buffer,
+    previous: BufferAccess,
+    next: BufferAccess,
+) -> vk::BufferMemoryBarrier2<'static> {
+    vk::BufferMemoryBarrier2::default()
+        .src_stage_mask(previous.stage())
+        .src_access_mask(previous.access())
+        .dst_stage_mask(next.stage())
+        .dst_access_mask(next.access())
+        .buffer(buffer)
+        .offset(0)
+        .size(vk::WHOLE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk::Handle;
+
+    #[test]
+    fn write_then_read_transitions_between_passes() {
+        let image = vk::Image::from_raw(0x1234);
+        let readback = vk::Buffer::from_raw(0x5678);
+        let mut graph = RenderGraph::new();
+        let output = graph.import_image(image, None);
+        let buffer = graph.import_buffer(readback);
+        graph
+            .add_pass("ray_march")
+            .image(output, ImageAccess::ComputeWrite)
+            .record(|_, _| {});
+        graph
+            .add_pass("readback")
+            .image(output, ImageAccess::TransferRead)
+            .buffer(buffer, BufferAccess::TransferWrite)
+            .record(|_, _| {});
+        graph
+            .add_pass("host")
+            .buffer(buffer, BufferAccess::HostRead);
+        graph
+            .add_pass("readback_again")
+            .image(output, ImageAccess::TransferRead);
+
+        let passes = graph.compile();
+        assert_eq!(passes.len(), 4);
+
+        // First use discards the undefined contents.
+        let first = &passes[0].image_barriers[0];
+        assert_eq!(first.old_layout, vk::ImageLayout::UNDEFINED);
+        assert_eq!(first.new_layout, vk::ImageLayout::GENERAL);
+        assert_eq!(
+            first.dst_stage_mask,
+            vk::PipelineStageFlags2::COMPUTE_SHADER
+        );
+
+        // The read waits on the write and moves the image to a copy layout.
+        assert_eq!(passes[1].pass, "readback");
+        let barrier = &passes[1].image_barriers[0];
+        assert_eq!(barrier.image, image);
+        assert_eq!(barrier.src_stage_mask, first.dst_stage_mask);
+        assert_eq!(barrier.src_access_mask, first.dst_access_mask);
+        assert_eq!(
+            barrier.src_access_mask,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE
+        );
+        assert_eq!(barrier.dst_stage_mask, vk::PipelineStageFlags2::TRANSFER);
+        assert_eq!(barrier.dst_access_mask, vk::AccessFlags2::TRANSFER_READ);
+        assert_eq!(barrier.old_layout, first.new_layout);
+        assert_eq!(barrier.new_layout, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+        // The buffer's first use needs no barrier.
+        assert!(passes[1].buffer_barriers.is_empty());
+
+        // The host read waits on the copy into the buffer.
+        let host = &passes[2].buffer_barriers[0];
+        assert_eq!(host.buffer, readback);
+        assert_eq!(host.src_access_mask, vk::AccessFlags2::TRANSFER_WRITE);
+        assert_eq!(host.dst_stage_mask, vk::PipelineStageFlags2::HOST);
+
+        // Reading again in the same layout needs nothing.
+        assert!(passes[3].is_empty());
+        assert_eq!(
+            graph.final_image_access(output),
+            Some(ImageAccess::TransferRead)
+        );
+    }
+}
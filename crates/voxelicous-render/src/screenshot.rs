@@ -9,6 +9,8 @@ use image::{ImageBuffer, Rgba};
 use tracing::info;
 use voxelicous_gpu::GpuError;
 
+use crate::clipmap_ray_march_pipeline::OutputFormat;
+
 /// Screenshot capture configuration.
 ///
 /// Defines which frames to capture and where to save them.
@@ -162,10 +164,15 @@ pub fn parse_frame_indices(s: &str) -> HashSet<u64> {
     frames
 }
 
-/// Save RGBA pixel data to an image file.
+/// Save readback pixel data to an image file.
+///
+/// The data is converted from `format` to RGBA8 before encoding: BGRA is
+/// swizzled and half-float color is tonemapped and quantized, the same
+/// conversion as `ClipmapRayMarchPipeline::read_output`.
 ///
 /// # Arguments
-/// * `data` - Raw RGBA pixel data (4 bytes per pixel)
+/// * `data` - Raw pixel data in `format`
+/// * `format` - Pixel format of `data`
 /// * `width` - Image width in pixels
 /// * `height` - Image height in pixels
 /// * `path` - Output file path (format determined by extension)
@@ -174,13 +181,17 @@ pub fn parse_frame_indices(s: &str) -> HashSet<u64> {
 /// `Ok(())` on success, or an error if saving fails.
 pub fn save_screenshot(
     data: Vec<u8>,
+    format: OutputFormat,
     width: u32,
     height: u32,
     path: impl AsRef<Path>,
 ) -> Result<(), ScreenshotError> {
     let path = path.as_ref();
 
-    let image = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data)
+    if data.len() as u64 != format.readback_size(width, height) {
+        return Err(ScreenshotError::InvalidImageData);
+    }
+    let image = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, format.decode_rgba8(data))
         .ok_or(ScreenshotError::InvalidImageData)?;
 
     image
@@ -197,11 +208,13 @@ pub fn save_screenshot(
 /// and saves it to a file.
 ///
 /// # Arguments
-/// * `read_output` - Function to read RGBA data from the pipeline
+/// * `read_output` - Function to read raw data in `format` from the pipeline
+/// * `format` - Pixel format of the readback data
 /// * `dimensions` - Function to get (width, height) of the output
 /// * `path` - Output file path
 pub fn capture_screenshot<F, D>(
     read_output: F,
+    format: OutputFormat,
     dimensions: D,
     path: impl AsRef<Path>,
 ) -> Result<(), ScreenshotError>
@@ -211,7 +224,7 @@ where
 {
     let (width, height) = dimensions();
     let data = read_output().map_err(|e| ScreenshotError::ReadbackFailed(e.to_string()))?;
-    save_screenshot(data, width, height, path)
+    save_screenshot(data, format, width, height, path)
 }
 
 /// Errors that can occur during screenshot capture.
@@ -271,6 +284,28 @@ mod tests {
         assert_eq!(config.output_path(42), PathBuf::from("frame_42.png"));
     }
 
+    #[test]
+    fn bgra_screenshots_are_saved_as_rgb() {
+        let path = std::env::temp_dir().join(format!(
+            "voxelicous_bgra_screenshot_{}.png",
+            std::process::id()
+        ));
+        // Red then blue, in BGRA byte order.
+        let bgra = vec![0, 0, 255, 255, 255, 0, 0, 255];
+        save_screenshot(bgra, OutputFormat::Bgra8Unorm, 2, 1, &path).unwrap();
+
+        let saved = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(saved.get_pixel(1, 0).0, [0, 0, 255, 255]);
+
+        // A buffer sized for another format is rejected.
+        assert!(matches!(
+            save_screenshot(vec![0; 8], OutputFormat::Rgba16Float, 2, 1, &path),
+            Err(ScreenshotError::InvalidImageData)
+        ));
+    }
+
     #[test]
     fn config_should_capture() {
         let config = ScreenshotConfig::new().with_frames([0, 5, 10]);
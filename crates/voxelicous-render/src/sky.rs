@@ -0,0 +1,166 @@
+//! Sky color parameters and per-biome blending.
+
+use glam::{Vec3, Vec3Swizzles};
+use voxelicous_world::{TerrainBiome, TerrainGenerator};
+
+/// Daytime sky gradient parameters fed to the ray march shader.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyConfig {
+    /// Sky color at the horizon.
+    pub horizon: Vec3,
+    /// Sky color straight up.
+    pub zenith: Vec3,
+    /// How far the whole sky is washed toward the horizon color (`0.0..=1.0`).
+    pub haze: f32,
+}
+
+impl Default for SkyConfig {
+    fn default() -> Self {
+        Self {
+            horizon: Vec3::new(0.74, 0.84, 0.96),
+            zenith: Vec3::new(0.20, 0.47, 0.85),
+            haze: 0.0,
+        }
+    }
+}
+
+impl SkyConfig {
+    /// Sky parameters for a biome.
+    pub fn for_biome(biome: TerrainBiome) -> Self {
+        match biome {
+            TerrainBiome::Plains | TerrainBiome::Hills => Self::default(),
+            TerrainBiome::Forest => Self {
+                horizon: Vec3::new(0.70, 0.82, 0.90),
+                zenith: Vec3::new(0.18, 0.44, 0.78),
+                haze: 0.1,
+            },
+            TerrainBiome::Desert => Self {
+                horizon: Vec3::new(0.90, 0.84, 0.70),
+                zenith: Vec3::new(0.38, 0.55, 0.78),
+                haze: 0.45,
+            },
+            TerrainBiome::SnowyMountains => Self {
+                horizon: Vec3::new(0.72, 0.86, 1.0),
+                zenith: Vec3::new(0.10, 0.38, 0.92),
+                haze: 0.0,
+            },
+        }
+    }
+
+    /// Sky parameters for the biome under a camera position.
+    pub fn at_camera(generator: &TerrainGenerator, camera_pos: Vec3) -> (TerrainBiome, Self) {
+        let xz = camera_pos.xz().floor();
+        let biome = generator.biome_at(xz.x as i64, xz.y as i64);
+        (biome, Self::for_biome(biome))
+    }
+
+    /// Linearly interpolate toward `target` by `t` (clamped to `0.0..=1.0`).
+    pub fn lerp(&self, target: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            horizon: self.horizon.lerp(target.horizon, t),
+            zenith: self.zenith.lerp(target.zenith, t),
+            haze: self.haze + (target.haze - self.haze) * t,
+        }
+    }
+}
+
+/// Sky parameters that ease toward a target over time.
+#[derive(Debug, Clone)]
+pub struct SkyBlend {
+    current: SkyConfig,
+    target: SkyConfig,
+    /// Exponential approach rate (1/seconds).
+    rate: f32,
+}
+
+impl SkyBlend {
+    /// Default approach rate; roughly 95% of a transition takes 3 seconds.
+    pub const DEFAULT_RATE: f32 = 1.0;
+
+    /// Create a blend resting at `sky`.
+    pub fn new(sky: SkyConfig) -> Self {
+        Self {
+            current: sky,
+            target: sky,
+            rate: Self::DEFAULT_RATE,
+        }
+    }
+
+    /// Set how quickly the sky approaches its target (1/seconds).
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate.max(0.0);
+        self
+    }
+
+    /// Set the sky to blend toward.
+    pub fn set_sky(&mut self, target: SkyConfig) {
+        self.target = target;
+    }
+
+    /// Advance the blend by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        let t = 1.0 - (-self.rate * dt.max(0.0)).exp();
+        self.current = self.current.lerp(&self.target, t);
+    }
+
+    /// Current blended sky.
+    pub fn current(&self) -> &SkyConfig {
+        &self.current
+    }
+
+    /// Sky being blended toward.
+    pub fn target(&self) -> &SkyConfig {
+        &self.target
+    }
+}
+
+impl Default for SkyBlend {
+    fn default() -> Self {
+        Self::new(SkyConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blend_converges_to_target_sky() {
+        let desert = SkyConfig::for_biome(TerrainBiome::Desert);
+        let mut blend = SkyBlend::default();
+        blend.set_sky(desert);
+
+        blend.update(1.0 / 60.0);
+        let first = *blend.current();
+        assert_ne!(first, SkyConfig::default());
+        assert_ne!(first, desert);
+
+        for _ in 0..600 {
+            blend.update(1.0 / 60.0);
+        }
+        let current = blend.current();
+        assert!(current.horizon.abs_diff_eq(desert.horizon, 1e-3));
+        assert!(current.zenith.abs_diff_eq(desert.zenith, 1e-3));
+        assert!((current.haze - desert.haze).abs() < 1e-3);
+
+        // Endpoints of the raw lerp are exact.
+        let start = SkyConfig::default();
+        assert_eq!(start.lerp(&desert, 0.0), start);
+        assert_eq!(start.lerp(&desert, 1.0), desert);
+    }
+
+    #[test]
+    fn camera_biome_lookup_is_stable() {
+        let generator = TerrainGenerator::with_seed(42);
+        let camera = Vec3::new(1234.6, 90.0, -987.2);
+        let (biome, sky) = SkyConfig::at_camera(&generator, camera);
+        assert_eq!(biome, generator.biome_at(1234, -988));
+        assert_eq!(sky, SkyConfig::for_biome(biome));
+
+        // Height and sub-voxel jitter do not change the sampled column.
+        for offset in [Vec3::new(0.2, 0.0, 0.1), Vec3::new(0.0, 500.0, 0.0)] {
+            assert_eq!(SkyConfig::at_camera(&generator, camera + offset).0, biome);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Day/night cycle driving the sun direction and sky colors.
+
+use std::f32::consts::TAU;
+
+use glam::Vec3;
+
+use crate::camera::CameraUniforms;
+use crate::sky::SkyConfig;
+
+/// Hours in a full day.
+pub const HOURS_PER_DAY: f32 = 24.0;
+
+const DAWN_SKY: SkyConfig = SkyConfig {
+    horizon: Vec3::new(0.93, 0.62, 0.42),
+    zenith: Vec3::new(0.30, 0.40, 0.66),
+    haze: 0.25,
+};
+
+const DUSK_SKY: SkyConfig = SkyConfig {
+    horizon: Vec3::new(0.95, 0.50, 0.30),
+    zenith: Vec3::new(0.24, 0.28, 0.55),
+    haze: 0.3,
+};
+
+const NIGHT_SKY: SkyConfig = SkyConfig {
+    horizon: Vec3::new(0.03, 0.06, 0.12),
+    zenith: Vec3::new(0.005, 0.01, 0.03),
+    haze: 0.0,
+};
+
+/// Sky keyframe at an hour of the day.
+#[derive(Clone, Copy)]
+enum Key {
+    Night,
+    Dawn,
+    Noon,
+    Dusk,
+}
+
+/// Keyframes in hour order; the first and last wrap around midnight.
+const KEYS: [(f32, Key); 8] = [
+    (0.0, Key::Night),
+    (4.5, Key::Night),
+    (6.0, Key::Dawn),
+    (8.5, Key::Noon),
+    (15.5, Key::Noon),
+    (18.0, Key::Dusk),
+    (19.5, Key::Night),
+    (24.0, Key::Night),
+];
+
+/// Time-of-day driver for the sun and sky.
+///
+/// Hours run from `0.0` (midnight) through `12.0` (noon) and wrap at `24.0`.
+/// The sun rises at 6:00 and sets at 18:00, matching the shader's day phase.
+/// [`Self::apply`] writes the day phase and sky into [`CameraUniforms`].
+#[derive(Debug, Clone)]
+pub struct TimeOfDay {
+    hours: f32,
+    day_sky: SkyConfig,
+}
+
+impl TimeOfDay {
+    /// Create a cycle at `hours`.
+    pub fn new(hours: f32) -> Self {
+        let mut time = Self {
+            hours: 0.0,
+            day_sky: SkyConfig::default(),
+        };
+        time.set_time_of_day(hours);
+        time
+    }
+
+    /// Jump to `hours` (wrapped to `0.0..24.0`).
+    pub fn set_time_of_day(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(HOURS_PER_DAY);
+    }
+
+    /// Current time in hours.
+    pub fn time_of_day(&self) -> f32 {
+        self.hours
+    }
+
+    /// Advance the clock by `hours`.
+    pub fn advance(&mut self, hours: f32) {
+        self.set_time_of_day(self.hours + hours);
+    }
+
+    /// Set the midday sky, e.g. the biome sky from a [`crate::SkyBlend`].
+    pub fn set_day_sky(&mut self, sky: SkyConfig) {
+        self.day_sky = sky;
+    }
+
+    /// Normalized day phase as used by the shader (`0.25` is noon).
+    pub fn day_phase(&self) -> f32 {
+        ((self.hours - 6.0) / HOURS_PER_DAY).rem_euclid(1.0)
+    }
+
+    /// Direction sunlight travels, from the sun toward the scene.
+    ///
+    /// Points straight-ish down at noon and up while the sun is below the
+    /// horizon.
+    pub fn sun_direction(&self) -> Vec3 {
+        let orbit = self.day_phase() * TAU;
+        -Vec3::new(orbit.cos(), orbit.sin(), 0.35).normalize()
+    }
+
+    /// Whether the sun is above the horizon.
+    pub fn sun_above_horizon(&self) -> bool {
+        self.sun_direction().y < 0.0
+    }
+
+    /// Sky colors for the current time, interpolated between presets.
+    pub fn sky(&self) -> SkyConfig {
+        let next = KEYS
+            .iter()
+            .position(|&(hour, _)| hour > self.hours)
+            .unwrap_or(KEYS.len() - 1);
+        let (start, from) = KEYS[next - 1];
+        let (end, to) = KEYS[next];
+        let t = (self.hours - start) / (end - start);
+        self.preset(from).lerp(&self.preset(to), t)
+    }
+
+    /// Write the day phase and sky into camera uniforms.
+    ///
+    /// Shadow rays are disabled while the sun is below the horizon.
+    pub fn apply(&self, uniforms: &mut CameraUniforms) {
+        uniforms.day_night[0] = self.day_phase();
+        if !self.sun_above_horizon() {
+            uniforms.day_night[1] = 1.0;
+        }
+        uniforms.set_sky(&self.sky());
+    }
+
+    fn preset(&self, key: Key) -> SkyConfig {
+        match key {
+            Key::Night => NIGHT_SKY,
+            Key::Dawn => DAWN_SKY,
+            Key::Noon => self.day_sky,
+            Key::Dusk => DUSK_SKY,
+        }
+    }
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self::new(12.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+
+    #[test]
+    fn sun_follows_the_clock() {
+        let mut time = TimeOfDay::default();
+        time.set_time_of_day(12.0);
+        assert!(time.sun_direction().y < -0.9, "{}", time.sun_direction());
+        assert!((time.day_phase() - 0.25).abs() < 1e-6);
+        assert_eq!(time.sky(), SkyConfig::default());
+
+        for hours in [0.0, 24.0] {
+            time.set_time_of_day(hours);
+            assert!(time.sun_direction().y >= 0.0, "{}", time.sun_direction());
+            assert!(!time.sun_above_horizon());
+            assert_eq!(time.sky(), NIGHT_SKY);
+        }
+
+        // Sunrise and sunset sit on the horizon.
+        for hours in [6.0, 18.0] {
+            time.set_time_of_day(hours);
+            assert!(time.sun_direction().y.abs() < 1e-5);
+        }
+
+        // At night the uniforms carry the phase and disable shadow rays.
+        let mut uniforms = Camera::default().uniforms();
+        time.set_time_of_day(23.0);
+        time.apply(&mut uniforms);
+        assert!((uniforms.day_night[0] - time.day_phase()).abs() < 1e-6);
+        assert!((uniforms.day_night[1] - 1.0).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,83 @@
+//! Per-frame GPU upload budget shared across renderers.
+//!
+//! Each renderer caps its own uploads with an entry count, but several
+//! renderers streaming at once can still spike the frame together. An
+//! [`UploadBudget`] is owned by the app, refilled once per frame and passed
+//! to every sync call, so the total bytes written in a frame stay bounded.
+
+/// Bytes the renderers may upload in the current frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadBudget {
+    bytes_per_frame: u64,
+    remaining: u64,
+}
+
+impl UploadBudget {
+    /// Create a full budget of `bytes_per_frame`.
+    #[must_use]
+    pub const fn new(bytes_per_frame: u64) -> Self {
+        Self {
+            bytes_per_frame,
+            remaining: bytes_per_frame,
+        }
+    }
+
+    /// Refill the budget; call once at the start of each frame.
+    pub fn begin_frame(&mut self) {
+        self.remaining = self.bytes_per_frame;
+    }
+
+    /// Change the per-frame allowance, effective from the next frame.
+    pub fn set_bytes_per_frame(&mut self, bytes_per_frame: u64) {
+        self.bytes_per_frame = bytes_per_frame;
+    }
+
+    /// Bytes allowed per frame.
+    #[must_use]
+    pub const fn bytes_per_frame(&self) -> u64 {
+        self.bytes_per_frame
+    }
+
+    /// Bytes left to spend this frame.
+    #[must_use]
+    pub const fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Bytes spent so far this frame.
+    #[must_use]
+    pub const fn used(&self) -> u64 {
+        self.bytes_per_frame.saturating_sub(self.remaining)
+    }
+
+    /// Spend the budget on up to `count` items of `item_bytes` each.
+    ///
+    /// Returns how many items fit; only those are charged. An item larger
+    /// than the whole per-frame allowance never fits.
+    pub fn take(&mut self, count: usize, item_bytes: u64) -> usize {
+        let fits = self.remaining.checked_div(item_bytes).unwrap_or(u64::MAX);
+        let taken = usize::try_from(fits).map_or(count, |fits| fits.min(count));
+        self.remaining -= taken as u64 * item_bytes;
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_charges_whole_items_until_the_frame_refills() {
+        let mut budget = UploadBudget::new(100);
+        assert_eq!(budget.take(4, 30), 3);
+        assert_eq!(budget.remaining(), 10);
+        assert_eq!(budget.take(1, 30), 0);
+        assert_eq!(budget.take(5, 2), 5);
+        assert_eq!(budget.used(), 100);
+
+        budget.begin_frame();
+        assert_eq!(budget.remaining(), 100);
+        assert_eq!(budget.take(2, 0), 2);
+        assert_eq!(budget.take(1, 101), 0);
+    }
+}
@@ -0,0 +1,606 @@
+//! Portable compute ray marching on wgpu (feature `wgpu`).
+//!
+//! The Vulkan path binds everything by buffer device address, which only
+//! `ash` exposes. This backend runs a WGSL port of the clipmap ray marcher on
+//! any wgpu adapter (Metal, DX12, GL, WebGPU) instead. It reads the same
+//! brick pools and page tables as [`crate::ClipmapRenderer`] and the same
+//! [`CameraUniforms`], but traces LOD0 only, with flat material colors, sun
+//! lighting and leaf wind. [`crate::RenderPipeline`] records into Vulkan
+//! command buffers, so this renderer drives its own device and queue instead.
+
+use std::borrow::Cow;
+use std::sync::mpsc;
+
+use bytemuck::{Pod, Zeroable};
+use voxelicous_world::ClipmapStreamingController;
+use wgpu::util::DeviceExt;
+
+use crate::camera::CameraUniforms;
+use crate::clipmap_ray_march_pipeline::RenderStats;
+use crate::clipmap_render::WindSettings;
+use crate::cpu_ray_march::lod0_page_table;
+
+/// Workgroup edge length of the WGSL ray marcher.
+const WORKGROUP_SIZE: u32 = 8;
+/// Texture format of [`WgpuClipmapRenderer::output_texture`].
+pub const WGPU_OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// Uniform parameters of the WGSL ray marcher.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct WgpuRayMarchParams {
+    pub screen_size: [u32; 2],
+    pub max_steps: u32,
+    /// Visible pages along X and Z in the uploaded page table.
+    pub page_grid: u32,
+    /// LOD0 page coordinate of the page table's first entry (xyz).
+    pub origin_page: [i32; 4],
+    /// Word offset of the palette32 pool in the concatenated pool buffer.
+    pub palette32_base: u32,
+    /// Word offset of the raw16 pool in the concatenated pool buffer.
+    pub raw16_base: u32,
+    /// Visible pages along Y in the uploaded page table.
+    pub page_grid_y: u32,
+    pub _pad: u32,
+    /// Leaf sway amount in `0.0..=1.0`; 0 disables wind.
+    pub wind_strength: f32,
+    /// Wind wave speed in radians per second.
+    pub wind_speed: f32,
+    pub _pad1: [u32; 2],
+}
+
+/// Errors from the wgpu backend.
+#[derive(Debug)]
+pub enum WgpuBackendError {
+    /// No adapter matched the request.
+    NoAdapter,
+    /// The adapter refused to create a device.
+    RequestDevice(String),
+    /// Reading the output image back failed.
+    ReadbackFailed(String),
+}
+
+impl std::fmt::Display for WgpuBackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "No wgpu adapter available"),
+            Self::RequestDevice(e) => write!(f, "Failed to create wgpu device: {e}"),
+            Self::ReadbackFailed(e) => write!(f, "Failed to read back wgpu output: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for WgpuBackendError {}
+
+/// World data uploaded for tracing.
+struct WorldBuffers {
+    pages: wgpu::Buffer,
+    headers: wgpu::Buffer,
+    pools: wgpu::Buffer,
+    page_grid: u32,
+    page_grid_y: u32,
+    origin_page: [i32; 3],
+    palette32_base: u32,
+    raw16_base: u32,
+}
+
+/// Clipmap ray marcher running a WGSL compute shader through wgpu.
+pub struct WgpuClipmapRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    camera_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    output: wgpu::Texture,
+    width: u32,
+    height: u32,
+    world: WorldBuffers,
+    wind: WindSettings,
+}
+
+impl WgpuClipmapRenderer {
+    /// Create a renderer on an existing device with a `width`x`height`
+    /// output image.
+    ///
+    /// Nothing is drawn but sky until [`Self::upload_clipmap`] is called.
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue, width: u32, height: u32) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ray_march_clipmap.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
+                voxelicous_shaders::RAY_MARCH_CLIPMAP_WGSL,
+            )),
+        });
+
+        let uniform = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("wgpu ray march layout"),
+            entries: &[
+                uniform(0),
+                uniform(1),
+                storage(2),
+                storage(3),
+                storage(4),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: WGPU_OUTPUT_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("wgpu ray march pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("wgpu ray march pipeline"),
+            layout: Some(&layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("wgpu camera uniforms"),
+            size: std::mem::size_of::<CameraUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("wgpu ray march params"),
+            size: std::mem::size_of::<WgpuRayMarchParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let output = create_output(&device, width, height);
+        let world = WorldBuffers {
+            pages: storage_buffer(&device, "wgpu page table", &[]),
+            headers: storage_buffer(&device, "wgpu brick headers", &[]),
+            pools: storage_buffer(&device, "wgpu brick pools", &[]),
+            page_grid: 0,
+            page_grid_y: 0,
+            origin_page: [0; 3],
+            palette32_base: 0,
+            raw16_base: 0,
+        };
+
+        Self {
+            device,
+            queue,
+            pipeline,
+            bind_group_layout,
+            camera_buffer,
+            params_buffer,
+            output,
+            width: width.max(1),
+            height: height.max(1),
+            world,
+            wind: WindSettings::default(),
+        }
+    }
+
+    /// Create a renderer on the default adapter without a surface.
+    ///
+    /// Software adapters are accepted, so this also works on CI machines
+    /// without a GPU as long as some wgpu backend is present.
+    pub fn headless(width: u32, height: u32) -> Result<Self, WgpuBackendError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+                .ok_or(WgpuBackendError::NoAdapter)?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("voxelicous wgpu device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                memory_hints: wgpu::MemoryHints::default(),
+            },
+            None,
+        ))
+        .map_err(|e| WgpuBackendError::RequestDevice(e.to_string()))?;
+        Ok(Self::new(device, queue, width, height))
+    }
+
+    /// The device the renderer draws with.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The queue the renderer submits to.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Image the trace is written to ([`WGPU_OUTPUT_FORMAT`]).
+    pub fn output_texture(&self) -> &wgpu::Texture {
+        &self.output
+    }
+
+    /// Output dimensions.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Recreate the output image at a new size.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        if (width, height) != (self.width, self.height) {
+            self.output = create_output(&self.device, width, height);
+            self.width = width;
+            self.height = height;
+        }
+    }
+
+    /// Set the leaf wind animation, as [`crate::ClipmapRenderer::set_wind`].
+    pub fn set_wind(&mut self, wind: WindSettings) {
+        self.wind = wind.clamped();
+    }
+
+    /// Current leaf wind animation.
+    pub fn wind(&self) -> WindSettings {
+        self.wind
+    }
+
+    /// Upload the LOD0 pages and brick pools of a clipmap.
+    ///
+    /// The whole visible LOD0 region is re-uploaded; call this after
+    /// streaming or edits change the clipmap.
+    pub fn upload_clipmap(&mut self, clipmap: &ClipmapStreamingController) {
+        let (origin_page, pages) = lod0_page_table(clipmap);
+        let pools = clipmap.store().gpu_pools();
+        let mut pool_bytes =
+            Vec::with_capacity(pools.palette16.len() + pools.palette32.len() + pools.raw16.len());
+        pool_bytes.extend_from_slice(pools.palette16);
+        let palette32_base = (pool_bytes.len() / 4) as u32;
+        pool_bytes.extend_from_slice(pools.palette32);
+        let raw16_base = (pool_bytes.len() / 4) as u32;
+        pool_bytes.extend_from_slice(pools.raw16);
+
+        self.world = WorldBuffers {
+            pages: storage_buffer(
+                &self.device,
+                "wgpu page table",
+                bytemuck::cast_slice(&pages),
+            ),
+            headers: storage_buffer(&self.device, "wgpu brick headers", pools.headers),
+            pools: storage_buffer(&self.device, "wgpu brick pools", &pool_bytes),
+            page_grid: clipmap.visible_page_grid_xz() as u32,
+            page_grid_y: clipmap.visible_page_grid_y() as u32,
+            origin_page,
+            palette32_base,
+            raw16_base,
+        };
+    }
+
+    /// Trace a frame into the output image and submit it.
+    pub fn render(&self, camera: &CameraUniforms, max_steps: u32) {
+        let params = WgpuRayMarchParams {
+            screen_size: [self.width, self.height],
+            max_steps,
+            page_grid: self.world.page_grid,
+            origin_page: [
+                self.world.origin_page[0],
+                self.world.origin_page[1],
+                self.world.origin_page[2],
+                0,
+            ],
+            palette32_base: self.world.palette32_base,
+            raw16_base: self.world.raw16_base,
+            page_grid_y: self.world.page_grid_y,
+            _pad: 0,
+            wind_strength: self.wind.strength,
+            wind_speed: self.wind.speed,
+            _pad1: [0; 2],
+        };
+        self.queue
+            .write_buffer(&self.camera_buffer, 0, bytemuck::bytes_of(camera));
+        self.queue
+            .write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let output_view = self
+            .output
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("wgpu ray march bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.world.pages.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.world.headers.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: self.world.pools.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&output_view),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("wgpu ray march"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("wgpu ray march"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                self.width.div_ceil(WORKGROUP_SIZE),
+                self.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Always `None`: the WGSL ray marcher does not count rays.
+    ///
+    /// Mirrors [`ClipmapRayMarchPipeline::take_render_stats`] so callers can
+    /// treat both renderers alike.
+    ///
+    /// [`ClipmapRayMarchPipeline::take_render_stats`]: crate::ClipmapRayMarchPipeline::take_render_stats
+    pub fn take_render_stats(&self) -> Option<RenderStats> {
+        None
+    }
+
+    /// Read the output image back as tightly packed RGBA8 rows.
+    pub fn read_pixels(&self) -> Result<Vec<u8>, WgpuBackendError> {
+        let row_bytes = self.width * 4;
+        let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("wgpu output readback"),
+            size: u64::from(padded_row_bytes) * u64::from(self.height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("wgpu output readback"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.output,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .map_err(|e| WgpuBackendError::ReadbackFailed(e.to_string()))?
+            .map_err(|e| WgpuBackendError::ReadbackFailed(e.to_string()))?;
+
+        let mapped = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((row_bytes * self.height) as usize);
+        for row in mapped.chunks_exact(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        drop(mapped);
+        readback.unmap();
+        Ok(pixels)
+    }
+}
+
+fn storage_buffer(device: &wgpu::Device, label: &str, contents: &[u8]) -> wgpu::Buffer {
+    // Bindings may not be empty; keep at least one zeroed word.
+    let contents = if contents.is_empty() {
+        &[0; 4]
+    } else {
+        contents
+    };
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents,
+        usage: wgpu::BufferUsages::STORAGE,
+    })
+}
+
+fn create_output(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("wgpu ray march output"),
+        size: wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: WGPU_OUTPUT_FORMAT,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use voxelicous_core::BlockId;
+    use voxelicous_voxel::{WorldCoord, PAGE_VOXELS_PER_AXIS};
+    use voxelicous_world::{TerrainConfig, TerrainGenerator};
+
+    use super::*;
+    use crate::camera::Camera;
+
+    #[test]
+    #[ignore = "needs a wgpu adapter"]
+    fn headless_render_shows_terrain_below_the_camera() {
+        let mut renderer =
+            WgpuClipmapRenderer::headless(64, 64).expect("no wgpu adapter available");
+
+        // Stand above dry land so the view is not just a water plane.
+        let generator = TerrainGenerator::with_seed(7);
+        let z = (0..8_192)
+            .step_by(16)
+            .find(|&z| generator.height_at(0, z) > 70)
+            .expect("seed 7 has land along x = 0");
+        let eye = Vec3::new(0.5, generator.height_at(0, z) as f32 + 12.0, z as f32 + 0.5);
+        let mut clipmap = ClipmapStreamingController::new(generator);
+        clipmap.set_visible_page_grid(4);
+        assert!(clipmap.generate_initial_pages(eye) > 0);
+
+        let camera = Camera::new(
+            eye,
+            eye + Vec3::new(1.0, -0.8, 0.3),
+            Vec3::Y,
+            std::f32::consts::FRAC_PI_3,
+            1.0,
+            0.1,
+            1000.0,
+        );
+        let uniforms = camera.uniforms();
+        let max_steps = 4 * PAGE_VOXELS_PER_AXIS as u32 * 3;
+
+        // Before any upload only sky is drawn.
+        renderer.render(&uniforms, max_steps);
+        let sky = renderer.read_pixels().unwrap();
+        assert_eq!(sky.len(), 64 * 64 * 4);
+
+        renderer.upload_clipmap(&clipmap);
+        renderer.render(&uniforms, max_steps);
+        let terrain = renderer.read_pixels().unwrap();
+
+        // Looking down at the ground fills the lower half of the view.
+        let lower_half = 32 * 64 * 4..;
+        let differing = sky[lower_half.clone()]
+            .chunks_exact(4)
+            .zip(terrain[lower_half].chunks_exact(4))
+            .filter(|(a, b)| a != b)
+            .count();
+        assert_eq!(differing, 32 * 64);
+        let center = (32 * 64 + 32) * 4;
+        assert_ne!(sky[center..center + 4], terrain[center..center + 4]);
+    }
+
+    #[test]
+    #[ignore = "needs a wgpu adapter"]
+    fn leaf_wind_animates_frames_over_time() {
+        const GROUND: i32 = 70;
+        let mut renderer =
+            WgpuClipmapRenderer::headless(64, 64).expect("no wgpu adapter available");
+        let generator =
+            TerrainGenerator::with_height_fn(TerrainConfig::default(), Box::new(|_, _| GROUND));
+        let mut clipmap = ClipmapStreamingController::new(generator);
+        clipmap.set_visible_page_grid(4);
+        assert!(clipmap.generate_initial_pages(Vec3::new(0.5, 80.0, 0.5)) > 0);
+
+        let eye = Vec3::new(2.5, GROUND as f32 + 14.0, -12.0);
+        let camera = Camera::new(
+            eye,
+            Vec3::new(2.5, GROUND as f32 + 3.0, 2.5),
+            Vec3::Y,
+            std::f32::consts::FRAC_PI_3,
+            1.0,
+            0.1,
+            1000.0,
+        );
+        let max_steps = 4 * PAGE_VOXELS_PER_AXIS as u32 * 3;
+        let frames = |renderer: &WgpuClipmapRenderer| {
+            let mut uniforms = camera.uniforms();
+            let mut frame = |time: f32| {
+                uniforms.set_time(time);
+                renderer.render(&uniforms, max_steps);
+                renderer.read_pixels().unwrap()
+            };
+            (frame(0.0), frame(1.3))
+        };
+        let wind = WindSettings {
+            strength: 0.5,
+            speed: 2.0,
+        };
+
+        // Without leaves in view the wind changes nothing.
+        renderer.set_wind(wind);
+        renderer.upload_clipmap(&clipmap);
+        let (first, second) = frames(&renderer);
+        assert_eq!(first, second);
+
+        let coord = |x: i32, y: i32, z: i32| WorldCoord {
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+        };
+        clipmap.edit_box(
+            coord(0, GROUND + 1, 0),
+            coord(4, GROUND + 3, 4),
+            BlockId::LEAVES,
+        );
+        renderer.upload_clipmap(&clipmap);
+
+        // Calm air keeps the canopy still.
+        renderer.set_wind(WindSettings::default());
+        let (first, second) = frames(&renderer);
+        assert_eq!(first, second);
+
+        renderer.set_wind(wind);
+        let (first, second) = frames(&renderer);
+        assert_ne!(first, second);
+    }
+}
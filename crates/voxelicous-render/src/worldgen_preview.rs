@@ -0,0 +1,408 @@
+//! Top-down shaded relief preview of generated terrain.
+//!
+//! Skips voxel pages and ray marching entirely: the surface heightmap is
+//! shaded on the CPU, uploaded to an image and blitted to the target, so
+//! seeds and terrain settings can be scrubbed at interactive rates.
+
+use std::cell::Cell;
+
+use ash::vk;
+use glam::Vec3;
+use gpu_allocator::MemoryLocation;
+use voxelicous_core::types::{BlockId, MaterialRegistry};
+use voxelicous_core::Fnv1a;
+use voxelicous_gpu::error::Result;
+use voxelicous_gpu::memory::{GpuAllocator, GpuBuffer, GpuImage};
+use voxelicous_world::{SurfaceSample, TerrainConfig, TerrainGenerator, WorldSeed};
+
+/// Bytes per preview pixel (RGBA8).
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Color of columns whose top block has no material.
+const FALLBACK_COLOR: [u8; 3] = [255, 0, 255];
+
+/// Light coming from the north-west, above the horizon.
+const LIGHT_DIRECTION: Vec3 = Vec3::new(-0.5, 0.7, -0.5);
+
+/// Light that reaches slopes facing away from the sun.
+const AMBIENT: f32 = 0.35;
+
+/// XZ area of the world shown by a preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewRegion {
+    /// World XZ column at the centre of the image.
+    pub center: (i64, i64),
+    /// World columns per pixel along each axis.
+    pub blocks_per_pixel: u32,
+}
+
+impl Default for PreviewRegion {
+    fn default() -> Self {
+        Self {
+            center: (0, 0),
+            blocks_per_pixel: 4,
+        }
+    }
+}
+
+impl PreviewRegion {
+    /// World column of the image's top-left pixel for a `width`x`height`
+    /// preview.
+    pub fn min(&self, width: u32, height: u32) -> (i64, i64) {
+        let step = i64::from(self.blocks_per_pixel.max(1));
+        (
+            self.center.0 - i64::from(width / 2) * step,
+            self.center.1 - i64::from(height / 2) * step,
+        )
+    }
+}
+
+/// Shade a heightmap as a top-down relief in RGBA8, row-major.
+///
+/// `samples` must hold `width * height` columns spaced `step` blocks apart,
+/// as returned by [`TerrainGenerator::export_heightmap`]. Columns take the
+/// color of their top block (or of the water above them) and are lit from
+/// their height gradient.
+pub fn shaded_relief(samples: &[SurfaceSample], width: u32, height: u32, step: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    assert_eq!(
+        samples.len(),
+        w * h,
+        "heightmap does not match preview size"
+    );
+
+    let materials = MaterialRegistry::default();
+    let light = LIGHT_DIRECTION.normalize();
+    let step = step.max(1) as f32;
+    let height_at = |x: usize, z: usize| samples[x + z * w].surface_height as f32;
+
+    let mut pixels = Vec::with_capacity(w * h * BYTES_PER_PIXEL);
+    for z in 0..h {
+        for x in 0..w {
+            let sample = &samples[x + z * w];
+            let underwater = sample.water_level > sample.surface_height;
+            let block = match (underwater, sample.frozen) {
+                (true, true) => BlockId::ICE,
+                (true, false) => BlockId::WATER,
+                (false, _) => sample.top_block,
+            };
+            let color = materials.get(block).map_or(FALLBACK_COLOR, |m| m.color);
+
+            // Central differences, one-sided at the image edges.
+            let (x0, x1) = (x.saturating_sub(1), (x + 1).min(w - 1));
+            let (z0, z1) = (z.saturating_sub(1), (z + 1).min(h - 1));
+            let dx = (height_at(x1, z) - height_at(x0, z)) / ((x1 - x0).max(1) as f32 * step);
+            let dz = (height_at(x, z1) - height_at(x, z0)) / ((z1 - z0).max(1) as f32 * step);
+            let shade = if underwater {
+                1.0
+            } else {
+                let normal = Vec3::new(-dx, 1.0, -dz).normalize();
+                AMBIENT + (1.0 - AMBIENT) * normal.dot(light).max(0.0)
+            };
+
+            for channel in color {
+                pixels.push((f32::from(channel) * shade).round().min(255.0) as u8);
+            }
+            pixels.push(u8::MAX);
+        }
+    }
+    pixels
+}
+
+/// FNV-1a hash of preview pixels.
+fn pixel_hash(pixels: &[u8]) -> u64 {
+    Fnv1a::hash_bytes(pixels)
+}
+
+/// Terrain preview rendered without building any voxel data.
+///
+/// [`Self::render`] regenerates the relief for a seed on the CPU;
+/// [`Self::record`] stages and uploads the pixels and blits them to a target
+/// image such as the swapchain.
+pub struct WorldGenPreview {
+    config: TerrainConfig,
+    image: GpuImage,
+    /// One staging buffer per frame in flight, so staging a frame never
+    /// overwrites pixels an earlier frame is still copying.
+    staging: Vec<GpuBuffer>,
+    /// Whether each staging buffer holds the current pixels.
+    staged: Vec<Cell<bool>>,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    contents_hash: u64,
+}
+
+impl WorldGenPreview {
+    /// Create a `width`x`height` preview of terrain generated with `config`,
+    /// recorded into up to `frames_in_flight` frames at once.
+    ///
+    /// Only the seed changes between renders; every other setting comes
+    /// from `config`.
+    pub fn new(
+        allocator: &mut GpuAllocator,
+        config: TerrainConfig,
+        width: u32,
+        height: u32,
+        frames_in_flight: usize,
+    ) -> Result<Self> {
+        let (width, height) = (width.max(1), height.max(1));
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .extent(vk::Extent3D {
+                width,
+                height,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::TRANSFER_SRC)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let image =
+            allocator.create_image(&image_info, MemoryLocation::GpuOnly, "worldgen_preview")?;
+
+        let staging_size = u64::from(width) * u64::from(height) * BYTES_PER_PIXEL as u64;
+        let frames_in_flight = frames_in_flight.max(1);
+        let mut staging = Vec::with_capacity(frames_in_flight);
+        for i in 0..frames_in_flight {
+            match allocator.create_buffer(
+                staging_size,
+                vk::BufferUsageFlags::TRANSFER_SRC,
+                MemoryLocation::CpuToGpu,
+                &format!("worldgen_preview_staging_{i}"),
+            ) {
+                Ok(buffer) => staging.push(buffer),
+                Err(e) => {
+                    for buffer in &mut staging {
+                        allocator.free_buffer(buffer)?;
+                    }
+                    let mut image = image;
+                    allocator.free_image(&mut image)?;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(Self {
+            config,
+            image,
+            staged: (0..frames_in_flight).map(|_| Cell::new(false)).collect(),
+            staging,
+            pixels: vec![0; staging_size as usize],
+            width,
+            height,
+            contents_hash: pixel_hash(&[]),
+        })
+    }
+
+    /// Generate the relief of `seed` over `region` for the next recorded
+    /// frames.
+    ///
+    /// Returns the hash of the pixels, which is also available from
+    /// [`Self::contents_hash`].
+    pub fn render(&mut self, seed: WorldSeed, region: PreviewRegion) -> u64 {
+        let generator = TerrainGenerator::new(TerrainConfig {
+            seed,
+            ..self.config.clone()
+        });
+        let step = region.blocks_per_pixel.max(1);
+        let samples = generator.export_heightmap(
+            region.min(self.width, self.height),
+            (self.width, self.height),
+            step,
+        );
+        self.pixels = shaded_relief(&samples, self.width, self.height, step);
+        self.contents_hash = pixel_hash(&self.pixels);
+        for staged in &self.staged {
+            staged.set(false);
+        }
+        self.contents_hash
+    }
+
+    /// Hash of the pixels generated by the last [`Self::render`].
+    pub fn contents_hash(&self) -> u64 {
+        self.contents_hash
+    }
+
+    /// Preview size in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Access the preview image.
+    pub fn image(&self) -> &GpuImage {
+        &self.image
+    }
+
+    /// Staging buffer of frame `frame_index`, refreshed with the current
+    /// pixels if [`Self::render`] ran since it was last staged.
+    fn stage(&self, frame_index: usize) -> Result<&GpuBuffer> {
+        let staging = &self.staging[frame_index];
+        if !self.staged[frame_index].replace(true) {
+            staging.write(&self.pixels)?;
+        }
+        Ok(staging)
+    }
+
+    /// Record the upload of the current pixels and a blit to `dst_image`.
+    ///
+    /// The pixels go through the staging buffer of `frame_index`. The
+    /// preview is stretched over `dst_extent` with nearest filtering.
+    ///
+    /// # Safety
+    /// `cmd` must be recording, the previous frame recorded with
+    /// `frame_index` must have finished, and `dst_image` must be in
+    /// `TRANSFER_DST_OPTIMAL` layout when the commands execute.
+    pub unsafe fn record(
+        &self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        frame_index: usize,
+        dst_image: vk::Image,
+        dst_extent: vk::Extent2D,
+    ) -> Result<()> {
+        let staging = self.stage(frame_index)?;
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let layers = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        // Waits for the previous frame's blit to finish reading the image.
+        let to_transfer_dst = vk::ImageMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+            .src_access_mask(vk::AccessFlags2::NONE)
+            .dst_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+            .dst_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+            .old_layout(vk::ImageLayout::UNDEFINED)
+            .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .image(self.image.image)
+            .subresource_range(subresource_range);
+        device.cmd_pipeline_barrier2(
+            cmd,
+            &vk::DependencyInfo::default()
+                .image_memory_barriers(std::slice::from_ref(&to_transfer_dst)),
+        );
+
+        let copy = vk::BufferImageCopy::default()
+            .image_subresource(layers)
+            .image_extent(self.image.extent);
+        device.cmd_copy_buffer_to_image(
+            cmd,
+            staging.buffer,
+            self.image.image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            std::slice::from_ref(&copy),
+        );
+
+        let to_transfer_src = vk::ImageMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+            .src_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+            .dst_access_mask(vk::AccessFlags2::TRANSFER_READ)
+            .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .image(self.image.image)
+            .subresource_range(subresource_range);
+        device.cmd_pipeline_barrier2(
+            cmd,
+            &vk::DependencyInfo::default()
+                .image_memory_barriers(std::slice::from_ref(&to_transfer_src)),
+        );
+
+        let corner = |width: u32, height: u32| vk::Offset3D {
+            x: i32::try_from(width).unwrap_or(i32::MAX),
+            y: i32::try_from(height).unwrap_or(i32::MAX),
+            z: 1,
+        };
+        let blit = vk::ImageBlit::default()
+            .src_subresource(layers)
+            .src_offsets([vk::Offset3D::default(), corner(self.width, self.height)])
+            .dst_subresource(layers)
+            .dst_offsets([
+                vk::Offset3D::default(),
+                corner(dst_extent.width, dst_extent.height),
+            ]);
+        device.cmd_blit_image(
+            cmd,
+            self.image.image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            dst_image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            std::slice::from_ref(&blit),
+            vk::Filter::NEAREST,
+        );
+        Ok(())
+    }
+
+    /// Destroy GPU resources.
+    ///
+    /// # Safety
+    /// The device must be idle.
+    pub unsafe fn destroy(mut self, allocator: &mut GpuAllocator) -> Result<()> {
+        allocator.free_image(&mut self.image)?;
+        for buffer in &mut self.staging {
+            allocator.free_buffer(buffer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxelicous_gpu::testing::test_gpu;
+
+    use super::*;
+
+    fn staged_pixels(buffer: &GpuBuffer, len: usize) -> Vec<u8> {
+        let ptr = buffer.mapped_ptr().expect("staging is host visible");
+        unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec()
+    }
+
+    #[test]
+    #[ignore = "needs a Vulkan device"]
+    fn changing_the_seed_changes_the_uploaded_pixels() {
+        let gpu = test_gpu().expect("no Vulkan device available");
+        let mut allocator = gpu.allocator().lock();
+        let mut preview =
+            WorldGenPreview::new(&mut allocator, TerrainConfig::default(), 48, 32, 2).unwrap();
+        let region = PreviewRegion {
+            center: (200, -150),
+            blocks_per_pixel: 8,
+        };
+        let len = 48 * 32 * BYTES_PER_PIXEL;
+
+        let first = preview.render(1, region);
+        assert_eq!(preview.contents_hash(), first);
+        let pixels = staged_pixels(preview.stage(0).unwrap(), len);
+        assert!(pixels.chunks_exact(4).all(|pixel| pixel[3] == u8::MAX));
+        assert_eq!(pixel_hash(&pixels), first);
+        assert_eq!(preview.render(1, region), first);
+
+        // A new seed is staged per frame, leaving other frames' buffers be.
+        let second = preview.render(2, region);
+        assert_ne!(second, first);
+        assert_eq!(pixel_hash(&staged_pixels(&preview.staging[0], len)), first);
+        assert_eq!(
+            pixel_hash(&staged_pixels(preview.stage(1).unwrap(), len)),
+            second
+        );
+        assert_eq!(
+            pixel_hash(&staged_pixels(preview.stage(0).unwrap(), len)),
+            second
+        );
+
+        unsafe { preview.destroy(&mut allocator).unwrap() };
+    }
+}
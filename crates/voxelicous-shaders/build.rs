@@ -15,12 +15,35 @@ fn main() {
     // Create compiler
     let compiler = Compiler::new().expect("Failed to create shader compiler");
 
-    // Compile ray_march_clipmap.comp (clipmap compute)
+    // Compile ray_march_clipmap.comp (clipmap compute), one variant per workgroup size,
+    // each with an RGBA8 and a format-less output image
     compile_shader(
         &compiler,
         shader_dir.join("ray_march_clipmap.comp"),
         Path::new(&out_dir).join("ray_march_clipmap.spv"),
         ShaderKind::Compute,
+        &[("WORKGROUP_SIZE", "8")],
+    );
+    compile_shader(
+        &compiler,
+        shader_dir.join("ray_march_clipmap.comp"),
+        Path::new(&out_dir).join("ray_march_clipmap_16.spv"),
+        ShaderKind::Compute,
+        &[("WORKGROUP_SIZE", "16")],
+    );
+    compile_shader(
+        &compiler,
+        shader_dir.join("ray_march_clipmap.comp"),
+        Path::new(&out_dir).join("ray_march_clipmap_formatless.spv"),
+        ShaderKind::Compute,
+        &[("WORKGROUP_SIZE", "8"), ("FORMATLESS_OUTPUT", "1")],
+    );
+    compile_shader(
+        &compiler,
+        shader_dir.join("ray_march_clipmap.comp"),
+        Path::new(&out_dir).join("ray_march_clipmap_16_formatless.spv"),
+        ShaderKind::Compute,
+        &[("WORKGROUP_SIZE", "16"), ("FORMATLESS_OUTPUT", "1")],
     );
 
     // Compile crosshair_overlay.comp (post ray-march overlay)
@@ -29,6 +52,14 @@ fn main() {
         shader_dir.join("crosshair_overlay.comp"),
         Path::new(&out_dir).join("crosshair_overlay.spv"),
         ShaderKind::Compute,
+        &[],
+    );
+    compile_shader(
+        &compiler,
+        shader_dir.join("crosshair_overlay.comp"),
+        Path::new(&out_dir).join("crosshair_overlay_formatless.spv"),
+        ShaderKind::Compute,
+        &[("FORMATLESS_OUTPUT", "1")],
     );
 }
 
@@ -37,6 +68,7 @@ fn compile_shader(
     input: impl AsRef<Path>,
     output: impl AsRef<Path>,
     kind: ShaderKind,
+    defines: &[(&str, &str)],
 ) {
     let input_path = input.as_ref();
     let output_path = output.as_ref();
@@ -53,6 +85,9 @@ fn compile_shader(
     );
     options.set_target_spirv(shaderc::SpirvVersion::V1_6);
     options.set_optimization_level(shaderc::OptimizationLevel::Performance);
+    for (name, value) in defines {
+        options.add_macro_definition(name, Some(value));
+    }
 
     let result = compiler
         .compile_into_spirv(&source, kind, file_name, "main", Some(&options))
@@ -0,0 +1,271 @@
+// Portable clipmap ray marcher for the wgpu backend.
+//
+// Traces LOD0 with a voxel DDA. The page table holds one entry of 64 brick
+// ids per visible page, laid out densely from the LOD0 origin page. Brick
+// headers and payloads use the same byte layout as ray_march_clipmap.comp;
+// the palette16, palette32 and raw16 pools are concatenated into one buffer
+// to stay within downlevel storage buffer limits.
+
+const PAGE_VOXELS: i32 = 32;
+const PAGE_BRICKS: u32 = 64u;
+const HEADER_WORDS: u32 = 8u;
+const PALETTE16_WORDS: u32 = 72u;
+const PALETTE32_WORDS: u32 = 96u;
+const RAW16_WORDS: u32 = 256u;
+const TAU: f32 = 6.28318530718;
+// Mirrors BlockId::LEAVES.
+const BLOCK_LEAVES: u32 = 8u;
+// Leaf wind: brightness swing and normal tilt at full strength, as in
+// ray_march_clipmap.comp.
+const LEAF_WIND_TINT: f32 = 0.18;
+const LEAF_WIND_BEND: f32 = 0.35;
+
+struct CameraUniforms {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    inverse_view: mat4x4<f32>,
+    inverse_projection: mat4x4<f32>,
+    position: vec4<f32>,
+    direction: vec4<f32>,
+    day_night: vec4<f32>,
+    sky_horizon: vec4<f32>,
+    sky_zenith: vec4<f32>,
+}
+
+struct Params {
+    screen_size: vec2<u32>,
+    max_steps: u32,
+    page_grid: u32,
+    origin_page: vec4<i32>,
+    // Word offsets of the palette32 and raw16 pools in `pools`.
+    palette32_base: u32,
+    raw16_base: u32,
+    // Pages along Y; `page_grid` covers X and Z.
+    page_grid_y: u32,
+    _pad0: u32,
+    // Leaf sway amount (0 disables wind) and wave speed in radians per second.
+    wind_strength: f32,
+    wind_speed: f32,
+    _pad1: vec2<u32>,
+}
+
+struct Hit {
+    block: u32,
+    normal: vec3<f32>,
+    voxel: vec3<i32>,
+}
+
+@group(0) @binding(0) var<uniform> camera: CameraUniforms;
+@group(0) @binding(1) var<uniform> params: Params;
+@group(0) @binding(2) var<storage, read> pages: array<u32>;
+@group(0) @binding(3) var<storage, read> headers: array<u32>;
+@group(0) @binding(4) var<storage, read> pools: array<u32>;
+@group(0) @binding(5) var output: texture_storage_2d<rgba8unorm, write>;
+
+fn pool_byte(word_base: u32, byte: u32) -> u32 {
+    let word = pools[word_base + (byte >> 2u)];
+    return (word >> ((byte & 3u) * 8u)) & 0xFFu;
+}
+
+fn pool_u16(word_base: u32, byte: u32) -> u32 {
+    let word = pools[word_base + (byte >> 2u)];
+    return (word >> ((byte & 2u) * 8u)) & 0xFFFFu;
+}
+
+fn brick_voxel(brick: u32, v: vec3<u32>) -> u32 {
+    let h = brick * HEADER_WORDS;
+    let palette_len = headers[h] & 0xFFu;
+    let encoding = (headers[h] >> 8u) & 0xFFu;
+    let data_index = headers[h + 1u];
+
+    // Skip empty 2x2x2 cells via the occupancy mask.
+    let cell = v.x / 2u + (v.y / 2u) * 4u + (v.z / 2u) * 16u;
+    let occ = select(headers[h + 2u], headers[h + 3u], cell >= 32u);
+    if ((occ >> (cell & 31u)) & 1u) == 0u {
+        return 0u;
+    }
+
+    let voxel = v.x + v.y * 8u + v.z * 64u;
+    switch encoding {
+        case 0u: {
+            let base = data_index * PALETTE16_WORDS;
+            let packed = pool_byte(base, 32u + voxel / 2u);
+            let index = select(packed & 0xFu, packed >> 4u, (voxel & 1u) == 1u);
+            return pool_u16(base, index * 2u);
+        }
+        case 1u: {
+            let base = params.palette32_base + data_index * PALETTE32_WORDS;
+            let bit = voxel * 5u;
+            let lo = pool_byte(base, 64u + bit / 8u);
+            var hi = 0u;
+            if bit / 8u + 1u < 320u {
+                hi = pool_byte(base, 64u + bit / 8u + 1u);
+            }
+            var index = ((lo | (hi << 8u)) >> (bit % 8u)) & 0x1Fu;
+            if index >= palette_len {
+                index = 0u;
+            }
+            return pool_u16(base, index * 2u);
+        }
+        default: {
+            let base = params.raw16_base + data_index * RAW16_WORDS;
+            return pool_u16(base, voxel * 2u);
+        }
+    }
+}
+
+fn block_at(voxel: vec3<i32>) -> u32 {
+    let local = voxel - params.origin_page.xyz * PAGE_VOXELS;
+    let grid = params.page_grid;
+    let extent = vec3<i32>(vec3<u32>(grid, params.page_grid_y, grid)) * PAGE_VOXELS;
+    if any(local < vec3<i32>(0)) || any(local >= extent) {
+        return 0u;
+    }
+
+    let page = vec3<u32>(local / PAGE_VOXELS);
+    let in_page = vec3<u32>(local % PAGE_VOXELS);
+    let brick = in_page / 8u;
+    let page_index = page.x + (page.y + page.z * params.page_grid_y) * grid;
+    let brick_index = brick.x + brick.y * 4u + brick.z * 16u;
+    let id = pages[page_index * PAGE_BRICKS + brick_index];
+    if id == 0u {
+        return 0u;
+    }
+    return brick_voxel(id, in_page % 8u);
+}
+
+fn trace(origin: vec3<f32>, dir: vec3<f32>) -> Hit {
+    var hit: Hit;
+    hit.block = 0u;
+    hit.normal = vec3<f32>(0.0, 1.0, 0.0);
+
+    let safe_dir = select(dir, vec3<f32>(1e-8), abs(dir) < vec3<f32>(1e-8));
+    let inv_dir = 1.0 / safe_dir;
+    let box_min = vec3<f32>(params.origin_page.xyz * PAGE_VOXELS);
+    let grid = vec3<i32>(vec3<u32>(params.page_grid, params.page_grid_y, params.page_grid));
+    let box_max = box_min + vec3<f32>(grid * PAGE_VOXELS);
+    let t0 = (box_min - origin) * inv_dir;
+    let t1 = (box_max - origin) * inv_dir;
+    let t_near = min(t0, t1);
+    let t_far = max(t0, t1);
+    let t_enter = max(max(max(t_near.x, t_near.y), t_near.z), 0.0);
+    let t_exit = min(min(t_far.x, t_far.y), t_far.z);
+    if t_enter >= t_exit {
+        return hit;
+    }
+
+    let start = origin + dir * (t_enter + 1e-4);
+    let step = vec3<i32>(sign(safe_dir));
+    let delta = abs(inv_dir);
+    var voxel = vec3<i32>(floor(start));
+    let voxel_f = vec3<f32>(voxel);
+    var side = select(voxel_f - start, voxel_f + 1.0 - start, step > vec3<i32>(0)) * inv_dir;
+    var normal = vec3<f32>(0.0, 1.0, 0.0);
+    if t_near.x >= t_near.y && t_near.x >= t_near.z {
+        normal = vec3<f32>(-f32(step.x), 0.0, 0.0);
+    } else if t_near.y >= t_near.z {
+        normal = vec3<f32>(0.0, -f32(step.y), 0.0);
+    } else {
+        normal = vec3<f32>(0.0, 0.0, -f32(step.z));
+    }
+
+    let span = t_exit - t_enter;
+    for (var i = 0u; i < params.max_steps; i++) {
+        let block = block_at(voxel);
+        if block != 0u {
+            hit.block = block;
+            hit.normal = normal;
+            hit.voxel = voxel;
+            return hit;
+        }
+
+        var t = 0.0;
+        if side.x < side.y && side.x < side.z {
+            voxel.x += step.x;
+            t = side.x;
+            side.x += delta.x;
+            normal = vec3<f32>(-f32(step.x), 0.0, 0.0);
+        } else if side.y < side.z {
+            voxel.y += step.y;
+            t = side.y;
+            side.y += delta.y;
+            normal = vec3<f32>(0.0, -f32(step.y), 0.0);
+        } else {
+            voxel.z += step.z;
+            t = side.z;
+            side.z += delta.z;
+            normal = vec3<f32>(0.0, 0.0, -f32(step.z));
+        }
+        if t > span {
+            break;
+        }
+    }
+    return hit;
+}
+
+fn block_color(block: u32) -> vec3<f32> {
+    switch block {
+        case 1u: { return vec3<f32>(0.5, 0.5, 0.5); }
+        case 2u: { return vec3<f32>(0.54, 0.35, 0.17); }
+        case 3u: { return vec3<f32>(0.34, 0.49, 0.27); }
+        case 4u: { return vec3<f32>(0.94, 0.95, 0.98); }
+        case 5u: { return vec3<f32>(0.84, 0.78, 0.52); }
+        case 6u: { return vec3<f32>(0.22, 0.45, 0.77); }
+        case 7u: { return vec3<f32>(0.38, 0.27, 0.16); }
+        case 8u: { return vec3<f32>(0.24, 0.46, 0.20); }
+        case 9u: { return vec3<f32>(0.88, 0.28, 0.33); }
+        case 10u: { return vec3<f32>(0.70, 0.85, 0.95); }
+        case 11u: { return vec3<f32>(0.18, 0.18, 0.20); }
+        default: { return vec3<f32>(1.0, 0.0, 1.0); }
+    }
+}
+
+// Mirrors WindSettings::leaf_phase.
+fn leaf_wind_phase(voxel: vec3<f32>, time: f32) -> f32 {
+    return dot(voxel, vec3<f32>(0.37, 0.51, 0.23)) + time * params.wind_speed;
+}
+
+fn sun_direction() -> vec3<f32> {
+    let orbit = fract(camera.day_night.x) * TAU;
+    return normalize(vec3<f32>(cos(orbit), sin(orbit), 0.35));
+}
+
+fn sky_color(dir: vec3<f32>, daylight: f32) -> vec3<f32> {
+    let sky_t = clamp(dir.y * 0.5 + 0.5, 0.0, 1.0);
+    var day_sky = mix(camera.sky_horizon.rgb, camera.sky_zenith.rgb, pow(sky_t, 0.7));
+    day_sky = mix(day_sky, camera.sky_horizon.rgb, camera.sky_horizon.w);
+    let night_sky = mix(vec3<f32>(0.03, 0.06, 0.12), vec3<f32>(0.005, 0.01, 0.03), pow(sky_t, 0.8));
+    return mix(night_sky, day_sky, daylight);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.screen_size.x || id.y >= params.screen_size.y {
+        return;
+    }
+
+    let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(params.screen_size);
+    let ndc = uv * 2.0 - 1.0;
+    let clip = vec4<f32>(ndc.x, -ndc.y, 1.0, 1.0);
+    let view = camera.inverse_projection * clip;
+    let dir = normalize((camera.inverse_view * vec4<f32>(view.xyz / view.w, 0.0)).xyz);
+
+    let sun_dir = sun_direction();
+    let daylight = smoothstep(-0.18, 0.08, sun_dir.y);
+    let hit = trace(camera.position.xyz, dir);
+
+    var color = sky_color(dir, daylight);
+    if hit.block != 0u {
+        var base_color = block_color(hit.block);
+        var normal = hit.normal;
+        if hit.block == BLOCK_LEAVES && params.wind_strength > 0.0 {
+            let phase = leaf_wind_phase(vec3<f32>(hit.voxel), camera.day_night.z);
+            base_color *= 1.0 + params.wind_strength * LEAF_WIND_TINT * sin(phase);
+            let bend = vec3<f32>(sin(phase), 0.0, cos(phase * 0.7)) * (params.wind_strength * LEAF_WIND_BEND);
+            normal = normalize(normal + bend);
+        }
+        let diffuse = max(dot(normal, sun_dir), 0.0) * daylight;
+        color = base_color * (0.35 + 0.65 * diffuse);
+    }
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(color, 1.0));
+}
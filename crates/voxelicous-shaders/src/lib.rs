@@ -1,7 +1,8 @@
 //! Shader compilation for the Voxelicous engine.
 //!
 //! This crate contains GLSL shaders and their compiled SPIR-V bytecode.
-//! Shaders are compiled at build time using shaderc.
+//! Shaders are compiled at build time using shaderc. The WGSL ray marcher
+//! for the portable wgpu backend is embedded as source.
 
 use std::sync::OnceLock;
 
@@ -10,9 +11,28 @@ mod spirv_bytes {
     /// Ray march clipmap compute shader (compiled SPIR-V).
     pub static RAY_MARCH_CLIPMAP_COMP: &[u8] =
         include_bytes!(concat!(env!("OUT_DIR"), "/ray_march_clipmap.spv"));
+    /// Ray march clipmap compute shader with 16x16 workgroups (compiled SPIR-V).
+    pub static RAY_MARCH_CLIPMAP_16_COMP: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/ray_march_clipmap_16.spv"));
+    /// Ray march clipmap compute shader with a format-less output image (compiled SPIR-V).
+    pub static RAY_MARCH_CLIPMAP_FORMATLESS_COMP: &[u8] = include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/ray_march_clipmap_formatless.spv"
+    ));
+    /// Ray march clipmap compute shader with 16x16 workgroups and a format-less output
+    /// image (compiled SPIR-V).
+    pub static RAY_MARCH_CLIPMAP_16_FORMATLESS_COMP: &[u8] = include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/ray_march_clipmap_16_formatless.spv"
+    ));
     /// Crosshair overlay compute shader (compiled SPIR-V).
     pub static CROSSHAIR_OVERLAY_COMP: &[u8] =
         include_bytes!(concat!(env!("OUT_DIR"), "/crosshair_overlay.spv"));
+    /// Crosshair overlay compute shader with a format-less output image (compiled SPIR-V).
+    pub static CROSSHAIR_OVERLAY_FORMATLESS_COMP: &[u8] = include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/crosshair_overlay_formatless.spv"
+    ));
 }
 
 /// Convert byte slice to aligned u32 Vec (SPIR-V requires 4-byte alignment).
@@ -28,22 +48,86 @@ fn bytes_to_spirv(bytes: &[u8]) -> Vec<u32> {
 }
 
 static RAY_MARCH_CLIPMAP_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
+static RAY_MARCH_CLIPMAP_16_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
+static RAY_MARCH_CLIPMAP_FORMATLESS_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
+static RAY_MARCH_CLIPMAP_16_FORMATLESS_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
 static CROSSHAIR_OVERLAY_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
+static CROSSHAIR_OVERLAY_FORMATLESS_SPIRV: OnceLock<Vec<u32>> = OnceLock::new();
 
 /// Get ray march clipmap shader as u32 slice for Vulkan.
 pub fn ray_march_clipmap_shader() -> &'static [u32] {
     RAY_MARCH_CLIPMAP_SPIRV.get_or_init(|| bytes_to_spirv(spirv_bytes::RAY_MARCH_CLIPMAP_COMP))
 }
 
+/// Workgroup edge lengths the ray march clipmap shader is compiled for.
+pub const RAY_MARCH_WORKGROUP_SIZES: [u32; 2] = [8, 16];
+
+/// Get the ray march clipmap shader compiled for `size`x`size` workgroups.
+///
+/// Returns `None` for sizes not in [`RAY_MARCH_WORKGROUP_SIZES`].
+pub fn ray_march_clipmap_shader_for_workgroup(size: u32) -> Option<&'static [u32]> {
+    match size {
+        8 => Some(ray_march_clipmap_shader()),
+        16 => Some(
+            RAY_MARCH_CLIPMAP_16_SPIRV
+                .get_or_init(|| bytes_to_spirv(spirv_bytes::RAY_MARCH_CLIPMAP_16_COMP)),
+        ),
+        _ => None,
+    }
+}
+
+/// Get the ray march clipmap shader for `size`x`size` workgroups, writing an
+/// RGBA8 output image or, with `formatless`, an image of any storage format.
+///
+/// The format-less variants need `shaderStorageImageWriteWithoutFormat`.
+/// Returns `None` for sizes not in [`RAY_MARCH_WORKGROUP_SIZES`].
+pub fn ray_march_clipmap_shader_for_output(size: u32, formatless: bool) -> Option<&'static [u32]> {
+    if !formatless {
+        return ray_march_clipmap_shader_for_workgroup(size);
+    }
+    match size {
+        8 => Some(
+            RAY_MARCH_CLIPMAP_FORMATLESS_SPIRV
+                .get_or_init(|| bytes_to_spirv(spirv_bytes::RAY_MARCH_CLIPMAP_FORMATLESS_COMP)),
+        ),
+        16 => Some(
+            RAY_MARCH_CLIPMAP_16_FORMATLESS_SPIRV
+                .get_or_init(|| bytes_to_spirv(spirv_bytes::RAY_MARCH_CLIPMAP_16_FORMATLESS_COMP)),
+        ),
+        _ => None,
+    }
+}
+
 /// Get crosshair overlay shader as u32 slice for Vulkan.
 pub fn crosshair_overlay_shader() -> &'static [u32] {
     CROSSHAIR_OVERLAY_SPIRV.get_or_init(|| bytes_to_spirv(spirv_bytes::CROSSHAIR_OVERLAY_COMP))
 }
 
+/// Get the crosshair overlay shader with a format-less output image.
+///
+/// Needs `shaderStorageImageWriteWithoutFormat`.
+pub fn crosshair_overlay_formatless_shader() -> &'static [u32] {
+    CROSSHAIR_OVERLAY_FORMATLESS_SPIRV
+        .get_or_init(|| bytes_to_spirv(spirv_bytes::CROSSHAIR_OVERLAY_FORMATLESS_COMP))
+}
+
+/// WGSL source of the portable clipmap ray marcher used by the wgpu backend.
+pub const RAY_MARCH_CLIPMAP_WGSL: &str = include_str!("../shaders/ray_march_clipmap.wgsl");
+
 #[cfg(test)]
 mod tests {
+    use voxelicous_core::BlockId;
+
     use super::*;
 
+    #[test]
+    fn shader_block_ids_match_core() {
+        let leaves = BlockId::LEAVES.0;
+        let glsl = include_str!("../shaders/ray_march_clipmap.comp");
+        assert!(glsl.contains(&format!("const uint BLOCK_LEAVES = {leaves}u;")));
+        assert!(RAY_MARCH_CLIPMAP_WGSL.contains(&format!("const BLOCK_LEAVES: u32 = {leaves}u;")));
+    }
+
     #[test]
     fn clipmap_shader_loads() {
         let shader = ray_march_clipmap_shader();
@@ -51,10 +135,28 @@ mod tests {
         assert!(shader.len() > 100, "Shader too small");
     }
 
+    #[test]
+    fn clipmap_workgroup_variants_load() {
+        for size in RAY_MARCH_WORKGROUP_SIZES {
+            let shader = ray_march_clipmap_shader_for_workgroup(size).unwrap();
+            assert_eq!(shader[0], 0x0723_0203, "Invalid SPIR-V magic number");
+        }
+        assert!(ray_march_clipmap_shader_for_workgroup(12).is_none());
+        for size in RAY_MARCH_WORKGROUP_SIZES {
+            let shader = ray_march_clipmap_shader_for_output(size, true).unwrap();
+            assert_eq!(shader[0], 0x0723_0203, "Invalid SPIR-V magic number");
+        }
+        assert!(ray_march_clipmap_shader_for_output(12, true).is_none());
+    }
+
     #[test]
     fn crosshair_shader_loads() {
-        let shader = crosshair_overlay_shader();
-        assert_eq!(shader[0], 0x0723_0203, "Invalid SPIR-V magic number");
-        assert!(shader.len() > 20, "Shader too small");
+        for shader in [
+            crosshair_overlay_shader(),
+            crosshair_overlay_formatless_shader(),
+        ] {
+            assert_eq!(shader[0], 0x0723_0203, "Invalid SPIR-V magic number");
+            assert!(shader.len() > 20, "Shader too small");
+        }
     }
 }
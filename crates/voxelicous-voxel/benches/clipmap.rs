@@ -0,0 +1,132 @@
+//! Clipmap brick storage benchmarks.
+//!
+//! Run with: `cargo bench -p voxelicous-voxel`
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use voxelicous_core::types::BlockId;
+use voxelicous_voxel::{
+    downsample_volume_2x, downsample_voxel, encode_brick, ClipmapVoxelStore, BRICK_SIZE,
+    BRICK_VOXELS,
+};
+
+const SEED: u64 = 0x5EED_CAFE;
+
+/// Deterministic xorshift so inputs are identical across runs.
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Terrain-like brick: solid below a wavy surface with a few block types.
+fn terrain_brick(variant: u64) -> [BlockId; BRICK_VOXELS] {
+    let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+    for z in 0..BRICK_SIZE {
+        for x in 0..BRICK_SIZE {
+            let surface = 3 + ((x as u64 + z as u64 * 3 + variant) % 4) as usize;
+            for y in 0..=surface {
+                let idx = x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE;
+                voxels[idx] = match surface - y {
+                    0 => BlockId::GRASS,
+                    1 | 2 => BlockId::DIRT,
+                    _ => BlockId::STONE,
+                };
+            }
+        }
+    }
+    voxels
+}
+
+/// Noisy brick with many distinct ids, forcing the raw16 encoding.
+fn noisy_brick(state: &mut u64) -> [BlockId; BRICK_VOXELS] {
+    let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+    for voxel in &mut voxels {
+        *voxel = BlockId((next_random(state) % 64) as u16);
+    }
+    voxels
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let terrain = terrain_brick(0);
+    let mut state = SEED;
+    let noisy = noisy_brick(&mut state);
+
+    let mut group = c.benchmark_group("encode_brick");
+    group.throughput(Throughput::Elements(BRICK_VOXELS as u64));
+    group.bench_function("terrain", |b| b.iter(|| encode_brick(black_box(&terrain))));
+    group.bench_function("noisy", |b| b.iter(|| encode_brick(black_box(&noisy))));
+    group.finish();
+}
+
+fn bench_allocate(c: &mut Criterion) {
+    const BRICKS: usize = 256;
+    let bricks: Vec<_> = (0..BRICKS as u64).map(terrain_brick).collect();
+
+    let mut group = c.benchmark_group("clipmap_store");
+    group.throughput(Throughput::Elements(BRICKS as u64));
+    group.bench_function("allocate_brick", |b| {
+        b.iter_batched(
+            ClipmapVoxelStore::new,
+            |mut store| {
+                for brick in &bricks {
+                    black_box(store.allocate_brick(brick));
+                }
+                store
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("allocate_free_reuse", |b| {
+        let mut store = ClipmapVoxelStore::new();
+        let ids: Vec<_> = bricks.iter().map(|v| store.allocate_brick(v)).collect();
+        for id in ids {
+            store.free_brick(id);
+        }
+        b.iter(|| {
+            let ids: Vec<_> = bricks.iter().map(|v| store.allocate_brick(v)).collect();
+            for id in ids {
+                store.free_brick(black_box(id));
+            }
+        });
+    });
+    group.finish();
+}
+
+fn bench_downsample(c: &mut Criterion) {
+    let mut state = SEED;
+    let children: Vec<[BlockId; 8]> = (0..1024)
+        .map(|_| {
+            let mut block = [BlockId::AIR; 8];
+            for voxel in &mut block {
+                *voxel = BlockId((next_random(&mut state) % 10) as u16);
+            }
+            block
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("downsample");
+    group.throughput(Throughput::Elements(children.len() as u64));
+    group.bench_function("downsample_voxel", |b| {
+        b.iter(|| {
+            for block in &children {
+                black_box(downsample_voxel(block));
+            }
+        });
+    });
+    group.finish();
+
+    const VOLUME: usize = 32;
+    let volume: Vec<BlockId> = (0..VOLUME * VOLUME * VOLUME)
+        .map(|_| BlockId((next_random(&mut state) % 10) as u16))
+        .collect();
+    let mut group = c.benchmark_group("downsample_volume_2x");
+    group.throughput(Throughput::Elements(volume.len() as u64));
+    group.bench_function("32^3", |b| {
+        b.iter(|| downsample_volume_2x(black_box(&volume), VOLUME));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode, bench_allocate, bench_downsample);
+criterion_main!(benches);
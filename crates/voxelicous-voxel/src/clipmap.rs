@@ -30,6 +30,15 @@ pub const PALETTE16_STRIDE: usize = 288;
 pub const PALETTE32_STRIDE: usize = 384;
 /// Raw16 entry stride (bytes).
 pub const RAW16_STRIDE: usize = 1024;
+/// Normal entry stride (bytes, one octahedral normal per voxel).
+pub const NORMAL_STRIDE: usize = BRICK_VOXELS;
+/// Light entry stride (bytes, one block-light level per voxel).
+pub const LIGHT_STRIDE: usize = BRICK_VOXELS;
+
+/// Brick header flag: the brick carries per-voxel normals in the normal pool.
+pub const BRICK_FLAG_NORMALS: u16 = 1 << 0;
+/// Brick header flag: the brick carries per-voxel block light in the light pool.
+pub const BRICK_FLAG_LIGHT: u16 = 1 << 1;
 
 /// Newtype for brick identifiers (0 = empty).
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
@@ -95,8 +104,8 @@ pub struct BrickHeader {
     pub occ_l2: u8,
     pub _padding: u16,
     pub avg_color: u32,
-    pub _pad0: u32,
-    pub _pad1: u32,
+    pub normal_index: u32,
+    pub light_index: u32,
 }
 
 impl Default for BrickHeader {
@@ -112,8 +121,8 @@ impl Default for BrickHeader {
             occ_l2: 0,
             _padding: 0,
             avg_color: 0,
-            _pad0: 0,
-            _pad1: 0,
+            normal_index: 0,
+            light_index: 0,
         }
     }
 }
@@ -134,6 +143,57 @@ impl Default for ClipmapPage {
     }
 }
 
+/// When [`ClipmapVoxelStore`] palette-compresses bricks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompressionPolicy {
+    /// Palette-encode every brick that fits a palette when it is allocated.
+    #[default]
+    Always,
+    /// Store bricks as raw16 until [`ClipmapVoxelStore::compress_brick`]
+    /// is called, e.g. for bricks that are still being edited.
+    Lazy,
+    /// Always store bricks as raw16; [`ClipmapVoxelStore::compress_brick`]
+    /// does nothing.
+    Never,
+}
+
+impl CompressionPolicy {
+    /// Level used by allocations that do not pick one.
+    pub const fn default_level(self) -> CompressLevel {
+        match self {
+            Self::Always => CompressLevel::Full,
+            Self::Lazy | Self::Never => CompressLevel::Fast,
+        }
+    }
+}
+
+/// How much work a single allocation spends on compressing its bricks.
+///
+/// Lets callers trade memory for latency per page, e.g. keeping pages near
+/// the camera quick to (re)build and packing distant ones tightly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompressLevel {
+    /// Store bricks as raw16. Air bricks still collapse to the shared empty
+    /// brick, but nothing is palette-encoded; bricks can be compressed
+    /// later with [`ClipmapVoxelStore::compress_brick`].
+    Fast,
+    /// Palette-encode every brick that fits a palette, unless the store's
+    /// policy is [`CompressionPolicy::Never`].
+    #[default]
+    Full,
+}
+
+/// Pool entry a re-encode replaced, still reserved until
+/// [`ClipmapVoxelStore::free_payload`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetiredPayload {
+    encoding: BrickEncoding,
+    data_index: u32,
+}
+
+/// Callback run when a brick gets a new encoding.
+type EncodingHook = Box<dyn FnMut(BrickId, BrickEncoding) + Send + Sync>;
+
 /// Encoded brick payload for CPU-side encoding/decoding.
 #[derive(Clone, Debug)]
 pub struct EncodedBrick {
@@ -143,16 +203,33 @@ pub struct EncodedBrick {
 }
 
 /// Clipmap voxel store with brick headers and data pools.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct ClipmapVoxelStore {
     headers: Vec<BrickHeader>,
     palette16_pool: Vec<u8>,
     palette32_pool: Vec<u8>,
     raw16_pool: Vec<u8>,
+    normal_pool: Vec<u8>,
+    light_pool: Vec<u8>,
     free_headers: Vec<u32>,
     free_palette16: Vec<u32>,
     free_palette32: Vec<u32>,
     free_raw16: Vec<u32>,
+    free_normals: Vec<u32>,
+    free_lights: Vec<u32>,
+    compression: CompressionPolicy,
+    on_encoding_change: Option<EncodingHook>,
+}
+
+impl std::fmt::Debug for ClipmapVoxelStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClipmapVoxelStore")
+            .field("bricks", &self.headers.len())
+            .field("free_headers", &self.free_headers.len())
+            .field("compression", &self.compression)
+            .field("memory_bytes", &self.memory_bytes())
+            .finish_non_exhaustive()
+    }
 }
 
 impl ClipmapVoxelStore {
@@ -174,49 +251,90 @@ impl ClipmapVoxelStore {
         self.headers.get(id.0 as usize)
     }
 
+    /// Current compression policy.
+    pub fn compression_policy(&self) -> CompressionPolicy {
+        self.compression
+    }
+
+    /// Set when bricks are palette-compressed.
+    ///
+    /// Only affects bricks allocated or compressed afterwards.
+    pub fn set_compression_policy(&mut self, policy: CompressionPolicy) {
+        self.compression = policy;
+    }
+
+    /// Run `hook` whenever a brick is allocated or re-encoded, with the
+    /// brick id and its new encoding.
+    pub fn on_encoding_change<F>(&mut self, hook: F)
+    where
+        F: FnMut(BrickId, BrickEncoding) + Send + Sync + 'static,
+    {
+        self.on_encoding_change = Some(Box::new(hook));
+    }
+
     /// Encode and allocate a brick, returning its BrickId.
     pub fn allocate_brick(&mut self, voxels: &[BlockId; BRICK_VOXELS]) -> BrickId {
+        self.allocate_brick_with_normals(voxels, None)
+    }
+
+    /// Encode and allocate a brick with optional per-voxel octahedral normals.
+    ///
+    /// A normal byte of 0 means "no stored normal"; the renderer falls back to
+    /// the axis-aligned face normal for those voxels.
+    pub fn allocate_brick_with_normals(
+        &mut self,
+        voxels: &[BlockId; BRICK_VOXELS],
+        normals: Option<&[u8; BRICK_VOXELS]>,
+    ) -> BrickId {
+        self.allocate_brick_with(voxels, normals, self.compression.default_level())
+    }
+
+    /// Encode and allocate a brick at an explicit compression level.
+    ///
+    /// [`CompressionPolicy::Never`] still keeps the brick raw16 at
+    /// [`CompressLevel::Full`].
+    pub fn allocate_brick_with(
+        &mut self,
+        voxels: &[BlockId; BRICK_VOXELS],
+        normals: Option<&[u8; BRICK_VOXELS]>,
+        level: CompressLevel,
+    ) -> BrickId {
         if voxels.iter().all(|v| v.is_air()) {
             return BrickId(0);
         }
 
-        let encoded = encode_brick(voxels);
-        let (data_index, encoding) = match encoded.encoding {
-            BrickEncoding::Palette16 => {
-                let index = Self::allocate_pool_entry(
-                    PALETTE16_STRIDE,
-                    &mut self.palette16_pool,
-                    &mut self.free_palette16,
-                    &encoded.data,
-                );
-                (index, BrickEncoding::Palette16)
-            }
-            BrickEncoding::Palette32 => {
-                let index = Self::allocate_pool_entry(
-                    PALETTE32_STRIDE,
-                    &mut self.palette32_pool,
-                    &mut self.free_palette32,
-                    &encoded.data,
-                );
-                (index, BrickEncoding::Palette32)
-            }
-            BrickEncoding::Raw16 => {
+        let encoded =
+            if level == CompressLevel::Full && self.compression != CompressionPolicy::Never {
+                encode_brick(voxels)
+            } else {
+                EncodedBrick {
+                    encoding: BrickEncoding::Raw16,
+                    palette_len: 0,
+                    data: encode_raw16(voxels),
+                }
+            };
+        let encoding = encoded.encoding;
+        let data_index = self.allocate_payload(&encoded);
+
+        let (occ_l0_lo, occ_l0_hi, occ_l1, occ_l2) = compute_occupancy(voxels);
+
+        let (flags, normal_index) = match normals {
+            Some(normals) => {
                 let index = Self::allocate_pool_entry(
-                    RAW16_STRIDE,
-                    &mut self.raw16_pool,
-                    &mut self.free_raw16,
-                    &encoded.data,
+                    NORMAL_STRIDE,
+                    &mut self.normal_pool,
+                    &mut self.free_normals,
+                    normals,
                 );
-                (index, BrickEncoding::Raw16)
+                (BRICK_FLAG_NORMALS, index)
             }
+            None => (0, 0),
         };
 
-        let (occ_l0_lo, occ_l0_hi, occ_l1, occ_l2) = compute_occupancy(voxels);
-
         let header = BrickHeader {
             palette_len: encoded.palette_len,
             encoding: encoding as u8,
-            flags: 0,
+            flags,
             data_index,
             occ_l0_lo,
             occ_l0_hi,
@@ -224,21 +342,124 @@ impl ClipmapVoxelStore {
             occ_l2,
             _padding: 0,
             avg_color: 0,
-            _pad0: 0,
-            _pad1: 0,
+            normal_index,
+            light_index: 0,
         };
 
-        if let Some(index) = self.free_headers.pop() {
-            let idx = index as usize;
-            if idx < self.headers.len() {
-                self.headers[idx] = header;
-                return BrickId(index);
+        let id = match self.free_headers.pop() {
+            Some(index) if (index as usize) < self.headers.len() => {
+                self.headers[index as usize] = header;
+                BrickId(index)
+            }
+            _ => {
+                self.headers.push(header);
+                BrickId(self.headers.len() as u32 - 1)
+            }
+        };
+        self.notify_encoding(id, encoding);
+        id
+    }
+
+    /// Allocate the bricks of a page whose voxel `(x, y, z)` (each
+    /// `0..PAGE_VOXELS_PER_AXIS`) is `f(x, y, z)`.
+    ///
+    /// Bricks come back in page order (`bx + by * 4 + bz * 16`). All-air
+    /// bricks collapse to the shared empty brick as they are built, so an
+    /// empty region allocates nothing and a page costs only its occupied
+    /// bricks.
+    pub fn allocate_page_from_fn<F>(&mut self, f: F) -> [BrickId; PAGE_BRICKS]
+    where
+        F: Fn(u32, u32, u32) -> BlockId,
+    {
+        self.allocate_page_from_fn_with(f, self.compression.default_level())
+    }
+
+    /// [`Self::allocate_page_from_fn`] at an explicit compression level.
+    pub fn allocate_page_from_fn_with<F>(
+        &mut self,
+        f: F,
+        level: CompressLevel,
+    ) -> [BrickId; PAGE_BRICKS]
+    where
+        F: Fn(u32, u32, u32) -> BlockId,
+    {
+        let mut ids = [BrickId(0); PAGE_BRICKS];
+        let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+        for (brick, id) in ids.iter_mut().enumerate() {
+            let bx = brick % PAGE_BRICKS_PER_AXIS;
+            let by = (brick / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS;
+            let bz = brick / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS);
+            for (i, voxel) in voxels.iter_mut().enumerate() {
+                let x = bx * BRICK_SIZE + i % BRICK_SIZE;
+                let y = by * BRICK_SIZE + (i / BRICK_SIZE) % BRICK_SIZE;
+                let z = bz * BRICK_SIZE + i / (BRICK_SIZE * BRICK_SIZE);
+                *voxel = f(x as u32, y as u32, z as u32);
             }
+            *id = self.allocate_brick_with(&voxels, None, level);
         }
+        ids
+    }
 
-        let id = BrickId(self.headers.len() as u32);
-        self.headers.push(header);
-        id
+    /// Palette-encode a raw16 brick in place, keeping its id.
+    ///
+    /// Does nothing under [`CompressionPolicy::Never`], for bricks that are
+    /// already palette-encoded, or for bricks with too many distinct blocks
+    /// for a palette. Returns the brick's encoding afterwards, or `None` for
+    /// the empty or an unknown brick. The old raw16 entry is reused right
+    /// away, so callers that mirror the pools on the GPU should use
+    /// [`Self::compress_brick_deferred`] instead.
+    pub fn compress_brick(&mut self, id: BrickId) -> Option<BrickEncoding> {
+        let (encoding, retired) = self.compress_brick_deferred(id)?;
+        if let Some(payload) = retired {
+            self.free_payload(payload);
+        }
+        Some(encoding)
+    }
+
+    /// [`Self::compress_brick`] that keeps the replaced raw16 entry reserved.
+    ///
+    /// Also returns that entry when the brick was re-encoded; hand it to
+    /// [`Self::free_payload`] once nothing reads it any more, e.g. after the
+    /// GPU frames still in flight have finished. The brick's header and new
+    /// payload must be re-uploaded.
+    pub fn compress_brick_deferred(
+        &mut self,
+        id: BrickId,
+    ) -> Option<(BrickEncoding, Option<RetiredPayload>)> {
+        if id.0 == 0 {
+            return None;
+        }
+        let header = *self.header(id)?;
+        let encoding = BrickEncoding::from_u8(header.encoding)?;
+        if self.compression == CompressionPolicy::Never || encoding != BrickEncoding::Raw16 {
+            return Some((encoding, None));
+        }
+
+        let encoded = encode_brick(&self.decode_brick(id)?);
+        if encoded.encoding == BrickEncoding::Raw16 {
+            return Some((encoding, None));
+        }
+        let retired = RetiredPayload {
+            encoding,
+            data_index: header.data_index,
+        };
+        let data_index = self.allocate_payload(&encoded);
+        let header = &mut self.headers[id.0 as usize];
+        header.encoding = encoded.encoding as u8;
+        header.palette_len = encoded.palette_len;
+        header.data_index = data_index;
+        self.notify_encoding(id, encoded.encoding);
+        Some((encoded.encoding, Some(retired)))
+    }
+
+    /// Return a payload retired by [`Self::compress_brick_deferred`] to its
+    /// pool's free list.
+    pub fn free_payload(&mut self, payload: RetiredPayload) {
+        match payload.encoding {
+            BrickEncoding::Palette16 => self.free_palette16.push(payload.data_index),
+            BrickEncoding::Palette32 => self.free_palette32.push(payload.data_index),
+            BrickEncoding::Raw16 => self.free_raw16.push(payload.data_index),
+        }
     }
 
     /// Free a brick, returning its pool entry to the free list.
@@ -252,11 +473,16 @@ impl ClipmapVoxelStore {
         }
         let header = self.headers[index];
         if let Some(encoding) = BrickEncoding::from_u8(header.encoding) {
-            match encoding {
-                BrickEncoding::Palette16 => self.free_palette16.push(header.data_index),
-                BrickEncoding::Palette32 => self.free_palette32.push(header.data_index),
-                BrickEncoding::Raw16 => self.free_raw16.push(header.data_index),
-            }
+            self.free_payload(RetiredPayload {
+                encoding,
+                data_index: header.data_index,
+            });
+        }
+        if header.flags & BRICK_FLAG_NORMALS != 0 {
+            self.free_normals.push(header.normal_index);
+        }
+        if header.flags & BRICK_FLAG_LIGHT != 0 {
+            self.free_lights.push(header.light_index);
         }
         self.headers[index] = BrickHeader::default();
         self.free_headers.push(id.0);
@@ -301,6 +527,111 @@ impl ClipmapVoxelStore {
         &self.raw16_pool
     }
 
+    /// Get the normal pool as raw bytes.
+    pub fn normal_pool(&self) -> &[u8] {
+        &self.normal_pool
+    }
+
+    /// Get the light pool as raw bytes.
+    pub fn light_pool(&self) -> &[u8] {
+        &self.light_pool
+    }
+
+    /// Bytes held by the brick headers and data pools.
+    ///
+    /// All-air bricks share the reserved id 0, so they cost nothing beyond
+    /// its single header.
+    pub fn memory_bytes(&self) -> usize {
+        self.headers.len() * std::mem::size_of::<BrickHeader>()
+            + self.palette16_pool.len()
+            + self.palette32_pool.len()
+            + self.raw16_pool.len()
+            + self.normal_pool.len()
+            + self.light_pool.len()
+    }
+
+    /// Borrow the header and pool data in the byte layout the shaders read.
+    ///
+    /// Independent of any graphics API, so other backends can upload these
+    /// slices as-is.
+    pub fn gpu_pools(&self) -> GpuBrickPools<'_> {
+        GpuBrickPools {
+            headers: bytemuck::cast_slice(&self.headers),
+            palette16: &self.palette16_pool,
+            palette32: &self.palette32_pool,
+            raw16: &self.raw16_pool,
+            normals: &self.normal_pool,
+            light: &self.light_pool,
+        }
+    }
+
+    /// Get the stored per-voxel normals of a brick, if it has any.
+    pub fn brick_normals(&self, id: BrickId) -> Option<&[u8]> {
+        let header = self.header(id)?;
+        if header.flags & BRICK_FLAG_NORMALS == 0 {
+            return None;
+        }
+        self.pool_entry(&self.normal_pool, NORMAL_STRIDE, header.normal_index)
+    }
+
+    /// Attach per-voxel block light to an allocated brick, replacing any it
+    /// already carries. Ignored for the empty brick and unknown ids.
+    pub fn set_brick_light(&mut self, id: BrickId, light: &[u8; BRICK_VOXELS]) {
+        let Some(header) = self.headers.get_mut(id.0 as usize).filter(|_| id.0 != 0) else {
+            return;
+        };
+        if header.flags & BRICK_FLAG_LIGHT != 0 {
+            let offset = header.light_index as usize * LIGHT_STRIDE;
+            self.light_pool[offset..offset + LIGHT_STRIDE].copy_from_slice(light);
+            return;
+        }
+        header.light_index = Self::allocate_pool_entry(
+            LIGHT_STRIDE,
+            &mut self.light_pool,
+            &mut self.free_lights,
+            light,
+        );
+        header.flags |= BRICK_FLAG_LIGHT;
+    }
+
+    /// Get the stored per-voxel block light of a brick, if it has any.
+    pub fn brick_light(&self, id: BrickId) -> Option<&[u8]> {
+        let header = self.header(id)?;
+        if header.flags & BRICK_FLAG_LIGHT == 0 {
+            return None;
+        }
+        self.pool_entry(&self.light_pool, LIGHT_STRIDE, header.light_index)
+    }
+
+    fn allocate_payload(&mut self, encoded: &EncodedBrick) -> u32 {
+        match encoded.encoding {
+            BrickEncoding::Palette16 => Self::allocate_pool_entry(
+                PALETTE16_STRIDE,
+                &mut self.palette16_pool,
+                &mut self.free_palette16,
+                &encoded.data,
+            ),
+            BrickEncoding::Palette32 => Self::allocate_pool_entry(
+                PALETTE32_STRIDE,
+                &mut self.palette32_pool,
+                &mut self.free_palette32,
+                &encoded.data,
+            ),
+            BrickEncoding::Raw16 => Self::allocate_pool_entry(
+                RAW16_STRIDE,
+                &mut self.raw16_pool,
+                &mut self.free_raw16,
+                &encoded.data,
+            ),
+        }
+    }
+
+    fn notify_encoding(&mut self, id: BrickId, encoding: BrickEncoding) {
+        if let Some(hook) = self.on_encoding_change.as_mut() {
+            hook(id, encoding);
+        }
+    }
+
     fn allocate_pool_entry(
         stride: usize,
         pool: &mut Vec<u8>,
@@ -328,6 +659,89 @@ impl ClipmapVoxelStore {
     }
 }
 
+/// Brick headers and payload pools as raw GPU bytes.
+///
+/// Each header is 32 bytes; payloads are fixed-stride entries addressed by
+/// the header's `data_index`. [`Self::block_at`] resolves a voxel exactly as
+/// the ray march shader does.
+#[derive(Clone, Copy, Debug)]
+pub struct GpuBrickPools<'a> {
+    pub headers: &'a [u8],
+    pub palette16: &'a [u8],
+    pub palette32: &'a [u8],
+    pub raw16: &'a [u8],
+    pub normals: &'a [u8],
+    pub light: &'a [u8],
+}
+
+impl GpuBrickPools<'_> {
+    /// Block id of voxel `(x, y, z)` (each `0..BRICK_SIZE`) in a brick.
+    ///
+    /// Empty 2x2x2 cells are skipped via the header's occupancy mask. Returns
+    /// `None` for out-of-range coordinates, bricks or data.
+    pub fn block_at(&self, brick: BrickId, x: usize, y: usize, z: usize) -> Option<u16> {
+        if x >= BRICK_SIZE || y >= BRICK_SIZE || z >= BRICK_SIZE {
+            return None;
+        }
+        let base = brick.0 as usize * std::mem::size_of::<BrickHeader>();
+        let header = self
+            .headers
+            .get(base..base + std::mem::size_of::<BrickHeader>())?;
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([
+                header[offset],
+                header[offset + 1],
+                header[offset + 2],
+                header[offset + 3],
+            ])
+        };
+
+        let occ = u64::from(read_u32(8)) | (u64::from(read_u32(12)) << 32);
+        let cell = x / 2 + (y / 2) * 4 + (z / 2) * 16;
+        if occ & (1 << cell) == 0 {
+            return Some(0);
+        }
+
+        let palette_len = usize::from(header[0]);
+        let data_index = read_u32(4) as usize;
+        let voxel = x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE;
+        let read_u16 = |pool: &[u8], offset: usize| {
+            Some(u16::from_le_bytes([
+                *pool.get(offset)?,
+                *pool.get(offset + 1)?,
+            ]))
+        };
+        match BrickEncoding::from_u8(header[1])? {
+            BrickEncoding::Palette16 => {
+                let entry = data_index * PALETTE16_STRIDE;
+                let packed = *self.palette16.get(entry + 32 + voxel / 2)?;
+                let index = if voxel % 2 == 0 {
+                    packed & 0xF
+                } else {
+                    packed >> 4
+                };
+                read_u16(self.palette16, entry + usize::from(index) * 2)
+            }
+            BrickEncoding::Palette32 => {
+                let entry = data_index * PALETTE32_STRIDE;
+                let bit = voxel * 5;
+                let lo = usize::from(*self.palette32.get(entry + 64 + bit / 8)?);
+                let hi = if bit / 8 + 1 < 320 {
+                    usize::from(*self.palette32.get(entry + 64 + bit / 8 + 1)?)
+                } else {
+                    0
+                };
+                let mut index = ((lo | (hi << 8)) >> (bit % 8)) & 0x1F;
+                if index >= palette_len {
+                    index = 0;
+                }
+                read_u16(self.palette32, entry + index * 2)
+            }
+            BrickEncoding::Raw16 => read_u16(self.raw16, data_index * RAW16_STRIDE + voxel * 2),
+        }
+    }
+}
+
 /// Compute occupancy masks for a brick.
 pub fn compute_occupancy(voxels: &[BlockId; BRICK_VOXELS]) -> (u32, u32, u8, u8) {
     let mut occ_l0: u64 = 0;
@@ -603,6 +1017,94 @@ fn decode_raw16(data: &[u8]) -> [BlockId; BRICK_VOXELS] {
     out
 }
 
+/// Encode a unit normal into a single byte (4-bit octahedral per axis).
+///
+/// Never returns 0, which marks voxels without a normal; a zero vector is
+/// the only input that does.
+pub fn encode_octahedral_normal(normal: [f32; 3]) -> u8 {
+    let [x, y, z] = normal;
+    let l1 = x.abs() + y.abs() + z.abs();
+    if l1 <= f32::EPSILON {
+        return 0;
+    }
+    let (mut u, mut v) = (x / l1, y / l1);
+    if z < 0.0 {
+        (u, v) = ((1.0 - v.abs()) * u.signum(), (1.0 - u.abs()) * v.signum());
+    }
+    let qu = ((u * 0.5 + 0.5) * 15.0).round().clamp(0.0, 15.0) as u8;
+    let qv = ((v * 0.5 + 0.5) * 15.0).round().clamp(0.0, 15.0) as u8;
+    // All four corners of the octahedral square decode to -Z, so the
+    // (0, 0) corner can use the (15, 15) code and leave 0 free.
+    match qu | (qv << 4) {
+        0 => 0xFF,
+        code => code,
+    }
+}
+
+/// Decode a byte produced by [`encode_octahedral_normal`].
+///
+/// Returns `None` for 0, which marks voxels without a stored normal.
+pub fn decode_octahedral_normal(code: u8) -> Option<[f32; 3]> {
+    if code == 0 {
+        return None;
+    }
+    let mut u = f32::from(code & 0x0F) / 15.0 * 2.0 - 1.0;
+    let mut v = f32::from(code >> 4) / 15.0 * 2.0 - 1.0;
+    let z = 1.0 - u.abs() - v.abs();
+    if z < 0.0 {
+        (u, v) = ((1.0 - v.abs()) * u.signum(), (1.0 - u.abs()) * v.signum());
+    }
+    let len = (u * u + v * v + z * z).sqrt();
+    Some([u / len, v / len, z / len])
+}
+
+/// Estimate a surface normal for a solid voxel from its 3x3x3 neighbourhood.
+///
+/// `solid_at(dx, dy, dz)` reports whether the neighbour at that offset is solid,
+/// or `None` if it is unknown (e.g. outside the sampled region). Offsets whose
+/// mirror is unknown are skipped so region borders don't tilt the result.
+/// Returns an octahedral normal code, or 0 when the voxel is buried or the
+/// gradient is axis-aligned (the face normal is already exact there).
+pub fn estimate_surface_normal(solid_at: impl Fn(i32, i32, i32) -> Option<bool>) -> u8 {
+    let exposed = [
+        (1, 0, 0),
+        (-1, 0, 0),
+        (0, 1, 0),
+        (0, -1, 0),
+        (0, 0, 1),
+        (0, 0, -1),
+    ]
+    .iter()
+    .any(|&(dx, dy, dz)| solid_at(dx, dy, dz) == Some(false));
+    if !exposed {
+        return 0;
+    }
+
+    let mut gradient = [0i32; 3];
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy, dz) == (0, 0, 0) || solid_at(-dx, -dy, -dz).is_none() {
+                    continue;
+                }
+                if solid_at(dx, dy, dz) == Some(false) {
+                    gradient[0] += dx;
+                    gradient[1] += dy;
+                    gradient[2] += dz;
+                }
+            }
+        }
+    }
+
+    if gradient.iter().filter(|&&g| g != 0).count() < 2 {
+        return 0;
+    }
+
+    let [gx, gy, gz] = gradient.map(|g| g as f32);
+    let len = (gx * gx + gy * gy + gz * gz).sqrt();
+    encode_octahedral_normal([gx / len, gy / len, gz / len])
+}
+
 /// Downsample a 2x2x2 voxel block into one voxel.
 pub fn downsample_voxel(children: &[BlockId; 8]) -> BlockId {
     let mut counts: HashMap<BlockId, usize> = HashMap::new();
@@ -615,7 +1117,12 @@ pub fn downsample_voxel(children: &[BlockId; 8]) -> BlockId {
             *counts.entry(*v).or_insert(0) += 1;
             has_surface |= matches!(
                 *v,
-                BlockId::GRASS | BlockId::SNOW | BlockId::SAND | BlockId::LEAVES | BlockId::WATER
+                BlockId::GRASS
+                    | BlockId::SNOW
+                    | BlockId::SAND
+                    | BlockId::LEAVES
+                    | BlockId::WATER
+                    | BlockId::ICE
             );
         } else {
             has_air = true;
@@ -628,12 +1135,16 @@ pub fn downsample_voxel(children: &[BlockId; 8]) -> BlockId {
 
     // Preserve thin top surface shells on coarse LODs where air+surface blocks mix.
     if has_air && has_surface {
+        let ice_count = counts.get(&BlockId::ICE).copied().unwrap_or(0);
         let water_count = counts.get(&BlockId::WATER).copied().unwrap_or(0);
         let snow_count = counts.get(&BlockId::SNOW).copied().unwrap_or(0);
         let sand_count = counts.get(&BlockId::SAND).copied().unwrap_or(0);
         let grass_count = counts.get(&BlockId::GRASS).copied().unwrap_or(0);
         let leaves_count = counts.get(&BlockId::LEAVES).copied().unwrap_or(0);
 
+        if ice_count >= 2 {
+            return BlockId::ICE;
+        }
         if water_count >= 2 {
             return BlockId::WATER;
         }
@@ -699,6 +1210,177 @@ pub fn downsample_volume_2x(input: &[BlockId], size: usize) -> Vec<BlockId> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn gpu_pools_resolve_voxels_like_the_shader() {
+        let mut store = ClipmapVoxelStore::new();
+        let mut bricks = Vec::new();
+        // Sparse, palette32 and raw16 bricks.
+        for palette in [3u16, 24, 200] {
+            let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+            for (i, voxel) in voxels.iter_mut().enumerate() {
+                if i % 7 != 0 || palette > 3 {
+                    *voxel = BlockId((i as u16 * 31) % palette);
+                }
+            }
+            voxels[BRICK_VOXELS - 1] = BlockId(palette);
+            let id = store.allocate_brick(&voxels);
+            bricks.push((id, voxels));
+        }
+        assert_eq!(
+            bricks
+                .iter()
+                .map(|(id, _)| store.header(*id).unwrap().encoding)
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+
+        let pools = store.gpu_pools();
+        assert_eq!(pools.headers.len(), store.brick_count() * 32);
+        for (id, voxels) in &bricks {
+            for z in 0..BRICK_SIZE {
+                for y in 0..BRICK_SIZE {
+                    for x in 0..BRICK_SIZE {
+                        let expected = voxels[x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE];
+                        assert_eq!(pools.block_at(*id, x, y, z), Some(expected.0));
+                    }
+                }
+            }
+        }
+        // Brick 0 is the shared empty brick.
+        assert_eq!(pools.block_at(BrickId(0), 1, 2, 3), Some(0));
+        assert_eq!(pools.block_at(BrickId(99), 0, 0, 0), None);
+    }
+
+    #[test]
+    fn gpu_pools_reject_out_of_range_voxels() {
+        let mut store = ClipmapVoxelStore::new();
+        let id = store.allocate_brick(&[BlockId::STONE; BRICK_VOXELS]);
+        let pools = store.gpu_pools();
+        let last = BRICK_SIZE - 1;
+        assert_eq!(pools.block_at(id, last, last, last), Some(BlockId::STONE.0));
+        for (x, y, z) in [(BRICK_SIZE, 0, 0), (0, BRICK_SIZE, 0), (0, 0, usize::MAX)] {
+            assert_eq!(pools.block_at(id, x, y, z), None);
+            assert_eq!(pools.block_at(BrickId(0), x, y, z), None);
+        }
+    }
+
+    #[test]
+    fn compression_policy_controls_brick_encoding() {
+        use std::sync::{Arc, Mutex};
+
+        let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+        for (i, voxel) in voxels.iter_mut().enumerate().step_by(3) {
+            *voxel = if i % 2 == 0 {
+                BlockId::STONE
+            } else {
+                BlockId::DIRT
+            };
+        }
+        let encoding_of = |store: &ClipmapVoxelStore, id| {
+            BrickEncoding::from_u8(store.header(id).unwrap().encoding)
+        };
+
+        // Never: stays raw even when compression is requested.
+        let mut store = ClipmapVoxelStore::new();
+        store.set_compression_policy(CompressionPolicy::Never);
+        let id = store.allocate_brick(&voxels);
+        assert_eq!(store.compress_brick(id), Some(BrickEncoding::Raw16));
+        assert_eq!(encoding_of(&store, id), Some(BrickEncoding::Raw16));
+
+        // Always: palette-encoded on allocation.
+        let mut store = ClipmapVoxelStore::new();
+        assert_eq!(store.compression_policy(), CompressionPolicy::Always);
+        let id = store.allocate_brick(&voxels);
+        assert_eq!(encoding_of(&store, id), Some(BrickEncoding::Palette16));
+
+        // Lazy: raw until compressed, then palette-encoded under the same id.
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let mut store = ClipmapVoxelStore::new();
+        store.set_compression_policy(CompressionPolicy::Lazy);
+        store.on_encoding_change(move |id, encoding| sink.lock().unwrap().push((id, encoding)));
+        let id = store.allocate_brick(&voxels);
+        assert_eq!(encoding_of(&store, id), Some(BrickEncoding::Raw16));
+        assert_eq!(store.compress_brick(id), Some(BrickEncoding::Palette16));
+        assert_eq!(store.decode_brick(id).unwrap()[..], voxels[..]);
+        assert_eq!(store.compress_brick(BrickId(0)), None);
+        assert_eq!(
+            *events.lock().unwrap(),
+            [(id, BrickEncoding::Raw16), (id, BrickEncoding::Palette16)]
+        );
+
+        // The freed raw entry is reused by the next raw brick.
+        let raw_pool = store.raw16_pool().len();
+        store.allocate_brick(&voxels);
+        assert_eq!(store.raw16_pool().len(), raw_pool);
+    }
+
+    #[test]
+    fn page_from_fn_skips_air_bricks() {
+        // Stone below y = 12, air above: the lower half of the page.
+        let block = |_x: u32, y: u32, _z: u32| {
+            if y < 12 {
+                BlockId::STONE
+            } else {
+                BlockId::AIR
+            }
+        };
+        let mut store = ClipmapVoxelStore::new();
+        let ids = store.allocate_page_from_fn(block);
+
+        // Only the two lower brick layers allocate; one brick per voxel
+        // would need 32768 entries, a dense page 64.
+        let allocated = ids.iter().filter(|id| id.0 != 0).count();
+        assert_eq!(allocated, 2 * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS);
+        assert_eq!(store.brick_count(), allocated + 1);
+
+        for (brick, &id) in ids.iter().enumerate() {
+            let voxels = if id.0 == 0 {
+                [BlockId::AIR; BRICK_VOXELS]
+            } else {
+                store.decode_brick(id).unwrap()
+            };
+            let origin = [
+                brick % PAGE_BRICKS_PER_AXIS,
+                (brick / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS,
+                brick / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS),
+            ]
+            .map(|b| b * BRICK_SIZE);
+            for (i, &voxel) in voxels.iter().enumerate() {
+                let x = origin[0] + i % BRICK_SIZE;
+                let y = origin[1] + (i / BRICK_SIZE) % BRICK_SIZE;
+                let z = origin[2] + i / (BRICK_SIZE * BRICK_SIZE);
+                assert_eq!(voxel, block(x as u32, y as u32, z as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn fast_pages_are_larger_but_decode_the_same() {
+        // Repeating strata: every brick fits a small palette.
+        let block = |_x: u32, y: u32, _z: u32| match y % 3 {
+            0 => BlockId::STONE,
+            1 => BlockId::DIRT,
+            _ => BlockId::SAND,
+        };
+        let mut fast = ClipmapVoxelStore::new();
+        let fast_ids = fast.allocate_page_from_fn_with(block, CompressLevel::Fast);
+        let mut full = ClipmapVoxelStore::new();
+        let full_ids = full.allocate_page_from_fn_with(block, CompressLevel::Full);
+
+        assert!(fast.memory_bytes() > full.memory_bytes());
+        assert!(full.raw16_pool().is_empty());
+        for (&fast_id, &full_id) in fast_ids.iter().zip(&full_ids) {
+            assert_eq!(fast.decode_brick(fast_id), full.decode_brick(full_id));
+        }
+
+        // Fast bricks can still be packed later.
+        assert_eq!(
+            fast.compress_brick(fast_ids[0]),
+            Some(BrickEncoding::Palette16)
+        );
+    }
+
     #[test]
     fn brick_header_size() {
         assert_eq!(std::mem::size_of::<BrickHeader>(), 32);
@@ -744,6 +1426,74 @@ mod tests {
         assert_eq!(decoded[..], voxels[..]);
     }
 
+    #[test]
+    fn octahedral_normal_roundtrip() {
+        let inv_sqrt2 = std::f32::consts::FRAC_1_SQRT_2;
+        for normal in [
+            [inv_sqrt2, inv_sqrt2, 0.0],
+            [-inv_sqrt2, 0.0, inv_sqrt2],
+            [0.0, -inv_sqrt2, -inv_sqrt2],
+            [0.577, 0.577, -0.577],
+        ] {
+            let code = encode_octahedral_normal(normal);
+            let decoded = decode_octahedral_normal(code).expect("normal should be stored");
+            let dot: f32 = normal.iter().zip(decoded).map(|(a, b)| a * b).sum();
+            assert!(dot > 0.95, "{normal:?} decoded to {decoded:?}");
+        }
+        assert_eq!(decode_octahedral_normal(0), None);
+    }
+
+    #[test]
+    fn octahedral_normals_never_encode_to_the_sentinel() {
+        // Just off -Z toward -X/-Y lands on the (0, 0) corner.
+        let corner = [-0.01, -0.01, -1.0];
+        assert_eq!(encode_octahedral_normal(corner), 0xFF);
+        let decoded = decode_octahedral_normal(0xFF).unwrap();
+        assert!(decoded[2] < -0.99, "{decoded:?}");
+
+        for i in 0..32 {
+            for j in 0..=32 {
+                let phi = i as f32 / 32.0 * std::f32::consts::TAU;
+                let theta = j as f32 / 32.0 * std::f32::consts::PI;
+                let normal = [
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                ];
+                let code = encode_octahedral_normal(normal);
+                assert_ne!(code, 0, "{normal:?}");
+                let decoded = decode_octahedral_normal(code).unwrap();
+                let dot: f32 = normal.iter().zip(decoded).map(|(a, b)| a * b).sum();
+                assert!(dot > 0.9, "{normal:?} decoded to {decoded:?}");
+            }
+        }
+        assert_eq!(encode_octahedral_normal([0.0; 3]), 0);
+    }
+
+    #[test]
+    fn store_keeps_brick_normals() {
+        let mut store = ClipmapVoxelStore::new();
+        let voxels = [BlockId::STONE; BRICK_VOXELS];
+        let mut normals = [0u8; BRICK_VOXELS];
+        normals[7] = encode_octahedral_normal([0.6, 0.8, 0.0]);
+
+        let plain = store.allocate_brick(&voxels);
+        assert!(store.brick_normals(plain).is_none());
+
+        let id = store.allocate_brick_with_normals(&voxels, Some(&normals));
+        let header = *store.header(id).unwrap();
+        assert_ne!(header.flags & BRICK_FLAG_NORMALS, 0);
+        assert_eq!(store.brick_normals(id).unwrap(), &normals[..]);
+
+        store.free_brick(id);
+        let reused = store.allocate_brick_with_normals(&voxels, Some(&normals));
+        assert_eq!(
+            store.header(reused).unwrap().normal_index,
+            header.normal_index
+        );
+        assert_eq!(store.normal_pool().len(), NORMAL_STRIDE);
+    }
+
     #[test]
     fn occupancy_masks() {
         let mut voxels = [BlockId::AIR; BRICK_VOXELS];
@@ -836,6 +1586,22 @@ mod tests {
         assert_eq!(out, BlockId::WATER);
     }
 
+    #[test]
+    fn downsample_preserves_ice_sheet() {
+        let children = [
+            BlockId::ICE,
+            BlockId::ICE,
+            BlockId::ICE,
+            BlockId::ICE,
+            BlockId::AIR,
+            BlockId::AIR,
+            BlockId::AIR,
+            BlockId::AIR,
+        ];
+        let out = downsample_voxel(&children);
+        assert_eq!(out, BlockId::ICE);
+    }
+
     #[test]
     fn downsample_preserves_surface_leaves() {
         let children = [
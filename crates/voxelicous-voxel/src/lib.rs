@@ -1,11 +1,19 @@
 //! Clipmap voxel data structures for the Voxelicous engine.
 
 pub mod clipmap;
+pub mod light;
+pub mod mesh;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use clipmap::{
-    compute_occupancy, decode_brick, downsample_volume_2x, downsample_voxel, encode_brick,
-    BrickEncoding, BrickHeader, BrickId, ClipmapPage, ClipmapVoxelStore, EncodedBrick, LodLevel,
-    PageId, VoxelCoord, WorldCoord, BRICK_SIZE, BRICK_VOXELS, CLIPMAP_LOD_COUNT, CLIPMAP_PAGE_GRID,
-    PAGE_BRICKS, PAGE_BRICKS_PER_AXIS, PAGE_VOXELS_PER_AXIS, PALETTE16_STRIDE, PALETTE32_STRIDE,
-    RAW16_STRIDE,
+    compute_occupancy, decode_brick, decode_octahedral_normal, downsample_volume_2x,
+    downsample_voxel, encode_brick, encode_octahedral_normal, estimate_surface_normal,
+    BrickEncoding, BrickHeader, BrickId, ClipmapPage, ClipmapVoxelStore, CompressLevel,
+    CompressionPolicy, EncodedBrick, GpuBrickPools, LodLevel, PageId, RetiredPayload, VoxelCoord,
+    WorldCoord, BRICK_FLAG_LIGHT, BRICK_FLAG_NORMALS, BRICK_SIZE, BRICK_VOXELS, CLIPMAP_LOD_COUNT,
+    CLIPMAP_PAGE_GRID, LIGHT_STRIDE, NORMAL_STRIDE, PAGE_BRICKS, PAGE_BRICKS_PER_AXIS,
+    PAGE_VOXELS_PER_AXIS, PALETTE16_STRIDE, PALETTE32_STRIDE, RAW16_STRIDE,
 };
+pub use light::{emitted_light, passes_light, propagate_block_light, MAX_BLOCK_LIGHT};
+pub use mesh::{FaceDir, Mesh, MeshQuad, MeshWarning};
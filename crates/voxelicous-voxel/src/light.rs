@@ -0,0 +1,134 @@
+//! CPU block-light propagation.
+//!
+//! Emissive blocks seed a light level from their material's `emission`,
+//! which then floods outward through air and cutout blocks, losing one
+//! level per voxel step. Opaque voxels stop the flood but still record the
+//! brightest light reaching one of their faces, so a renderer can read the
+//! light of a surface straight from the voxel it hit.
+
+use std::collections::VecDeque;
+
+use voxelicous_core::types::{BlockId, MaterialRegistry};
+
+/// Brightest block-light level (a full-strength emitter).
+pub const MAX_BLOCK_LIGHT: u8 = 15;
+
+/// Light level a block emits, from its material's `emission` (0.0-1.0).
+pub fn emitted_light(materials: &MaterialRegistry, block: BlockId) -> u8 {
+    materials.get(block).map_or(0, |material| {
+        (material.emission.clamp(0.0, 1.0) * f32::from(MAX_BLOCK_LIGHT)).round() as u8
+    })
+}
+
+/// Whether light floods through a block (air and alpha-cutout blocks).
+pub fn passes_light(materials: &MaterialRegistry, block: BlockId) -> bool {
+    block.is_air()
+        || materials
+            .get(block)
+            .is_some_and(|material| material.cutout_alpha.is_some())
+}
+
+/// Flood-fill block light over a `dims`-sized region.
+///
+/// Returns one level per voxel, indexed `x + y * dims[0] + z * dims[0] *
+/// dims[1]`. Light does not cross the region's bounds. Regions without
+/// emitters return all zeros without flooding.
+pub fn propagate_block_light(
+    dims: [usize; 3],
+    materials: &MaterialRegistry,
+    block_at: impl Fn(usize, usize, usize) -> BlockId,
+) -> Vec<u8> {
+    let [sx, sy, sz] = dims;
+    let len = sx * sy * sz;
+    let mut light = vec![0u8; len];
+    let mut transparent = vec![false; len];
+    let mut queue = VecDeque::new();
+
+    for z in 0..sz {
+        for y in 0..sy {
+            for x in 0..sx {
+                let idx = x + y * sx + z * sx * sy;
+                let block = block_at(x, y, z);
+                transparent[idx] = passes_light(materials, block);
+                let emitted = emitted_light(materials, block);
+                if emitted > 0 {
+                    light[idx] = emitted;
+                    queue.push_back((x, y, z));
+                }
+            }
+        }
+    }
+
+    while let Some((x, y, z)) = queue.pop_front() {
+        let level = light[x + y * sx + z * sx * sy];
+        if level <= 1 {
+            continue;
+        }
+        let neighbours = [
+            (x.wrapping_sub(1), y, z),
+            (x + 1, y, z),
+            (x, y.wrapping_sub(1), z),
+            (x, y + 1, z),
+            (x, y, z.wrapping_sub(1)),
+            (x, y, z + 1),
+        ];
+        for (nx, ny, nz) in neighbours {
+            if nx >= sx || ny >= sy || nz >= sz {
+                continue;
+            }
+            let idx = nx + ny * sx + nz * sx * sy;
+            if light[idx] >= level - 1 {
+                continue;
+            }
+            light[idx] = level - 1;
+            if transparent[idx] {
+                queue.push_back((nx, ny, nz));
+            }
+        }
+    }
+
+    light
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voxelicous_core::types::Material;
+
+    const LAMP: BlockId = BlockId(200);
+
+    #[test]
+    fn light_fades_with_distance_and_stops_at_walls() {
+        let mut materials = MaterialRegistry::default();
+        materials.register(
+            LAMP,
+            Material {
+                emission: 1.0,
+                ..Material::STONE
+            },
+        );
+
+        // A lamp at x = 2 in a 16-long corridor, walled off at x = 8.
+        let dims = [16, 3, 3];
+        let light = propagate_block_light(dims, &materials, |x, _, _| match x {
+            2 => LAMP,
+            8 => BlockId::STONE,
+            _ => BlockId::AIR,
+        });
+        let at = |x: usize| light[x + dims[0] + dims[0] * dims[1]];
+
+        assert_eq!(at(2), MAX_BLOCK_LIGHT);
+        for x in 3..8 {
+            assert_eq!(at(x), at(x - 1) - 1, "x = {x}");
+        }
+        assert_eq!(at(0), MAX_BLOCK_LIGHT - 2);
+
+        // The wall's face is lit, but nothing gets past it.
+        assert_eq!(at(8), MAX_BLOCK_LIGHT - 6);
+        assert!((9..16).all(|x| at(x) == 0));
+
+        // Without emitters the region stays dark.
+        let dark = propagate_block_light(dims, &materials, |_, _, _| BlockId::AIR);
+        assert!(dark.iter().all(|&level| level == 0));
+    }
+}
@@ -0,0 +1,239 @@
+//! Voxel face meshes and a validator for mesher output.
+//!
+//! A [`Mesh`] is a list of axis-aligned quads, each covering a rectangle of
+//! whole voxel faces, as a greedy mesher emits them. [`Mesh::validate`]
+//! splits the quads back into unit faces to catch mesher regressions:
+//! empty quads, cracks, overlapping quads and faces left between two solid
+//! voxels.
+
+use std::collections::{HashMap, HashSet};
+
+use voxelicous_core::types::BlockId;
+
+/// Direction a quad faces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FaceDir {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl FaceDir {
+    /// Axis the face normal points along (0 = X, 1 = Y, 2 = Z).
+    #[must_use]
+    pub const fn axis(self) -> usize {
+        match self {
+            Self::PosX | Self::NegX => 0,
+            Self::PosY | Self::NegY => 1,
+            Self::PosZ | Self::NegZ => 2,
+        }
+    }
+
+    /// Returns `true` if the normal points toward +axis.
+    #[must_use]
+    pub const fn is_positive(self) -> bool {
+        matches!(self, Self::PosX | Self::PosY | Self::PosZ)
+    }
+
+    /// The two in-plane axes of a face, in ascending order.
+    #[must_use]
+    pub const fn plane_axes(self) -> [usize; 2] {
+        match self.axis() {
+            0 => [1, 2],
+            1 => [0, 2],
+            _ => [0, 1],
+        }
+    }
+}
+
+/// An axis-aligned rectangle of voxel faces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MeshQuad {
+    /// Voxel owning the quad's minimum-corner face.
+    pub voxel: [i32; 3],
+    /// Direction the quad faces, out of its voxels.
+    pub dir: FaceDir,
+    /// Width in voxel faces along each of [`FaceDir::plane_axes`].
+    pub size: [u32; 2],
+    /// Block the quad was emitted for.
+    pub block: BlockId,
+}
+
+/// Quads of a meshed voxel region.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Mesh {
+    pub quads: Vec<MeshQuad>,
+}
+
+/// A problem found by [`Mesh::validate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshWarning {
+    /// Quad `quad` has zero width or height.
+    DegenerateQuad { quad: usize },
+    /// Quads `quad` and `other` cover the same face in the same direction.
+    OverlappingQuads { quad: usize, other: usize },
+    /// Quads `quad` and `other` lie back to back, so both sides of the face
+    /// are solid and it should have been culled.
+    InternalFace { quad: usize, other: usize },
+    /// The unit edge from `start` along `axis` borders an odd number of
+    /// faces, leaving a crack or a stray face.
+    NonManifoldEdge {
+        start: [i32; 3],
+        axis: usize,
+        faces: usize,
+    },
+}
+
+/// A unit face: normal axis, plane coordinate and in-plane cell.
+type FaceCell = (usize, i32, i32, i32);
+
+impl Mesh {
+    /// Number of unit voxel faces covered by all quads.
+    #[must_use]
+    pub fn face_count(&self) -> u64 {
+        self.quads
+            .iter()
+            .map(|quad| u64::from(quad.size[0]) * u64::from(quad.size[1]))
+            .sum()
+    }
+
+    /// Check the mesh for mesher bugs; an empty result means it is clean.
+    ///
+    /// Edges bordered by an even number of faces are fine, so closed voxel
+    /// surfaces pass even where two blocks only touch along an edge. Open
+    /// meshes (e.g. a region cut at its bounds) report their border edges.
+    #[must_use]
+    pub fn validate(&self) -> Vec<MeshWarning> {
+        let mut warnings = Vec::new();
+        // Quad covering each unit face, per direction sign.
+        let mut faces: HashMap<(FaceCell, bool), usize> = HashMap::new();
+        let mut edges: HashMap<([i32; 3], usize), usize> = HashMap::new();
+        let mut reported = HashSet::new();
+
+        for (index, quad) in self.quads.iter().enumerate() {
+            if quad.size[0] == 0 || quad.size[1] == 0 {
+                warnings.push(MeshWarning::DegenerateQuad { quad: index });
+                continue;
+            }
+            let axis = quad.dir.axis();
+            let [u_axis, v_axis] = quad.dir.plane_axes();
+            let plane = quad.voxel[axis] + i32::from(quad.dir.is_positive());
+            for du in 0..quad.size[0] as i32 {
+                for dv in 0..quad.size[1] as i32 {
+                    let u = quad.voxel[u_axis] + du;
+                    let v = quad.voxel[v_axis] + dv;
+                    let cell = (axis, plane, u, v);
+                    let positive = quad.dir.is_positive();
+
+                    if let Some(&other) = faces.get(&(cell, positive)) {
+                        if reported.insert((other, index)) {
+                            warnings.push(MeshWarning::OverlappingQuads { quad: index, other });
+                        }
+                    } else {
+                        faces.insert((cell, positive), index);
+                    }
+                    if let Some(&other) = faces.get(&(cell, !positive)) {
+                        if reported.insert((other, index)) {
+                            warnings.push(MeshWarning::InternalFace { quad: index, other });
+                        }
+                    }
+
+                    let mut corner = [0; 3];
+                    corner[axis] = plane;
+                    corner[u_axis] = u;
+                    corner[v_axis] = v;
+                    let mut next_u = corner;
+                    next_u[u_axis] += 1;
+                    let mut next_v = corner;
+                    next_v[v_axis] += 1;
+                    for edge in [
+                        (corner, u_axis),
+                        (next_v, u_axis),
+                        (corner, v_axis),
+                        (next_u, v_axis),
+                    ] {
+                        *edges.entry(edge).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let mut open: Vec<_> = edges
+            .into_iter()
+            .filter(|&(_, faces)| faces % 2 == 1)
+            .collect();
+        open.sort_unstable();
+        warnings.extend(
+            open.into_iter()
+                .map(|((start, axis), faces)| MeshWarning::NonManifoldEdge { start, axis, faces }),
+        );
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The six quads of a solid `side`-wide cube at the origin.
+    fn cube(side: u32) -> Mesh {
+        let far = side as i32 - 1;
+        let quad = |voxel, dir| MeshQuad {
+            voxel,
+            dir,
+            size: [side, side],
+            block: BlockId::STONE,
+        };
+        Mesh {
+            quads: vec![
+                quad([far, 0, 0], FaceDir::PosX),
+                quad([0, 0, 0], FaceDir::NegX),
+                quad([0, far, 0], FaceDir::PosY),
+                quad([0, 0, 0], FaceDir::NegY),
+                quad([0, 0, far], FaceDir::PosZ),
+                quad([0, 0, 0], FaceDir::NegZ),
+            ],
+        }
+    }
+
+    #[test]
+    fn closed_cube_is_clean_and_broken_meshes_are_flagged() {
+        let mesh = cube(2);
+        assert_eq!(mesh.face_count(), 24);
+        assert_eq!(mesh.validate(), Vec::new());
+
+        // A copy of the +X face turned inward, as if the voxel beyond it
+        // were solid too.
+        let mut inward = mesh.clone();
+        inward.quads.push(MeshQuad {
+            voxel: [2, 0, 0],
+            dir: FaceDir::NegX,
+            ..mesh.quads[0]
+        });
+        let warnings = inward.validate();
+        assert!(warnings.contains(&MeshWarning::InternalFace { quad: 6, other: 0 }));
+        assert!(warnings
+            .iter()
+            .any(|warning| matches!(warning, MeshWarning::NonManifoldEdge { faces: 3, .. })));
+
+        // Duplicates, missing faces and empty quads.
+        let mut broken = mesh.clone();
+        broken.quads.push(mesh.quads[2]);
+        broken.quads.remove(5);
+        broken.quads.push(MeshQuad {
+            size: [0, 2],
+            ..mesh.quads[0]
+        });
+        let warnings = broken.validate();
+        assert!(warnings.contains(&MeshWarning::OverlappingQuads { quad: 5, other: 2 }));
+        assert!(warnings.contains(&MeshWarning::DegenerateQuad { quad: 6 }));
+        assert!(warnings.contains(&MeshWarning::NonManifoldEdge {
+            start: [0, 0, 0],
+            axis: 0,
+            faces: 1,
+        }));
+    }
+}
@@ -0,0 +1,137 @@
+//! Deterministic voxel fixtures for tests.
+//!
+//! Enabled by the `testing` feature. Every shape is a pure function of its
+//! kind and depth, so tests in any crate can build the same volume and
+//! compare results against known solid counts.
+
+use voxelicous_core::types::BlockId;
+
+/// Canonical fixture shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoldenKind {
+    /// Every voxel solid.
+    SolidCube,
+    /// One-voxel-thick shell around an empty interior.
+    HollowBox,
+    /// Ball inscribed in the volume.
+    Sphere,
+    /// Solid where `x + y + z` is even.
+    Checkerboard,
+    /// Column `x` is solid up to and including `y == x`.
+    Staircase,
+}
+
+impl GoldenKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [Self; 5] = [
+        Self::SolidCube,
+        Self::HollowBox,
+        Self::Sphere,
+        Self::Checkerboard,
+        Self::Staircase,
+    ];
+}
+
+/// Block of voxel `(x, y, z)` in a `size`-wide golden volume of `kind`.
+///
+/// Solid voxels are [`BlockId::STONE`]. Suitable as the generator for
+/// [`ClipmapVoxelStore::allocate_page_from_fn`](crate::ClipmapVoxelStore::allocate_page_from_fn)
+/// with `size == PAGE_VOXELS_PER_AXIS`.
+#[must_use]
+pub fn golden_block(kind: GoldenKind, size: u32, x: u32, y: u32, z: u32) -> BlockId {
+    let last = size.saturating_sub(1);
+    let solid = match kind {
+        GoldenKind::SolidCube => true,
+        GoldenKind::HollowBox => [x, y, z].iter().any(|&c| c == 0 || c == last),
+        GoldenKind::Sphere => {
+            // Compare voxel centres in doubled coordinates to stay integral.
+            let offset = |c: u32| i64::from(2 * c + 1) - i64::from(size);
+            let distance_sq = offset(x).pow(2) + offset(y).pow(2) + offset(z).pow(2);
+            distance_sq <= i64::from(size).pow(2)
+        }
+        GoldenKind::Checkerboard => (x + y + z) % 2 == 0,
+        GoldenKind::Staircase => y <= x,
+    };
+    if solid {
+        BlockId::STONE
+    } else {
+        BlockId::AIR
+    }
+}
+
+/// Dense `2^depth`-wide golden volume of `kind`, indexed
+/// `x + y * size + z * size * size`.
+#[must_use]
+pub fn golden_volume(kind: GoldenKind, depth: u32) -> Vec<BlockId> {
+    let size = 1u32 << depth;
+    let mut voxels = Vec::with_capacity((size as usize).pow(3));
+    for z in 0..size {
+        for y in 0..size {
+            for x in 0..size {
+                voxels.push(golden_block(kind, size, x, y, z));
+            }
+        }
+    }
+    voxels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ClipmapVoxelStore, BRICK_SIZE, BRICK_VOXELS, PAGE_BRICKS_PER_AXIS, PAGE_VOXELS_PER_AXIS,
+    };
+
+    fn solid_count(voxels: &[BlockId]) -> usize {
+        voxels.iter().filter(|block| !block.is_air()).count()
+    }
+
+    #[test]
+    fn golden_shapes_have_expected_solid_counts() {
+        for depth in [2, 4] {
+            let n = 1usize << depth;
+            let count = |kind| solid_count(&golden_volume(kind, depth));
+            assert_eq!(count(GoldenKind::SolidCube), n.pow(3));
+            assert_eq!(count(GoldenKind::HollowBox), n.pow(3) - (n - 2).pow(3));
+            assert_eq!(count(GoldenKind::Checkerboard), n.pow(3) / 2);
+            assert_eq!(count(GoldenKind::Staircase), n * n * (n + 1) / 2);
+
+            // Close to the inscribed ball's volume, and symmetric.
+            let sphere = golden_volume(GoldenKind::Sphere, depth);
+            let ideal = std::f64::consts::PI / 6.0 * (n as f64).powi(3);
+            let ratio = solid_count(&sphere) as f64 / ideal;
+            assert!((0.85..1.15).contains(&ratio), "depth {depth}: {ratio}");
+            assert_eq!(sphere.first(), Some(&BlockId::AIR));
+            assert_eq!(sphere.first(), sphere.last());
+        }
+    }
+
+    #[test]
+    fn golden_shapes_are_reproducible() {
+        for kind in GoldenKind::ALL {
+            assert_eq!(golden_volume(kind, 3), golden_volume(kind, 3), "{kind:?}");
+        }
+
+        // A page built from a golden shape decodes to the same voxels.
+        let size = PAGE_VOXELS_PER_AXIS as u32;
+        let volume = golden_volume(GoldenKind::Sphere, size.trailing_zeros());
+        let mut store = ClipmapVoxelStore::new();
+        let ids =
+            store.allocate_page_from_fn(|x, y, z| golden_block(GoldenKind::Sphere, size, x, y, z));
+        for (brick, &id) in ids.iter().enumerate() {
+            let bx = brick % PAGE_BRICKS_PER_AXIS;
+            let by = (brick / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS;
+            let bz = brick / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS);
+            let decoded = store
+                .decode_brick(id)
+                .unwrap_or([BlockId::AIR; BRICK_VOXELS]);
+            for (i, block) in decoded.iter().enumerate() {
+                let x = bx * BRICK_SIZE + i % BRICK_SIZE;
+                let y = by * BRICK_SIZE + (i / BRICK_SIZE) % BRICK_SIZE;
+                let z = bz * BRICK_SIZE + i / (BRICK_SIZE * BRICK_SIZE);
+                let n = PAGE_VOXELS_PER_AXIS;
+                assert_eq!(*block, volume[x + y * n + z * n * n]);
+            }
+        }
+    }
+}
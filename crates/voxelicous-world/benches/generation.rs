@@ -0,0 +1,45 @@
+//! Terrain generation and page build benchmarks.
+//!
+//! Run with: `cargo bench -p voxelicous-world --features bench`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use voxelicous_world::clipmap_streaming::build_page_bricks;
+use voxelicous_world::TerrainGenerator;
+
+const SEED: u64 = 42;
+/// Page row that straddles the default sea level (y = 60).
+const SURFACE_PAGE_Y: i64 = 1;
+
+fn bench_surface_at(c: &mut Criterion) {
+    let generator = TerrainGenerator::with_seed(SEED);
+
+    let mut group = c.benchmark_group("terrain");
+    group.throughput(Throughput::Elements(32 * 32));
+    group.bench_function("surface_at_32x32", |b| {
+        b.iter(|| {
+            for z in 0..32 {
+                for x in 0..32 {
+                    black_box(generator.surface_at(black_box(x), black_box(z)));
+                }
+            }
+        });
+    });
+    group.finish();
+}
+
+fn bench_build_page(c: &mut Criterion) {
+    let generator = TerrainGenerator::with_seed(SEED);
+
+    let mut group = c.benchmark_group("build_page_voxels");
+    group.sample_size(20);
+    for (name, voxel_size, page_y) in [("lod0", 1, SURFACE_PAGE_Y), ("lod2", 4, 0), ("lod4", 16, 0)]
+    {
+        group.bench_function(name, |b| {
+            b.iter(|| build_page_bricks(&generator, black_box((3, page_y, -2)), voxel_size));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_surface_at, bench_build_page);
+criterion_main!(benches);
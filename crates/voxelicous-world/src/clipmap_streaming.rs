@@ -2,19 +2,32 @@
 
 use std::{
     collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    io::Write,
+    path::{Path, PathBuf},
     sync::mpsc::{self, Receiver, Sender, TryRecvError},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use glam::Vec3;
-use voxelicous_core::types::BlockId;
+use image::{GrayImage, Luma};
+use serde::{Deserialize, Serialize};
+use voxelicous_core::math::{Aabb, Frustum};
+use voxelicous_core::types::{BlockId, MaterialRegistry};
 use voxelicous_voxel::{
-    downsample_voxel, BrickEncoding, BrickId, ClipmapVoxelStore, WorldCoord, BRICK_SIZE,
-    BRICK_VOXELS, CLIPMAP_LOD_COUNT, CLIPMAP_PAGE_GRID, PAGE_BRICKS, PAGE_BRICKS_PER_AXIS,
-    PAGE_VOXELS_PER_AXIS,
+    downsample_voxel, emitted_light, estimate_surface_normal, propagate_block_light, BrickEncoding,
+    BrickId, ClipmapVoxelStore, CompressLevel, RetiredPayload, WorldCoord, BRICK_FLAG_LIGHT,
+    BRICK_FLAG_NORMALS, BRICK_SIZE, BRICK_VOXELS, CLIPMAP_LOD_COUNT, CLIPMAP_PAGE_GRID,
+    MAX_BLOCK_LIGHT, PAGE_BRICKS, PAGE_BRICKS_PER_AXIS, PAGE_VOXELS_PER_AXIS,
 };
 
-use crate::generation::{SurfaceSample, TerrainGenerator};
+use crate::diagnostics::DiagnosticSnapshot;
+#[cfg(feature = "profiling")]
+use crate::diagnostics::ProfilerSummary;
+use crate::error::{Result, WorldError};
+use crate::generation::{SurfaceSample, TerrainConfig, TerrainGenerator};
+use crate::page_cache::{self, CacheCodec};
+use crate::snapshot::WorldSnapshot;
 
 /// Dirty ranges to upload to GPU after a clipmap update.
 #[derive(Debug, Default)]
@@ -24,6 +37,40 @@ pub struct ClipmapDirtyState {
     pub dirty_palette16_entries: Vec<u32>,
     pub dirty_palette32_entries: Vec<u32>,
     pub dirty_raw16_entries: Vec<u32>,
+    pub dirty_normal_entries: Vec<u32>,
+    pub dirty_light_entries: Vec<u32>,
+    /// Coordinates of pages unloaded since the last take, per LOD, in
+    /// unload order.
+    pub unloaded_pages: Vec<Vec<(i64, i64, i64)>>,
+}
+
+/// Streaming progress of one clipmap LOD, for debug overlays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LodStatus {
+    pub lod: usize,
+    /// The LOD has completed at least one full build.
+    pub ready: bool,
+    /// The LOD has at least one loaded page.
+    pub renderable: bool,
+    pub loaded_pages: usize,
+    /// Pages queued but not yet handed to a worker.
+    pub pending: usize,
+    /// Pages being built by workers.
+    pub inflight: usize,
+}
+
+/// How pending page builds are shared between LODs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageBuildFairness {
+    /// Drain finer LODs before starting on coarser ones.
+    #[default]
+    FinestFirst,
+    /// Weighted round-robin between LODs with pending pages.
+    ///
+    /// While several LODs have work, each gets builds in proportion to its
+    /// weight (weights below `1` count as `1`), so coarse LODs keep filling
+    /// in while LOD0 is busy, e.g. during fast movement.
+    Weighted([u32; CLIPMAP_LOD_COUNT]),
 }
 
 #[derive(Clone, Debug)]
@@ -34,8 +81,14 @@ struct ClipmapLodState {
     page_coords: Vec<[i32; 4]>,
     page_loaded: Vec<bool>,
     loaded_pages: usize,
+    /// Loaded pages without any allocated brick.
+    empty_pages: usize,
     dirty_pages: Vec<usize>,
+    unloaded_pages: Vec<(i64, i64, i64)>,
     pending_pages: VecDeque<(i64, i64, i64)>,
+    /// Slots left outside a shrunk visible grid: eviction frame, page index
+    /// and the page coordinate the slot held at the time.
+    retired_pages: Vec<(u64, usize, [i32; 4])>,
     generation: u64,
     inflight_pages: usize,
     ready: bool,
@@ -51,8 +104,11 @@ impl ClipmapLodState {
             page_coords: vec![invalid_page_coord(); page_count],
             page_loaded: vec![false; page_count],
             loaded_pages: 0,
+            empty_pages: 0,
             dirty_pages: Vec::new(),
+            unloaded_pages: Vec::new(),
             pending_pages: VecDeque::new(),
+            retired_pages: Vec::new(),
             generation: 0,
             inflight_pages: 0,
             ready: false,
@@ -64,26 +120,107 @@ impl ClipmapLodState {
 struct BuiltPage {
     coord: (i64, i64, i64),
     bricks: Vec<[BlockId; BRICK_VOXELS]>,
+    /// Per-brick octahedral normals (empty when the page stores none).
+    normals: Vec<Option<[u8; BRICK_VOXELS]>>,
     occ: u64,
 }
 
+impl BuiltPage {
+    /// All-air page. It carries no brick payload and allocates nothing when
+    /// applied; every brick slot points at the shared empty brick.
+    const fn empty(coord: (i64, i64, i64)) -> Self {
+        Self {
+            coord,
+            bricks: Vec::new(),
+            normals: Vec::new(),
+            occ: 0,
+        }
+    }
+
+    /// Page holding `bricks`, as loaded from the page cache.
+    fn from_bricks(
+        coord: (i64, i64, i64),
+        bricks: Vec<[BlockId; BRICK_VOXELS]>,
+        voxel_size: i64,
+    ) -> Self {
+        if all_air(&bricks) {
+            return Self::empty(coord);
+        }
+        let occ = bricks
+            .iter()
+            .enumerate()
+            .filter(|(_, voxels)| voxels.iter().any(|v| v.is_solid()))
+            .fold(0, |occ, (brick_idx, _)| occ | (1u64 << brick_idx));
+        // Matches the builders: only coarse pages store normals.
+        let normals = if voxel_size > 1 {
+            compute_page_normals(&bricks)
+        } else {
+            Vec::new()
+        };
+        Self {
+            coord,
+            bricks,
+            normals,
+            occ,
+        }
+    }
+
+    /// Builders return [`Self::empty`] for all-air pages, so this is a
+    /// length check rather than a voxel scan.
+    fn is_empty(&self) -> bool {
+        self.bricks.is_empty()
+    }
+}
+
+fn all_air(bricks: &[[BlockId; BRICK_VOXELS]]) -> bool {
+    bricks
+        .iter()
+        .all(|voxels| voxels.iter().all(|v| v.is_air()))
+}
+
 #[derive(Clone, Debug)]
 struct PageBuildResult {
     lod: usize,
     generation: u64,
+    /// Estimated cost charged against the in-flight cost budget.
+    cost: u32,
     page: BuiltPage,
 }
 
+/// Store memory released a few frames late, once the GPU stops reading it.
+#[derive(Clone, Copy, Debug)]
+enum DeferredFree {
+    Brick(BrickId),
+    Payload(RetiredPayload),
+}
+
+/// Where page builds run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StreamingMode {
+    /// Pages build on the rayon pool and land over later updates.
+    #[default]
+    Async,
+    /// Pages build on the calling thread during the update that starts
+    /// them, so results do not depend on thread timing. For tools and tests.
+    Sync,
+}
+
 /// Clipmap streaming controller (toroidal page tables + brick pools).
 pub struct ClipmapStreamingController {
-    generator: TerrainGenerator,
+    generator: Arc<TerrainGenerator>,
     edits: HashMap<WorldCoord, BlockId>,
     edit_snapshot: Arc<HashMap<WorldCoord, BlockId>>,
     store: ClipmapVoxelStore,
     lods: Vec<ClipmapLodState>,
-    visible_page_grid: usize,
+    visible_page_grid_xz: usize,
+    visible_page_grid_y: usize,
+    initial_page_radius: usize,
     active_lod_count: usize,
     camera_voxel: WorldCoord,
+    camera_velocity: Vec3,
+    prefetch_seconds: f32,
+    teleport_budget: Duration,
+    visible_grid_hysteresis_frames: u64,
     frame_counter: u64,
     coarse_lod_cursor: usize,
     bootstrap_lod: usize,
@@ -91,19 +228,48 @@ pub struct ClipmapStreamingController {
     dirty_palette16_entries: Vec<u32>,
     dirty_palette32_entries: Vec<u32>,
     dirty_raw16_entries: Vec<u32>,
+    dirty_normal_entries: Vec<u32>,
+    dirty_light_entries: Vec<u32>,
+    /// Loaded finest-LOD pages holding an emissive block.
+    emitter_pages: HashSet<(i64, i64, i64)>,
     page_build_tx: Sender<PageBuildResult>,
     page_build_rx: Receiver<PageBuildResult>,
     inflight_jobs: usize,
-    pending_brick_frees: VecDeque<(u64, BrickId)>,
+    max_inflight_jobs: Option<usize>,
+    /// Summed [`TerrainGenerator::estimate_page_cost`] of in-flight builds.
+    inflight_cost: u32,
+    max_inflight_cost: Option<u32>,
+    page_build_fairness: PageBuildFairness,
+    /// Smooth weighted round-robin credit per LOD.
+    fairness_credit: [i64; CLIPMAP_LOD_COUNT],
+    fast_compression_lods: usize,
+    materials: MaterialRegistry,
+    pending_brick_frees: VecDeque<(u64, DeferredFree)>,
+    cache_codec: CacheCodec,
+    page_cache_dir: Option<Arc<Path>>,
+    /// [`TerrainGenerator::cache_key`] of the generator, while caching.
+    page_cache_key: u64,
+    streaming_mode: StreamingMode,
 }
 
 impl ClipmapStreamingController {
+    /// Minimap value for a page column with no loaded pages.
+    pub const MINIMAP_UNLOADED: u8 = 0;
+    /// Minimap value for a loaded page column containing only air.
+    pub const MINIMAP_EMPTY: u8 = 96;
+    /// Minimap value for a page column with at least one solid page.
+    pub const MINIMAP_OCCUPIED: u8 = 255;
+
     const PAGE_APPLY_BUDGET_STEADY: usize = 2;
     const PAGE_APPLY_BUDGET_BOOTSTRAP: usize = 12;
-    const MAX_INFLIGHT_PAGE_JOBS: usize = 16;
+    const MIN_INFLIGHT_PAGE_JOBS: usize = 4;
+    const VISIBLE_PAGES_PER_INFLIGHT_JOB: usize = 4;
     const PENDING_PAGE_BACKLOG_FRAMES: usize = 2;
     const BRICK_FREE_DELAY_FRAMES: u64 = 3;
     const SYNC_EDIT_LODS: usize = 2;
+    const DEFAULT_PREFETCH_SECONDS: f32 = 0.5;
+    const DEFAULT_TELEPORT_BUDGET: Duration = Duration::from_millis(50);
+    const DEFAULT_VISIBLE_GRID_HYSTERESIS_FRAMES: u64 = 90;
 
     /// Create a new clipmap streaming controller.
     pub fn new(generator: TerrainGenerator) -> Self {
@@ -112,14 +278,20 @@ impl ClipmapStreamingController {
             .map(|_| ClipmapLodState::new())
             .collect();
         Self {
-            generator,
+            generator: Arc::new(generator),
             edits: HashMap::new(),
             edit_snapshot: Arc::new(HashMap::new()),
             store: ClipmapVoxelStore::new(),
             lods,
-            visible_page_grid: CLIPMAP_PAGE_GRID,
+            visible_page_grid_xz: CLIPMAP_PAGE_GRID,
+            visible_page_grid_y: CLIPMAP_PAGE_GRID,
+            initial_page_radius: 1,
             active_lod_count: 1,
             camera_voxel: WorldCoord { x: 0, y: 0, z: 0 },
+            camera_velocity: Vec3::ZERO,
+            prefetch_seconds: Self::DEFAULT_PREFETCH_SECONDS,
+            teleport_budget: Self::DEFAULT_TELEPORT_BUDGET,
+            visible_grid_hysteresis_frames: Self::DEFAULT_VISIBLE_GRID_HYSTERESIS_FRAMES,
             frame_counter: 0,
             coarse_lod_cursor: 0,
             bootstrap_lod: 0,
@@ -127,10 +299,118 @@ impl ClipmapStreamingController {
             dirty_palette16_entries: Vec::new(),
             dirty_palette32_entries: Vec::new(),
             dirty_raw16_entries: Vec::new(),
+            dirty_normal_entries: Vec::new(),
+            dirty_light_entries: Vec::new(),
+            emitter_pages: HashSet::new(),
             page_build_tx,
             page_build_rx,
             inflight_jobs: 0,
+            max_inflight_jobs: None,
+            inflight_cost: 0,
+            max_inflight_cost: None,
+            page_build_fairness: PageBuildFairness::FinestFirst,
+            fairness_credit: [0; CLIPMAP_LOD_COUNT],
+            fast_compression_lods: 0,
+            materials: MaterialRegistry::default(),
             pending_brick_frees: VecDeque::new(),
+            cache_codec: CacheCodec::default(),
+            page_cache_dir: None,
+            page_cache_key: 0,
+            streaming_mode: StreamingMode::default(),
+        }
+    }
+
+    /// Set the radius, in LOD0 pages around the camera page, that
+    /// [`Self::generate_initial_pages`] builds up front.
+    ///
+    /// The default of `1` covers a 3x3x3 block of pages.
+    #[must_use]
+    pub fn with_initial_page_radius(mut self, radius: usize) -> Self {
+        self.initial_page_radius = radius;
+        self
+    }
+
+    /// Radius of the initial page region in LOD0 pages.
+    pub fn initial_page_radius(&self) -> usize {
+        self.initial_page_radius
+    }
+
+    /// Terrain generator backing this clipmap.
+    pub fn generator(&self) -> &TerrainGenerator {
+        &self.generator
+    }
+
+    /// Regenerate the world from a new terrain configuration.
+    ///
+    /// Swaps the generator (keeping any custom height function), drops
+    /// in-flight page builds and requeues every active LOD. Runtime edits are
+    /// kept and still override the new terrain. The LOD0 pages within
+    /// [`Self::initial_page_radius`] of the last camera position are rebuilt
+    /// before returning, as in [`Self::generate_initial_pages`]; the rest
+    /// stream back in through [`Self::update`]. Pages cached under the old
+    /// configuration are no longer read. Snapshots taken earlier keep the old
+    /// generator. Returns the number of pages built.
+    pub fn set_terrain_config(&mut self, config: TerrainConfig) -> usize {
+        self.generator = Arc::new(self.generator.with_config(config));
+        if self.page_cache_dir.is_some() {
+            self.page_cache_key = self.generator.cache_key();
+        }
+        if self.needs_seeding() {
+            return 0;
+        }
+
+        for lod in 0..self.active_lod_limit() {
+            let Some(origin) = self.lods[lod].origin else {
+                continue;
+            };
+            let voxel_size = self.lod_voxel_size(lod);
+            let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+            self.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
+        }
+
+        let camera = self.camera_voxel;
+        self.generate_initial_pages(Vec3::new(
+            camera.x as f32 + 0.5,
+            camera.y as f32 + 0.5,
+            camera.z as f32 + 0.5,
+        ))
+    }
+
+    /// Take an immutable, thread-safe view of the world for block queries.
+    ///
+    /// See [`WorldSnapshot`] for the staleness guarantees.
+    #[must_use]
+    pub fn read_snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot::new(Arc::clone(&self.generator), Arc::clone(&self.edit_snapshot))
+    }
+
+    /// Capture seed, config, camera and streaming state for a bug report.
+    pub fn diagnostic_snapshot(&self) -> DiagnosticSnapshot {
+        let lods = self.lod_status();
+        #[cfg(feature = "profiling")]
+        let profiler = {
+            let snapshot = voxelicous_profiler::snapshot();
+            Some(ProfilerSummary {
+                frame_number: snapshot.frame_number,
+                fps: snapshot.fps,
+                frame_time_ms: snapshot.frame_time_ms,
+            })
+        };
+        #[cfg(not(feature = "profiling"))]
+        let profiler = None;
+        DiagnosticSnapshot {
+            seed: self.generator.config().seed,
+            terrain: self.generator.config().clone(),
+            camera_voxel: [
+                self.camera_voxel.x,
+                self.camera_voxel.y,
+                self.camera_voxel.z,
+            ],
+            camera_velocity: self.camera_velocity.to_array(),
+            loaded_pages: lods.iter().map(|status| status.loaded_pages).sum(),
+            edit_count: self.edits.len(),
+            lods,
+            profiler,
         }
     }
 
@@ -145,26 +425,88 @@ impl ClipmapStreamingController {
 
     /// Set a block id at world voxel coordinates.
     ///
-    /// Returns `true` when the effective block value changed.
+    /// Returns `true` when the effective block value changed. Bedrock at or
+    /// below the world floor cannot be edited.
     pub fn set_block_at_world(&mut self, x: i64, y: i64, z: i64, block: BlockId) -> bool {
-        let coord = WorldCoord { x, y, z };
-        let previous = self.block_at_world(x, y, z);
-        if previous == block {
-            return false;
-        }
+        !self
+            .apply_edit_batch([(WorldCoord { x, y, z }, block)])
+            .is_empty()
+    }
 
-        // Store only differences from procedural terrain.
-        let generated = self.generator.block_at_world(x, y, z);
-        if block == generated {
-            self.edits.remove(&coord);
-        } else {
-            self.edits.insert(coord, block);
-        }
-        self.edit_snapshot = Arc::new(self.edits.clone());
+    /// Fill every voxel within `radius` of `center` with `block`.
+    ///
+    /// Pages touched by the brush are rebuilt once for the whole stroke
+    /// rather than once per voxel. Returns the number of voxels that changed.
+    pub fn edit_sphere(&mut self, center: WorldCoord, radius: i64, block: BlockId) -> usize {
+        self.apply_edit_batch(sphere_voxels(center, radius).map(|coord| (coord, block)))
+            .len()
+    }
 
-        self.apply_edit_immediate(coord);
-        self.enqueue_pages_affected_by_edit(coord);
-        true
+    /// Fill the inclusive box between `min` and `max` with `block`.
+    ///
+    /// Like [`Self::edit_sphere`], affected pages are rebuilt once per call.
+    /// Returns the number of voxels that changed.
+    pub fn edit_box(&mut self, min: WorldCoord, max: WorldCoord, block: BlockId) -> usize {
+        self.apply_edit_batch(box_voxels(min, max).map(|coord| (coord, block)))
+            .len()
+    }
+
+    /// Blow a hole: set to air every solid voxel within `radius` of `center`
+    /// whose distance passes `falloff`.
+    ///
+    /// `falloff` gets each voxel's distance from `center` and decides whether
+    /// it is destroyed, so callers can shape ragged or hollow blasts. Like
+    /// the brushes, affected pages are rebuilt once per call. Bedrock
+    /// survives. Returns the number of voxels destroyed.
+    pub fn explode(
+        &mut self,
+        center: WorldCoord,
+        radius: f64,
+        falloff: impl Fn(f64) -> bool,
+    ) -> usize {
+        let radius = radius.max(0.0);
+        let reach = radius.ceil() as i64;
+        let min = WorldCoord {
+            x: center.x - reach,
+            y: center.y - reach,
+            z: center.z - reach,
+        };
+        let max = WorldCoord {
+            x: center.x + reach,
+            y: center.y + reach,
+            z: center.z + reach,
+        };
+        let targets: Vec<_> = box_voxels(min, max)
+            .filter(|coord| {
+                let (dx, dy, dz) = (coord.x - center.x, coord.y - center.y, coord.z - center.z);
+                let distance = ((dx * dx + dy * dy + dz * dz) as f64).sqrt();
+                distance <= radius
+                    && falloff(distance)
+                    && self.block_at_world(coord.x, coord.y, coord.z).is_solid()
+            })
+            .collect();
+        self.apply_edit_batch(targets.into_iter().map(|coord| (coord, BlockId::AIR)))
+            .len()
+    }
+
+    /// Record `blocks` as one edit and rebuild the pages it touches once.
+    ///
+    /// Returns every voxel that changed together with the block it held
+    /// before, which is what [`EditHistory`](crate::EditHistory) restores.
+    pub(crate) fn apply_edit_batch(
+        &mut self,
+        blocks: impl IntoIterator<Item = (WorldCoord, BlockId)>,
+    ) -> Vec<(WorldCoord, BlockId)> {
+        let replaced: Vec<_> = blocks
+            .into_iter()
+            .filter_map(|(coord, block)| {
+                self.record_edit(coord, block)
+                    .map(|previous| (coord, previous))
+            })
+            .collect();
+        let coords: Vec<_> = replaced.iter().map(|&(coord, _)| coord).collect();
+        self.commit_edits(&coords);
+        replaced
     }
 
     /// Destroy (set to air) the block at world voxel coordinates.
@@ -178,39 +520,23 @@ impl ClipmapStreamingController {
     }
 
     /// Update the clipmap around the given camera position (world units).
+    ///
+    /// Works in either [`StreamingMode`]; in [`StreamingMode::Sync`] the
+    /// page builds it starts run before it returns.
     #[cfg_attr(
         feature = "profiling-tracy",
         tracing::instrument(level = "trace", skip_all)
     )]
     pub fn update(&mut self, camera_pos: Vec3) {
         self.process_deferred_brick_frees();
+        self.evict_retired_pages();
 
-        let camera_voxel = WorldCoord {
-            x: camera_pos.x.floor() as i64,
-            y: camera_pos.y.floor() as i64,
-            z: camera_pos.z.floor() as i64,
-        };
+        let camera_voxel = camera_voxel_of(camera_pos);
         self.camera_voxel = camera_voxel;
         let active_lod_count = self.active_lod_limit();
         // First update: seed origins and enqueue LOD0 only to avoid long stalls.
-        if self.lods[..active_lod_count]
-            .iter()
-            .any(|lod| lod.origin.is_none())
-        {
-            for lod in 0..active_lod_count {
-                let voxel_size = self.lod_voxel_size(lod);
-                let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-                let coverage = self.lod_coverage(lod);
-                let origin = aligned_origin(camera_voxel, coverage, page_size);
-                self.lods[lod].origin = Some(origin);
-            }
-
-            let voxel_size0 = self.lod_voxel_size(0);
-            let page_size0 = PAGE_VOXELS_PER_AXIS as i64 * voxel_size0;
-            let coverage0 = self.lod_coverage(0);
-            let origin0 = aligned_origin(camera_voxel, coverage0, page_size0);
-            self.enqueue_full_rebuild(0, origin0, voxel_size0, page_size0);
-            self.bootstrap_lod = 0;
+        if self.needs_seeding() {
+            self.seed_origins(camera_voxel);
         } else if self.bootstrap_lod < active_lod_count {
             // While bootstrapping, keep LOD0 updated and enqueue next LOD once current is filled.
             self.update_lod(0, camera_voxel, false);
@@ -227,8 +553,7 @@ impl ClipmapStreamingController {
                 if self.bootstrap_lod < active_lod_count {
                     let voxel_size = self.lod_voxel_size(self.bootstrap_lod);
                     let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-                    let coverage = self.lod_coverage(self.bootstrap_lod);
-                    let origin = aligned_origin(camera_voxel, coverage, page_size);
+                    let origin = self.lod_origin_around(self.bootstrap_lod, camera_voxel);
                     self.lods[self.bootstrap_lod].origin = Some(origin);
                     self.enqueue_full_rebuild(self.bootstrap_lod, origin, voxel_size, page_size);
                 }
@@ -256,6 +581,241 @@ impl ClipmapStreamingController {
         self.frame_counter = self.frame_counter.wrapping_add(1);
     }
 
+    /// [`Self::update`] for an asynchronously streamed world.
+    ///
+    /// Never blocks on page builds; returns [`WorldError::AsyncOnly`] if the
+    /// world streams in [`StreamingMode::Sync`].
+    pub fn update_async(&mut self, camera_pos: Vec3) -> Result<()> {
+        if self.streaming_mode != StreamingMode::Async {
+            return Err(WorldError::AsyncOnly);
+        }
+        self.update(camera_pos);
+        Ok(())
+    }
+
+    /// Stream until every active LOD around `camera_pos` is fully built.
+    ///
+    /// Only available in [`StreamingMode::Sync`], where the builds run on
+    /// this thread; returns [`WorldError::SyncOnly`] otherwise. Builds still
+    /// in flight from an earlier async update are waited for first. Large
+    /// visible grids take a while to fill; returns
+    /// [`WorldError::SyncStalled`] if the LODs still have not settled after
+    /// every visible page could have been built twice.
+    pub fn update_sync(&mut self, camera_pos: Vec3) -> Result<()> {
+        if self.streaming_mode != StreamingMode::Sync {
+            return Err(WorldError::SyncOnly);
+        }
+        while self.inflight_jobs > 0 {
+            let Ok(result) = self.page_build_rx.recv() else {
+                break;
+            };
+            self.land_page_build(result);
+        }
+
+        let update_limit = self.sync_update_limit();
+        for _ in 0..update_limit {
+            self.update(camera_pos);
+            let active_lod_count = self.active_lod_limit();
+            let settled = self.bootstrap_lod >= active_lod_count
+                && self.inflight_jobs == 0
+                && self.lods[..active_lod_count]
+                    .iter()
+                    .all(|lod| lod.ready && lod.pending_pages.is_empty());
+            if settled {
+                self.debug_assert_settled();
+                return Ok(());
+            }
+        }
+        Err(WorldError::SyncStalled {
+            updates: update_limit,
+        })
+    }
+
+    /// Updates [`Self::update_sync`] runs before reporting a stall.
+    ///
+    /// Each unsettled sync update lands at least one page, so twice the
+    /// visible pages of every active LOD, plus one update per LOD to
+    /// bootstrap it, is ample.
+    fn sync_update_limit(&self) -> usize {
+        let pages =
+            self.visible_page_grid_xz * self.visible_page_grid_xz * self.visible_page_grid_y;
+        (2 * pages + 1) * self.active_lod_limit()
+    }
+
+    /// Where page builds run.
+    pub fn streaming_mode(&self) -> StreamingMode {
+        self.streaming_mode
+    }
+
+    /// Set where page builds run; builds already in flight still land.
+    pub fn set_streaming_mode(&mut self, mode: StreamingMode) {
+        self.streaming_mode = mode;
+    }
+
+    #[track_caller]
+    fn debug_assert_settled(&self) {
+        debug_assert!(
+            self.lods[..self.active_lod_limit()]
+                .iter()
+                .all(|lod| lod.inflight_pages == 0),
+            "page builds still in flight after a synchronous update"
+        );
+    }
+
+    /// Synchronously build the LOD0 pages within
+    /// [`Self::initial_page_radius`] of the camera page.
+    ///
+    /// Pages are generated in parallel on the rayon pool and applied before
+    /// returning, so the first frame already has terrain around the camera.
+    /// The rest of the clipmap keeps streaming through [`Self::update`].
+    /// Returns the number of pages built.
+    #[cfg_attr(
+        feature = "profiling-tracy",
+        tracing::instrument(level = "trace", skip_all)
+    )]
+    pub fn generate_initial_pages(&mut self, camera_pos: Vec3) -> usize {
+        use rayon::prelude::*;
+
+        let coords = self.prepare_initial_pages(camera_pos);
+        let voxel_size = self.lod_voxel_size(0);
+        let generator = &self.generator;
+        let edits = &self.edit_snapshot;
+        let pages: Vec<_> = coords
+            .par_iter()
+            .map(|&coord| build_page_voxels(generator, edits, coord, voxel_size, None))
+            .collect();
+        let count = pages.len();
+        for page in pages {
+            self.apply_built_page(0, page);
+        }
+        count
+    }
+
+    /// Move the clipmap to a distant camera position without an empty frame.
+    ///
+    /// Every active LOD is recentered on `camera_pos` (far jumps wipe and
+    /// requeue the LOD), then the LOD0 pages around the camera are built
+    /// synchronously as in [`Self::generate_initial_pages`]. The remaining
+    /// LOD0 pages keep building in parallel batches until
+    /// [`Self::teleport_budget`] runs out; whatever is left streams through
+    /// [`Self::update`]. The camera velocity is reset so prefetching does
+    /// not lean toward the old position. Returns the number of pages built.
+    #[cfg_attr(
+        feature = "profiling-tracy",
+        tracing::instrument(level = "trace", skip_all)
+    )]
+    pub fn teleport(&mut self, camera_pos: Vec3) -> usize {
+        use rayon::prelude::*;
+
+        let started = Instant::now();
+        let camera_voxel = camera_voxel_of(camera_pos);
+        self.camera_voxel = camera_voxel;
+        self.camera_velocity = Vec3::ZERO;
+        if self.needs_seeding() {
+            self.seed_origins(camera_voxel);
+        } else {
+            // LODs past the bootstrap cursor are rebuilt when it reaches them.
+            let active_lod_count = self.active_lod_limit();
+            let recenter = (self.bootstrap_lod + 1).min(active_lod_count);
+            for lod in 0..recenter {
+                self.update_lod(lod, camera_voxel, false);
+            }
+        }
+
+        let mut built = self.generate_initial_pages(camera_pos);
+        let voxel_size = self.lod_voxel_size(0);
+        let batch = rayon::current_num_threads().max(1);
+        while started.elapsed() < self.teleport_budget {
+            let mut coords = Vec::with_capacity(batch);
+            while coords.len() < batch {
+                let Some(coord) = self.lods[0].pending_pages.pop_front() else {
+                    break;
+                };
+                if self.is_page_in_coverage(0, coord) {
+                    coords.push(coord);
+                }
+            }
+            if coords.is_empty() {
+                break;
+            }
+
+            let generator = &self.generator;
+            let edits = &self.edit_snapshot;
+            let pages: Vec<_> = coords
+                .par_iter()
+                .map(|&coord| build_page_voxels(generator, edits, coord, voxel_size, None))
+                .collect();
+            built += pages.len();
+            for page in pages {
+                self.apply_built_page(0, page);
+            }
+        }
+        built
+    }
+
+    /// Wall-clock time [`Self::teleport`] may block building LOD0 pages
+    /// beyond the initial radius.
+    pub fn teleport_budget(&self) -> Duration {
+        self.teleport_budget
+    }
+
+    /// Set the [`Self::teleport`] blocking budget.
+    ///
+    /// `Duration::ZERO` limits a teleport to the initial page radius.
+    pub fn set_teleport_budget(&mut self, budget: Duration) {
+        self.teleport_budget = budget;
+    }
+
+    /// Compression used for cached pages.
+    pub fn cache_codec(&self) -> CacheCodec {
+        self.cache_codec
+    }
+
+    /// Set the compression used for cached pages.
+    pub fn set_cache_codec(&mut self, codec: CacheCodec) {
+        self.cache_codec = codec;
+    }
+
+    /// Directory built pages are cached in, if any.
+    pub fn page_cache_dir(&self) -> Option<&Path> {
+        self.page_cache_dir.as_deref()
+    }
+
+    /// Cache built pages in `dir`, or `None` to always generate them.
+    ///
+    /// Page builds load a page from the cache when it has one and store the
+    /// pages they generate, so revisited or reloaded areas skip generation.
+    /// The cache only holds generator output: pages touched by edits and
+    /// pages crossed by a LOD seam are always built. Files are keyed by the
+    /// generator's [`TerrainGenerator::cache_key`], so worlds with different
+    /// seeds or configurations can share a directory.
+    pub fn set_page_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.page_cache_dir = dir.map(Arc::from);
+        if self.page_cache_dir.is_some() {
+            self.page_cache_key = self.generator.cache_key();
+        }
+    }
+
+    /// Build a page of `lod` as streaming would and write it for a disk
+    /// cache, compressed with [`Self::cache_codec`].
+    ///
+    /// Read it back with [`page_cache::read_page`](crate::page_cache::read_page).
+    pub fn write_cached_page(
+        &self,
+        lod: usize,
+        page_coord: (i64, i64, i64),
+        writer: &mut impl Write,
+    ) -> Result<()> {
+        let page = build_page_voxels(
+            &self.generator,
+            &self.edit_snapshot,
+            page_coord,
+            self.lod_voxel_size(lod),
+            self.lod_seam(lod),
+        );
+        page_cache::write_page(writer, &page.bricks, self.cache_codec)
+    }
+
     /// Take and clear the dirty state accumulated during updates.
     pub fn take_dirty_state(&mut self) -> ClipmapDirtyState {
         let dirty_pages = self
@@ -263,13 +823,36 @@ impl ClipmapStreamingController {
             .iter_mut()
             .map(|lod| std::mem::take(&mut lod.dirty_pages))
             .collect();
+        let unloaded_pages = self
+            .lods
+            .iter_mut()
+            .map(|lod| std::mem::take(&mut lod.unloaded_pages))
+            .collect();
 
         ClipmapDirtyState {
             dirty_pages,
+            unloaded_pages,
             dirty_headers: std::mem::take(&mut self.dirty_headers),
             dirty_palette16_entries: std::mem::take(&mut self.dirty_palette16_entries),
             dirty_palette32_entries: std::mem::take(&mut self.dirty_palette32_entries),
             dirty_raw16_entries: std::mem::take(&mut self.dirty_raw16_entries),
+            dirty_normal_entries: std::mem::take(&mut self.dirty_normal_entries),
+            dirty_light_entries: std::mem::take(&mut self.dirty_light_entries),
+        }
+    }
+
+    /// Mark every loaded page dirty so the next dirty state re-uploads them.
+    ///
+    /// Needed when a renderer that was drawing something else starts drawing
+    /// this controller.
+    pub fn mark_all_pages_dirty(&mut self) {
+        for lod in &mut self.lods {
+            lod.dirty_pages.extend(
+                lod.page_loaded
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, &loaded)| loaded.then_some(index)),
+            );
         }
     }
 
@@ -283,6 +866,47 @@ impl ClipmapStreamingController {
         &mut self.store
     }
 
+    /// Palette-encode the raw bricks of a loaded page, e.g. once it has
+    /// settled under [`CompressionPolicy::Lazy`].
+    ///
+    /// Bricks holding edits stay raw, so editing them again stays cheap.
+    /// Replaced raw16 entries are freed as late as unloaded bricks, so GPU
+    /// frames in flight never read a reused entry. Re-encoded bricks are
+    /// marked dirty. Returns the number of bricks re-encoded.
+    ///
+    /// [`CompressionPolicy::Lazy`]: voxelicous_voxel::CompressionPolicy::Lazy
+    pub fn compress_page(&mut self, lod: usize, page_coord: (i64, i64, i64)) -> usize {
+        if lod >= self.active_lod_limit() || !self.page_slot_matches_coord(lod, page_coord) {
+            return 0;
+        }
+        let voxel_size = self.lod_voxel_size(lod);
+        let brick_size = BRICK_SIZE as i64 * voxel_size;
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let page = [page_coord.0, page_coord.1, page_coord.2];
+        let base_offset = Self::page_index_from_coord(page_coord) * PAGE_BRICKS;
+        let mut compressed = 0;
+        for brick_idx in 0..PAGE_BRICKS {
+            let id = BrickId(self.lods[lod].page_brick_indices[base_offset + brick_idx]);
+            let brick = [
+                brick_idx % PAGE_BRICKS_PER_AXIS,
+                (brick_idx / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS,
+                brick_idx / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS),
+            ];
+            let min =
+                [0, 1, 2].map(|axis| page[axis] * page_size + brick[axis] as i64 * brick_size);
+            if id.0 == 0 || has_edits_in(&self.edits, min, brick_size) {
+                continue;
+            }
+            let Some((_, Some(retired))) = self.store.compress_brick_deferred(id) else {
+                continue;
+            };
+            self.queue_deferred_free(DeferredFree::Payload(retired));
+            self.mark_brick_dirty(id);
+            compressed += 1;
+        }
+        compressed
+    }
+
     /// Get page brick indices for a given LOD (SoA).
     pub fn page_brick_indices(&self, lod: usize) -> &[u32] {
         &self.lods[lod].page_brick_indices
@@ -298,6 +922,61 @@ impl ClipmapStreamingController {
         &self.lods[lod].page_coords
     }
 
+    /// Coordinates of the loaded pages of a LOD in Morton order.
+    ///
+    /// Unlike [`Self::page_coords`], the order only depends on which pages
+    /// are loaded, not on the toroidal slots they landed in.
+    pub fn loaded_pages_sorted(&self, lod: usize) -> Vec<(i64, i64, i64)> {
+        let state = &self.lods[lod];
+        let mut pages: Vec<_> = state
+            .page_coords
+            .iter()
+            .zip(&state.page_loaded)
+            .filter(|&(_, &loaded)| loaded)
+            .map(|(coord, _)| slot_coord_to_page(*coord))
+            .collect();
+        pages.sort_by_key(|&coord| page_morton_code(coord));
+        pages
+    }
+
+    /// Occupancy mask of the brick containing a world voxel at one LOD.
+    ///
+    /// Returns the brick's 4x4x4 mask of 2x2x2-voxel cells (bit
+    /// `x + y * 4 + z * 16`), the same bits the ray marcher uses to skip empty
+    /// space; `Some(0)` means the brick is all air. Returns `None` when the
+    /// page covering `world` is not loaded at this LOD, in which case callers
+    /// should fall back to sampling the generator.
+    #[must_use]
+    pub fn brick_occupancy(&self, lod: usize, world: WorldCoord) -> Option<u64> {
+        if lod >= self.active_lod_limit() {
+            return None;
+        }
+        let voxel_size = self.lod_voxel_size(lod);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let page_coord = (
+            div_floor(world.x, page_size),
+            div_floor(world.y, page_size),
+            div_floor(world.z, page_size),
+        );
+        if !self.page_slot_matches_coord(lod, page_coord) {
+            return None;
+        }
+
+        let brick_size = BRICK_SIZE as i64 * voxel_size;
+        let local = |v: i64| (mod_floor(v, page_size) / brick_size) as usize;
+        let brick_idx = local(world.x)
+            + local(world.y) * PAGE_BRICKS_PER_AXIS
+            + local(world.z) * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+        let page_index = Self::page_index_from_coord(page_coord);
+        let id = BrickId(self.lods[lod].page_brick_indices[page_index * PAGE_BRICKS + brick_idx]);
+        if id.0 == 0 {
+            return Some(0);
+        }
+        self.store
+            .header(id)
+            .map(|header| u64::from(header.occ_l0_lo) | (u64::from(header.occ_l0_hi) << 32))
+    }
+
     /// Get clipmap origin (min corner) for a given LOD.
     pub fn lod_origin(&self, lod: usize) -> WorldCoord {
         self.lods[lod]
@@ -310,92 +989,385 @@ impl ClipmapStreamingController {
         1i64 << lod
     }
 
-    /// Get active visible page grid size per axis.
+    /// Get active visible page grid size along X and Z.
+    ///
+    /// Same as [`Self::visible_page_grid_xz`].
     pub fn visible_page_grid(&self) -> usize {
-        self.visible_page_grid
+        self.visible_page_grid_xz
     }
 
-    /// Set active visible page grid size per axis.
+    /// Get active visible page grid size along X and Z.
+    pub fn visible_page_grid_xz(&self) -> usize {
+        self.visible_page_grid_xz
+    }
+
+    /// Get active visible page grid size along Y.
+    pub fn visible_page_grid_y(&self) -> usize {
+        self.visible_page_grid_y
+    }
+
+    /// Set active visible page grid size on every axis.
     ///
-    /// Values are clamped to `1..=CLIPMAP_PAGE_GRID`.
+    /// Values are clamped to `1..=CLIPMAP_PAGE_GRID`. When the grid shrinks,
+    /// pages outside it stay loaded for
+    /// [`Self::visible_grid_hysteresis_frames`] updates, so growing it back
+    /// soon after reuses them instead of rebuilding.
     /// Returns `true` when the effective value changed.
     pub fn set_visible_page_grid(&mut self, page_grid: usize) -> bool {
-        let clamped = page_grid.clamp(1, CLIPMAP_PAGE_GRID);
-        if clamped == self.visible_page_grid {
-            return false;
-        }
-        self.visible_page_grid = clamped;
-        self.reconfigure_visible_coverage_all_lods();
-        true
+        self.set_visible_page_grid_dims(page_grid, page_grid)
     }
 
-    /// Returns whether multi-LOD rendering is enabled.
-    pub fn lod_enabled(&self) -> bool {
-        self.active_lod_limit() > 1
+    /// Set active visible page grid size along X and Z, keeping Y.
+    ///
+    /// Clamped and applied like [`Self::set_visible_page_grid`].
+    pub fn set_visible_page_grid_xz(&mut self, page_grid: usize) -> bool {
+        self.set_visible_page_grid_dims(page_grid, self.visible_page_grid_y)
     }
 
-    /// Get number of active LOD levels.
-    pub fn active_lod_count(&self) -> usize {
-        self.active_lod_limit()
+    /// Set active visible page grid size along Y, keeping X and Z.
+    ///
+    /// A grid shallower than it is wide suits terrain-style worlds, where
+    /// most vertical pages would be empty air or solid rock. Clamped and
+    /// applied like [`Self::set_visible_page_grid`].
+    pub fn set_visible_page_grid_y(&mut self, page_grid: usize) -> bool {
+        self.set_visible_page_grid_dims(self.visible_page_grid_xz, page_grid)
     }
 
-    /// Enable or disable multi-LOD rendering at runtime.
-    ///
-    /// When disabled, only LOD0 is updated and rendered.
-    /// Returns `true` when the effective mode changed.
-    pub fn set_lod_enabled(&mut self, enabled: bool) -> bool {
-        let target_lod_count = if enabled { CLIPMAP_LOD_COUNT } else { 1 };
-        if target_lod_count == self.active_lod_count {
+    fn set_visible_page_grid_dims(&mut self, xz: usize, y: usize) -> bool {
+        let xz = xz.clamp(1, CLIPMAP_PAGE_GRID);
+        let y = y.clamp(1, CLIPMAP_PAGE_GRID);
+        if (xz, y) == (self.visible_page_grid_xz, self.visible_page_grid_y) {
             return false;
         }
-        self.active_lod_count = target_lod_count;
+        self.visible_page_grid_xz = xz;
+        self.visible_page_grid_y = y;
         self.reconfigure_visible_coverage_all_lods();
         true
     }
 
-    /// Get coverage (extent) for a given LOD in base voxels.
-    pub fn lod_coverage(&self, lod: usize) -> i64 {
-        let voxels = (self.visible_page_grid * PAGE_VOXELS_PER_AXIS) as i64;
-        voxels * self.lod_voxel_size(lod)
+    /// Visible page grid size as `(x, y, z)`.
+    fn page_grid_dims(&self) -> (i64, i64, i64) {
+        let xz = self.visible_page_grid_xz as i64;
+        (xz, self.visible_page_grid_y as i64, xz)
     }
 
-    /// Returns true if this LOD has completed at least one full build.
-    pub fn lod_ready(&self, lod: usize) -> bool {
-        lod < self.active_lod_limit() && self.lods[lod].ready
+    /// Updates that pages outside a shrunk visible grid are kept before
+    /// eviction.
+    pub fn visible_grid_hysteresis_frames(&self) -> u64 {
+        self.visible_grid_hysteresis_frames
     }
 
-    /// Returns true if this LOD has at least one loaded page.
-    pub fn lod_renderable(&self, lod: usize) -> bool {
-        lod < self.active_lod_limit() && self.lods[lod].loaded_pages > 0
+    /// Set how many updates pages outside a shrunk visible grid are kept.
+    ///
+    /// Defaults to 90 (1.5 seconds at 60 FPS), which absorbs view distance
+    /// scaling that oscillates with frame time. `0` evicts them as soon as
+    /// the grid shrinks.
+    pub fn set_visible_grid_hysteresis_frames(&mut self, frames: u64) {
+        self.visible_grid_hysteresis_frames = frames;
     }
 
-    #[cfg_attr(
-        feature = "profiling-tracy",
-        tracing::instrument(level = "trace", skip_all)
-    )]
-    fn update_lod(&mut self, lod: usize, camera_voxel: WorldCoord, force: bool) {
-        let voxel_size = self.lod_voxel_size(lod);
-        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-        let coverage = self.lod_coverage(lod);
-        let origin = aligned_origin(camera_voxel, coverage, page_size);
+    /// Maximum number of page builds queued on the rayon pool at once.
+    ///
+    /// Defaults to one job per four pages of [`Self::visible_page_grid_xz`]
+    /// (16 at the full grid), so larger view distances keep more work in
+    /// flight.
+    pub fn max_inflight_page_jobs(&self) -> usize {
+        self.max_inflight_jobs.unwrap_or_else(|| {
+            (self.visible_page_grid_xz / Self::VISIBLE_PAGES_PER_INFLIGHT_JOB)
+                .max(Self::MIN_INFLIGHT_PAGE_JOBS)
+        })
+    }
 
-        let old_origin = self.lods[lod].origin.unwrap_or(origin);
-        let shift = if force {
-            let grid = self.visible_page_grid as i64;
-            (grid, grid, grid)
-        } else {
-            (
-                (origin.x - old_origin.x) / page_size,
+    /// Override the in-flight page build cap, or `None` to derive it from the
+    /// visible page grid.
+    ///
+    /// Values are clamped to at least `1`. Machines with many cores can raise
+    /// this to keep more pages building in parallel.
+    pub fn set_max_inflight_page_jobs(&mut self, jobs: Option<usize>) {
+        self.max_inflight_jobs = jobs.map(|jobs| jobs.max(1));
+    }
+
+    /// Cap on the summed estimated cost of in-flight page builds, if any.
+    pub fn max_inflight_page_cost(&self) -> Option<u32> {
+        self.max_inflight_cost
+    }
+
+    /// Weight the in-flight build budget by estimated page cost.
+    ///
+    /// With a cap, new builds start only while the summed
+    /// [`TerrainGenerator::estimate_page_cost`] of the builds in flight is
+    /// below it, so fewer expensive pages (dense forest) build at once than
+    /// cheap ones (open sky, flat plains). The job cap from
+    /// [`Self::set_max_inflight_page_jobs`] still applies. `None` (the
+    /// default) counts pages only.
+    pub fn set_max_inflight_page_cost(&mut self, cost: Option<u32>) {
+        self.max_inflight_cost = cost.map(|cost| cost.max(1));
+    }
+
+    /// How pending page builds are shared between LODs.
+    pub fn page_build_fairness(&self) -> PageBuildFairness {
+        self.page_build_fairness
+    }
+
+    /// Set how pending page builds are shared between LODs.
+    ///
+    /// The default, [`PageBuildFairness::FinestFirst`], lets coarse LODs
+    /// starve while LOD0 keeps getting new pages.
+    pub fn set_page_build_fairness(&mut self, fairness: PageBuildFairness) {
+        self.page_build_fairness = fairness;
+        self.fairness_credit = [0; CLIPMAP_LOD_COUNT];
+    }
+
+    /// Number of finest LODs whose pages are stored with
+    /// [`CompressLevel::Fast`].
+    pub fn fast_compression_lods(&self) -> usize {
+        self.fast_compression_lods
+    }
+
+    /// Store pages of the `lods` finest LODs with [`CompressLevel::Fast`].
+    ///
+    /// Pages near the camera are rebuilt most often, so skipping palette
+    /// encoding there cuts rebuild latency at the cost of raw16 memory.
+    /// Coarser LODs keep the store's compression policy. Only affects pages
+    /// built afterwards; defaults to `0`.
+    pub fn set_fast_compression_lods(&mut self, lods: usize) {
+        self.fast_compression_lods = lods.min(CLIPMAP_LOD_COUNT);
+    }
+
+    /// Materials whose emission lights the finest LOD.
+    pub fn materials(&self) -> &MaterialRegistry {
+        &self.materials
+    }
+
+    /// Set the materials used for block-light propagation.
+    ///
+    /// Emissive blocks flood light into their finest-LOD page and the loaded
+    /// pages around it. Only affects pages built afterwards; defaults to the
+    /// built-in materials.
+    pub fn set_materials(&mut self, materials: MaterialRegistry) {
+        self.materials = materials;
+    }
+
+    /// Set the camera velocity in world units per second.
+    ///
+    /// Page loading is prioritized around the point the camera reaches
+    /// after [`Self::prefetch_seconds`], so pages ahead of a moving camera
+    /// stream in first.
+    pub fn set_camera_velocity(&mut self, velocity: Vec3) {
+        self.camera_velocity = if velocity.is_finite() {
+            velocity
+        } else {
+            Vec3::ZERO
+        };
+    }
+
+    /// Camera velocity used for prefetching.
+    pub fn camera_velocity(&self) -> Vec3 {
+        self.camera_velocity
+    }
+
+    /// Set how far ahead, in seconds of camera motion, loading looks.
+    ///
+    /// `0.0` centers load priority on the camera itself.
+    pub fn set_prefetch_seconds(&mut self, seconds: f32) {
+        self.prefetch_seconds = seconds.max(0.0);
+    }
+
+    /// Prefetch lookahead in seconds.
+    pub fn prefetch_seconds(&self) -> f32 {
+        self.prefetch_seconds
+    }
+
+    /// Returns whether multi-LOD rendering is enabled.
+    pub fn lod_enabled(&self) -> bool {
+        self.active_lod_limit() > 1
+    }
+
+    /// Get number of active LOD levels.
+    pub fn active_lod_count(&self) -> usize {
+        self.active_lod_limit()
+    }
+
+    /// Enable or disable multi-LOD rendering at runtime.
+    ///
+    /// When disabled, only LOD0 is updated and rendered.
+    /// Returns `true` when the effective mode changed.
+    pub fn set_lod_enabled(&mut self, enabled: bool) -> bool {
+        let target_lod_count = if enabled { CLIPMAP_LOD_COUNT } else { 1 };
+        if target_lod_count == self.active_lod_count {
+            return false;
+        }
+        self.active_lod_count = target_lod_count;
+        self.reconfigure_visible_coverage_all_lods();
+        true
+    }
+
+    /// Get horizontal (X/Z) coverage (extent) for a given LOD in base voxels.
+    pub fn lod_coverage(&self, lod: usize) -> i64 {
+        let voxels = (self.visible_page_grid_xz * PAGE_VOXELS_PER_AXIS) as i64;
+        voxels * self.lod_voxel_size(lod)
+    }
+
+    /// Get vertical (Y) coverage (extent) for a given LOD in base voxels.
+    pub fn lod_coverage_y(&self, lod: usize) -> i64 {
+        let voxels = (self.visible_page_grid_y * PAGE_VOXELS_PER_AXIS) as i64;
+        voxels * self.lod_voxel_size(lod)
+    }
+
+    /// Page-aligned origin of `lod`'s coverage centered on `camera_voxel`.
+    fn lod_origin_around(&self, lod: usize, camera_voxel: WorldCoord) -> WorldCoord {
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * self.lod_voxel_size(lod);
+        aligned_origin(
+            camera_voxel,
+            (self.lod_coverage(lod), self.lod_coverage_y(lod)),
+            page_size,
+        )
+    }
+
+    /// Returns true if this LOD has completed at least one full build.
+    pub fn lod_ready(&self, lod: usize) -> bool {
+        lod < self.active_lod_limit() && self.lods[lod].ready
+    }
+
+    /// Returns true if this LOD has at least one loaded page.
+    pub fn lod_renderable(&self, lod: usize) -> bool {
+        lod < self.active_lod_limit() && self.lods[lod].loaded_pages > 0
+    }
+
+    /// Streaming progress of every active LOD, finest first.
+    pub fn lod_status(&self) -> Vec<LodStatus> {
+        self.lods[..self.active_lod_limit()]
+            .iter()
+            .enumerate()
+            .map(|(lod, state)| LodStatus {
+                lod,
+                ready: state.ready,
+                renderable: state.loaded_pages > 0,
+                loaded_pages: state.loaded_pages,
+                pending: state.pending_pages.len(),
+                inflight: state.inflight_pages,
+            })
+            .collect()
+    }
+
+    /// Pages of `lod`'s visible grid whose bounds intersect `frustum`,
+    /// nearest to the camera first.
+    ///
+    /// Page bounds are in world units, so `Camera::frustum()` can be passed
+    /// as is; its far plane limits the range. Use this to prioritize uploads
+    /// or prefetch what is on screen. Empty until the LOD has been seeded.
+    pub fn pages_in_frustum(&self, lod: usize, frustum: &Frustum) -> Vec<(i64, i64, i64)> {
+        let Some(origin) = self.lods[lod].origin else {
+            return Vec::new();
+        };
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * self.lod_voxel_size(lod);
+        let origin_page = (
+            div_floor(origin.x, page_size),
+            div_floor(origin.y, page_size),
+            div_floor(origin.z, page_size),
+        );
+
+        let grid = self.page_grid_dims();
+        let mut pages = Vec::new();
+        for z in 0..grid.2 {
+            for y in 0..grid.1 {
+                for x in 0..grid.0 {
+                    let coord = (origin_page.0 + x, origin_page.1 + y, origin_page.2 + z);
+                    let min = Vec3::new(coord.0 as f32, coord.1 as f32, coord.2 as f32)
+                        * page_size as f32;
+                    let bounds = Aabb::new(min, min + Vec3::splat(page_size as f32));
+                    if frustum.test_aabb(&bounds) {
+                        pages.push(coord);
+                    }
+                }
+            }
+        }
+        pages.sort_unstable_by_key(|&coord| page_load_key(coord, self.camera_voxel, page_size));
+        pages
+    }
+
+    /// Number of loaded pages in a LOD, empty ones included.
+    pub fn loaded_page_count(&self, lod: usize) -> usize {
+        self.lods[lod].loaded_pages
+    }
+
+    /// Number of loaded all-air pages in a LOD.
+    ///
+    /// Empty pages share the store's empty brick and only occupy their page
+    /// table slot, so high-altitude views cost almost no brick memory.
+    pub fn empty_page_count(&self, lod: usize) -> usize {
+        self.lods[lod].empty_pages
+    }
+
+    /// Render a top-down occupancy map of one LOD's page columns.
+    ///
+    /// Pixel `(x, y)` covers page column `(center.0 - radius + x, center.1 - radius + y)`
+    /// in LOD page coordinates (X right, Z down). Columns with any solid loaded
+    /// page are [`Self::MINIMAP_OCCUPIED`], columns that are loaded but empty
+    /// are [`Self::MINIMAP_EMPTY`], everything else is [`Self::MINIMAP_UNLOADED`].
+    #[must_use]
+    pub fn occupancy_minimap(&self, lod: usize, center: (i64, i64), radius: i32) -> GrayImage {
+        let radius = radius.max(0).unsigned_abs();
+        let side = 2 * radius + 1;
+        let mut image = GrayImage::new(side, side);
+        let radius = i64::from(radius);
+        let Some(origin) = self.lods.get(lod).and_then(|state| state.origin) else {
+            return image;
+        };
+
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * self.lod_voxel_size(lod);
+        let origin_page_y = div_floor(origin.y, page_size);
+        let grid_y = self.visible_page_grid_y as i64;
+        for (px, py, pixel) in image.enumerate_pixels_mut() {
+            let column_x = center.0 - radius + i64::from(px);
+            let column_z = center.1 - radius + i64::from(py);
+            let mut value = Self::MINIMAP_UNLOADED;
+            for page_y in origin_page_y..origin_page_y + grid_y {
+                let page_coord = (column_x, page_y, column_z);
+                if !self.is_page_in_coverage(lod, page_coord)
+                    || !self.page_slot_matches_coord(lod, page_coord)
+                {
+                    continue;
+                }
+                value = Self::MINIMAP_EMPTY;
+                let page_index = Self::page_index_from_coord(page_coord);
+                if self.lods[lod].page_occ[page_index] != [0, 0] {
+                    value = Self::MINIMAP_OCCUPIED;
+                    break;
+                }
+            }
+            *pixel = Luma([value]);
+        }
+        image
+    }
+
+    #[cfg_attr(
+        feature = "profiling-tracy",
+        tracing::instrument(level = "trace", skip_all)
+    )]
+    fn update_lod(&mut self, lod: usize, camera_voxel: WorldCoord, force: bool) {
+        let voxel_size = self.lod_voxel_size(lod);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let origin = self.lod_origin_around(lod, camera_voxel);
+        let grid = self.page_grid_dims();
+
+        let old_origin = self.lods[lod].origin.unwrap_or(origin);
+        let shift = if force {
+            grid
+        } else {
+            (
+                (origin.x - old_origin.x) / page_size,
                 (origin.y - old_origin.y) / page_size,
                 (origin.z - old_origin.z) / page_size,
             )
         };
 
-        let max_shift = shift.0.abs().max(shift.1.abs()).max(shift.2.abs());
-        let visible_grid = self.visible_page_grid;
+        let shifted_out =
+            shift.0.abs() >= grid.0 || shift.1.abs() >= grid.1 || shift.2.abs() >= grid.2;
 
-        if force || max_shift as usize >= visible_grid {
+        if force || shifted_out {
             self.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
+            self.enqueue_seam_pages(lod + 1, [true; 3]);
             return;
         }
 
@@ -421,6 +1393,146 @@ impl ClipmapStreamingController {
         }
 
         self.lods[lod].origin = Some(origin);
+        self.enqueue_seam_pages(lod + 1, [shift.0 != 0, shift.1 != 0, shift.2 != 0]);
+    }
+
+    /// Seam between `lod` and the next finer LOD, if `lod` needs stitching.
+    ///
+    /// LOD1 voxels already downsample every base voxel they cover, so only
+    /// LOD2 and up get a seam.
+    fn lod_seam(&self, lod: usize) -> Option<LodSeam> {
+        if lod < 2 || lod >= self.active_lod_limit() {
+            return None;
+        }
+        let fine = lod - 1;
+        let origin = self.lods[fine].origin?;
+        Some(LodSeam::new(
+            origin,
+            (self.lod_coverage(fine), self.lod_coverage_y(fine)),
+            self.lod_voxel_size(fine),
+        ))
+    }
+
+    /// Requeue loaded pages of `lod` crossed by the seam faces normal to
+    /// `axes`, after the finer LOD moved them.
+    ///
+    /// Pages are rebuilt in place, so the old data stays visible until the
+    /// stitched page lands. All-air pages are left alone: the seam rarely
+    /// adds detail there and the faces cross thousands of them.
+    fn enqueue_seam_pages(&mut self, lod: usize, axes: [bool; 3]) {
+        let Some(seam) = self.lod_seam(lod) else {
+            return;
+        };
+        let voxel_size = self.lod_voxel_size(lod);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let min = [seam.min.x, seam.min.y, seam.min.z];
+        let max = [seam.max.x, seam.max.y, seam.max.z];
+        // Page range covering voxels within one coarse voxel of [lo, hi).
+        let pages = |lo: i64, hi: i64| {
+            div_floor(lo - voxel_size, page_size)..=div_floor(hi + voxel_size - 1, page_size)
+        };
+
+        let mut seen = HashSet::new();
+        let mut coords = Vec::new();
+        for axis in (0..3).filter(|&axis| axes[axis]) {
+            for face in [min[axis], max[axis]] {
+                let mut ranges = [0, 1, 2].map(|other| pages(min[other], max[other]));
+                ranges[axis] = pages(face, face);
+                for z in ranges[2].clone() {
+                    for y in ranges[1].clone() {
+                        for x in ranges[0].clone() {
+                            let coord = (x, y, z);
+                            let page_index = Self::page_index_from_coord(coord);
+                            if self.page_slot_matches_coord(lod, coord)
+                                && self.lods[lod].page_occ[page_index] != [0, 0]
+                                && seen.insert(coord)
+                            {
+                                coords.push(coord);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if coords.is_empty() {
+            return;
+        }
+
+        let pending = &mut self.lods[lod].pending_pages;
+        pending.retain(|coord| !seen.contains(coord));
+        let priority_voxel = self.priority_voxel();
+        coords.sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
+        self.lods[lod].pending_pages.extend(coords);
+    }
+
+    /// Voxel that pending pages are prioritized around.
+    fn priority_voxel(&self) -> WorldCoord {
+        let lookahead = (self.camera_velocity * self.prefetch_seconds).round();
+        WorldCoord {
+            x: self.camera_voxel.x + lookahead.x as i64,
+            y: self.camera_voxel.y + lookahead.y as i64,
+            z: self.camera_voxel.z + lookahead.z as i64,
+        }
+    }
+
+    fn needs_seeding(&self) -> bool {
+        self.lods[..self.active_lod_limit()]
+            .iter()
+            .any(|lod| lod.origin.is_none())
+    }
+
+    fn seed_origins(&mut self, camera_voxel: WorldCoord) {
+        for lod in 0..self.active_lod_limit() {
+            let origin = self.lod_origin_around(lod, camera_voxel);
+            self.lods[lod].origin = Some(origin);
+        }
+
+        let voxel_size0 = self.lod_voxel_size(0);
+        let page_size0 = PAGE_VOXELS_PER_AXIS as i64 * voxel_size0;
+        let origin0 = self.lod_origin_around(0, camera_voxel);
+        self.enqueue_full_rebuild(0, origin0, voxel_size0, page_size0);
+        self.bootstrap_lod = 0;
+    }
+
+    /// Seed or recenter origins and claim the initial LOD0 pages from the
+    /// pending queue, nearest first.
+    fn prepare_initial_pages(&mut self, camera_pos: Vec3) -> Vec<(i64, i64, i64)> {
+        let camera_voxel = camera_voxel_of(camera_pos);
+        self.camera_voxel = camera_voxel;
+        if self.needs_seeding() {
+            self.seed_origins(camera_voxel);
+        } else {
+            // Origins seeded by an earlier reconfiguration may not cover the
+            // camera yet.
+            self.update_lod(0, camera_voxel, false);
+        }
+
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * self.lod_voxel_size(0);
+        let center = (
+            div_floor(camera_voxel.x, page_size),
+            div_floor(camera_voxel.y, page_size),
+            div_floor(camera_voxel.z, page_size),
+        );
+        let radius = self.initial_page_radius as i64;
+        let mut coords = Vec::new();
+        for z in -radius..=radius {
+            for y in -radius..=radius {
+                for x in -radius..=radius {
+                    let coord = (center.0 + x, center.1 + y, center.2 + z);
+                    if self.is_page_in_coverage(0, coord) && !self.page_slot_matches_coord(0, coord)
+                    {
+                        coords.push(coord);
+                    }
+                }
+            }
+        }
+        coords.sort_unstable_by_key(|&coord| page_load_key(coord, camera_voxel, page_size));
+
+        let claimed: HashSet<_> = coords.iter().copied().collect();
+        self.lods[0]
+            .pending_pages
+            .retain(|coord| !claimed.contains(coord));
+        coords
     }
 
     fn enqueue_full_rebuild(
@@ -454,6 +1566,7 @@ impl ClipmapStreamingController {
             lod_state.origin = Some(origin);
             lod_state.pending_pages.clear();
             lod_state.pending_pages.reserve(page_count);
+            lod_state.retired_pages.clear();
             lod_state.page_brick_indices.as_mut_slice().fill(0);
             for occ in &mut lod_state.page_occ {
                 *occ = [0, 0];
@@ -464,49 +1577,49 @@ impl ClipmapStreamingController {
                 .fill(invalid_page_coord());
             lod_state.page_loaded.as_mut_slice().fill(false);
             lod_state.loaded_pages = 0;
+            lod_state.empty_pages = 0;
             lod_state.dirty_pages.clear();
             lod_state.dirty_pages.extend(0..page_count);
             lod_state.ready = false;
         }
 
-        let visible_grid = self.visible_page_grid;
-        let mut coords = Vec::with_capacity(visible_grid * visible_grid * visible_grid);
-        for z in 0..visible_grid {
-            for y in 0..visible_grid {
-                for x in 0..visible_grid {
-                    coords.push((
-                        page_origin.0 + x as i64,
-                        page_origin.1 + y as i64,
-                        page_origin.2 + z as i64,
-                    ));
+        let grid = self.page_grid_dims();
+        let mut coords = Vec::with_capacity((grid.0 * grid.1 * grid.2) as usize);
+        for z in 0..grid.2 {
+            for y in 0..grid.1 {
+                for x in 0..grid.0 {
+                    coords.push((page_origin.0 + x, page_origin.1 + y, page_origin.2 + z));
                 }
             }
         }
 
         let pending_budget = self.pending_page_budget(self.current_apply_budget());
         self.reduce_coords_to_pending_budget(page_size, &mut coords, pending_budget);
-        let camera_voxel = self.camera_voxel;
-        coords.sort_unstable_by_key(|&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
-        });
+        let priority_voxel = self.priority_voxel();
+        coords.sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
         self.enqueue_pending_pages(lod, coords, false, pending_budget);
     }
 
     fn enqueue_slice(&mut self, lod: usize, page_origin: (i64, i64, i64), axis: Axis, shift: i64) {
-        let count = shift.unsigned_abs() as usize;
-        let grid = self.visible_page_grid as i64;
+        let count = shift.abs();
+        let (grid_x, grid_y, grid_z) = self.page_grid_dims();
+        // Extent along the shift axis, then along the two other axes.
+        let (along, rows, cols) = match axis {
+            Axis::X => (grid_x, grid_y, grid_z),
+            Axis::Y => (grid_y, grid_x, grid_z),
+            Axis::Z => (grid_z, grid_x, grid_y),
+        };
 
         let (start, end) = if shift > 0 {
-            (grid - count as i64, grid)
+            (along - count, along)
         } else {
-            (0, count as i64)
+            (0, count)
         };
 
-        let visible_grid = self.visible_page_grid;
-        let mut coords = Vec::with_capacity(count * visible_grid * visible_grid);
+        let mut coords = Vec::with_capacity((count * rows * cols) as usize);
         for idx in start..end {
-            for j in 0..visible_grid as i64 {
-                for k in 0..visible_grid as i64 {
+            for j in 0..rows {
+                for k in 0..cols {
                     let (px, py, pz) = match axis {
                         Axis::X => (page_origin.0 + idx, page_origin.1 + j, page_origin.2 + k),
                         Axis::Y => (page_origin.0 + j, page_origin.1 + idx, page_origin.2 + k),
@@ -525,10 +1638,8 @@ impl ClipmapStreamingController {
         let page_size = PAGE_VOXELS_PER_AXIS as i64 * self.lod_voxel_size(lod);
         let pending_budget = self.pending_page_budget(self.current_apply_budget());
         self.reduce_coords_to_pending_budget(page_size, &mut coords, pending_budget);
-        let camera_voxel = self.camera_voxel;
-        coords.sort_unstable_by_key(|&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
-        });
+        let priority_voxel = self.priority_voxel();
+        coords.sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
         self.enqueue_pending_pages(lod, coords, false, pending_budget);
     }
 
@@ -545,16 +1656,9 @@ impl ClipmapStreamingController {
                 Err(TryRecvError::Empty | TryRecvError::Disconnected) => break,
             };
 
-            self.inflight_jobs = self.inflight_jobs.saturating_sub(1);
-            let lod_state = &mut self.lods[result.lod];
-            lod_state.inflight_pages = lod_state.inflight_pages.saturating_sub(1);
-
-            if result.generation != lod_state.generation {
-                continue;
+            if self.land_page_build(result) {
+                apply_budget -= 1;
             }
-
-            self.apply_built_page(result.lod, result.page);
-            apply_budget -= 1;
         }
 
         let pending_budget = self.pending_page_budget(self.current_apply_budget());
@@ -572,43 +1676,138 @@ impl ClipmapStreamingController {
         }
     }
 
+    /// Retire a finished page build and apply it unless its LOD moved on.
+    ///
+    /// Returns `true` when the page was applied.
+    fn land_page_build(&mut self, result: PageBuildResult) -> bool {
+        self.inflight_jobs = self.inflight_jobs.saturating_sub(1);
+        self.inflight_cost = self.inflight_cost.saturating_sub(result.cost);
+        let lod_state = &mut self.lods[result.lod];
+        lod_state.inflight_pages = lod_state.inflight_pages.saturating_sub(1);
+
+        if result.generation != lod_state.generation {
+            return false;
+        }
+
+        self.apply_built_page(result.lod, result.page);
+        true
+    }
+
     fn spawn_pending_jobs(&mut self) {
-        while self.inflight_jobs < Self::MAX_INFLIGHT_PAGE_JOBS {
+        while self.inflight_jobs < self.max_inflight_page_jobs() {
+            if self
+                .max_inflight_cost
+                .is_some_and(|budget| self.inflight_cost >= budget)
+            {
+                break;
+            }
             let Some((lod, coord, voxel_size, generation)) = self.pop_next_pending_page() else {
                 break;
             };
 
+            let cost = if self.max_inflight_cost.is_some() {
+                self.estimate_page_cost(coord, voxel_size)
+            } else {
+                0
+            };
             self.inflight_jobs += 1;
+            self.inflight_cost += cost;
             self.lods[lod].inflight_pages += 1;
 
             let tx = self.page_build_tx.clone();
-            let generator = self.generator.clone();
+            let generator = Arc::clone(&self.generator);
             let edits = Arc::clone(&self.edit_snapshot);
-            rayon::spawn(move || {
-                let page = build_page_voxels(&generator, &edits, coord, voxel_size);
+            let seam = self.lod_seam(lod);
+            let cache = self.page_cache_dir.clone().map(|dir| PageCacheTarget {
+                dir,
+                generator_key: self.page_cache_key,
+                codec: self.cache_codec,
+            });
+            let build = move || {
+                let page = cache.map_or_else(
+                    || build_page_voxels(&generator, &edits, coord, voxel_size, seam),
+                    |cache| {
+                        build_page_voxels_cached(
+                            &cache, &generator, &edits, coord, voxel_size, seam,
+                        )
+                    },
+                );
                 let _ = tx.send(PageBuildResult {
                     lod,
                     generation,
+                    cost,
                     page,
                 });
-            });
+            };
+            match self.streaming_mode {
+                StreamingMode::Async => rayon::spawn(build),
+                StreamingMode::Sync => build(),
+            }
         }
     }
 
+    fn estimate_page_cost(&self, page_coord: (i64, i64, i64), voxel_size: i64) -> u32 {
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        self.generator.estimate_page_cost(
+            (
+                page_coord.0 * page_size,
+                page_coord.1 * page_size,
+                page_coord.2 * page_size,
+            ),
+            page_size,
+        )
+    }
+
     fn pop_next_pending_page(&mut self) -> Option<(usize, (i64, i64, i64), i64, u64)> {
-        for lod in 0..self.active_lod_limit() {
-            let voxel_size = self.lod_voxel_size(lod);
-            let generation = self.lods[lod].generation;
-            while let Some(coord) = self.lods[lod].pending_pages.pop_front() {
-                if !self.is_page_in_coverage(lod, coord) {
-                    continue;
+        match self.page_build_fairness {
+            PageBuildFairness::FinestFirst => {
+                (0..self.active_lod_limit()).find_map(|lod| self.pop_pending_page_from(lod))
+            }
+            PageBuildFairness::Weighted(weights) => loop {
+                let lod = self.next_weighted_lod(&weights)?;
+                if let Some(page) = self.pop_pending_page_from(lod) {
+                    return Some(page);
                 }
+            },
+        }
+    }
+
+    /// Pop the nearest pending page of `lod` that is still in coverage.
+    fn pop_pending_page_from(&mut self, lod: usize) -> Option<(usize, (i64, i64, i64), i64, u64)> {
+        let voxel_size = self.lod_voxel_size(lod);
+        let generation = self.lods[lod].generation;
+        while let Some(coord) = self.lods[lod].pending_pages.pop_front() {
+            if self.is_page_in_coverage(lod, coord) {
                 return Some((lod, coord, voxel_size, generation));
             }
         }
         None
     }
 
+    /// Pick the LOD the next page comes from by smooth weighted round-robin
+    /// over the LODs with pending pages.
+    fn next_weighted_lod(&mut self, weights: &[u32; CLIPMAP_LOD_COUNT]) -> Option<usize> {
+        let mut total = 0;
+        let mut best: Option<usize> = None;
+        for (lod, &weight) in weights.iter().enumerate().take(self.active_lod_limit()) {
+            if self.lods[lod].pending_pages.is_empty() {
+                self.fairness_credit[lod] = 0;
+                continue;
+            }
+            let weight = i64::from(weight.max(1));
+            self.fairness_credit[lod] += weight;
+            total += weight;
+            if best.map_or(true, |best| {
+                self.fairness_credit[lod] > self.fairness_credit[best]
+            }) {
+                best = Some(lod);
+            }
+        }
+        let lod = best?;
+        self.fairness_credit[lod] -= total;
+        Some(lod)
+    }
+
     fn apply_built_page(&mut self, lod: usize, page: BuiltPage) {
         let page_coord = page.coord;
         if !self.is_page_in_coverage(lod, page_coord) {
@@ -616,16 +1815,66 @@ impl ClipmapStreamingController {
         }
 
         let page_index = Self::page_index_from_coord(page_coord);
+        let base_offset = page_index * PAGE_BRICKS;
 
-        self.clear_page_slot(lod, page_index);
+        // Synchronous edit rebuilds can land an all-air page on a slot that
+        // already holds it; skip the re-upload like the streamer does.
+        if page.is_empty()
+            && self.page_slot_matches_coord(lod, page_coord)
+            && self.lods[lod].page_occ[page_index] == [0, 0]
+            && self.lods[lod].page_brick_indices[base_offset..base_offset + PAGE_BRICKS]
+                .iter()
+                .all(|&id| id == 0)
+        {
+            return;
+        }
 
-        let base_offset = page_index * PAGE_BRICKS;
+        #[cfg(feature = "profiling")]
+        voxelicous_profiler::profile_scope!(
+            voxelicous_profiler::EventCategory::ClipmapEncode,
+            [
+                page_coord.0 as i32,
+                page_coord.1 as i32,
+                page_coord.2 as i32
+            ]
+        );
+
+        let was_emitter = lod == 0 && self.emitter_pages.contains(&page_coord);
+        if self.page_slot_matches_coord(lod, page_coord) {
+            self.release_page_slot(lod, page_index);
+        } else {
+            self.clear_page_slot(lod, page_index);
+        }
+
+        let level = if lod < self.fast_compression_lods {
+            CompressLevel::Fast
+        } else {
+            self.store.compression_policy().default_level()
+        };
+        let light = if lod == 0 {
+            let materials = &self.materials;
+            if page
+                .bricks
+                .iter()
+                .flatten()
+                .any(|&block| emitted_light(materials, block) > 0)
+            {
+                self.emitter_pages.insert(page_coord);
+            }
+            self.page_light(page_coord, &page.bricks)
+        } else {
+            Vec::new()
+        };
         let mut occ: u64 = 0;
         for (brick_idx, voxels) in page.bricks.iter().enumerate() {
-            let brick_id = self.store.allocate_brick(voxels);
+            let normals = page.normals.get(brick_idx).and_then(Option::as_ref);
+            let brick_id = self.store.allocate_brick_with(voxels, normals, level);
             self.lods[lod].page_brick_indices[base_offset + brick_idx] = brick_id.0;
 
             if brick_id.0 != 0 {
+                if let Some(light) = light.get(brick_idx).and_then(Option::as_ref) {
+                    self.store.set_brick_light(brick_id, light);
+                }
                 occ |= 1u64 << brick_idx;
                 self.mark_brick_dirty(brick_id);
             }
@@ -643,8 +1892,199 @@ impl ClipmapStreamingController {
         if !self.lods[lod].page_loaded[page_index] {
             self.lods[lod].page_loaded[page_index] = true;
             self.lods[lod].loaded_pages += 1;
+            // `occ` has a bit per allocated brick.
+            if occ == 0 {
+                self.lods[lod].empty_pages += 1;
+            }
         }
         self.lods[lod].dirty_pages.push(page_index);
+
+        if lod == 0 {
+            self.relight_neighbour_pages(page_coord, was_emitter);
+        }
+    }
+
+    /// Whether a finest-LOD page at `page_coord` or next to it emits light.
+    fn emitters_near(&self, page_coord: (i64, i64, i64)) -> bool {
+        !self.emitter_pages.is_empty()
+            && neighbour_page_offsets(true).any(|(dx, dy, dz)| {
+                self.emitter_pages.contains(&(
+                    page_coord.0 + dx,
+                    page_coord.1 + dy,
+                    page_coord.2 + dz,
+                ))
+            })
+    }
+
+    /// Block light of the finest-LOD page at `page_coord` holding `bricks`.
+    ///
+    /// Light floods in from emitters in the loaded neighbouring pages too.
+    /// Returns one entry per brick, `None` for unlit bricks, or an empty list
+    /// when no emitter is near.
+    fn page_light(
+        &self,
+        page_coord: (i64, i64, i64),
+        bricks: &[[BlockId; BRICK_VOXELS]],
+    ) -> Vec<Option<[u8; BRICK_VOXELS]>> {
+        if !self.emitters_near(page_coord) {
+            return Vec::new();
+        }
+
+        let axis = LIGHT_REGION_VOXELS;
+        let region = self.light_region(page_coord, bricks);
+        let region_light = propagate_block_light([axis; 3], &self.materials, |x, y, z| {
+            region[x + y * axis + z * axis * axis]
+        });
+
+        (0..PAGE_BRICKS)
+            .map(|brick_idx| {
+                let bx = brick_idx % PAGE_BRICKS_PER_AXIS;
+                let by = (brick_idx / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS;
+                let bz = brick_idx / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS);
+                let mut light = [0u8; BRICK_VOXELS];
+                for z in 0..BRICK_SIZE {
+                    for y in 0..BRICK_SIZE {
+                        for x in 0..BRICK_SIZE {
+                            let rx = LIGHT_HALO + bx * BRICK_SIZE + x;
+                            let ry = LIGHT_HALO + by * BRICK_SIZE + y;
+                            let rz = LIGHT_HALO + bz * BRICK_SIZE + z;
+                            light[x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE] =
+                                region_light[rx + ry * axis + rz * axis * axis];
+                        }
+                    }
+                }
+                light.iter().any(|&level| level > 0).then_some(light)
+            })
+            .collect()
+    }
+
+    /// Blocks of the finest-LOD page at `page_coord`, given as `bricks`, and
+    /// the [`LIGHT_HALO`] voxels around it read from loaded neighbours.
+    ///
+    /// Indexed like [`propagate_block_light`] over [`LIGHT_REGION_VOXELS`]
+    /// per axis. Neighbours that are not loaded read as air; they relight
+    /// this page when they land.
+    fn light_region(
+        &self,
+        page_coord: (i64, i64, i64),
+        bricks: &[[BlockId; BRICK_VOXELS]],
+    ) -> Vec<BlockId> {
+        let axis = LIGHT_REGION_VOXELS;
+        let mut region = vec![BlockId::AIR; axis * axis * axis];
+        let page_voxels = PAGE_VOXELS_PER_AXIS as i64;
+        let brick_voxels = BRICK_SIZE as i64;
+        let halo = LIGHT_HALO as i64;
+        let bricks_along =
+            div_floor(-halo, brick_voxels)..=div_floor(page_voxels + halo - 1, brick_voxels);
+
+        for bz in bricks_along.clone() {
+            for by in bricks_along.clone() {
+                for bx in bricks_along.clone() {
+                    let origin = [bx, by, bz].map(|b| b * brick_voxels);
+                    let Some(voxels) = self.light_region_brick(page_coord, origin, bricks) else {
+                        continue;
+                    };
+                    for (idx, &block) in voxels.iter().enumerate() {
+                        let local = [
+                            origin[0] + (idx % BRICK_SIZE) as i64,
+                            origin[1] + ((idx / BRICK_SIZE) % BRICK_SIZE) as i64,
+                            origin[2] + (idx / (BRICK_SIZE * BRICK_SIZE)) as i64,
+                        ];
+                        if local.iter().any(|&v| v < -halo || v >= page_voxels + halo) {
+                            continue;
+                        }
+                        let [rx, ry, rz] = local.map(|v| (v + halo) as usize);
+                        region[rx + ry * axis + rz * axis * axis] = block;
+                    }
+                }
+            }
+        }
+        region
+    }
+
+    /// Voxels of the brick at page-local voxel `origin` around `page_coord`,
+    /// or `None` when it is air or its page is not loaded.
+    fn light_region_brick(
+        &self,
+        page_coord: (i64, i64, i64),
+        origin: [i64; 3],
+        bricks: &[[BlockId; BRICK_VOXELS]],
+    ) -> Option<[BlockId; BRICK_VOXELS]> {
+        let page_voxels = PAGE_VOXELS_PER_AXIS as i64;
+        let brick_voxels = BRICK_SIZE as i64;
+        let [bx, by, bz] = origin.map(|v| (mod_floor(v, page_voxels) / brick_voxels) as usize);
+        let brick_idx =
+            bx + by * PAGE_BRICKS_PER_AXIS + bz * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+        let [dx, dy, dz] = origin.map(|v| div_floor(v, page_voxels));
+        if (dx, dy, dz) == (0, 0, 0) {
+            return bricks.get(brick_idx).copied();
+        }
+
+        let neighbour = (page_coord.0 + dx, page_coord.1 + dy, page_coord.2 + dz);
+        if !self.page_slot_matches_coord(0, neighbour) {
+            return None;
+        }
+        let page_index = Self::page_index_from_coord(neighbour);
+        let id = self.lods[0].page_brick_indices[page_index * PAGE_BRICKS + brick_idx];
+        if id == 0 {
+            return None;
+        }
+        self.store.decode_brick(BrickId(id))
+    }
+
+    /// Relight the loaded finest-LOD pages around `page_coord` after it
+    /// landed, when light can reach them. `was_emitter` says whether the
+    /// page it replaced emitted light, which may have to be taken back.
+    fn relight_neighbour_pages(&mut self, page_coord: (i64, i64, i64), was_emitter: bool) {
+        for (dx, dy, dz) in neighbour_page_offsets(false) {
+            let neighbour = (page_coord.0 + dx, page_coord.1 + dy, page_coord.2 + dz);
+            if self.page_slot_matches_coord(0, neighbour)
+                && (was_emitter || self.emitters_near(neighbour))
+            {
+                self.relight_page(neighbour);
+            }
+        }
+    }
+
+    /// Recompute the block light of a loaded finest-LOD page in place.
+    fn relight_page(&mut self, page_coord: (i64, i64, i64)) {
+        let base_offset = Self::page_index_from_coord(page_coord) * PAGE_BRICKS;
+        let ids: Vec<BrickId> = self.lods[0].page_brick_indices
+            [base_offset..base_offset + PAGE_BRICKS]
+            .iter()
+            .map(|&id| BrickId(id))
+            .collect();
+        // All-air pages have no bricks to hold light.
+        if ids.iter().all(|id| id.0 == 0) {
+            return;
+        }
+
+        let bricks: Vec<_> = ids
+            .iter()
+            .map(|&id| {
+                if id.0 == 0 {
+                    [BlockId::AIR; BRICK_VOXELS]
+                } else {
+                    self.store
+                        .decode_brick(id)
+                        .unwrap_or([BlockId::AIR; BRICK_VOXELS])
+                }
+            })
+            .collect();
+        let light = self.page_light(page_coord, &bricks);
+        for (brick_idx, &id) in ids.iter().enumerate() {
+            if id.0 == 0 {
+                continue;
+            }
+            match light.get(brick_idx).and_then(Option::as_ref) {
+                Some(light) => self.store.set_brick_light(id, light),
+                None if self.store.brick_light(id).is_some() => {
+                    self.store.set_brick_light(id, &[0; BRICK_VOXELS]);
+                }
+                None => continue,
+            }
+            self.mark_brick_dirty(id);
+        }
     }
 
     fn invalidate_page_slot(&mut self, lod: usize, page_coord: (i64, i64, i64)) {
@@ -652,7 +2092,18 @@ impl ClipmapStreamingController {
         self.clear_page_slot(lod, page_index);
     }
 
+    /// Unload the page in a slot, recording it as unloaded.
     fn clear_page_slot(&mut self, lod: usize, page_index: usize) {
+        let state = &mut self.lods[lod];
+        if state.page_loaded[page_index] {
+            let coord = slot_coord_to_page(state.page_coords[page_index]);
+            state.unloaded_pages.push(coord);
+        }
+        self.release_page_slot(lod, page_index);
+    }
+
+    /// Free a slot's bricks and mark it empty.
+    fn release_page_slot(&mut self, lod: usize, page_index: usize) {
         let base_offset = page_index * PAGE_BRICKS;
         let mut had_data = false;
         let mut ids_to_free = Vec::new();
@@ -671,12 +2122,19 @@ impl ClipmapStreamingController {
         }
         self.lods[lod].page_occ[page_index] = [0, 0];
         if self.lods[lod].page_coords[page_index] != invalid_page_coord() {
+            if lod == 0 {
+                let coord = slot_coord_to_page(self.lods[lod].page_coords[page_index]);
+                self.emitter_pages.remove(&coord);
+            }
             self.lods[lod].page_coords[page_index] = invalid_page_coord();
             had_data = true;
         }
         if self.lods[lod].page_loaded[page_index] {
             self.lods[lod].page_loaded[page_index] = false;
             self.lods[lod].loaded_pages = self.lods[lod].loaded_pages.saturating_sub(1);
+            if ids_to_free.is_empty() {
+                self.lods[lod].empty_pages = self.lods[lod].empty_pages.saturating_sub(1);
+            }
             had_data = true;
         }
 
@@ -693,22 +2151,66 @@ impl ClipmapStreamingController {
         if id.0 == 0 {
             return;
         }
+        self.queue_deferred_free(DeferredFree::Brick(id));
+    }
+
+    fn queue_deferred_free(&mut self, free: DeferredFree) {
         let release_frame = self
             .frame_counter
             .wrapping_add(Self::BRICK_FREE_DELAY_FRAMES);
-        self.pending_brick_frees.push_back((release_frame, id));
+        self.pending_brick_frees.push_back((release_frame, free));
+    }
+
+    /// Evict retired pages whose hysteresis window has passed.
+    ///
+    /// Slots that were reused or whose page is visible again are left alone.
+    fn evict_retired_pages(&mut self) {
+        for lod in 0..CLIPMAP_LOD_COUNT {
+            if self.lods[lod].retired_pages.is_empty() {
+                continue;
+            }
+            let frame = self.frame_counter;
+            let (due, waiting): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut self.lods[lod].retired_pages)
+                    .into_iter()
+                    .partition(|&(evict_frame, _, _)| evict_frame <= frame);
+            self.lods[lod].retired_pages = waiting;
+            let mut due: Vec<_> = due
+                .into_iter()
+                .map(|(_, page_index, coord)| (page_index, coord))
+                .collect();
+            due.sort_by_key(|&(_, coord)| page_morton_code(slot_coord_to_page(coord)));
+            for (page_index, coord) in due {
+                let page_coord = slot_coord_to_page(coord);
+                if self.lods[lod].page_coords[page_index] == coord
+                    && !self.is_page_in_coverage(lod, page_coord)
+                {
+                    self.clear_page_slot(lod, page_index);
+                }
+            }
+        }
     }
 
     fn process_deferred_brick_frees(&mut self) {
-        while let Some((release_frame, id)) = self.pending_brick_frees.front().copied() {
+        while let Some((release_frame, free)) = self.pending_brick_frees.front().copied() {
             if release_frame > self.frame_counter {
                 break;
             }
             self.pending_brick_frees.pop_front();
-            self.store.free_brick(id);
+            match free {
+                DeferredFree::Brick(id) => self.store.free_brick(id),
+                DeferredFree::Payload(payload) => self.store.free_payload(payload),
+            }
         }
     }
 
+    /// Order slots by the Morton code of the pages they hold, so unloads
+    /// happen in the same order whichever slots the pages occupy.
+    fn sort_slots_by_page(&self, lod: usize, slots: &mut [usize]) {
+        let coords = &self.lods[lod].page_coords;
+        slots.sort_by_key(|&slot| page_morton_code(slot_coord_to_page(coords[slot])));
+    }
+
     fn page_index_from_coord(page_coord: (i64, i64, i64)) -> usize {
         let page_ix = mod_floor(page_coord.0, CLIPMAP_PAGE_GRID as i64) as usize;
         let page_iy = mod_floor(page_coord.1, CLIPMAP_PAGE_GRID as i64) as usize;
@@ -726,9 +2228,7 @@ impl ClipmapStreamingController {
             div_floor(origin.y, page_size),
             div_floor(origin.z, page_size),
         );
-        let grid = self.visible_page_grid as i64;
-
-        is_page_coord_in_range(page_coord, origin_page, grid)
+        is_page_coord_in_range(page_coord, origin_page, self.page_grid_dims())
     }
 
     fn active_lod_limit(&self) -> usize {
@@ -744,7 +2244,7 @@ impl ClipmapStreamingController {
     }
 
     fn pending_page_budget(&self, apply_budget: usize) -> usize {
-        (Self::MAX_INFLIGHT_PAGE_JOBS + apply_budget) * Self::PENDING_PAGE_BACKLOG_FRAMES
+        (self.max_inflight_page_jobs() + apply_budget) * Self::PENDING_PAGE_BACKLOG_FRAMES
     }
 
     fn reduce_coords_to_pending_budget(
@@ -757,9 +2257,9 @@ impl ClipmapStreamingController {
             return;
         }
 
-        let camera_voxel = self.camera_voxel;
+        let priority_voxel = self.priority_voxel();
         coords.select_nth_unstable_by_key(pending_budget, |&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
+            page_load_key(coord, priority_voxel, page_size)
         });
         coords.truncate(pending_budget);
     }
@@ -821,10 +2321,8 @@ impl ClipmapStreamingController {
             self.reduce_coords_to_pending_budget(page_size, &mut merged, pending_budget);
         }
 
-        let camera_voxel = self.camera_voxel;
-        merged.sort_unstable_by_key(|&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
-        });
+        let priority_voxel = self.priority_voxel();
+        merged.sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
         self.lods[lod].pending_pages = merged.into();
         if !self.lods[lod].pending_pages.is_empty() || self.lods[lod].inflight_pages > 0 {
             self.lods[lod].ready = false;
@@ -852,26 +2350,26 @@ impl ClipmapStreamingController {
             div_floor(origin.z, page_size),
         );
 
-        let mut nearest: BinaryHeap<(i128, (i64, i64, i64))> = BinaryHeap::new();
-        let grid = self.visible_page_grid as i64;
-        let camera_voxel = self.camera_voxel;
-        for z in 0..grid {
-            for y in 0..grid {
-                for x in 0..grid {
+        let mut nearest: BinaryHeap<(PageLoadKey, (i64, i64, i64))> = BinaryHeap::new();
+        let grid = self.page_grid_dims();
+        let priority_voxel = self.priority_voxel();
+        for z in 0..grid.2 {
+            for y in 0..grid.1 {
+                for x in 0..grid.0 {
                     let coord = (origin_page.0 + x, origin_page.1 + y, origin_page.2 + z);
                     if self.page_slot_matches_coord(lod, coord) {
                         continue;
                     }
 
-                    let distance = page_distance_to_camera_sq(coord, camera_voxel, page_size);
+                    let key = page_load_key(coord, priority_voxel, page_size);
                     if nearest.len() < pending_budget {
-                        nearest.push((distance, coord));
+                        nearest.push((key, coord));
                         continue;
                     }
 
-                    if matches!(nearest.peek(), Some((farthest, _)) if distance < *farthest) {
+                    if matches!(nearest.peek(), Some((farthest, _)) if key < *farthest) {
                         nearest.pop();
-                        nearest.push((distance, coord));
+                        nearest.push((key, coord));
                     }
                 }
             }
@@ -882,9 +2380,7 @@ impl ClipmapStreamingController {
         }
 
         let mut coords: Vec<_> = nearest.into_iter().map(|(_, coord)| coord).collect();
-        coords.sort_unstable_by_key(|&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
-        });
+        coords.sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
         self.enqueue_pending_pages(lod, coords, false, pending_budget);
     }
 
@@ -905,14 +2401,13 @@ impl ClipmapStreamingController {
     fn reconfigure_visible_coverage_for_lod(&mut self, lod: usize) {
         let voxel_size = self.lod_voxel_size(lod);
         let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-        let coverage = self.lod_coverage(lod);
-        let origin = aligned_origin(self.camera_voxel, coverage, page_size);
+        let origin = self.lod_origin_around(lod, self.camera_voxel);
         let origin_page = (
             div_floor(origin.x, page_size),
             div_floor(origin.y, page_size),
             div_floor(origin.z, page_size),
         );
-        let grid = self.visible_page_grid as i64;
+        let grid = self.page_grid_dims();
 
         {
             let lod_state = &mut self.lods[lod];
@@ -922,7 +2417,7 @@ impl ClipmapStreamingController {
             lod_state.ready = false;
         }
 
-        // Evict currently loaded pages that are now outside the visible range.
+        // Retire currently loaded pages that are now outside the visible range.
         let mut stale_slots = Vec::new();
         for (page_index, slot_coord) in self.lods[lod].page_coords.iter().enumerate() {
             if *slot_coord == invalid_page_coord() {
@@ -937,22 +2432,38 @@ impl ClipmapStreamingController {
                 stale_slots.push(page_index);
             }
         }
-        for page_index in stale_slots {
-            self.clear_page_slot(lod, page_index);
+        if self.visible_grid_hysteresis_frames == 0 {
+            self.sort_slots_by_page(lod, &mut stale_slots);
+            for page_index in stale_slots {
+                self.clear_page_slot(lod, page_index);
+            }
+        } else {
+            // Pages retired by an earlier shrink keep their original deadline.
+            let evict_frame = self
+                .frame_counter
+                .wrapping_add(self.visible_grid_hysteresis_frames);
+            let lod_state = &mut self.lods[lod];
+            let retired: HashSet<_> = lod_state
+                .retired_pages
+                .iter()
+                .map(|&(_, page_index, _)| page_index)
+                .collect();
+            for page_index in stale_slots {
+                if !retired.contains(&page_index) {
+                    let coord = lod_state.page_coords[page_index];
+                    lod_state
+                        .retired_pages
+                        .push((evict_frame, page_index, coord));
+                }
+            }
         }
 
         // Enqueue only pages missing from the current visible range.
-        let visible_grid = self.visible_page_grid;
-        let mut missing_coords = Vec::new();
-        missing_coords.reserve(visible_grid * visible_grid * visible_grid);
-        for z in 0..visible_grid {
-            for y in 0..visible_grid {
-                for x in 0..visible_grid {
-                    let coord = (
-                        origin_page.0 + x as i64,
-                        origin_page.1 + y as i64,
-                        origin_page.2 + z as i64,
-                    );
+        let mut missing_coords = Vec::with_capacity((grid.0 * grid.1 * grid.2) as usize);
+        for z in 0..grid.2 {
+            for y in 0..grid.1 {
+                for x in 0..grid.0 {
+                    let coord = (origin_page.0 + x, origin_page.1 + y, origin_page.2 + z);
                     if !self.page_slot_matches_coord(lod, coord) {
                         missing_coords.push(coord);
                     }
@@ -962,10 +2473,9 @@ impl ClipmapStreamingController {
 
         let pending_budget = self.pending_page_budget(self.current_apply_budget());
         self.reduce_coords_to_pending_budget(page_size, &mut missing_coords, pending_budget);
-        let camera_voxel = self.camera_voxel;
-        missing_coords.sort_unstable_by_key(|&coord| {
-            page_distance_to_camera_sq(coord, camera_voxel, page_size)
-        });
+        let priority_voxel = self.priority_voxel();
+        missing_coords
+            .sort_unstable_by_key(|&coord| page_load_key(coord, priority_voxel, page_size));
         self.enqueue_pending_pages(lod, missing_coords, false, pending_budget);
     }
 
@@ -974,6 +2484,7 @@ impl ClipmapStreamingController {
             let lod_state = &mut self.lods[lod];
             lod_state.generation = lod_state.generation.wrapping_add(1);
             lod_state.pending_pages.clear();
+            lod_state.retired_pages.clear();
             lod_state.inflight_pages = 0;
             lod_state.origin = None;
             lod_state.ready = false;
@@ -986,6 +2497,7 @@ impl ClipmapStreamingController {
                 loaded_slots.push(page_index);
             }
         }
+        self.sort_slots_by_page(lod, &mut loaded_slots);
         for page_index in loaded_slots {
             self.clear_page_slot(lod, page_index);
         }
@@ -1018,23 +2530,66 @@ impl ClipmapStreamingController {
                 }
                 None => {}
             }
+            if header.flags & BRICK_FLAG_NORMALS != 0 {
+                self.dirty_normal_entries.push(header.normal_index);
+            }
+            if header.flags & BRICK_FLAG_LIGHT != 0 {
+                self.dirty_light_entries.push(header.light_index);
+            }
+        }
+    }
+
+    /// Record a single edit without touching pages.
+    ///
+    /// Returns the replaced block when the effective value changed.
+    fn record_edit(&mut self, coord: WorldCoord, block: BlockId) -> Option<BlockId> {
+        let generated = self.generator.block_at_world(coord.x, coord.y, coord.z);
+        if generated == BlockId::BEDROCK {
+            return None;
+        }
+        let previous = self.block_at_world(coord.x, coord.y, coord.z);
+        if previous == block {
+            return None;
+        }
+
+        // Store only differences from procedural terrain.
+        if block == generated {
+            self.edits.remove(&coord);
+        } else {
+            self.edits.insert(coord, block);
+        }
+        Some(previous)
+    }
+
+    /// Publish recorded edits and rebuild/enqueue every page they touch.
+    fn commit_edits(&mut self, coords: &[WorldCoord]) {
+        if coords.is_empty() {
+            return;
         }
+        self.edit_snapshot = Arc::new(self.edits.clone());
+        self.apply_edits_immediate(coords);
+        self.enqueue_pages_affected_by_edits(coords);
     }
 
-    fn apply_edit_immediate(&mut self, world: WorldCoord) {
+    fn apply_edits_immediate(&mut self, coords: &[WorldCoord]) {
         let sync_lods = Self::SYNC_EDIT_LODS.min(self.active_lod_limit());
         let edits_snapshot = Arc::clone(&self.edit_snapshot);
 
         for lod in 0..sync_lods {
-            let affected_pages = self.affected_pages_for_edit(lod, world);
+            let affected_pages = self.affected_pages_for_edits(lod, coords);
             let voxel_size = self.lod_voxel_size(lod);
             for page_coord in affected_pages {
                 if !self.is_page_in_coverage(lod, page_coord) {
                     continue;
                 }
 
-                let page =
-                    build_page_voxels(&self.generator, &edits_snapshot, page_coord, voxel_size);
+                let page = build_page_voxels(
+                    &self.generator,
+                    &edits_snapshot,
+                    page_coord,
+                    voxel_size,
+                    self.lod_seam(lod),
+                );
                 self.apply_built_page(lod, page);
                 self.lods[lod]
                     .pending_pages
@@ -1044,7 +2599,7 @@ impl ClipmapStreamingController {
         }
     }
 
-    fn enqueue_pages_affected_by_edit(&mut self, world: WorldCoord) {
+    fn enqueue_pages_affected_by_edits(&mut self, coords: &[WorldCoord]) {
         let pending_budget = self.pending_page_budget(self.current_apply_budget());
         for lod in Self::SYNC_EDIT_LODS.min(self.active_lod_limit())..self.active_lod_limit() {
             if self.lods[lod].origin.is_none() {
@@ -1052,7 +2607,7 @@ impl ClipmapStreamingController {
             }
 
             let mut pending_edit_coords = Vec::new();
-            for page_coord in self.affected_pages_for_edit(lod, world) {
+            for page_coord in self.affected_pages_for_edits(lod, coords) {
                 if !self.is_page_in_coverage(lod, page_coord) {
                     continue;
                 }
@@ -1066,6 +2621,20 @@ impl ClipmapStreamingController {
         }
     }
 
+    /// Unique pages touched by a batch of edits, in first-seen order.
+    fn affected_pages_for_edits(&self, lod: usize, coords: &[WorldCoord]) -> Vec<(i64, i64, i64)> {
+        let mut seen = HashSet::new();
+        let mut affected_pages = Vec::new();
+        for &world in coords {
+            for page_coord in self.affected_pages_for_edit(lod, world) {
+                if seen.insert(page_coord) {
+                    affected_pages.push(page_coord);
+                }
+            }
+        }
+        affected_pages
+    }
+
     fn affected_pages_for_edit(&self, lod: usize, world: WorldCoord) -> Vec<(i64, i64, i64)> {
         let voxel_size = self.lod_voxel_size(lod);
         let half = voxel_size / 2;
@@ -1104,6 +2673,79 @@ impl ClipmapStreamingController {
     }
 }
 
+/// Box where the ray marcher hands a coarse LOD over to the next finer one.
+///
+/// The finer LOD's coverage shrunk by one of its pages, matching the
+/// transition band in the shader. Coarse voxels straddling its faces sit
+/// next to fine voxels on screen and are stitched to them.
+///
+/// Only the default handover is modelled. Explicit renderer transition
+/// distances (`ClipmapRenderer::set_lod_transition_distances`) move the
+/// handover to a box around the camera, which this seam does not follow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LodSeam {
+    min: WorldCoord,
+    max: WorldCoord,
+}
+
+impl LodSeam {
+    fn new(fine_origin: WorldCoord, fine_coverage: (i64, i64), fine_voxel_size: i64) -> Self {
+        let (xz, y) = fine_coverage;
+        let band = PAGE_VOXELS_PER_AXIS as i64 * fine_voxel_size;
+        // The shader keeps the full box when the band would swallow it.
+        let band = if xz > 2 * band && y > 2 * band {
+            band
+        } else {
+            0
+        };
+        Self {
+            min: WorldCoord {
+                x: fine_origin.x + band,
+                y: fine_origin.y + band,
+                z: fine_origin.z + band,
+            },
+            max: WorldCoord {
+                x: fine_origin.x + xz - band,
+                y: fine_origin.y + y - band,
+                z: fine_origin.z + xz - band,
+            },
+        }
+    }
+
+    /// Whether the coarse voxel at `world` lies within one voxel of a face.
+    fn touches(&self, world_x: i64, world_y: i64, world_z: i64, voxel_size: i64) -> bool {
+        let axes = [
+            (world_x, self.min.x, self.max.x),
+            (world_y, self.min.y, self.max.y),
+            (world_z, self.min.z, self.max.z),
+        ];
+        let near = axes
+            .iter()
+            .all(|&(p, min, max)| p + voxel_size > min - voxel_size && p < max + voxel_size);
+        let deep_inside = axes
+            .iter()
+            .all(|&(p, min, max)| p >= min + voxel_size && p + voxel_size <= max - voxel_size);
+        near && !deep_inside
+    }
+
+    /// Whether any voxel of the page at `page_coord` [`Self::touches`] a face.
+    fn crosses_page(&self, page_coord: (i64, i64, i64), voxel_size: i64) -> bool {
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let axes = [
+            (page_coord.0 * page_size, self.min.x, self.max.x),
+            (page_coord.1 * page_size, self.min.y, self.max.y),
+            (page_coord.2 * page_size, self.min.z, self.max.z),
+        ];
+        let near = axes
+            .iter()
+            .all(|&(lo, min, max)| lo + page_size > min - voxel_size && lo < max + voxel_size);
+        let deep_inside = axes
+            .iter()
+            .all(|&(lo, min, max)| lo >= min + voxel_size && lo + page_size <= max - voxel_size);
+        near && !deep_inside
+    }
+}
+
 #[derive(Clone, Copy)]
 enum Axis {
     X,
@@ -1111,11 +2753,53 @@ enum Axis {
     Z,
 }
 
-fn aligned_origin(camera: WorldCoord, coverage: i64, page_size: i64) -> WorldCoord {
-    let half = coverage / 2;
-    let ox = div_floor(camera.x - half, page_size) * page_size;
-    let oy = div_floor(camera.y - half, page_size) * page_size;
-    let oz = div_floor(camera.z - half, page_size) * page_size;
+/// Voxels within `radius` of `center`, in Z, Y, X order.
+pub(crate) fn sphere_voxels(center: WorldCoord, radius: i64) -> impl Iterator<Item = WorldCoord> {
+    let radius = radius.max(0);
+    let radius_sq = radius * radius;
+    box_voxels(
+        WorldCoord {
+            x: center.x - radius,
+            y: center.y - radius,
+            z: center.z - radius,
+        },
+        WorldCoord {
+            x: center.x + radius,
+            y: center.y + radius,
+            z: center.z + radius,
+        },
+    )
+    .filter(move |coord| {
+        let (dx, dy, dz) = (coord.x - center.x, coord.y - center.y, coord.z - center.z);
+        dx * dx + dy * dy + dz * dz <= radius_sq
+    })
+}
+
+/// Voxels of the inclusive box between two corners, in Z, Y, X order.
+pub(crate) fn box_voxels(min: WorldCoord, max: WorldCoord) -> impl Iterator<Item = WorldCoord> {
+    let (lo_x, hi_x) = (min.x.min(max.x), min.x.max(max.x));
+    let (lo_y, hi_y) = (min.y.min(max.y), min.y.max(max.y));
+    let (lo_z, hi_z) = (min.z.min(max.z), min.z.max(max.z));
+    (lo_z..=hi_z).flat_map(move |z| {
+        (lo_y..=hi_y).flat_map(move |y| (lo_x..=hi_x).map(move |x| WorldCoord { x, y, z }))
+    })
+}
+
+fn camera_voxel_of(camera_pos: Vec3) -> WorldCoord {
+    WorldCoord {
+        x: camera_pos.x.floor() as i64,
+        y: camera_pos.y.floor() as i64,
+        z: camera_pos.z.floor() as i64,
+    }
+}
+
+/// Page-aligned origin of a `(horizontal, vertical)` coverage centered on
+/// `camera`.
+fn aligned_origin(camera: WorldCoord, coverage: (i64, i64), page_size: i64) -> WorldCoord {
+    let (half_xz, half_y) = (coverage.0 / 2, coverage.1 / 2);
+    let ox = div_floor(camera.x - half_xz, page_size) * page_size;
+    let oy = div_floor(camera.y - half_y, page_size) * page_size;
+    let oz = div_floor(camera.z - half_xz, page_size) * page_size;
     WorldCoord {
         x: ox,
         y: oy,
@@ -1126,14 +2810,58 @@ fn aligned_origin(camera: WorldCoord, coverage: i64, page_size: i64) -> WorldCoo
 fn is_page_coord_in_range(
     page_coord: (i64, i64, i64),
     origin_page: (i64, i64, i64),
-    grid: i64,
+    grid: (i64, i64, i64),
 ) -> bool {
     page_coord.0 >= origin_page.0
-        && page_coord.0 < origin_page.0 + grid
+        && page_coord.0 < origin_page.0 + grid.0
         && page_coord.1 >= origin_page.1
-        && page_coord.1 < origin_page.1 + grid
+        && page_coord.1 < origin_page.1 + grid.1
         && page_coord.2 >= origin_page.2
-        && page_coord.2 < origin_page.2 + grid
+        && page_coord.2 < origin_page.2 + grid.2
+}
+
+/// Sort key from [`page_load_key`]: squared distance, then Morton code.
+type PageLoadKey = (i128, u64);
+
+/// Load order of a page: nearest to the camera first, with ties broken by
+/// the page's Morton code so equal-distance pages always stream in the same
+/// order and captures stay reproducible.
+fn page_load_key(
+    page_coord: (i64, i64, i64),
+    camera_voxel: WorldCoord,
+    page_size: i64,
+) -> PageLoadKey {
+    (
+        page_distance_to_camera_sq(page_coord, camera_voxel, page_size),
+        page_morton_code(page_coord),
+    )
+}
+
+/// Page coordinate stored in a slot's `page_coords` entry.
+fn slot_coord_to_page(coord: [i32; 4]) -> (i64, i64, i64) {
+    (
+        i64::from(coord[0]),
+        i64::from(coord[1]),
+        i64::from(coord[2]),
+    )
+}
+
+/// Z-order code of a page coordinate.
+///
+/// Interleaves 21 bits per axis, biased by `2^20` so negative coordinates
+/// sort below positive ones; that covers far more pages than a clipmap
+/// ever has in flight.
+fn page_morton_code(page_coord: (i64, i64, i64)) -> u64 {
+    fn spread(value: i64) -> u64 {
+        let mut bits = (value.wrapping_add(1 << 20) as u64) & 0x1F_FFFF;
+        bits = (bits | bits << 32) & 0x001F_0000_0000_FFFF;
+        bits = (bits | bits << 16) & 0x001F_0000_FF00_00FF;
+        bits = (bits | bits << 8) & 0x100F_00F0_0F00_F00F;
+        bits = (bits | bits << 4) & 0x10C3_0C30_C30C_30C3;
+        bits = (bits | bits << 2) & 0x1249_2492_4924_9249;
+        bits
+    }
+    spread(page_coord.0) | spread(page_coord.1) << 1 | spread(page_coord.2) << 2
 }
 
 fn page_distance_to_camera_sq(
@@ -1162,7 +2890,19 @@ fn build_page_voxels(
     edits: &HashMap<WorldCoord, BlockId>,
     page_coord: (i64, i64, i64),
     voxel_size: i64,
+    seam: Option<LodSeam>,
 ) -> BuiltPage {
+    // Tagged with the page coordinate so slow pages show up in the profiler.
+    #[cfg(feature = "profiling")]
+    voxelicous_profiler::profile_scope!(
+        voxelicous_profiler::EventCategory::ClipmapPageBuild,
+        [
+            page_coord.0 as i32,
+            page_coord.1 as i32,
+            page_coord.2 as i32
+        ]
+    );
+
     let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
     let page_origin = WorldCoord {
         x: page_coord.0 * page_size,
@@ -1196,9 +2936,18 @@ fn build_page_voxels(
                             let world_z = brick_origin.z + (z as i64) * voxel_size;
 
                             let idx = x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE;
-                            let block = sample_voxel_from_generator(
-                                generator, edits, world_x, world_y, world_z, voxel_size,
-                            );
+                            let on_seam = seam.is_some_and(|seam| {
+                                seam.touches(world_x, world_y, world_z, voxel_size)
+                            });
+                            let block = if on_seam {
+                                sample_stitched_voxel(
+                                    generator, edits, world_x, world_y, world_z, voxel_size,
+                                )
+                            } else {
+                                sample_voxel_from_generator(
+                                    generator, edits, world_x, world_y, world_z, voxel_size,
+                                )
+                            };
                             voxels[idx] = block;
                             any_solid |= block.is_solid();
                         }
@@ -1216,13 +2965,79 @@ fn build_page_voxels(
         }
     }
 
+    if all_air(&bricks) {
+        return BuiltPage::empty(page_coord);
+    }
+
+    // Coarse voxels are large enough that face normals look faceted; store
+    // smoothed normals so the shader can light slopes.
+    let normals = compute_page_normals(&bricks);
+
     BuiltPage {
         coord: page_coord,
         bricks,
+        normals,
         occ,
     }
 }
 
+/// Page cache a page build reads and writes.
+struct PageCacheTarget {
+    dir: Arc<Path>,
+    generator_key: u64,
+    codec: CacheCodec,
+}
+
+/// [`build_page_voxels`] through the page cache `cache`.
+///
+/// A cached page is loaded instead of generated; a missing or unreadable one
+/// is generated and stored. Pages with edits in or next to them bypass the
+/// cache, which only holds generator output. The cache is best effort, so a
+/// failed store only costs a rebuild next time.
+fn build_page_voxels_cached(
+    cache: &PageCacheTarget,
+    generator: &TerrainGenerator,
+    edits: &HashMap<WorldCoord, BlockId>,
+    page_coord: (i64, i64, i64),
+    voxel_size: i64,
+    seam: Option<LodSeam>,
+) -> BuiltPage {
+    if seam.is_some_and(|seam| seam.crosses_page(page_coord, voxel_size))
+        || page_has_edits(edits, page_coord, voxel_size)
+    {
+        return build_page_voxels(generator, edits, page_coord, voxel_size, seam);
+    }
+    let path = page_cache::page_path(&cache.dir, cache.generator_key, page_coord, voxel_size);
+    if let Ok(bricks) = page_cache::load_page(&path) {
+        return BuiltPage::from_bricks(page_coord, bricks, voxel_size);
+    }
+    let page = build_page_voxels(generator, edits, page_coord, voxel_size, seam);
+    let _ = page_cache::store_page(&path, &page.bricks, cache.codec);
+    page
+}
+
+/// Whether any edit lies in the page or within one voxel of its border,
+/// close enough to change what the page builds.
+fn page_has_edits(
+    edits: &HashMap<WorldCoord, BlockId>,
+    page_coord: (i64, i64, i64),
+    voxel_size: i64,
+) -> bool {
+    let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+    let min = [page_coord.0, page_coord.1, page_coord.2].map(|c| c * page_size - voxel_size);
+    has_edits_in(edits, min, page_size + 2 * voxel_size)
+}
+
+/// Whether any edit lies in the cube of `size` voxels at `min`.
+fn has_edits_in(edits: &HashMap<WorldCoord, BlockId>, min: [i64; 3], size: i64) -> bool {
+    edits.keys().any(|world| {
+        [world.x, world.y, world.z]
+            .into_iter()
+            .zip(min)
+            .all(|(v, min)| (min..min + size).contains(&v))
+    })
+}
+
 #[cfg_attr(
     feature = "profiling-tracy",
     tracing::instrument(level = "trace", skip_all)
@@ -1303,13 +3118,109 @@ fn build_page_voxels_unit_lod(
         }
     }
 
+    if all_air(&bricks) {
+        return BuiltPage::empty(page_coord);
+    }
+
     BuiltPage {
         coord: page_coord,
         bricks,
+        normals: Vec::new(),
         occ,
     }
 }
 
+/// Build the bricks of one page straight from the generator, ignoring edits.
+///
+/// Only exposed with the `bench` feature; the controller builds pages
+/// internally.
+#[cfg(feature = "bench")]
+#[must_use]
+pub fn build_page_bricks(
+    generator: &TerrainGenerator,
+    page_coord: (i64, i64, i64),
+    voxel_size: i64,
+) -> Vec<[BlockId; BRICK_VOXELS]> {
+    let page = build_page_voxels(generator, &HashMap::new(), page_coord, voxel_size, None);
+    if page.is_empty() {
+        vec![[BlockId::AIR; BRICK_VOXELS]; PAGE_BRICKS]
+    } else {
+        page.bricks
+    }
+}
+
+/// Estimate per-voxel surface normals for a page from neighbour occupancy.
+///
+/// Returns one entry per brick; bricks without any non-axis-aligned surface
+/// voxel get `None` and keep using face normals.
+fn compute_page_normals(bricks: &[[BlockId; BRICK_VOXELS]]) -> Vec<Option<[u8; BRICK_VOXELS]>> {
+    let axis = PAGE_VOXELS_PER_AXIS as i64;
+    let solid_at = |x: i64, y: i64, z: i64| -> Option<bool> {
+        if x < 0 || y < 0 || z < 0 || x >= axis || y >= axis || z >= axis {
+            return None;
+        }
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        let brick_idx = x / BRICK_SIZE
+            + (y / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS
+            + (z / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+        let voxel_idx = x % BRICK_SIZE
+            + (y % BRICK_SIZE) * BRICK_SIZE
+            + (z % BRICK_SIZE) * BRICK_SIZE * BRICK_SIZE;
+        Some(bricks[brick_idx][voxel_idx].is_solid())
+    };
+
+    bricks
+        .iter()
+        .enumerate()
+        .map(|(brick_idx, voxels)| {
+            if voxels.iter().all(|v| v.is_air()) {
+                return None;
+            }
+
+            let bx = brick_idx % PAGE_BRICKS_PER_AXIS;
+            let by = (brick_idx / PAGE_BRICKS_PER_AXIS) % PAGE_BRICKS_PER_AXIS;
+            let bz = brick_idx / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS);
+            let mut normals = [0u8; BRICK_VOXELS];
+            let mut any_normal = false;
+            for z in 0..BRICK_SIZE {
+                for y in 0..BRICK_SIZE {
+                    for x in 0..BRICK_SIZE {
+                        let idx = x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE;
+                        if !voxels[idx].is_solid() {
+                            continue;
+                        }
+                        let px = (bx * BRICK_SIZE + x) as i64;
+                        let py = (by * BRICK_SIZE + y) as i64;
+                        let pz = (bz * BRICK_SIZE + z) as i64;
+                        let code = estimate_surface_normal(|dx, dy, dz| {
+                            solid_at(px + i64::from(dx), py + i64::from(dy), pz + i64::from(dz))
+                        });
+                        normals[idx] = code;
+                        any_normal |= code != 0;
+                    }
+                }
+            }
+
+            any_normal.then_some(normals)
+        })
+        .collect()
+}
+
+/// Voxels around a page that emitters can light it from; light loses one
+/// level per voxel, so nothing further out reaches it.
+const LIGHT_HALO: usize = MAX_BLOCK_LIGHT as usize - 1;
+
+/// Voxels per axis of a page plus its [`LIGHT_HALO`] on both sides.
+const LIGHT_REGION_VOXELS: usize = PAGE_VOXELS_PER_AXIS + 2 * LIGHT_HALO;
+
+/// Offsets of the 26 pages around a page, and the page itself with
+/// `include_center`.
+fn neighbour_page_offsets(include_center: bool) -> impl Iterator<Item = (i64, i64, i64)> {
+    (-1..=1)
+        .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))))
+        .filter(move |&offset| include_center || offset != (0, 0, 0))
+}
+
 fn build_tree_voxel_overlay(generator: &TerrainGenerator, page_origin: WorldCoord) -> Vec<BlockId> {
     let mut overlay =
         vec![BlockId::AIR; PAGE_VOXELS_PER_AXIS * PAGE_VOXELS_PER_AXIS * PAGE_VOXELS_PER_AXIS];
@@ -1391,6 +3302,37 @@ fn sample_voxel_from_generator(
     downsample_voxel(&children)
 }
 
+/// Sample a coarse voxel as the downsample of the finer LOD's voxels.
+///
+/// [`sample_voxel_from_generator`] only reads one base voxel per child, so
+/// next to the finer LOD it can disagree with what that LOD shows. Seam
+/// voxels pay for the full child samples instead.
+fn sample_stitched_voxel(
+    generator: &TerrainGenerator,
+    edits: &HashMap<WorldCoord, BlockId>,
+    world_x: i64,
+    world_y: i64,
+    world_z: i64,
+    voxel_size: i64,
+) -> BlockId {
+    let child = voxel_size / 2;
+    let mut children = [BlockId::AIR; 8];
+    let mut idx = 0;
+    for dz in 0..2 {
+        for dy in 0..2 {
+            for dx in 0..2 {
+                let cx = world_x + dx * child;
+                let cy = world_y + dy * child;
+                let cz = world_z + dz * child;
+                children[idx] = sample_voxel_from_generator(generator, edits, cx, cy, cz, child);
+                idx += 1;
+            }
+        }
+    }
+
+    downsample_voxel(&children)
+}
+
 fn sample_base_voxel(
     generator: &TerrainGenerator,
     edits: &HashMap<WorldCoord, BlockId>,
@@ -1449,8 +3391,12 @@ fn invalid_page_coord() -> [i32; 4] {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
     use std::time::Duration;
 
+    use voxelicous_core::types::Material;
+    use voxelicous_voxel::CompressionPolicy;
+
     use super::*;
     use crate::generation::TerrainConfig;
 
@@ -1549,6 +3495,72 @@ mod tests {
         assert!(!controller.set_visible_page_grid(reduced_grid));
     }
 
+    /// Pump updates until LOD0 has `pages` loaded pages.
+    fn pump_until_loaded(controller: &mut ClipmapStreamingController, camera: Vec3, pages: usize) {
+        for _ in 0..2_000 {
+            if controller.loaded_page_count(0) == pages {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            controller.update(camera);
+        }
+        panic!(
+            "expected {pages} loaded pages, got {}",
+            controller.loaded_page_count(0)
+        );
+    }
+
+    #[test]
+    fn restoring_a_shrunk_visible_grid_reuses_retained_pages() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let height_calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&height_calls);
+        let gen = TerrainGenerator::with_height_fn(
+            TerrainConfig::default(),
+            Box::new(move |_, _| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                70
+            }),
+        );
+        let mut controller = ClipmapStreamingController::new(gen);
+        let camera = Vec3::new(16.0, 80.0, 16.0);
+        controller.set_visible_page_grid(4);
+        pump_until_loaded(&mut controller, camera, 64);
+
+        // Shrink and restore within the window: nothing is rebuilt.
+        let calls = height_calls.load(Ordering::Relaxed);
+        assert!(controller.set_visible_page_grid(2));
+        for _ in 0..10 {
+            controller.update(camera);
+        }
+        assert_eq!(controller.loaded_page_count(0), 64);
+        assert!(controller.set_visible_page_grid(4));
+        for _ in 0..10 {
+            controller.update(camera);
+        }
+        assert_eq!(controller.loaded_page_count(0), 64);
+        assert_eq!(height_calls.load(Ordering::Relaxed), calls);
+
+        // Once the window passes, pages outside the grid are evicted.
+        assert!(controller.set_visible_page_grid(2));
+        for _ in 0..=controller.visible_grid_hysteresis_frames() {
+            controller.update(camera);
+        }
+        assert_eq!(controller.loaded_page_count(0), 8);
+
+        // Without hysteresis, restoring rebuilds the evicted pages.
+        controller.set_visible_grid_hysteresis_frames(0);
+        assert!(controller.set_visible_page_grid(4));
+        pump_until_loaded(&mut controller, camera, 64);
+        let calls = height_calls.load(Ordering::Relaxed);
+        assert!(controller.set_visible_page_grid(2));
+        assert_eq!(controller.loaded_page_count(0), 8);
+        assert!(controller.set_visible_page_grid(4));
+        pump_until_loaded(&mut controller, camera, 64);
+        assert!(height_calls.load(Ordering::Relaxed) > calls);
+    }
+
     #[test]
     fn loding_can_be_toggled_runtime() {
         let gen = TerrainGenerator::new(TerrainConfig::default());
@@ -1572,6 +3584,126 @@ mod tests {
         assert!(!controller.set_lod_enabled(false));
     }
 
+    #[test]
+    fn pages_in_frustum_keeps_pages_ahead_of_the_camera() {
+        use glam::Mat4;
+
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(3));
+        controller.set_visible_page_grid(6);
+
+        // Looking down +Z from the centre of page (0, 1, 0) with a 90 degree
+        // field of view.
+        let page = PAGE_VOXELS_PER_AXIS as f32;
+        let eye = Vec3::new(0.5, 1.5, 0.5) * page;
+        controller.update(eye);
+        let view = Mat4::look_to_rh(eye, Vec3::Z, Vec3::Y);
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 2.0 * page);
+        let frustum = Frustum::from_view_projection(projection * view);
+        let pages = controller.pages_in_frustum(0, &frustum);
+
+        let page_of = |p: Vec3| {
+            let p = (p / page).floor();
+            (p.x as i64, p.y as i64, p.z as i64)
+        };
+        let ahead = page_of(eye + Vec3::Z * page);
+        assert_eq!(pages[0], page_of(eye));
+        assert!(pages.contains(&ahead));
+        // Behind, beyond the far plane, and outside the field of view.
+        assert!(!pages.contains(&page_of(eye - Vec3::Z * page)));
+        assert!(!pages.contains(&page_of(eye + Vec3::Z * page * 3.0)));
+        assert!(!pages.contains(&page_of(eye + Vec3::X * page * 2.0)));
+        let all = controller.visible_page_grid().pow(3);
+        assert!(!pages.is_empty() && pages.len() < all);
+    }
+
+    #[test]
+    fn shallow_y_grid_streams_fewer_pages_over_the_same_footprint() {
+        let sky = Vec3::new(0.0, 4_000.0, 0.0);
+        let stream = |grid_y: usize| {
+            let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(11));
+            controller.set_visible_page_grid(4);
+            controller.set_visible_page_grid_y(grid_y);
+            for _ in 0..2000 {
+                controller.update(sky);
+                if controller.lod_ready(0) {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            assert!(controller.lod_ready(0));
+            controller
+        };
+        let cubic = stream(4);
+        let shallow = stream(2);
+
+        assert_eq!(cubic.loaded_page_count(0), 4 * 4 * 4);
+        assert_eq!(shallow.loaded_page_count(0), 4 * 2 * 4);
+        assert_eq!(shallow.visible_page_grid_xz(), 4);
+        assert_eq!(shallow.lod_coverage(0), cubic.lod_coverage(0));
+        assert_eq!(shallow.lod_coverage_y(0), 2 * PAGE_VOXELS_PER_AXIS as i64);
+
+        // Same columns, fewer layers.
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        let (cubic_origin, origin) = (cubic.lod_origin(0), shallow.lod_origin(0));
+        assert_eq!((cubic_origin.x, cubic_origin.z), (origin.x, origin.z));
+        let o = (
+            div_floor(origin.x, page_size),
+            div_floor(origin.y, page_size),
+            div_floor(origin.z, page_size),
+        );
+        assert_eq!(div_floor(4_000, page_size), o.1 + 1);
+        for (dx, dy, dz) in [(0, 0, 0), (3, 1, 3), (3, 0, 0), (0, 1, 3)] {
+            let coord = (o.0 + dx, o.1 + dy, o.2 + dz);
+            assert!(shallow.is_page_in_coverage(0, coord), "{coord:?}");
+            assert!(shallow.page_slot_matches_coord(0, coord), "{coord:?}");
+        }
+        for (dx, dy, dz) in [(0, 2, 0), (0, -1, 0), (4, 0, 0), (0, 0, -1)] {
+            let coord = (o.0 + dx, o.1 + dy, o.2 + dz);
+            assert!(!shallow.is_page_in_coverage(0, coord), "{coord:?}");
+        }
+        // The cubic grid still streams the layer the shallow one drops.
+        assert!(cubic.is_page_in_coverage(0, (o.0, o.1 + 2, o.2)));
+    }
+
+    #[test]
+    fn lod_status_reports_bootstrap_progress() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        // Enabling LODs through the setter skips bootstrap; start with every
+        // LOD active instead, as a fresh multi-LOD controller would.
+        controller.active_lod_count = CLIPMAP_LOD_COUNT;
+        controller.set_max_inflight_page_jobs(Some(4));
+
+        controller.update(Vec3::new(0.0, 0.0, 0.0));
+        let status = controller.lod_status();
+        assert_eq!(status.len(), controller.active_lod_count());
+
+        // Workers drain LOD0 first; coarser LODs only have queued pages while
+        // the bootstrap cursor is still on LOD0.
+        let lod0 = status[0];
+        assert_eq!(lod0.lod, 0);
+        assert!(!lod0.ready);
+        assert_eq!(lod0.inflight, 4);
+        assert!(lod0.pending > 0);
+        for (lod, coarse) in status.iter().enumerate().skip(1) {
+            assert_eq!(coarse.lod, lod);
+            assert!(!coarse.ready && !coarse.renderable);
+            assert_eq!((coarse.loaded_pages, coarse.inflight), (0, 0));
+        }
+        for entry in &status {
+            assert_eq!(entry.ready, controller.lod_ready(entry.lod));
+            assert_eq!(entry.renderable, controller.lod_renderable(entry.lod));
+            assert_eq!(entry.loaded_pages, controller.loaded_page_count(entry.lod));
+            assert_eq!(
+                entry.pending,
+                controller.lods[entry.lod].pending_pages.len()
+            );
+        }
+
+        assert!(controller.set_lod_enabled(false));
+        assert_eq!(controller.lod_status().len(), 1);
+    }
+
     #[test]
     fn runtime_reconfigure_does_not_reset_bootstrap() {
         let gen = TerrainGenerator::new(TerrainConfig::default());
@@ -1597,24 +3729,370 @@ mod tests {
 
         controller.update(camera);
 
-        let mut seen_renderable_before_ready = false;
-        for _ in 0..256 {
-            std::thread::sleep(Duration::from_millis(1));
-            controller.update(camera);
-            if controller.lod_renderable(0) && !controller.lod_ready(0) {
-                seen_renderable_before_ready = true;
+        let mut seen_renderable_before_ready = false;
+        for _ in 0..256 {
+            std::thread::sleep(Duration::from_millis(1));
+            controller.update(camera);
+            if controller.lod_renderable(0) && !controller.lod_ready(0) {
+                seen_renderable_before_ready = true;
+                break;
+            }
+        }
+
+        assert!(
+            seen_renderable_before_ready,
+            "Expected LOD0 to become renderable before it is fully ready"
+        );
+    }
+
+    #[test]
+    fn pending_pages_prioritize_camera_proximity() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        let camera = WorldCoord {
+            x: 10,
+            y: 11,
+            z: 12,
+        };
+        controller.camera_voxel = camera;
+
+        let lod = 0;
+        let voxel_size = controller.lod_voxel_size(lod);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let origin = controller.lod_origin_around(lod, camera);
+        controller.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
+
+        let Some((_, first_coord, _, _)) = controller.pop_next_pending_page() else {
+            panic!("Expected at least one pending page for LOD0");
+        };
+        assert_eq!(first_coord, (0, 0, 0));
+
+        let mut previous_distance = page_distance_to_camera_sq(first_coord, camera, page_size);
+        for _ in 0..32 {
+            let Some((_, coord, _, _)) = controller.pop_next_pending_page() else {
+                panic!("Expected enough pending pages to verify ordering");
+            };
+            let distance = page_distance_to_camera_sq(coord, camera, page_size);
+            assert!(
+                distance >= previous_distance,
+                "Pending pages should be popped nearest-to-farthest ({} then {})",
+                previous_distance,
+                distance
+            );
+            previous_distance = distance;
+        }
+    }
+
+    #[test]
+    fn weighted_fairness_gives_coarse_lods_a_share() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.active_lod_count = 3;
+        controller.camera_voxel = WorldCoord { x: 5, y: 6, z: 7 };
+        let fill = |controller: &mut ClipmapStreamingController| {
+            for lod in 0..3 {
+                let voxel_size = controller.lod_voxel_size(lod);
+                let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+                let origin = controller.lod_origin_around(lod, controller.camera_voxel);
+                controller.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
+            }
+        };
+        let pop_lods = |controller: &mut ClipmapStreamingController| {
+            let mut counts = [0usize; 3];
+            for _ in 0..40 {
+                let (lod, _, _, _) = controller.pop_next_pending_page().unwrap();
+                counts[lod] += 1;
+            }
+            counts
+        };
+
+        // By default LOD0 takes every build while it has work.
+        fill(&mut controller);
+        assert_eq!(pop_lods(&mut controller), [40, 0, 0]);
+
+        let mut weights = [1; CLIPMAP_LOD_COUNT];
+        weights[0] = 2;
+        controller.set_page_build_fairness(PageBuildFairness::Weighted(weights));
+        fill(&mut controller);
+        assert_eq!(pop_lods(&mut controller), [20, 10, 10]);
+        assert!(!controller.lods[0].pending_pages.is_empty());
+    }
+
+    #[test]
+    fn equal_distance_pages_pop_in_morton_order() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        // Camera in the middle of page (0, 0, 0): its six face neighbours
+        // are all the same distance away.
+        let neighbours = [
+            (1, 0, 0),
+            (0, 0, -1),
+            (0, 1, 0),
+            (-1, 0, 0),
+            (0, 0, 1),
+            (0, -1, 0),
+        ];
+        let expected = vec![
+            (0, 0, -1),
+            (0, -1, 0),
+            (-1, 0, 0),
+            (1, 0, 0),
+            (0, 1, 0),
+            (0, 0, 1),
+        ];
+
+        for rotation in 0..neighbours.len() {
+            let mut controller = ClipmapStreamingController::new(gen.clone());
+            controller.set_visible_page_grid(4);
+            let camera = Vec3::splat(page_size as f32 / 2.0);
+            controller.camera_voxel = camera_voxel_of(camera);
+            controller.lods[0].pending_pages.clear();
+
+            let mut coords = neighbours.to_vec();
+            coords.rotate_left(rotation);
+            controller.enqueue_pending_pages(0, coords, false, 64);
+            let mut popped = Vec::new();
+            while let Some((_, coord, _, _)) = controller.pop_next_pending_page() {
+                popped.push(coord);
+            }
+            assert_eq!(popped, expected, "rotation {rotation}");
+        }
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn page_builds_report_timing_tagged_with_page_coords() {
+        use voxelicous_profiler::EventCategory;
+
+        voxelicous_profiler::init_with_port(0);
+        let generator = TerrainGenerator::with_seed(3);
+        build_page_voxels(&generator, &HashMap::new(), (3, -1, 2), 1, None);
+        voxelicous_profiler::end_frame(0, 0.0, 0.0);
+
+        let snapshot = voxelicous_profiler::snapshot();
+        assert!(snapshot
+            .slowest_page_events
+            .iter()
+            .any(|event| event.category == EventCategory::ClipmapPageBuild
+                && event.context == [3, -1, 2]));
+    }
+
+    /// Voxel of a built page at page-local voxel coordinates.
+    fn page_voxel(page: &BuiltPage, [x, y, z]: [usize; 3]) -> BlockId {
+        if page.is_empty() {
+            return BlockId::AIR;
+        }
+        let brick = x / BRICK_SIZE
+            + y / BRICK_SIZE * PAGE_BRICKS_PER_AXIS
+            + z / BRICK_SIZE * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+        let (x, y, z) = (x % BRICK_SIZE, y % BRICK_SIZE, z % BRICK_SIZE);
+        page.bricks[brick][x + y * BRICK_SIZE + z * BRICK_SIZE * BRICK_SIZE]
+    }
+
+    #[test]
+    fn seam_voxels_match_the_downsampled_finer_lod() {
+        let generator = TerrainGenerator::with_seed(7);
+        let edits = HashMap::new();
+        let axis = PAGE_VOXELS_PER_AXIS as i64;
+
+        // Coarse page `coord` of `voxel_size`, and the downsample of the
+        // finer LOD's pages under it, voxel by voxel.
+        let downsampled_fine = |coord: (i64, i64, i64), voxel_size: i64| {
+            let fine_pages: Vec<_> = (0..8)
+                .map(|i| {
+                    let fine_coord = (
+                        coord.0 * 2 + (i & 1),
+                        coord.1 * 2 + (i >> 1 & 1),
+                        coord.2 * 2 + (i >> 2),
+                    );
+                    build_page_voxels(&generator, &edits, fine_coord, voxel_size / 2, None)
+                })
+                .collect();
+            move |[x, y, z]: [usize; 3]| {
+                let mut children = [BlockId::AIR; 8];
+                for (i, child) in children.iter_mut().enumerate() {
+                    let fine = [x * 2 + (i & 1), y * 2 + (i >> 1 & 1), z * 2 + (i >> 2)];
+                    let page = fine[0] / PAGE_VOXELS_PER_AXIS
+                        + fine[1] / PAGE_VOXELS_PER_AXIS * 2
+                        + fine[2] / PAGE_VOXELS_PER_AXIS * 4;
+                    *child = page_voxel(&fine_pages[page], fine.map(|v| v % PAGE_VOXELS_PER_AXIS));
+                }
+                downsample_voxel(&children)
+            }
+        };
+        // Page holding the terrain surface over the middle of column (0, 0).
+        let surface_page = |voxel_size: i64| {
+            let page_size = axis * voxel_size;
+            let height = generator.height_at(page_size / 2, page_size / 2);
+            (0, div_floor(i64::from(height), page_size), 0)
+        };
+
+        // LOD1 already matches LOD0 everywhere, so it needs no seam.
+        let coord = surface_page(2);
+        let expected = downsampled_fine(coord, 2);
+        let page = build_page_voxels(&generator, &edits, coord, 2, None);
+        for z in 0..PAGE_VOXELS_PER_AXIS {
+            for y in 0..PAGE_VOXELS_PER_AXIS {
+                for x in [0, 15, 16, PAGE_VOXELS_PER_AXIS - 1] {
+                    assert_eq!(page_voxel(&page, [x, y, z]), expected([x, y, z]));
+                }
+            }
+        }
+
+        // A LOD2 page cut in half along X by the LOD1 seam: the two voxel
+        // layers either side of it are stitched, the rest are not.
+        let coord = surface_page(4);
+        let page_size = axis * 4;
+        let seam_x = coord.0 * page_size + page_size / 2;
+        let seam = LodSeam {
+            min: WorldCoord {
+                x: seam_x,
+                y: -100_000,
+                z: -100_000,
+            },
+            max: WorldCoord {
+                x: 100_000,
+                y: 100_000,
+                z: 100_000,
+            },
+        };
+        let expected = downsampled_fine(coord, 4);
+        let plain = build_page_voxels(&generator, &edits, coord, 4, None);
+        let stitched = build_page_voxels(&generator, &edits, coord, 4, Some(seam));
+        let mut differs = false;
+        for z in 0..PAGE_VOXELS_PER_AXIS {
+            for y in 0..PAGE_VOXELS_PER_AXIS {
+                for x in 0..PAGE_VOXELS_PER_AXIS {
+                    let voxel = [x, y, z];
+                    if x == 15 || x == 16 {
+                        assert_eq!(page_voxel(&stitched, voxel), expected(voxel), "{voxel:?}");
+                        differs |= page_voxel(&plain, voxel) != expected(voxel);
+                    } else {
+                        assert_eq!(page_voxel(&stitched, voxel), page_voxel(&plain, voxel));
+                    }
+                }
+            }
+        }
+        assert!(differs, "point sampling should miss some fine detail");
+    }
+
+    #[test]
+    fn initial_pages_follow_the_camera_after_reconfiguring() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        // Reconfiguring before the first update seeds origins around (0, 0, 0).
+        controller.set_visible_page_grid(4);
+        let camera = Vec3::new(0.5, 88.0, 3_920.5);
+        assert_eq!(controller.generate_initial_pages(camera), 27);
+
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        let camera_voxel = camera_voxel_of(camera);
+        assert_eq!(
+            controller.lod_origin(0),
+            controller.lod_origin_around(0, camera_voxel)
+        );
+        assert!(controller.page_slot_matches_coord(
+            0,
+            (
+                div_floor(camera_voxel.x, page_size),
+                div_floor(camera_voxel.y, page_size),
+                div_floor(camera_voxel.z, page_size),
+            )
+        ));
+    }
+
+    #[test]
+    fn high_altitude_pages_share_the_empty_brick() {
+        use voxelicous_voxel::{BrickHeader, RAW16_STRIDE};
+
+        fn scanned_empty_pages(controller: &ClipmapStreamingController) -> usize {
+            let lod = &controller.lods[0];
+            lod.page_loaded
+                .iter()
+                .enumerate()
+                .filter(|&(index, &loaded)| {
+                    loaded
+                        && lod.page_brick_indices[index * PAGE_BRICKS..(index + 1) * PAGE_BRICKS]
+                            .iter()
+                            .all(|&id| id == 0)
+                })
+                .count()
+        }
+
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(11));
+        controller.set_visible_page_grid(4);
+        let sky = Vec3::new(0.0, 4_000.0, 0.0);
+        for _ in 0..2000 {
+            controller.update(sky);
+            if controller.lod_ready(0) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(controller.lod_ready(0));
+
+        let loaded = controller.loaded_page_count(0);
+        assert_eq!(loaded, 4 * 4 * 4);
+        assert_eq!(controller.empty_page_count(0), loaded);
+        let dense_page_bytes = PAGE_BRICKS * (RAW16_STRIDE + std::mem::size_of::<BrickHeader>());
+        assert!(
+            controller.store().memory_bytes() * 100 < loaded * dense_page_bytes,
+            "{} bytes for {loaded} empty pages",
+            controller.store().memory_bytes()
+        );
+
+        // Back at the surface the count tracks solid pages replacing empty ones.
+        let surface = controller.generator().height_at(0, 0) as f32;
+        controller.teleport(Vec3::new(0.0, surface, 0.0));
+        assert!(controller.empty_page_count(0) < controller.loaded_page_count(0));
+        assert_eq!(
+            controller.empty_page_count(0),
+            scanned_empty_pages(&controller)
+        );
+    }
+
+    #[test]
+    fn teleport_builds_lod0_around_the_target() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(5));
+        controller.set_visible_page_grid(4);
+        let home = Vec3::new(0.0, 60.0, 0.0);
+        for _ in 0..2000 {
+            controller.update(home);
+            if controller.lod_ready(0) {
                 break;
             }
+            std::thread::sleep(Duration::from_millis(1));
         }
+        assert!(controller.lod_ready(0));
 
-        assert!(
-            seen_renderable_before_ready,
-            "Expected LOD0 to become renderable before it is fully ready"
+        let target = Vec3::new(20_000.5, 70.0, -35_000.5);
+        controller.set_camera_velocity(Vec3::new(500.0, 0.0, 0.0));
+        let built = controller.teleport(target);
+        assert!(built > 0);
+        assert!(controller.lod_renderable(0));
+        assert_eq!(controller.camera_velocity(), Vec3::ZERO);
+
+        let camera_voxel = camera_voxel_of(target);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        assert_eq!(
+            controller.lod_origin(0),
+            controller.lod_origin_around(0, camera_voxel)
+        );
+        let camera_page = (
+            div_floor(camera_voxel.x, page_size),
+            div_floor(camera_voxel.y, page_size),
+            div_floor(camera_voxel.z, page_size),
         );
+        assert!(controller.page_slot_matches_coord(0, camera_page));
+        // Nothing from the old neighbourhood survives the jump.
+        assert!(controller
+            .page_coords(0)
+            .iter()
+            .filter(|coord| coord[0] != i32::MIN)
+            .all(|coord| i64::from(coord[0]) > 0 && i64::from(coord[2]) < 0));
     }
 
     #[test]
-    fn pending_pages_prioritize_camera_proximity() {
+    fn pending_pages_prefetch_along_camera_velocity() {
         let gen = TerrainGenerator::new(TerrainConfig::default());
         let mut controller = ClipmapStreamingController::new(gen);
         let camera = WorldCoord {
@@ -1623,33 +4101,31 @@ mod tests {
             z: 12,
         };
         controller.camera_voxel = camera;
+        controller.set_camera_velocity(Vec3::new(40.0, 0.0, 0.0));
+        controller.set_prefetch_seconds(2.0);
 
         let lod = 0;
         let voxel_size = controller.lod_voxel_size(lod);
         let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-        let coverage = controller.lod_coverage(lod);
-        let origin = aligned_origin(camera, coverage, page_size);
+        let origin = controller.lod_origin_around(lod, camera);
         controller.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
 
+        // 80 voxels ahead lands in page x=2, ahead of the camera's page.
+        let camera_page = div_floor(camera.x, page_size);
         let Some((_, first_coord, _, _)) = controller.pop_next_pending_page() else {
-            panic!("Expected at least one pending page for LOD0");
+            panic!("Expected pending pages for LOD0");
         };
-        assert_eq!(first_coord, (0, 0, 0));
-
-        let mut previous_distance = page_distance_to_camera_sq(first_coord, camera, page_size);
-        for _ in 0..32 {
-            let Some((_, coord, _, _)) = controller.pop_next_pending_page() else {
-                panic!("Expected enough pending pages to verify ordering");
-            };
-            let distance = page_distance_to_camera_sq(coord, camera, page_size);
+        assert_eq!(first_coord, (camera_page + 2, 0, 0));
+        for _ in 0..8 {
+            let (_, coord, _, _) = controller.pop_next_pending_page().unwrap();
             assert!(
-                distance >= previous_distance,
-                "Pending pages should be popped nearest-to-farthest ({} then {})",
-                previous_distance,
-                distance
+                coord.0 > camera_page,
+                "{coord:?} is not ahead of the camera"
             );
-            previous_distance = distance;
         }
+
+        controller.set_camera_velocity(Vec3::NAN);
+        assert_eq!(controller.camera_velocity(), Vec3::ZERO);
     }
 
     #[test]
@@ -1662,8 +4138,7 @@ mod tests {
         let lod = 0;
         let voxel_size = controller.lod_voxel_size(lod);
         let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
-        let coverage = controller.lod_coverage(lod);
-        let origin = aligned_origin(camera, coverage, page_size);
+        let origin = controller.lod_origin_around(lod, camera);
         controller.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
 
         let pending_budget = controller.pending_page_budget(controller.current_apply_budget());
@@ -1675,6 +4150,39 @@ mod tests {
         assert_eq!(controller.lods[lod].pending_pages.len(), pending_budget);
     }
 
+    #[test]
+    fn inflight_page_cap_is_configurable() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        assert_eq!(controller.max_inflight_page_jobs(), 16);
+
+        // The default follows the visible grid.
+        controller.set_visible_page_grid(8);
+        let small = controller.max_inflight_page_jobs();
+        controller.set_visible_page_grid(32);
+        assert!(controller.max_inflight_page_jobs() > small);
+
+        // Raising the cap keeps more pages building at once. Spawn without
+        // draining results, so fast workers cannot lower the count.
+        let camera = camera_voxel_of(Vec3::new(0.0, 0.0, 0.0));
+        controller.seed_origins(camera);
+        controller.spawn_pending_jobs();
+        assert_eq!(controller.inflight_jobs, 8);
+
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_visible_page_grid(32);
+        controller.set_max_inflight_page_jobs(Some(40));
+        controller.seed_origins(camera);
+        controller.spawn_pending_jobs();
+        assert_eq!(controller.inflight_jobs, 40);
+
+        controller.set_max_inflight_page_jobs(Some(0));
+        assert_eq!(controller.max_inflight_page_jobs(), 1);
+        controller.set_max_inflight_page_jobs(None);
+        assert_eq!(controller.max_inflight_page_jobs(), 8);
+    }
+
     #[test]
     fn starved_lod_refills_with_nearest_missing_pages() {
         let gen = TerrainGenerator::new(TerrainConfig::default());
@@ -1746,6 +4254,9 @@ mod tests {
                 }
             }
         }
+        // Pages the first update already built are dropped from the queue on
+        // merge; leave them out so the queue really starts full.
+        coords.retain(|&coord| !controller.page_slot_matches_coord(lod, coord));
         let camera_voxel = controller.camera_voxel;
         coords.sort_unstable_by_key(|&coord| {
             page_distance_to_camera_sq(coord, camera_voxel, page_size)
@@ -1765,6 +4276,43 @@ mod tests {
         assert!(controller.lods[lod].pending_pages.contains(&far_coord));
     }
 
+    #[test]
+    fn cost_budget_limits_inflight_builds_by_summed_cost() {
+        let gen = TerrainGenerator::with_seed(42);
+        let surface = i64::from(gen.height_at(0, 0));
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_max_inflight_page_jobs(Some(64));
+        controller.set_max_inflight_page_cost(Some(24));
+        assert_eq!(controller.max_inflight_page_cost(), Some(24));
+        let camera = WorldCoord {
+            x: 0,
+            y: surface,
+            z: 0,
+        };
+        controller.camera_voxel = camera;
+
+        let lod = 0;
+        let voxel_size = controller.lod_voxel_size(lod);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64 * voxel_size;
+        let origin = controller.lod_origin_around(lod, camera);
+        controller.enqueue_full_rebuild(lod, origin, voxel_size, page_size);
+
+        // Builds start in queue order until their summed cost reaches the cap.
+        let mut expected_jobs = 0;
+        let mut expected_cost = 0;
+        for &coord in &controller.lods[lod].pending_pages {
+            if expected_cost >= 24 {
+                break;
+            }
+            expected_jobs += 1;
+            expected_cost += controller.estimate_page_cost(coord, voxel_size);
+        }
+        assert!(expected_jobs < 64);
+        controller.spawn_pending_jobs();
+        assert_eq!(controller.inflight_jobs, expected_jobs);
+        assert_eq!(controller.inflight_cost, expected_cost);
+    }
+
     #[test]
     fn runtime_edit_overrides_generated_block() {
         let gen = TerrainGenerator::new(TerrainConfig::default());
@@ -1772,7 +4320,7 @@ mod tests {
 
         // Deep underground should be solid for generated terrain.
         let x = 0;
-        let y = -128;
+        let y = -32;
         let z = 0;
         assert!(controller.block_at_world(x, y, z).is_solid());
 
@@ -1783,6 +4331,562 @@ mod tests {
         assert_eq!(controller.block_at_world(x, y, z), BlockId::STONE);
     }
 
+    #[test]
+    fn terrain_config_change_regenerates_pages_and_keeps_edits() {
+        let gen = TerrainGenerator::with_seed(3);
+        let config = TerrainConfig {
+            sea_level: 200,
+            ..gen.config().clone()
+        };
+        // Raising the sea level also lifts the land, so pick a column that
+        // ends up under water with the new config.
+        let flooded = gen.with_config(config.clone());
+        let y = 199;
+        let x = (0..4096)
+            .find(|&x| {
+                gen.block_at_world(x, y, 0).is_air()
+                    && flooded.block_at_world(x, y, 0) == BlockId::WATER
+                    && flooded.block_at_world(x, y - 1, 0) == BlockId::WATER
+            })
+            .expect("a column flooded by the higher sea level");
+
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_visible_page_grid(4);
+        let camera = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.5);
+        assert!(controller.generate_initial_pages(camera) > 0);
+        assert!(controller.set_block_at_world(x, y - 1, 0, BlockId::STONE));
+
+        let built = controller.set_terrain_config(config);
+        assert!(built > 0);
+        assert!(controller.lod_renderable(0));
+        assert_eq!(controller.generator().config().sea_level, 200);
+
+        // The column floods to the new sea level around the kept edit.
+        assert_eq!(controller.block_at_world(x, y, 0), BlockId::WATER);
+        assert_eq!(controller.block_at_world(x, y - 1, 0), BlockId::STONE);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        assert!(controller
+            .page_slot_matches_coord(0, (div_floor(x, page_size), div_floor(y, page_size), 0)));
+    }
+
+    #[test]
+    fn terrain_config_change_skips_old_cached_pages() {
+        let dir = std::env::temp_dir().join(format!(
+            "voxelicous-config-page-cache-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let gen = TerrainGenerator::with_seed(7);
+        let config = TerrainConfig {
+            sea_level: 200,
+            ..gen.config().clone()
+        };
+        let camera = Vec3::new(0.5, 70.0, 0.5);
+        let controller = |gen: TerrainGenerator| {
+            let mut controller = ClipmapStreamingController::new(gen);
+            controller.set_streaming_mode(StreamingMode::Sync);
+            // Pages past the initial radius stream in through the cache.
+            controller.set_visible_page_grid(4);
+            controller
+        };
+
+        // Fill the cache with the old terrain, then switch configs.
+        let mut cached = controller(gen.clone());
+        cached.set_page_cache_dir(Some(dir.clone()));
+        cached.update_sync(camera).unwrap();
+        cached.set_terrain_config(config.clone());
+        cached.update_sync(camera).unwrap();
+
+        let mut fresh = controller(gen.with_config(config));
+        fresh.update_sync(camera).unwrap();
+
+        for lod in 0..fresh.active_lod_count() {
+            assert_eq!(cached.page_coords(lod), fresh.page_coords(lod));
+            assert_eq!(cached.page_occ(lod), fresh.page_occ(lod));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bedrock_floor_cannot_be_edited() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let floor = i64::from(gen.config().world_floor_y);
+        let mut controller = ClipmapStreamingController::new(gen);
+
+        assert_eq!(controller.block_at_world(3, floor, -7), BlockId::BEDROCK);
+        assert_eq!(
+            controller.block_at_world(3, floor - 40, -7),
+            BlockId::BEDROCK
+        );
+        assert_eq!(controller.block_at_world(3, floor + 1, -7), BlockId::STONE);
+
+        assert!(!controller.destroy_block_at_world(3, floor, -7));
+        assert!(!controller.set_block_at_world(3, floor, -7, BlockId::DIRT));
+        assert_eq!(controller.block_at_world(3, floor, -7), BlockId::BEDROCK);
+        assert!(controller.destroy_block_at_world(3, floor + 1, -7));
+    }
+
+    #[test]
+    fn sphere_brush_rebuilds_each_overlapping_page_once() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        // High above the terrain, straddling a page corner in X and Z.
+        let center = WorldCoord {
+            x: page_size,
+            y: 12 * page_size + page_size / 2,
+            z: page_size,
+        };
+        let radius = 5;
+        controller.update(Vec3::new(center.x as f32, center.y as f32, center.z as f32));
+        controller.take_dirty_state();
+        assert!(controller
+            .block_at_world(center.x, center.y, center.z)
+            .is_air());
+
+        let changed = controller.edit_sphere(center, radius, BlockId::STONE);
+
+        let mut expected_pages = HashSet::new();
+        let mut voxel_count = 0;
+        for dz in -radius..=radius {
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let (x, y, z) = (center.x + dx, center.y + dy, center.z + dz);
+                    if dx * dx + dy * dy + dz * dz <= radius * radius {
+                        voxel_count += 1;
+                        assert_eq!(controller.block_at_world(x, y, z), BlockId::STONE);
+                        expected_pages.insert(ClipmapStreamingController::page_index_from_coord((
+                            div_floor(x, page_size),
+                            div_floor(y, page_size),
+                            div_floor(z, page_size),
+                        )));
+                    } else {
+                        assert!(controller.block_at_world(x, y, z).is_air());
+                    }
+                }
+            }
+        }
+        assert_eq!(changed, voxel_count);
+        assert_eq!(expected_pages.len(), 4);
+
+        let dirty = controller.take_dirty_state();
+        let rebuilt: HashSet<usize> = dirty.dirty_pages[0].iter().copied().collect();
+        assert_eq!(rebuilt, expected_pages);
+        // A page is marked at most twice per rebuild (clear + apply); per-voxel
+        // rebuilds would mark it hundreds of times.
+        assert!(dirty.dirty_pages[0].len() <= 2 * expected_pages.len());
+    }
+
+    #[test]
+    fn explosion_hollows_stone_and_keeps_the_shell() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let surface = i64::from(gen.height_at(0, 0));
+        let mut controller = ClipmapStreamingController::new(gen);
+        let center = WorldCoord {
+            x: 0,
+            y: surface - 40,
+            z: 0,
+        };
+        controller.update(Vec3::new(0.0, center.y as f32, 0.0));
+        let reach = 6;
+        let around = || {
+            box_voxels(
+                WorldCoord {
+                    x: -reach,
+                    y: center.y - reach,
+                    z: -reach,
+                },
+                WorldCoord {
+                    x: reach,
+                    y: center.y + reach,
+                    z: reach,
+                },
+            )
+        };
+        assert!(around().all(|c| controller.block_at_world(c.x, c.y, c.z).is_solid()));
+
+        // Everything out to 4.5 goes, except a ring between 2 and 3 voxels.
+        let radius = 4.5;
+        let falloff = |distance: f64| !(2.0..=3.0).contains(&distance);
+        let destroyed = controller.explode(center, radius, falloff);
+
+        let mut expected = 0;
+        for c in around() {
+            let (dx, dy, dz) = (c.x - center.x, c.y - center.y, c.z - center.z);
+            let distance = ((dx * dx + dy * dy + dz * dz) as f64).sqrt();
+            let gone = distance <= radius && falloff(distance);
+            expected += usize::from(gone);
+            assert_eq!(
+                controller.block_at_world(c.x, c.y, c.z).is_air(),
+                gone,
+                "{c:?} at {distance}"
+            );
+        }
+        assert_eq!(destroyed, expected);
+        assert!(expected > 200);
+
+        // Air is not destroyed twice.
+        assert_eq!(controller.explode(center, radius, falloff), 0);
+    }
+
+    #[test]
+    fn box_brush_matches_per_voxel_edits() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        let min = WorldCoord {
+            x: -2,
+            y: 500,
+            z: 3,
+        };
+        let max = WorldCoord { x: 1, y: 502, z: 5 };
+
+        assert_eq!(controller.edit_box(max, min, BlockId::STONE), 4 * 3 * 3);
+        assert_eq!(controller.block_at_world(-2, 500, 3), BlockId::STONE);
+        assert_eq!(controller.block_at_world(1, 502, 5), BlockId::STONE);
+        assert!(controller.block_at_world(2, 502, 5).is_air());
+
+        // Re-applying the same brush changes nothing.
+        assert_eq!(controller.edit_box(min, max, BlockId::STONE), 0);
+        // Clearing back to generated terrain drops the stored overrides.
+        assert_eq!(controller.edit_box(min, max, BlockId::AIR), 4 * 3 * 3);
+        assert!(controller.edits.is_empty());
+    }
+
+    #[test]
+    fn brick_occupancy_matches_loaded_voxels() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let surface = gen.height_at(0, 0);
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_visible_page_grid(4);
+        let camera = Vec3::new(0.0, f32::from(i16::try_from(surface).unwrap()), 0.0);
+        for _ in 0..2000 {
+            controller.update(camera);
+            if controller.lod_ready(0) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(controller.lod_ready(0));
+
+        // Walk the column of bricks through the camera and compare the mask
+        // against the voxels it was built from.
+        let origin = controller.lod_origin(0);
+        let brick = BRICK_SIZE as i64;
+        let (mut empty, mut occupied) = (0, 0);
+        for brick_y in (origin.y..origin.y + controller.lod_coverage(0)).step_by(BRICK_SIZE) {
+            let occ = controller
+                .brick_occupancy(
+                    0,
+                    WorldCoord {
+                        x: 0,
+                        y: brick_y,
+                        z: 0,
+                    },
+                )
+                .expect("brick inside coverage");
+            let mut expected = 0u64;
+            for z in 0..brick {
+                for y in 0..brick {
+                    for x in 0..brick {
+                        if controller.block_at_world(x, brick_y + y, z).is_solid() {
+                            expected |= 1 << (x / 2 + (y / 2) * 4 + (z / 2) * 16);
+                        }
+                    }
+                }
+            }
+            assert_eq!(occ, expected, "brick at y={brick_y}");
+            if occ == 0 {
+                empty += 1;
+            } else {
+                occupied += 1;
+            }
+        }
+        assert!(empty > 0, "expected open air above the surface");
+        assert!(occupied > 0, "expected terrain below the surface");
+
+        // Outside loaded coverage (or at an inactive LOD) callers fall back.
+        let far = WorldCoord {
+            x: origin.x + controller.lod_coverage(0) + 1,
+            y: origin.y,
+            z: 0,
+        };
+        assert_eq!(controller.brick_occupancy(0, far), None);
+        assert_eq!(
+            controller.brick_occupancy(1, WorldCoord { x: 0, y: 0, z: 0 }),
+            None
+        );
+    }
+
+    #[test]
+    fn parallel_initial_pages_match_serial_build() {
+        let camera = Vec3::new(40.0, 60.0, -24.0);
+        let world = || {
+            let gen = TerrainGenerator::with_seed(7);
+            ClipmapStreamingController::new(gen).with_initial_page_radius(1)
+        };
+
+        let mut parallel = world();
+        let built = parallel.generate_initial_pages(camera);
+        assert_eq!(built, 27);
+
+        let mut serial = world();
+        let coords = serial.prepare_initial_pages(camera);
+        let voxel_size = serial.lod_voxel_size(0);
+        let pages: Vec<_> = coords
+            .iter()
+            .map(|&coord| {
+                build_page_voxels(
+                    &serial.generator,
+                    &serial.edit_snapshot,
+                    coord,
+                    voxel_size,
+                    None,
+                )
+            })
+            .collect();
+        for page in pages {
+            serial.apply_built_page(0, page);
+        }
+
+        assert_eq!(parallel.page_coords(0), serial.page_coords(0));
+        assert_eq!(parallel.page_occ(0), serial.page_occ(0));
+        assert_eq!(parallel.page_brick_indices(0), serial.page_brick_indices(0));
+        let (a, b) = (parallel.store().gpu_pools(), serial.store().gpu_pools());
+        assert_eq!(a.headers, b.headers);
+        assert_eq!(a.palette16, b.palette16);
+        assert_eq!(a.palette32, b.palette32);
+        assert_eq!(a.raw16, b.raw16);
+        assert_eq!(a.normals, b.normals);
+
+        // The claimed pages are not rebuilt by the background streamer.
+        let pending = &parallel.lods[0].pending_pages;
+        assert!(coords.iter().all(|coord| !pending.contains(coord)));
+        assert_eq!(parallel.generate_initial_pages(camera), 0);
+    }
+
+    #[test]
+    fn fast_compression_lods_store_raw_bricks() {
+        let camera = Vec3::new(40.0, 60.0, -24.0);
+        let build = |fast_lods| {
+            let gen = TerrainGenerator::with_seed(7);
+            let mut controller = ClipmapStreamingController::new(gen).with_initial_page_radius(1);
+            controller.set_fast_compression_lods(fast_lods);
+            assert_eq!(controller.generate_initial_pages(camera), 27);
+            controller
+        };
+        let full = build(0);
+        let fast = build(1);
+
+        assert!(fast.store().palette16_pool().is_empty());
+        assert!(!full.store().palette16_pool().is_empty());
+        assert!(fast.store().memory_bytes() > full.store().memory_bytes());
+        for (&a, &b) in fast
+            .page_brick_indices(0)
+            .iter()
+            .zip(full.page_brick_indices(0))
+        {
+            assert_eq!(a == 0, b == 0);
+            if a == 0 {
+                continue;
+            }
+            assert_eq!(
+                fast.store().decode_brick(BrickId(a)),
+                full.store().decode_brick(BrickId(b))
+            );
+        }
+    }
+
+    #[test]
+    fn compressing_a_page_keeps_edits_raw_and_frees_late() {
+        let camera = Vec3::new(40.0, 60.0, -24.0);
+        let gen = TerrainGenerator::with_seed(7);
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_streaming_mode(StreamingMode::Sync);
+        controller.set_visible_page_grid(2);
+        controller
+            .store_mut()
+            .set_compression_policy(CompressionPolicy::Lazy);
+        controller.update_sync(camera).unwrap();
+
+        // A page with several bricks, edited in its first brick.
+        let slot = (0..controller.page_coords(0).len())
+            .find(|&slot| controller.page_occ(0)[slot][0].count_ones() > 4)
+            .expect("a page with several bricks");
+        let coord = controller.page_coords(0)[slot].map(i64::from);
+        let page_coord = (coord[0], coord[1], coord[2]);
+        let bricks = |controller: &ClipmapStreamingController| {
+            controller.page_brick_indices(0)[slot * PAGE_BRICKS..(slot + 1) * PAGE_BRICKS]
+                .iter()
+                .map(|&id| BrickId(id))
+                .collect::<Vec<_>>()
+        };
+        let edited = bricks(&controller).iter().position(|id| id.0 != 0).unwrap();
+        let brick = [
+            edited % PAGE_BRICKS_PER_AXIS,
+            edited / PAGE_BRICKS_PER_AXIS % PAGE_BRICKS_PER_AXIS,
+            edited / (PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS),
+        ];
+        let [x, y, z] = [0, 1, 2].map(|axis| {
+            coord[axis] * PAGE_VOXELS_PER_AXIS as i64 + (brick[axis] * BRICK_SIZE) as i64
+        });
+        assert!(controller.set_block_at_world(x, y, z, BlockId::ICE));
+        for _ in 0..=ClipmapStreamingController::BRICK_FREE_DELAY_FRAMES {
+            controller.update(camera);
+        }
+
+        let ids = bricks(&controller);
+        let header =
+            |controller: &ClipmapStreamingController, id| *controller.store().header(id).unwrap();
+        let raw: Vec<u32> = ids
+            .iter()
+            .filter(|id| id.0 != 0)
+            .map(|&id| header(&controller, id).data_index)
+            .collect();
+        assert!(controller.compress_page(0, page_coord) > 0);
+        let encoding = |id| BrickEncoding::from_u8(header(&controller, id).encoding);
+        assert_eq!(encoding(ids[edited]), Some(BrickEncoding::Raw16));
+        assert!(ids
+            .iter()
+            .any(|&id| id.0 != 0 && encoding(id) == Some(BrickEncoding::Palette16)));
+
+        // Replaced raw entries are not reused until the delay has passed.
+        let solid = [BlockId::STONE; BRICK_VOXELS];
+        let early = controller.store_mut().allocate_brick(&solid);
+        assert!(!raw.contains(&header(&controller, early).data_index));
+        for _ in 0..=ClipmapStreamingController::BRICK_FREE_DELAY_FRAMES {
+            controller.update(camera);
+        }
+        let late = controller.store_mut().allocate_brick(&solid);
+        assert!(raw.contains(&header(&controller, late).data_index));
+    }
+
+    #[test]
+    fn shrinking_unloads_pages_in_a_stable_order() {
+        let run = || {
+            let gen =
+                TerrainGenerator::with_height_fn(TerrainConfig::default(), Box::new(|_, _| 70));
+            let mut controller = ClipmapStreamingController::new(gen);
+            let camera = Vec3::new(16.0, 80.0, 16.0);
+            controller.set_visible_page_grid(4);
+            controller.set_visible_grid_hysteresis_frames(0);
+            pump_until_loaded(&mut controller, camera, 64);
+            controller.take_dirty_state();
+
+            assert!(controller.set_visible_page_grid(2));
+            let remaining = controller.loaded_pages_sorted(0);
+            (controller.take_dirty_state().unloaded_pages, remaining)
+        };
+
+        let (unloaded, remaining) = run();
+        assert_eq!(unloaded[0].len(), 56);
+        assert_eq!(remaining.len(), 8);
+        for pages in [&unloaded[0], &remaining] {
+            assert!(pages
+                .windows(2)
+                .all(|pair| page_morton_code(pair[0]) < page_morton_code(pair[1])));
+        }
+        assert!(unloaded[0].iter().all(|page| !remaining.contains(page)));
+        assert_eq!(run(), (unloaded, remaining));
+    }
+
+    #[test]
+    fn sync_rebuild_skips_resident_empty_pages() {
+        let gen = TerrainGenerator::with_seed(3);
+        let mut controller = ClipmapStreamingController::new(gen);
+        controller.set_visible_page_grid(4);
+        let camera = Vec3::new(0.0, 60.0, 0.0);
+        for _ in 0..2000 {
+            controller.update(camera);
+            if controller.lod_ready(0) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(controller.lod_ready(0));
+        controller.take_dirty_state();
+
+        let loaded_page = |controller: &ClipmapStreamingController, empty: bool| {
+            controller
+                .page_coords(0)
+                .iter()
+                .enumerate()
+                .find(|&(index, coord)| {
+                    coord[0] != i32::MIN && (controller.page_occ(0)[index] == [0, 0]) == empty
+                })
+                .map(|(index, coord)| {
+                    let page_voxels = PAGE_VOXELS_PER_AXIS as i64;
+                    let voxel = WorldCoord {
+                        x: i64::from(coord[0]) * page_voxels,
+                        y: i64::from(coord[1]) * page_voxels,
+                        z: i64::from(coord[2]) * page_voxels,
+                    };
+                    (index, voxel)
+                })
+                .expect("loaded page")
+        };
+
+        // Rebuilding an all-air page that is already resident uploads nothing.
+        let (air_index, air_voxel) = loaded_page(&controller, true);
+        controller.apply_edits_immediate(&[air_voxel]);
+        assert!(!controller.take_dirty_state().dirty_pages[0].contains(&air_index));
+        assert_ne!(controller.page_coords(0)[air_index], invalid_page_coord());
+
+        // Pages with terrain are still rebuilt and re-uploaded.
+        let (solid_index, solid_voxel) = loaded_page(&controller, false);
+        controller.apply_edits_immediate(&[solid_voxel]);
+        assert!(controller.take_dirty_state().dirty_pages[0].contains(&solid_index));
+    }
+
+    #[test]
+    fn occupancy_minimap_marks_loaded_columns() {
+        let gen = TerrainGenerator::new(TerrainConfig::default());
+        let mut controller = ClipmapStreamingController::new(gen);
+        let grid = 4;
+        controller.set_visible_page_grid(grid);
+        let camera = Vec3::new(0.0, 60.0, 0.0);
+        for _ in 0..2000 {
+            controller.update(camera);
+            if controller.lod_ready(0) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(controller.lod_ready(0));
+
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        let origin = controller.lod_origin(0);
+        let origin_page = (
+            div_floor(origin.x, page_size),
+            div_floor(origin.z, page_size),
+        );
+        let radius = 4;
+        let center = (origin_page.0 + 2, origin_page.1 + 2);
+        let minimap = controller.occupancy_minimap(0, center, radius);
+        assert_eq!(minimap.dimensions(), (9, 9));
+
+        let mut occupied = 0;
+        for (px, py, pixel) in minimap.enumerate_pixels() {
+            let column_x = center.0 - i64::from(radius) + i64::from(px);
+            let column_z = center.1 - i64::from(radius) + i64::from(py);
+            let in_coverage = (origin_page.0..origin_page.0 + grid as i64).contains(&column_x)
+                && (origin_page.1..origin_page.1 + grid as i64).contains(&column_z);
+            if in_coverage {
+                assert_ne!(pixel[0], ClipmapStreamingController::MINIMAP_UNLOADED);
+                if pixel[0] == ClipmapStreamingController::MINIMAP_OCCUPIED {
+                    occupied += 1;
+                }
+            } else {
+                assert_eq!(pixel[0], ClipmapStreamingController::MINIMAP_UNLOADED);
+            }
+        }
+        // Terrain near sea level fills every column of the small coverage.
+        assert_eq!(occupied, grid * grid);
+
+        let unloaded_lod = controller.occupancy_minimap(3, center, radius);
+        assert!(unloaded_lod.pixels().all(|p| p[0] == 0));
+    }
+
     #[test]
     fn unit_lod_tree_overlay_overrides_flower_base_voxel() {
         let mut overlap_case: Option<(u64, i64, i64, i64)> = None;
@@ -1842,4 +4946,165 @@ mod tests {
             "Tree overlay should override flower base voxel in LOD0 page build"
         );
     }
+
+    #[test]
+    fn streaming_mode_misuse_returns_errors() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        controller.set_visible_page_grid(2);
+        let camera = Vec3::new(0.5, 70.0, 0.5);
+
+        assert_eq!(controller.streaming_mode(), StreamingMode::Async);
+        assert!(matches!(
+            controller.update_sync(camera),
+            Err(WorldError::SyncOnly)
+        ));
+
+        controller.set_streaming_mode(StreamingMode::Sync);
+        assert!(matches!(
+            controller.update_async(camera),
+            Err(WorldError::AsyncOnly)
+        ));
+        controller.update_sync(camera).unwrap();
+        assert!(controller.lod_ready(0));
+        let loaded = controller
+            .page_coords(0)
+            .iter()
+            .filter(|&&coord| coord != invalid_page_coord())
+            .count();
+        assert_eq!(loaded, 8);
+    }
+
+    #[test]
+    fn update_sync_lands_builds_started_asynchronously() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        controller.set_visible_page_grid(2);
+        let camera = Vec3::new(0.5, 70.0, 0.5);
+
+        controller.update_async(camera).unwrap();
+        controller.set_streaming_mode(StreamingMode::Sync);
+        controller.update_sync(camera).unwrap();
+
+        assert_eq!(controller.inflight_jobs, 0);
+        for lod in 0..controller.active_lod_limit() {
+            assert!(controller.lod_ready(lod));
+        }
+    }
+
+    #[test]
+    fn coarse_page_normals_follow_slopes() {
+        // Flat ground at y=10 for x<16, then a 45 degree ramp rising along +X.
+        let height = |x: usize| if x < 16 { 10 } else { 10 + (x - 16) };
+        let mut bricks = vec![[BlockId::AIR; BRICK_VOXELS]; PAGE_BRICKS];
+        for z in 0..PAGE_VOXELS_PER_AXIS {
+            for y in 0..PAGE_VOXELS_PER_AXIS {
+                for x in 0..PAGE_VOXELS_PER_AXIS {
+                    if y > height(x) {
+                        continue;
+                    }
+                    let brick_idx = x / BRICK_SIZE
+                        + (y / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS
+                        + (z / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+                    let voxel_idx = x % BRICK_SIZE
+                        + (y % BRICK_SIZE) * BRICK_SIZE
+                        + (z % BRICK_SIZE) * BRICK_SIZE * BRICK_SIZE;
+                    bricks[brick_idx][voxel_idx] = BlockId::STONE;
+                }
+            }
+        }
+
+        let normals = compute_page_normals(&bricks);
+        let normal_code = |x: usize, y: usize, z: usize| {
+            let brick_idx = x / BRICK_SIZE
+                + (y / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS
+                + (z / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+            let voxel_idx = x % BRICK_SIZE
+                + (y % BRICK_SIZE) * BRICK_SIZE
+                + (z % BRICK_SIZE) * BRICK_SIZE * BRICK_SIZE;
+            normals[brick_idx].map_or(0, |n| n[voxel_idx])
+        };
+
+        assert_eq!(
+            normal_code(8, 10, 16),
+            0,
+            "flat ground should keep the face normal"
+        );
+        assert_eq!(
+            normal_code(0, 10, 16),
+            0,
+            "flat ground at the page border should keep the face normal"
+        );
+
+        let [nx, ny, nz] = voxelicous_voxel::decode_octahedral_normal(normal_code(24, 18, 16))
+            .expect("slope voxel should store a normal");
+        assert!(
+            nx < -0.5 && ny > 0.5,
+            "unexpected slope normal ({nx}, {ny}, {nz})"
+        );
+        assert!(nz.abs() < 0.2);
+    }
+
+    #[test]
+    fn block_light_crosses_page_borders() {
+        const LAMP: BlockId = BlockId(200);
+
+        let generator = TerrainGenerator::with_seed(7);
+        let y = i64::from(generator.height_at(0, 0)) + 4;
+        let mut controller = ClipmapStreamingController::new(generator);
+        let mut materials = MaterialRegistry::default();
+        materials.register(
+            LAMP,
+            Material {
+                emission: 1.0,
+                ..Material::STONE
+            },
+        );
+        controller.set_materials(materials);
+        controller.set_streaming_mode(StreamingMode::Sync);
+        controller.set_visible_page_grid(2);
+        controller
+            .update_sync(Vec3::new(0.5, y as f32 + 0.5, 0.5))
+            .unwrap();
+
+        // The lamp sits at x = -1, the last column of its page; the stone it
+        // lights is across the border, in the next page.
+        let lamp = WorldCoord { x: -1, y, z: 0 };
+        let stone = WorldCoord { x: 0, y, z: 0 };
+        let page_size = PAGE_VOXELS_PER_AXIS as i64;
+        let page_y = div_floor(y, page_size);
+        assert!(controller.page_slot_matches_coord(0, (-1, page_y, 0)));
+        assert!(controller.page_slot_matches_coord(0, (0, page_y, 0)));
+        let light_at = |controller: &ClipmapStreamingController, world: WorldCoord| {
+            let page_coord = (
+                div_floor(world.x, page_size),
+                div_floor(world.y, page_size),
+                div_floor(world.z, page_size),
+            );
+            let [x, y, z] = [world.x, world.y, world.z].map(|v| mod_floor(v, page_size) as usize);
+            let brick_idx = x / BRICK_SIZE
+                + (y / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS
+                + (z / BRICK_SIZE) * PAGE_BRICKS_PER_AXIS * PAGE_BRICKS_PER_AXIS;
+            let page_index = ClipmapStreamingController::page_index_from_coord(page_coord);
+            let id = controller.page_brick_indices(0)[page_index * PAGE_BRICKS + brick_idx];
+            let voxel_idx = x % BRICK_SIZE
+                + (y % BRICK_SIZE) * BRICK_SIZE
+                + (z % BRICK_SIZE) * BRICK_SIZE * BRICK_SIZE;
+            controller
+                .store()
+                .brick_light(BrickId(id))
+                .map_or(0, |light| light[voxel_idx])
+        };
+
+        // A page landing next to a lit page picks up its light.
+        controller.set_block_at_world(lamp.x, lamp.y, lamp.z, LAMP);
+        controller.set_block_at_world(stone.x, stone.y, stone.z, BlockId::STONE);
+        assert_eq!(light_at(&controller, stone), MAX_BLOCK_LIGHT - 1);
+
+        // Removing the lamp darkens the neighbouring page again.
+        controller.set_block_at_world(lamp.x, lamp.y, lamp.z, BlockId::AIR);
+        assert_eq!(light_at(&controller, stone), 0);
+
+        // A lamp landing next to a loaded page relights it.
+        controller.set_block_at_world(lamp.x, lamp.y, lamp.z, LAMP);
+        assert_eq!(light_at(&controller, stone), MAX_BLOCK_LIGHT - 1);
+    }
 }
@@ -0,0 +1,95 @@
+//! Diagnostic dumps for bug reports.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clipmap_streaming::LodStatus;
+use crate::generation::TerrainConfig;
+use crate::WorldSeed;
+
+/// State of a world at one moment, taken by
+/// [`ClipmapStreamingController::diagnostic_snapshot`](crate::ClipmapStreamingController::diagnostic_snapshot).
+///
+/// Holds what is needed to regenerate the same terrain and to see what the
+/// streamer was doing, and serializes to JSON for attaching to bug reports.
+/// Voxel data is not included; the seed and config regenerate it, except for
+/// runtime edits, which are only counted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticSnapshot {
+    /// Terrain seed (also in `terrain`, repeated for quick reading).
+    pub seed: WorldSeed,
+    /// Full terrain generator configuration.
+    pub terrain: TerrainConfig,
+    /// Voxel the camera was in at the last streaming update.
+    pub camera_voxel: [i64; 3],
+    /// Camera velocity used for streaming prediction, in voxels per second.
+    pub camera_velocity: [f32; 3],
+    /// Loaded pages summed over every active LOD.
+    pub loaded_pages: usize,
+    /// Number of runtime block edits.
+    pub edit_count: usize,
+    /// Streaming progress per active LOD, finest first.
+    pub lods: Vec<LodStatus>,
+    /// Frame timing from the profiler; `None` when profiling is disabled.
+    pub profiler: Option<ProfilerSummary>,
+}
+
+/// Frame timing summary for a [`DiagnosticSnapshot`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfilerSummary {
+    /// Frame number at the time of the snapshot.
+    pub frame_number: u64,
+    /// Frames per second.
+    pub fps: f32,
+    /// Last frame time in milliseconds.
+    pub frame_time_ms: f32,
+}
+
+impl DiagnosticSnapshot {
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a snapshot written by [`Self::to_json`].
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use voxelicous_core::types::BlockId;
+    use voxelicous_voxel::WorldCoord;
+
+    use crate::{ClipmapStreamingController, TerrainGenerator};
+
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trips_through_json() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(1234));
+        controller.set_visible_page_grid(2);
+        assert!(controller.generate_initial_pages(Vec3::new(3.5, 70.0, -9.5)) > 0);
+        let center = WorldCoord { x: 0, y: 90, z: 0 };
+        let edited = controller.edit_sphere(center, 1, BlockId::STONE);
+
+        let snapshot = controller.diagnostic_snapshot();
+        assert_eq!(snapshot.seed, 1234);
+        assert!(snapshot.loaded_pages > 0);
+        let loaded: usize = (0..snapshot.lods.len())
+            .map(|lod| controller.loaded_page_count(lod))
+            .sum();
+        assert_eq!(snapshot.loaded_pages, loaded);
+        assert_eq!(snapshot.edit_count, edited);
+        assert_eq!(snapshot.camera_voxel, [3, 70, -10]);
+
+        let json = snapshot.to_json().unwrap();
+        let parsed = DiagnosticSnapshot::from_json(&json).unwrap();
+        assert_eq!(parsed.seed, snapshot.seed);
+        assert_eq!(parsed.terrain.seed, 1234);
+        assert_eq!(parsed.loaded_pages, snapshot.loaded_pages);
+        assert_eq!(parsed.lods, snapshot.lods);
+        assert_eq!(parsed.terrain.tree_params, snapshot.terrain.tree_params);
+    }
+}
@@ -0,0 +1,238 @@
+//! Undo/redo for world edits.
+
+use std::collections::VecDeque;
+
+use voxelicous_core::types::BlockId;
+use voxelicous_voxel::WorldCoord;
+
+use crate::clipmap_streaming::{box_voxels, sphere_voxels, ClipmapStreamingController};
+
+/// One applied edit: every voxel it changed, with its block before and after.
+#[derive(Clone, Debug)]
+struct EditRecord {
+    voxels: Vec<(WorldCoord, BlockId, BlockId)>,
+}
+
+/// Bounded undo/redo stack for edits made through it.
+///
+/// Each edit or brush stroke is one entry. Undo and redo go through the
+/// controller's regular edit path, so touched pages are rebuilt or requeued
+/// exactly as for a fresh edit. Undo writes back the exact blocks a stroke
+/// replaced; restoring a generated block drops the edit instead of storing
+/// it, so an undone stroke leaves no trace in the edit table.
+///
+/// Edits made on the controller directly are not recorded. Undoing across
+/// them writes the recorded blocks over whatever is there.
+#[derive(Clone, Debug)]
+pub struct EditHistory {
+    undo: VecDeque<EditRecord>,
+    redo: Vec<EditRecord>,
+    max_entries: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditHistory {
+    /// Undo entries kept by [`Self::new`].
+    pub const DEFAULT_MAX_ENTRIES: usize = 64;
+
+    /// Create an empty history keeping [`Self::DEFAULT_MAX_ENTRIES`] strokes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_max_entries(Self::DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Create an empty history keeping at most `max_entries` strokes (at
+    /// least one).
+    #[must_use]
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// Maximum number of undo entries.
+    #[must_use]
+    pub const fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Set the maximum number of undo entries (at least one), dropping the
+    /// oldest entries that no longer fit.
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries.max(1);
+        while self.undo.len() > self.max_entries {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Number of strokes [`Self::undo`] can revert.
+    #[must_use]
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of strokes [`Self::redo`] can reapply.
+    #[must_use]
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Forget every recorded stroke.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Recorded [`ClipmapStreamingController::set_block_at_world`].
+    pub fn set_block(
+        &mut self,
+        world: &mut ClipmapStreamingController,
+        coord: WorldCoord,
+        block: BlockId,
+    ) -> bool {
+        self.apply(world, std::iter::once(coord), block) > 0
+    }
+
+    /// Recorded [`ClipmapStreamingController::edit_sphere`].
+    pub fn edit_sphere(
+        &mut self,
+        world: &mut ClipmapStreamingController,
+        center: WorldCoord,
+        radius: i64,
+        block: BlockId,
+    ) -> usize {
+        self.apply(world, sphere_voxels(center, radius), block)
+    }
+
+    /// Recorded [`ClipmapStreamingController::edit_box`].
+    pub fn edit_box(
+        &mut self,
+        world: &mut ClipmapStreamingController,
+        min: WorldCoord,
+        max: WorldCoord,
+        block: BlockId,
+    ) -> usize {
+        self.apply(world, box_voxels(min, max), block)
+    }
+
+    /// Revert the most recent stroke. Returns `false` when there is none.
+    pub fn undo(&mut self, world: &mut ClipmapStreamingController) -> bool {
+        let Some(record) = self.undo.pop_back() else {
+            return false;
+        };
+        world.apply_edit_batch(
+            record
+                .voxels
+                .iter()
+                .map(|&(coord, before, _)| (coord, before)),
+        );
+        self.redo.push(record);
+        true
+    }
+
+    /// Reapply the most recently undone stroke. Returns `false` when there
+    /// is none.
+    pub fn redo(&mut self, world: &mut ClipmapStreamingController) -> bool {
+        let Some(record) = self.redo.pop() else {
+            return false;
+        };
+        world.apply_edit_batch(
+            record
+                .voxels
+                .iter()
+                .map(|&(coord, _, after)| (coord, after)),
+        );
+        self.push_undo(record);
+        true
+    }
+
+    fn apply(
+        &mut self,
+        world: &mut ClipmapStreamingController,
+        coords: impl Iterator<Item = WorldCoord>,
+        block: BlockId,
+    ) -> usize {
+        let replaced = world.apply_edit_batch(coords.map(|coord| (coord, block)));
+        if replaced.is_empty() {
+            return 0;
+        }
+        let changed = replaced.len();
+        self.redo.clear();
+        self.push_undo(EditRecord {
+            voxels: replaced
+                .into_iter()
+                .map(|(coord, before)| (coord, before, block))
+                .collect(),
+        });
+        changed
+    }
+
+    fn push_undo(&mut self, record: EditRecord) {
+        if self.undo.len() == self.max_entries {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::TerrainGenerator;
+
+    #[test]
+    fn undoing_a_box_restores_every_voxel() {
+        let mut world = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        world.set_visible_page_grid(4);
+        let surface = i64::from(world.generator().height_at(0, 0));
+        world.update(Vec3::new(0.0, 60.0, 0.0));
+
+        // A box straddling the surface holds air, soil and stone.
+        let min = WorldCoord {
+            x: -4,
+            y: surface - 4,
+            z: -4,
+        };
+        let max = WorldCoord {
+            x: 4,
+            y: surface + 4,
+            z: 4,
+        };
+        let before: Vec<_> = box_voxels(min, max)
+            .map(|c| (c, world.block_at_world(c.x, c.y, c.z)))
+            .collect();
+        assert!(before.iter().any(|&(_, block)| block.is_air()));
+        assert!(before.iter().any(|&(_, block)| !block.is_air()));
+
+        let mut history = EditHistory::with_max_entries(2);
+        assert!(history.edit_box(&mut world, min, max, BlockId::LOG) > 0);
+        assert!(history.edit_sphere(&mut world, min, 2, BlockId::ICE) > 0);
+
+        assert!(history.undo(&mut world));
+        assert!(history.undo(&mut world));
+        assert!(!history.undo(&mut world));
+        for &(c, block) in &before {
+            assert_eq!(world.block_at_world(c.x, c.y, c.z), block, "{c:?}");
+        }
+        // Restored generator blocks are not kept as edits.
+        assert_eq!(world.read_snapshot().edit_count(), 0);
+
+        assert!(history.redo(&mut world));
+        assert!(box_voxels(min, max).all(|c| world.block_at_world(c.x, c.y, c.z) == BlockId::LOG));
+
+        // Only the newest strokes are kept.
+        for block in [BlockId::STONE, BlockId::SAND, BlockId::DIRT] {
+            history.edit_box(&mut world, min, min, block);
+        }
+        assert_eq!((history.undo_len(), history.redo_len()), (2, 0));
+    }
+}
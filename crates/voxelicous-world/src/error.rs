@@ -0,0 +1,37 @@
+//! World error types.
+
+use thiserror::Error;
+
+/// World generation and streaming errors.
+#[derive(Error, Debug)]
+pub enum WorldError {
+    /// Operation is only available when the world streams asynchronously.
+    #[error("operation requires an asynchronously streamed world")]
+    AsyncOnly,
+
+    /// Operation is only available when the world streams synchronously.
+    #[error("operation requires a synchronously streamed world")]
+    SyncOnly,
+
+    /// Synchronous streaming did not settle within its update limit.
+    #[error("synchronous streaming did not settle after {updates} updates")]
+    SyncStalled {
+        /// Updates run before giving up.
+        updates: usize,
+    },
+
+    /// Reading or writing the on-disk cache failed.
+    #[error("cache I/O error: {0}")]
+    CacheIo(#[from] std::io::Error),
+
+    /// Saved world data could not be decoded.
+    #[error("saved world data is corrupt")]
+    SaveCorrupt,
+
+    /// No world with this name is registered.
+    #[error("unknown world: {0}")]
+    UnknownWorld(String),
+}
+
+/// Result type alias.
+pub type Result<T> = std::result::Result<T, WorldError>;
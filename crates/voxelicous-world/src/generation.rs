@@ -1,18 +1,132 @@
 //! Procedural terrain generation.
 
-use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin, Simplex, Value};
+use serde::{Deserialize, Serialize};
 use voxelicous_core::types::BlockId;
+use voxelicous_core::{Fnv1a, MasterSeed};
 
 use crate::WorldSeed;
 
 const TREE_CELL_SIZE: i64 = 8;
-const TREE_MAX_CANOPY_RADIUS: i64 = 3;
+/// Canopies wider than this could spill past the neighbouring tree cells.
+const TREE_MAX_CANOPY_RADIUS: i32 = 7;
+/// Cap on [`TerrainConfig::erosion_iterations`].
+///
+/// Each pass widens the stencil by a column, so `n` passes sample
+/// `2n² + 2n + 1` columns of height noise per surface lookup: 13 for the
+/// default of 2, 41 at the cap.
+pub const MAX_EROSION_ITERATIONS: u32 = 4;
+
+/// Tree placement parameters for one biome.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeParams {
+    /// Chance (`0.0..=1.0`) that a tree cell spawns a tree.
+    pub density: f64,
+    /// Trunk height range in voxels.
+    pub trunk_height: RangeInclusive<i32>,
+    /// Canopy radius range in voxels (capped at 7).
+    pub canopy_radius: RangeInclusive<i32>,
+}
+
+impl TreeParams {
+    /// Default trunk/canopy shape with the given density.
+    #[must_use]
+    pub const fn with_density(density: f64) -> Self {
+        Self {
+            density,
+            trunk_height: 4..=6,
+            canopy_radius: 2..=3,
+        }
+    }
+}
+
+/// Default per-biome tree parameters.
+///
+/// Every eligible tree cell of a wooded biome grows a tree, as in worlds
+/// generated before densities were configurable.
+#[must_use]
+pub fn default_tree_params() -> HashMap<TerrainBiome, TreeParams> {
+    HashMap::from([
+        (TerrainBiome::Plains, TreeParams::with_density(1.0)),
+        (TerrainBiome::Forest, TreeParams::with_density(1.0)),
+        (TerrainBiome::Desert, TreeParams::with_density(0.0)),
+        (TerrainBiome::Hills, TreeParams::with_density(1.0)),
+        (TerrainBiome::SnowyMountains, TreeParams::with_density(0.0)),
+    ])
+}
+
+/// Base noise summed by the terrain's fractal (fBm) generators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NoiseKind {
+    /// Gradient noise; smooth, rounded terrain.
+    #[default]
+    Perlin,
+    /// Simplex noise; sharper features with fewer grid artifacts.
+    Simplex,
+    /// Value noise; blocky, plateau-like terrain.
+    Value,
+}
+
+/// Fractal noise over the configured [`NoiseKind`].
+#[derive(Clone)]
+enum FbmNoise {
+    Perlin(Fbm<Perlin>),
+    Simplex(Fbm<Simplex>),
+    Value(Fbm<Value>),
+    /// Test stand-in from [`TerrainGenerator::with_noise_override`].
+    #[cfg(any(test, feature = "testing"))]
+    Override(Arc<NoiseOverrideFn>),
+}
+
+impl FbmNoise {
+    fn new(kind: NoiseKind, seed: u32, octaves: usize, lacunarity: f64, persistence: f64) -> Self {
+        match kind {
+            NoiseKind::Perlin => Self::Perlin(
+                Fbm::new(seed)
+                    .set_octaves(octaves)
+                    .set_lacunarity(lacunarity)
+                    .set_persistence(persistence),
+            ),
+            NoiseKind::Simplex => Self::Simplex(
+                Fbm::new(seed)
+                    .set_octaves(octaves)
+                    .set_lacunarity(lacunarity)
+                    .set_persistence(persistence),
+            ),
+            NoiseKind::Value => Self::Value(
+                Fbm::new(seed)
+                    .set_octaves(octaves)
+                    .set_lacunarity(lacunarity)
+                    .set_persistence(persistence),
+            ),
+        }
+    }
+}
+
+impl NoiseFn<f64, 2> for FbmNoise {
+    fn get(&self, point: [f64; 2]) -> f64 {
+        match self {
+            Self::Perlin(noise) => noise.get(point),
+            Self::Simplex(noise) => noise.get(point),
+            Self::Value(noise) => noise.get(point),
+            #[cfg(any(test, feature = "testing"))]
+            Self::Override(noise) => noise(point),
+        }
+    }
+}
 
 /// Terrain generator configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerrainConfig {
     /// Seed for noise generation.
     pub seed: WorldSeed,
+    /// Base noise of every fractal generator.
+    pub noise_kind: NoiseKind,
     /// Sea level (Y coordinate).
     pub sea_level: i32,
     /// Horizontal scale of terrain features.
@@ -27,6 +141,8 @@ pub struct TerrainConfig {
     pub persistence: f64,
     /// Depth of dirt-like layer below surface.
     pub dirt_depth: u32,
+    /// Depth below the surface where [`Biome3d::DeepStone`] starts.
+    pub deep_stone_depth: u32,
     /// Horizontal scale of broad biome regions.
     pub biome_scale: f64,
     /// Relative vertical scale of flatland biomes.
@@ -37,6 +153,10 @@ pub struct TerrainConfig {
     pub snow_height_offset: i32,
     /// Randomized offset for snow line in world units.
     pub snow_line_variation: f64,
+    /// Maximum snow cap thickness in voxels above the snow line (at least 1).
+    pub max_snow_depth: u8,
+    /// Water in columns colder than this temperature freezes at the surface.
+    pub ice_temperature: f64,
     /// Horizontal scale of temperature bands.
     pub temperature_scale: f64,
     /// Horizontal scale of moisture bands.
@@ -47,12 +167,23 @@ pub struct TerrainConfig {
     pub lake_threshold: f64,
     /// Horizontal scale of mountain region masks (controls massif size/separation).
     pub mountain_region_scale: f64,
+    /// Tree placement per biome; biomes missing from the map grow no trees.
+    pub tree_params: HashMap<TerrainBiome, TreeParams>,
+    /// Voxels at or below this Y are indestructible bedrock.
+    pub world_floor_y: i32,
+    /// How strongly steep slopes are smoothed toward their neighbours
+    /// (`0.0` disables erosion, `1.0` is the maximum).
+    pub erosion_strength: f64,
+    /// Erosion passes, at most [`MAX_EROSION_ITERATIONS`]; each smooths
+    /// against the previous pass's adjacent columns.
+    pub erosion_iterations: u32,
 }
 
 impl Default for TerrainConfig {
     fn default() -> Self {
         Self {
             seed: 0,
+            noise_kind: NoiseKind::Perlin,
             sea_level: 60,
             terrain_scale: 102.0,
             terrain_height: 72.0,
@@ -60,22 +191,29 @@ impl Default for TerrainConfig {
             lacunarity: 2.0,
             persistence: 0.5,
             dirt_depth: 4,
+            deep_stone_depth: 48,
             biome_scale: 2200.0,
             flat_height_scale: 0.14,
             mountain_height_scale: 1.45,
             snow_height_offset: 44,
             snow_line_variation: 10.0,
+            max_snow_depth: 3,
+            ice_temperature: -0.12,
             temperature_scale: 2400.0,
             moisture_scale: 2300.0,
             lake_scale: 360.0,
             lake_threshold: 0.56,
             mountain_region_scale: 1900.0,
+            tree_params: default_tree_params(),
+            world_floor_y: -64,
+            erosion_strength: 0.0,
+            erosion_iterations: 2,
         }
     }
 }
 
 /// Dominant biome at a world XZ coordinate.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TerrainBiome {
     /// Mild relief, sparse trees.
     Plains,
@@ -89,6 +227,28 @@ pub enum TerrainBiome {
     SnowyMountains,
 }
 
+/// Biome classification of a 3D world position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome3d {
+    /// Above ground or within the column's soil layer.
+    Surface(TerrainBiome),
+    /// In the stone below the soil layer, under the given surface biome.
+    Underground(TerrainBiome),
+    /// At least [`TerrainConfig::deep_stone_depth`] below the surface, or
+    /// in the bedrock floor.
+    DeepStone,
+}
+
+impl Biome3d {
+    /// Surface biome of the column, if the position is not deep stone.
+    pub const fn surface_biome(self) -> Option<TerrainBiome> {
+        match self {
+            Self::Surface(biome) | Self::Underground(biome) => Some(biome),
+            Self::DeepStone => None,
+        }
+    }
+}
+
 /// Surface sample for one world XZ column.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SurfaceSample {
@@ -102,6 +262,56 @@ pub struct SurfaceSample {
     pub biome: TerrainBiome,
     /// Water level for this column (sea and lakes).
     pub water_level: i32,
+    /// Snow cap thickness in voxels (0 without snow).
+    pub snow_depth: u8,
+    /// Whether the top water voxel is frozen to ice.
+    pub frozen: bool,
+}
+
+/// Terrain statistics over a sampled region, from
+/// [`TerrainGenerator::coverage_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport {
+    /// Number of columns sampled.
+    pub samples: usize,
+    /// Percentage (0-100) of sampled columns in each biome; every biome is
+    /// present, with 0 when it was not sampled.
+    pub biome_percent: HashMap<TerrainBiome, f64>,
+    /// Trees rooted in or overlapping the whole region (not just sampled
+    /// columns).
+    pub tree_count: usize,
+    /// Percentage (0-100) of sampled columns under sea or lake water.
+    pub water_percent: f64,
+    /// Mean surface height of the sampled columns.
+    pub mean_height: f64,
+    /// Lowest sampled surface height.
+    pub min_height: i32,
+    /// Highest sampled surface height.
+    pub max_height: i32,
+}
+
+impl CoverageReport {
+    /// Percentage (0-100) of sampled columns in `biome`.
+    #[must_use]
+    pub fn biome(&self, biome: TerrainBiome) -> f64 {
+        self.biome_percent.get(&biome).copied().unwrap_or(0.0)
+    }
+
+    /// Height difference between the highest and lowest sampled columns.
+    #[must_use]
+    pub const fn relief(&self) -> i32 {
+        self.max_height - self.min_height
+    }
+}
+
+/// Height of a column before rounding, with the relief weights that also
+/// pick its biome and surface blocks.
+#[derive(Debug, Clone, Copy)]
+struct HeightShape {
+    offset: f64,
+    ridge: f64,
+    mountain_weight: f64,
+    hill_weight: f64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -113,69 +323,136 @@ pub(crate) struct TreePlacement {
     pub canopy_radius: i32,
 }
 
+/// Custom surface height function, taking world XZ and returning the surface Y.
+pub type HeightFn = dyn Fn(i64, i64) -> i32 + Send + Sync;
+
+/// Noise field sampled by a [`TerrainGenerator`], for
+/// [`TerrainGenerator::with_noise_override`].
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoiseChannel {
+    /// Base terrain height and the large-scale relief shape.
+    Height,
+    /// Height detail, micro bumps and mountain ridge breaks.
+    Detail,
+    /// Mountain ridges; `0.0` is a ridge crest.
+    Ridge,
+    Temperature,
+    Moisture,
+    /// Large desert regions, on top of temperature and moisture.
+    Desert,
+    /// Where mountains may form.
+    MountainRegion,
+    /// Lake placement mask.
+    Lake,
+    LakeDepth,
+    /// Snow line variation and snow cover depth.
+    Snow,
+}
+
+/// Stand-in noise, taking the sample point and returning roughly `-1..=1`.
+#[cfg(any(test, feature = "testing"))]
+pub type NoiseOverrideFn = dyn Fn([f64; 2]) -> f64 + Send + Sync;
+
 /// Procedural terrain generator using fractal noise.
 #[derive(Clone)]
 pub struct TerrainGenerator {
     config: TerrainConfig,
-    height_noise: Fbm<Perlin>,
-    detail_noise: Fbm<Perlin>,
-    ridge_noise: Fbm<Perlin>,
-    temperature_noise: Fbm<Perlin>,
-    moisture_noise: Fbm<Perlin>,
-    desert_noise: Fbm<Perlin>,
-    mountain_region_noise: Fbm<Perlin>,
-    lake_noise: Fbm<Perlin>,
-    lake_depth_noise: Fbm<Perlin>,
-    snow_noise: Fbm<Perlin>,
+    height_fn: Option<Arc<HeightFn>>,
+    height_noise: FbmNoise,
+    detail_noise: FbmNoise,
+    ridge_noise: FbmNoise,
+    temperature_noise: FbmNoise,
+    moisture_noise: FbmNoise,
+    desert_noise: FbmNoise,
+    mountain_region_noise: FbmNoise,
+    lake_noise: FbmNoise,
+    lake_depth_noise: FbmNoise,
+    snow_noise: FbmNoise,
+    /// Sub-seeds of the per-cell and per-column placement hashes.
+    tree_seed: u64,
+    tree_roll_seed: u64,
+    flower_seed: u64,
 }
 
 impl TerrainGenerator {
     /// Create a new terrain generator with the given configuration.
     pub fn new(config: TerrainConfig) -> Self {
-        let height_noise = Fbm::<Perlin>::new(config.seed as u32)
-            .set_octaves(config.octaves)
-            .set_lacunarity(config.lacunarity)
-            .set_persistence(config.persistence);
-        let detail_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0x9E37_79B9) as u32)
-            .set_octaves(config.octaves.saturating_sub(1).max(1))
-            .set_lacunarity(config.lacunarity)
-            .set_persistence(config.persistence);
-        let ridge_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0xC2B2_AE35) as u32)
-            .set_octaves(config.octaves + 1)
-            .set_lacunarity(config.lacunarity)
-            .set_persistence((config.persistence * 0.8).clamp(0.1, 0.95));
-        let temperature_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0xA5A5_5A5A) as u32)
-            .set_octaves(2)
-            .set_lacunarity(2.0)
-            .set_persistence(0.5);
-        let moisture_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0xD6E8_FEB8) as u32)
-            .set_octaves(2)
-            .set_lacunarity(2.1)
-            .set_persistence(0.5);
-        let desert_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0x3C6E_F372) as u32)
-            .set_octaves(2)
-            .set_lacunarity(1.9)
-            .set_persistence(0.5);
-        let mountain_region_noise =
-            Fbm::<Perlin>::new(config.seed.wrapping_add(0xE703_7ED1) as u32)
-                .set_octaves(2)
-                .set_lacunarity(1.95)
-                .set_persistence(0.5);
-        let lake_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0x94D0_49BB) as u32)
-            .set_octaves(2)
-            .set_lacunarity(2.0)
-            .set_persistence(0.55);
-        let lake_depth_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0xB529_7A4D) as u32)
-            .set_octaves(2)
-            .set_lacunarity(2.3)
-            .set_persistence(0.5);
-        let snow_noise = Fbm::<Perlin>::new(config.seed.wrapping_add(0x27D4_EB2F) as u32)
-            .set_octaves(2)
-            .set_lacunarity(2.0)
-            .set_persistence(0.5);
+        let seed = MasterSeed::new(config.seed);
+        let height_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.height") as u32,
+            config.octaves,
+            config.lacunarity,
+            config.persistence,
+        );
+        let detail_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.detail") as u32,
+            config.octaves.saturating_sub(1).max(1),
+            config.lacunarity,
+            config.persistence,
+        );
+        let ridge_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.ridge") as u32,
+            config.octaves + 1,
+            config.lacunarity,
+            (config.persistence * 0.8).clamp(0.1, 0.95),
+        );
+        let temperature_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.temperature") as u32,
+            2,
+            2.0,
+            0.5,
+        );
+        let moisture_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.moisture") as u32,
+            2,
+            2.1,
+            0.5,
+        );
+        let desert_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.desert") as u32,
+            2,
+            1.9,
+            0.5,
+        );
+        let mountain_region_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.mountain_region") as u32,
+            2,
+            1.95,
+            0.5,
+        );
+        let lake_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.lake") as u32,
+            2,
+            2.0,
+            0.55,
+        );
+        let lake_depth_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.lake_depth") as u32,
+            2,
+            2.3,
+            0.5,
+        );
+        let snow_noise = FbmNoise::new(
+            config.noise_kind,
+            seed.derive("terrain.snow") as u32,
+            2,
+            2.0,
+            0.5,
+        );
 
         Self {
             config,
+            height_fn: None,
             height_noise,
             detail_noise,
             ridge_noise,
@@ -186,6 +463,9 @@ impl TerrainGenerator {
             lake_noise,
             lake_depth_noise,
             snow_noise,
+            tree_seed: seed.derive("trees.placement"),
+            tree_roll_seed: seed.derive("trees.roll"),
+            flower_seed: seed.derive("flowers"),
         }
     }
 
@@ -197,6 +477,58 @@ impl TerrainGenerator {
         })
     }
 
+    /// Create a terrain generator whose surface height comes from `height_fn`.
+    ///
+    /// Biome, surface block and water placement still use the noise fields from
+    /// `config`; only the surface height is replaced.
+    #[must_use]
+    pub fn with_height_fn(config: TerrainConfig, height_fn: Box<HeightFn>) -> Self {
+        Self {
+            height_fn: Some(Arc::from(height_fn)),
+            ..Self::new(config)
+        }
+    }
+
+    /// Same generator rebuilt from a new configuration.
+    ///
+    /// Keeps a custom height function from [`Self::with_height_fn`].
+    #[must_use]
+    pub fn with_config(&self, config: TerrainConfig) -> Self {
+        Self {
+            height_fn: self.height_fn.clone(),
+            ..Self::new(config)
+        }
+    }
+
+    /// Replace one noise channel with `noise`, for testing generation rules
+    /// against controlled inputs.
+    ///
+    /// `noise` gets the same sample points as the noise it replaces. Chain
+    /// calls to override several channels. Overrides are dropped by
+    /// [`Self::with_config`].
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn with_noise_override(
+        mut self,
+        channel: NoiseChannel,
+        noise: impl Fn([f64; 2]) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let noise = FbmNoise::Override(Arc::new(noise));
+        match channel {
+            NoiseChannel::Height => self.height_noise = noise,
+            NoiseChannel::Detail => self.detail_noise = noise,
+            NoiseChannel::Ridge => self.ridge_noise = noise,
+            NoiseChannel::Temperature => self.temperature_noise = noise,
+            NoiseChannel::Moisture => self.moisture_noise = noise,
+            NoiseChannel::Desert => self.desert_noise = noise,
+            NoiseChannel::MountainRegion => self.mountain_region_noise = noise,
+            NoiseChannel::Lake => self.lake_noise = noise,
+            NoiseChannel::LakeDepth => self.lake_depth_noise = noise,
+            NoiseChannel::Snow => self.snow_noise = noise,
+        }
+        self
+    }
+
     /// Get the terrain configuration.
     pub fn config(&self) -> &TerrainConfig {
         &self.config
@@ -207,11 +539,222 @@ impl TerrainGenerator {
         self.surface_at(world_x, world_z).surface_height
     }
 
+    /// Sample every column of an XZ region in parallel.
+    ///
+    /// Covers `min.0..min.0 + size.0` by `min.1..min.1 + size.1` and calls
+    /// `f(x, z, sample)` once per column from rayon worker threads. Samples do
+    /// not depend on scheduling, but callback order is unspecified.
+    pub fn export_region_parallel<F>(&self, min: (i64, i64), size: (u32, u32), f: F)
+    where
+        F: Fn(i64, i64, SurfaceSample) + Sync,
+    {
+        use rayon::prelude::*;
+
+        (0..size.1).into_par_iter().for_each(|dz| {
+            let z = min.1 + i64::from(dz);
+            for dx in 0..size.0 {
+                let x = min.0 + i64::from(dx);
+                f(x, z, self.surface_at(x, z));
+            }
+        });
+    }
+
+    /// Surface samples on a grid of `size` columns spaced `step` blocks apart.
+    ///
+    /// Starts at `min` and returns the samples row-major (`x` fastest), so
+    /// index `dx + dz * size.0` is the column at
+    /// `(min.0 + dx * step, min.1 + dz * step)`. Rows are sampled in
+    /// parallel. A `step` of 0 is treated as 1.
+    pub fn export_heightmap(
+        &self,
+        min: (i64, i64),
+        size: (u32, u32),
+        step: u32,
+    ) -> Vec<SurfaceSample> {
+        use rayon::prelude::*;
+
+        let step = i64::from(step.max(1));
+        (0..size.1)
+            .into_par_iter()
+            .flat_map_iter(|dz| {
+                let z = min.1 + i64::from(dz) * step;
+                (0..size.0).map(move |dx| self.surface_at(min.0 + i64::from(dx) * step, z))
+            })
+            .collect()
+    }
+
+    /// Biome, water, tree and height statistics over an XZ region.
+    ///
+    /// The region covers `min.0..min.0 + size.0` by `min.1..min.1 + size.1`
+    /// blocks; columns are sampled every `step` blocks from `min` (a `step`
+    /// of 0 is treated as 1). Useful for checking how config changes shift
+    /// the terrain's distribution.
+    pub fn coverage_report(&self, min: (i64, i64), size: (u32, u32), step: u32) -> CoverageReport {
+        let step = step.max(1);
+        let columns = (size.0.div_ceil(step), size.1.div_ceil(step));
+        let samples = self.export_heightmap(min, columns, step);
+
+        let mut biome_counts: HashMap<TerrainBiome, usize> = [
+            TerrainBiome::Plains,
+            TerrainBiome::Forest,
+            TerrainBiome::Desert,
+            TerrainBiome::Hills,
+            TerrainBiome::SnowyMountains,
+        ]
+        .into_iter()
+        .map(|biome| (biome, 0))
+        .collect();
+        let mut water = 0usize;
+        let mut height_sum = 0i64;
+        let mut min_height = i32::MAX;
+        let mut max_height = i32::MIN;
+        for sample in &samples {
+            *biome_counts.entry(sample.biome).or_default() += 1;
+            if sample.water_level > sample.surface_height {
+                water += 1;
+            }
+            height_sum += i64::from(sample.surface_height);
+            min_height = min_height.min(sample.surface_height);
+            max_height = max_height.max(sample.surface_height);
+        }
+
+        let total = samples.len().max(1) as f64;
+        let percent = |count: usize| count as f64 * 100.0 / total;
+        let tree_count = if size.0 == 0 || size.1 == 0 {
+            0
+        } else {
+            self.trees_in_area(
+                min.0,
+                min.0 + i64::from(size.0) - 1,
+                min.1,
+                min.1 + i64::from(size.1) - 1,
+            )
+            .len()
+        };
+
+        CoverageReport {
+            samples: samples.len(),
+            biome_percent: biome_counts
+                .into_iter()
+                .map(|(biome, count)| (biome, percent(count)))
+                .collect(),
+            tree_count,
+            water_percent: percent(water),
+            mean_height: height_sum as f64 / total,
+            min_height: if samples.is_empty() { 0 } else { min_height },
+            max_height: if samples.is_empty() { 0 } else { max_height },
+        }
+    }
+
+    /// Rough relative cost of generating a cubic region, such as a clipmap
+    /// page, from a 3x3 grid of surface probes.
+    ///
+    /// The region spans `size` blocks from `min` on every axis. Regions
+    /// entirely above or below the surface band (terrain, water and tree
+    /// tops) cost `1`; regions crossing it cost more, plus roughly one unit
+    /// per tree the probed biomes spawn in a 32x32 footprint. Trees are not
+    /// placed, so this stays far cheaper than generating the region.
+    pub fn estimate_page_cost(&self, min: (i64, i64, i64), size: i64) -> u32 {
+        const SURFACE_COST: u32 = 2;
+        const TREE_CELLS: f64 = 16.0;
+
+        let size = size.max(1);
+        let tree_top = self
+            .config
+            .tree_params
+            .values()
+            .filter(|params| params.density > 0.0)
+            .map(|params| *params.trunk_height.end() + 2)
+            .max()
+            .unwrap_or(0);
+
+        let mut band = (i64::MAX, i64::MIN);
+        let mut density = 0.0;
+        for dz in 0..3 {
+            for dx in 0..3 {
+                let sample =
+                    self.surface_at(min.0 + (size - 1) * dx / 2, min.2 + (size - 1) * dz / 2);
+                let top = (sample.surface_height + tree_top).max(sample.water_level);
+                band.0 = band.0.min(i64::from(sample.surface_height));
+                band.1 = band.1.max(i64::from(top));
+                // Mirrors the placement rules in `tree_in_cell`.
+                if sample.top_block == BlockId::GRASS && sample.water_level <= sample.surface_height
+                {
+                    density += self
+                        .config
+                        .tree_params
+                        .get(&sample.biome)
+                        .map_or(0.0, |params| params.density.clamp(0.0, 1.0));
+                }
+            }
+        }
+
+        if min.1 > band.1 || min.1 + size <= band.0 {
+            return 1;
+        }
+        let trees = (density / 9.0 * TREE_CELLS).ceil() as u32;
+        1 + SURFACE_COST + trees
+    }
+
+    /// Stable hash of surface heights and biomes over an XZ region.
+    ///
+    /// Covers the same columns as [`Self::export_region_parallel`]. The value
+    /// only depends on the generated terrain, so pinning it in a test catches
+    /// any change that alters what a seed produces.
+    pub fn fingerprint(&self, min: (i64, i64), size: (u32, u32)) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for dz in 0..size.1 {
+            let z = min.1 + i64::from(dz);
+            for dx in 0..size.0 {
+                let sample = self.surface_at(min.0 + i64::from(dx), z);
+                hasher.write(&sample.surface_height.to_le_bytes());
+                hasher.write(&[sample.biome as u8]);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Hash identifying what this generator produces, for keying caches of
+    /// its output.
+    ///
+    /// Covers the seed and the whole configuration, plus a small
+    /// [`Self::fingerprint`] so custom height functions and noise overrides
+    /// also change the key.
+    #[must_use]
+    pub fn cache_key(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        // Map keys serialize sorted, so the text is stable across runs.
+        if let Ok(config) = serde_json::to_value(&self.config) {
+            hasher.write(config.to_string().as_bytes());
+        }
+        hasher.write(&self.fingerprint((0, 0), (16, 16)).to_le_bytes());
+        hasher.finish()
+    }
+
     /// Get the dominant biome at world XZ coordinates.
     pub fn biome_at(&self, world_x: i64, world_z: i64) -> TerrainBiome {
         self.surface_at(world_x, world_z).biome
     }
 
+    /// Classify a world position as surface, underground or deep stone.
+    ///
+    /// Positions above the surface and in the soil layer (`dirt_depth` deep)
+    /// report the column's biome; the stone below is underground until
+    /// `deep_stone_depth`. Meant for ambience such as music and fog.
+    pub fn biome_3d(&self, world_x: i64, world_y: i64, world_z: i64) -> Biome3d {
+        let surface = self.surface_at(world_x, world_z);
+        let depth = i64::from(surface.surface_height) - world_y;
+        if world_y <= i64::from(self.config.world_floor_y)
+            || depth >= i64::from(self.config.deep_stone_depth)
+        {
+            Biome3d::DeepStone
+        } else if depth >= i64::from(self.config.dirt_depth) {
+            Biome3d::Underground(surface.biome)
+        } else {
+            Biome3d::Surface(surface.biome)
+        }
+    }
+
     /// Sample the terrain surface at world XZ coordinates.
     pub fn surface_at(&self, world_x: i64, world_z: i64) -> SurfaceSample {
         let nx = world_x as f64 / self.config.terrain_scale;
@@ -219,6 +762,81 @@ impl TerrainGenerator {
         let biome_nx = world_x as f64 / self.config.biome_scale;
         let biome_nz = world_z as f64 / self.config.biome_scale;
 
+        let shape = self.height_shape(world_x, world_z);
+        let surface_height = self.height_fn.as_ref().map_or_else(
+            || {
+                let offset = self.eroded_height_offset(world_x, world_z, shape.offset);
+                self.config.sea_level + offset.round() as i32
+            },
+            |height_fn| height_fn(world_x, world_z),
+        );
+
+        let temperature = self.temperature_noise.get([
+            world_x as f64 / self.config.temperature_scale,
+            world_z as f64 / self.config.temperature_scale,
+        ]);
+        let moisture = self.moisture_noise.get([
+            world_x as f64 / self.config.moisture_scale,
+            world_z as f64 / self.config.moisture_scale,
+        ]);
+        let desert_region = self
+            .desert_noise
+            .get([biome_nx * 0.65 + 7.1, biome_nz * 0.65 - 9.3]);
+
+        let biome = dominant_biome(
+            shape.mountain_weight,
+            shape.hill_weight,
+            temperature,
+            moisture,
+            desert_region,
+            surface_height,
+            self.config.sea_level + self.config.snow_height_offset,
+        );
+        let snow_line_noise = self.snow_noise.get([nx * 0.7 + 13.7, nz * 0.7 - 21.3])
+            * self.config.snow_line_variation;
+        let snow_line = self.config.sea_level + self.config.snow_height_offset;
+        let snow_threshold = f64::from(snow_line) + snow_line_noise;
+        let (top_block, subsurface_block) = surface_blocks_for_biome(
+            biome,
+            shape.ridge,
+            surface_height,
+            self.config.sea_level,
+            snow_threshold,
+        );
+        let water_level = self.water_level_at(
+            world_x,
+            world_z,
+            surface_height,
+            biome,
+            self.config.sea_level,
+        );
+        let snow_depth = if top_block == BlockId::SNOW {
+            let max_depth = f64::from(self.config.max_snow_depth.max(1));
+            let depth01 = (self.snow_noise.get([nx * 2.3 + 5.1, nz * 2.3 - 7.7]) + 1.0) * 0.5;
+            1 + (depth01.clamp(0.0, 0.999) * max_depth) as u8
+        } else {
+            0
+        };
+        let frozen = water_level > surface_height && temperature < self.config.ice_temperature;
+
+        SurfaceSample {
+            surface_height,
+            top_block,
+            subsurface_block,
+            biome,
+            water_level,
+            snow_depth,
+            frozen,
+        }
+    }
+
+    /// Unrounded height above sea level and the relief weights behind it.
+    fn height_shape(&self, world_x: i64, world_z: i64) -> HeightShape {
+        let nx = world_x as f64 / self.config.terrain_scale;
+        let nz = world_z as f64 / self.config.terrain_scale;
+        let biome_nx = world_x as f64 / self.config.biome_scale;
+        let biome_nz = world_z as f64 / self.config.biome_scale;
+
         let base = self.height_noise.get([nx * 0.58, nz * 0.58]);
         let detail = self.detail_noise.get([nx * 0.84, nz * 0.84]);
         let micro = self.detail_noise.get([nx * 1.75 + 19.2, nz * 1.75 - 11.8]);
@@ -277,55 +895,66 @@ impl TerrainGenerator {
             let plateau_mix = (highland_factor * 0.76).clamp(0.0, 0.76);
             height_offset = height_offset * (1.0 - plateau_mix) + snapped * plateau_mix;
         }
-        let surface_height = self.config.sea_level + height_offset.round() as i32;
-
-        let temperature = self.temperature_noise.get([
-            world_x as f64 / self.config.temperature_scale,
-            world_z as f64 / self.config.temperature_scale,
-        ]);
-        let moisture = self.moisture_noise.get([
-            world_x as f64 / self.config.moisture_scale,
-            world_z as f64 / self.config.moisture_scale,
-        ]);
-        let desert_region = self
-            .desert_noise
-            .get([biome_nx * 0.65 + 7.1, biome_nz * 0.65 - 9.3]);
-
-        let biome = dominant_biome(
+        HeightShape {
+            offset: height_offset,
+            ridge,
             mountain_weight,
             hill_weight,
-            temperature,
-            moisture,
-            desert_region,
-            surface_height,
-            self.config.sea_level + self.config.snow_height_offset,
-        );
-        let snow_line_noise = self.snow_noise.get([nx * 0.7 + 13.7, nz * 0.7 - 21.3])
-            * self.config.snow_line_variation;
-        let snow_line = self.config.sea_level + self.config.snow_height_offset;
-        let snow_threshold = f64::from(snow_line) + snow_line_noise;
-        let (top_block, subsurface_block) = surface_blocks_for_biome(
-            biome,
-            ridge,
-            surface_height,
-            self.config.sea_level,
-            snow_threshold,
-        );
-        let water_level = self.water_level_at(
-            world_x,
-            world_z,
-            surface_height,
-            biome,
-            self.config.sea_level,
-        );
+        }
+    }
 
-        SurfaceSample {
-            surface_height,
-            top_block,
-            subsurface_block,
-            biome,
-            water_level,
+    /// Apply [`TerrainConfig::erosion_strength`] to a column's height offset.
+    ///
+    /// Each pass pulls every height toward the mean of its four adjacent
+    /// columns from the previous pass, more strongly the steeper the slope
+    /// to them. The column's result only needs the uneroded noise within
+    /// `passes` columns of it, which is recomputed here rather than shared,
+    /// so it never depends on evaluation order.
+    fn eroded_height_offset(&self, world_x: i64, world_z: i64, offset: f64) -> f64 {
+        let strength = self.config.erosion_strength.clamp(0.0, 1.0);
+        let passes = self.config.erosion_iterations.min(MAX_EROSION_ITERATIONS);
+        if strength <= 0.0 || passes == 0 {
+            return offset;
         }
+
+        // Heights of the columns within `radius` steps, in a square grid.
+        // Each pass only has valid neighbours one step further in.
+        let radius = i64::from(passes);
+        let side = 2 * radius + 1;
+        let index = |dx: i64, dz: i64| ((dz + radius) * side + dx + radius) as usize;
+        let mut heights = vec![0.0; (side * side) as usize];
+        for dz in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx.abs() + dz.abs() <= radius {
+                    heights[index(dx, dz)] = if dx == 0 && dz == 0 {
+                        offset
+                    } else {
+                        self.height_shape(world_x + dx, world_z + dz).offset
+                    };
+                }
+            }
+        }
+
+        for reach in (0..radius).rev() {
+            let previous = heights.clone();
+            for dz in -reach..=reach {
+                for dx in -reach..=reach {
+                    if dx.abs() + dz.abs() > reach {
+                        continue;
+                    }
+                    let height = previous[index(dx, dz)];
+                    let mean = (previous[index(dx - 1, dz)]
+                        + previous[index(dx + 1, dz)]
+                        + previous[index(dx, dz - 1)]
+                        + previous[index(dx, dz + 1)])
+                        / 4.0;
+                    let slope = (mean - height).abs();
+                    heights[index(dx, dz)] =
+                        height + (mean - height) * strength * slope / (1.0 + slope);
+                }
+            }
+        }
+        heights[index(0, 0)]
     }
 
     pub(crate) fn block_from_surface_sample(
@@ -336,8 +965,12 @@ impl TerrainGenerator {
         surface: SurfaceSample,
     ) -> BlockId {
         let world_y = clamp_i64_to_i32(world_y);
-        if world_y > surface.surface_height {
-            if world_y <= surface.water_level {
+        if world_y <= self.config.world_floor_y {
+            BlockId::BEDROCK
+        } else if world_y > surface.surface_height {
+            if world_y == surface.water_level && surface.frozen {
+                BlockId::ICE
+            } else if world_y <= surface.water_level {
                 BlockId::WATER
             } else if self.should_place_flower(world_x, world_z, surface) {
                 if world_y == surface.surface_height + 1 {
@@ -350,6 +983,8 @@ impl TerrainGenerator {
             }
         } else if world_y == surface.surface_height {
             surface.top_block
+        } else if world_y > surface.surface_height - i32::from(surface.snow_depth) {
+            BlockId::SNOW
         } else if world_y > surface.surface_height - self.config.dirt_depth as i32 {
             surface.subsurface_block
         } else {
@@ -365,10 +1000,11 @@ impl TerrainGenerator {
         max_z: i64,
     ) -> Vec<TreePlacement> {
         let mut out = Vec::new();
-        let cell_min_x = div_floor(min_x - TREE_MAX_CANOPY_RADIUS, TREE_CELL_SIZE);
-        let cell_max_x = div_floor(max_x + TREE_MAX_CANOPY_RADIUS, TREE_CELL_SIZE);
-        let cell_min_z = div_floor(min_z - TREE_MAX_CANOPY_RADIUS, TREE_CELL_SIZE);
-        let cell_max_z = div_floor(max_z + TREE_MAX_CANOPY_RADIUS, TREE_CELL_SIZE);
+        let margin = i64::from(self.max_canopy_radius());
+        let cell_min_x = div_floor(min_x - margin, TREE_CELL_SIZE);
+        let cell_max_x = div_floor(max_x + margin, TREE_CELL_SIZE);
+        let cell_min_z = div_floor(min_z - margin, TREE_CELL_SIZE);
+        let cell_max_z = div_floor(max_z + margin, TREE_CELL_SIZE);
 
         for cell_z in cell_min_z..=cell_max_z {
             for cell_x in cell_min_x..=cell_max_x {
@@ -449,8 +1085,20 @@ impl TerrainGenerator {
         leaf_hit
     }
 
+    /// Largest canopy radius any biome can produce.
+    fn max_canopy_radius(&self) -> i32 {
+        self.config
+            .tree_params
+            .values()
+            .filter(|params| params.density > 0.0)
+            .map(|params| *params.canopy_radius.end())
+            .max()
+            .unwrap_or(0)
+            .clamp(0, TREE_MAX_CANOPY_RADIUS)
+    }
+
     fn tree_in_cell(&self, cell_x: i64, cell_z: i64) -> Option<TreePlacement> {
-        let hash = hash2(self.config.seed.wrapping_add(0x6C8E_9CF5), cell_x, cell_z);
+        let hash = hash2(self.tree_seed, cell_x, cell_z);
         let inner = (TREE_CELL_SIZE - 2) as u64;
         let offset_x = 1 + ((hash >> 8) % inner) as i64;
         let offset_z = 1 + ((hash >> 16) % inner) as i64;
@@ -465,18 +1113,16 @@ impl TerrainGenerator {
             return None;
         }
 
-        let density = match surface.biome {
-            TerrainBiome::Forest => 0.26,
-            TerrainBiome::Plains => 0.07,
-            TerrainBiome::Hills => 0.03,
-            TerrainBiome::Desert | TerrainBiome::SnowyMountains => 0.0,
-        };
-        if density <= 0.0 || hash_to_unit(hash >> 24) >= density {
+        let params = self.config.tree_params.get(&surface.biome)?;
+        // Separate hash so the roll spans the full unit interval.
+        let roll = hash2(self.tree_roll_seed, cell_x, cell_z);
+        if params.density <= 0.0 || hash_to_unit(roll) >= params.density {
             return None;
         }
 
-        let trunk_height = 4 + ((hash >> 32) % 3) as i32;
-        let canopy_radius = 2 + ((hash >> 40) % 2) as i32;
+        let trunk_height = pick_in_range(&params.trunk_height, hash >> 32).max(1);
+        let canopy_radius =
+            pick_in_range(&params.canopy_radius, hash >> 40).clamp(0, TREE_MAX_CANOPY_RADIUS);
         Some(TreePlacement {
             root_x,
             root_z,
@@ -498,7 +1144,7 @@ impl TerrainGenerator {
         if chance <= 0.0 {
             return false;
         }
-        let hash = hash2(self.config.seed.wrapping_add(0x8B8B_8B8B), world_x, world_z);
+        let hash = hash2(self.flower_seed, world_x, world_z);
         hash_to_unit(hash) < chance
     }
 
@@ -673,6 +1319,12 @@ fn hash_to_unit(hash: u64) -> f64 {
     mantissa as f64 / ((1u64 << 53) as f64)
 }
 
+fn pick_in_range(range: &RangeInclusive<i32>, hash: u64) -> i32 {
+    let start = *range.start();
+    let span = range.end().saturating_sub(start).max(0).unsigned_abs() + 1;
+    i32::try_from(hash % u64::from(span)).map_or(start, |offset| start.saturating_add(offset))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -690,6 +1342,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn custom_height_fn_overrides_surface_height() {
+        let generator =
+            TerrainGenerator::with_height_fn(TerrainConfig::default(), Box::new(|_, _| 70));
+
+        for x in (-200..200).step_by(7) {
+            for z in (-200..200).step_by(7) {
+                assert_eq!(generator.height_at(x, z), 70);
+                for y in 50..=70 {
+                    assert!(
+                        generator.block_at_world(x, y, z).is_solid(),
+                        "expected solid block at ({x}, {y}, {z})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn erosion_smooths_steep_mountain_steps() {
+        let config = TerrainConfig {
+            seed: 4,
+            ..Default::default()
+        };
+        let plain = TerrainGenerator::new(config.clone());
+        let eroded = TerrainGenerator::new(TerrainConfig {
+            erosion_strength: 1.0,
+            erosion_iterations: 3,
+            ..config
+        });
+
+        // Sample around the highest column of a coarse scan, i.e. mountains.
+        let (peak_x, peak_z) = (-4096..4096)
+            .step_by(128)
+            .flat_map(|x| (-4096..4096).step_by(128).map(move |z| (x, z)))
+            .max_by_key(|&(x, z)| plain.height_at(x, z))
+            .unwrap();
+        assert!(plain.height_at(peak_x, peak_z) > TerrainConfig::default().sea_level + 40);
+
+        // Count adjacent columns more than three blocks apart (sheer steps).
+        let steep_steps = |generator: &TerrainGenerator| {
+            let mut steep = 0;
+            for x in peak_x - 32..peak_x + 32 {
+                for z in peak_z - 32..peak_z + 32 {
+                    let h = generator.height_at(x, z);
+                    for (nx, nz) in [(x + 1, z), (x, z + 1)] {
+                        if (generator.height_at(nx, nz) - h).abs() > 3 {
+                            steep += 1;
+                        }
+                    }
+                }
+            }
+            steep
+        };
+        let (before, after) = (steep_steps(&plain), steep_steps(&eroded));
+        assert!(before > 0);
+        assert!(after * 2 < before, "steep steps {before} -> {after}");
+
+        // Erosion is a pure function of the seed, and passes are capped.
+        let again = TerrainGenerator::new(eroded.config().clone());
+        let capped = |iterations| {
+            TerrainGenerator::new(TerrainConfig {
+                erosion_iterations: iterations,
+                ..eroded.config().clone()
+            })
+        };
+        let (at_cap, past_cap) = (capped(MAX_EROSION_ITERATIONS), capped(1000));
+        for x in peak_x - 8..peak_x + 8 {
+            assert_eq!(eroded.height_at(x, peak_z), again.height_at(x, peak_z));
+            assert_eq!(at_cap.height_at(x, peak_z), past_cap.height_at(x, peak_z));
+        }
+    }
+
     #[test]
     fn different_seeds_different_terrain() {
         let gen1 = TerrainGenerator::with_seed(12345);
@@ -730,6 +1455,98 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cold_water_freezes_and_snow_caps_thicken() {
+        let generator = TerrainGenerator::new(TerrainConfig {
+            seed: 42,
+            ice_temperature: 0.0,
+            ..Default::default()
+        });
+        let temperature = |x: i64, z: i64| {
+            generator.temperature_noise.get([
+                x as f64 / generator.config.temperature_scale,
+                z as f64 / generator.config.temperature_scale,
+            ])
+        };
+
+        let (mut cold_water, mut warm_water) = (0, 0);
+        for x in (-6144..=6144).step_by(48) {
+            for z in (-6144..=6144).step_by(48) {
+                let surface = generator.surface_at(x, z);
+                if surface.water_level <= surface.surface_height {
+                    continue;
+                }
+                let top = generator.block_at_world(x, i64::from(surface.water_level), z);
+                let below = generator.block_at_world(x, i64::from(surface.water_level - 1), z);
+                if temperature(x, z) < 0.0 {
+                    assert_eq!(top, BlockId::ICE, "cold water at ({x}, {z})");
+                    cold_water += 1;
+                } else {
+                    assert_eq!(top, BlockId::WATER, "warm water at ({x}, {z})");
+                    warm_water += 1;
+                }
+                if surface.water_level - 1 > surface.surface_height {
+                    assert_eq!(below, BlockId::WATER);
+                }
+                assert_eq!(generator.surface_at(x, z), surface);
+            }
+        }
+        assert!(cold_water > 0 && warm_water > 0);
+
+        let mut depths = [0usize; 4];
+        for x in (-8192..=8192).step_by(32) {
+            for z in (-8192..=8192).step_by(32) {
+                let surface = generator.surface_at(x, z);
+                if surface.top_block != BlockId::SNOW {
+                    assert_eq!(surface.snow_depth, 0);
+                    continue;
+                }
+                depths[usize::from(surface.snow_depth)] += 1;
+                let y = i64::from(surface.surface_height);
+                let base = y - i64::from(surface.snow_depth);
+                for snow_y in base + 1..=y {
+                    assert_eq!(generator.block_at_world(x, snow_y, z), BlockId::SNOW);
+                }
+                assert_ne!(generator.block_at_world(x, base, z), BlockId::SNOW);
+            }
+        }
+        assert_eq!(depths[0], 0);
+        assert!(depths[1] + depths[2] + depths[3] > 0);
+    }
+
+    #[test]
+    fn overridden_climate_noise_picks_the_biome() {
+        // Flat lowland with no mountain region, a little above sea level.
+        let lowland = |generator: TerrainGenerator| {
+            generator
+                .with_noise_override(NoiseChannel::Height, |_| 0.5)
+                .with_noise_override(NoiseChannel::Detail, |_| 0.0)
+                .with_noise_override(NoiseChannel::Ridge, |_| 1.0)
+                .with_noise_override(NoiseChannel::MountainRegion, |_| -1.0)
+        };
+        let hot_dry = lowland(TerrainGenerator::with_seed(7))
+            .with_noise_override(NoiseChannel::Temperature, |_| 0.9)
+            .with_noise_override(NoiseChannel::Moisture, |_| -0.9)
+            .with_noise_override(NoiseChannel::Desert, |_| 0.9);
+        let cold_wet = lowland(TerrainGenerator::with_seed(7))
+            .with_noise_override(NoiseChannel::Temperature, |_| -0.5)
+            .with_noise_override(NoiseChannel::Moisture, |_| 0.9)
+            .with_noise_override(NoiseChannel::Desert, |_| -0.9);
+
+        for (x, z) in [(0, 0), (1234, -987), (-50_000, 8_000)] {
+            let desert = hot_dry.surface_at(x, z);
+            assert!(desert.surface_height > hot_dry.config().sea_level + 1);
+            assert_eq!(desert.biome, TerrainBiome::Desert);
+            assert_eq!(desert.top_block, BlockId::SAND);
+            assert_eq!(desert.subsurface_block, BlockId::SAND);
+
+            let forest = cold_wet.surface_at(x, z);
+            assert_eq!(forest.surface_height, desert.surface_height);
+            assert_eq!(forest.biome, TerrainBiome::Forest);
+            assert_eq!(forest.top_block, BlockId::GRASS);
+        }
+    }
+
     #[test]
     fn world_contains_multiple_biomes() {
         let generator = TerrainGenerator::with_seed(12345);
@@ -765,6 +1582,27 @@ mod tests {
         assert!(saw_snowy, "Expected to encounter snowy mountain biome");
     }
 
+    #[test]
+    fn biome_3d_separates_surface_from_underground() {
+        let generator = TerrainGenerator::with_seed(12345);
+        let config = generator.config();
+        for (x, z) in [(0, 0), (900, -300), (-4096, 2048)] {
+            let surface = generator.surface_at(x, z);
+            let height = i64::from(surface.surface_height);
+            let at = |y| generator.biome_3d(x, y, z);
+
+            assert_eq!(at(height + 20), Biome3d::Surface(surface.biome));
+            assert_eq!(at(height), Biome3d::Surface(surface.biome));
+            let stone = height - i64::from(config.dirt_depth);
+            assert_eq!(at(stone), Biome3d::Underground(surface.biome));
+            let deep = height - i64::from(config.deep_stone_depth);
+            assert_eq!(at(deep), Biome3d::DeepStone);
+            assert_eq!(at(deep).surface_biome(), None);
+            assert_eq!(at(i64::from(config.world_floor_y)), Biome3d::DeepStone);
+            assert_eq!(at(deep), generator.biome_3d(x, deep, z));
+        }
+    }
+
     #[test]
     fn snow_appears_on_some_mountain_peaks() {
         let generator = TerrainGenerator::with_seed(42);
@@ -820,6 +1658,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tree_params_control_per_biome_density() {
+        let count_in = |generator: &TerrainGenerator, biome: TerrainBiome| {
+            generator
+                .trees_in_area(-2048, 2048, -2048, 2048)
+                .into_iter()
+                .filter(|tree| generator.surface_at(tree.root_x, tree.root_z).biome == biome)
+                .count()
+        };
+
+        let default_gen = TerrainGenerator::with_seed(42);
+        let default_plains = count_in(&default_gen, TerrainBiome::Plains);
+        assert!(count_in(&default_gen, TerrainBiome::Forest) > 0);
+
+        let mut tree_params = default_tree_params();
+        tree_params.insert(TerrainBiome::Forest, TreeParams::with_density(0.0));
+        tree_params.insert(TerrainBiome::Plains, TreeParams::with_density(0.5));
+        let tuned = TerrainGenerator::new(TerrainConfig {
+            seed: 42,
+            tree_params,
+            ..Default::default()
+        });
+
+        assert_eq!(count_in(&tuned, TerrainBiome::Forest), 0);
+        let tuned_plains = count_in(&tuned, TerrainBiome::Plains);
+        assert!(
+            tuned_plains > 0 && tuned_plains < default_plains,
+            "lowering plains density should spawn fewer plains trees"
+        );
+    }
+
+    #[test]
+    fn parallel_region_export_matches_serial_scan() {
+        let generator = TerrainGenerator::with_seed(42);
+        let min = (-300, 1_000);
+        let size = (96, 64);
+
+        let collected = std::sync::Mutex::new(Vec::new());
+        generator.export_region_parallel(min, size, |x, z, sample| {
+            collected.lock().unwrap().push((x, z, sample));
+        });
+        let mut parallel = collected.into_inner().unwrap();
+        parallel.sort_by_key(|&(x, z, _)| (z, x));
+
+        let mut serial = Vec::new();
+        for z in min.1..min.1 + i64::from(size.1) {
+            for x in min.0..min.0 + i64::from(size.0) {
+                serial.push((x, z, generator.surface_at(x, z)));
+            }
+        }
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn heightmap_export_is_row_major_and_strided() {
+        let generator = TerrainGenerator::with_seed(7);
+        let heightmap = generator.export_heightmap((-40, 25), (12, 9), 3);
+        assert_eq!(heightmap.len(), 12 * 9);
+        for dz in 0..9 {
+            for dx in 0..12 {
+                let expected = generator.surface_at(-40 + dx * 3, 25 + dz * 3);
+                assert_eq!(heightmap[(dx + dz * 12) as usize], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_seed_dependent() {
+        let min = (-128, 512);
+        let size = (64, 48);
+        let fingerprint = TerrainGenerator::with_seed(42).fingerprint(min, size);
+
+        // Pinned output for seed 42; update only for intentional generation changes.
+        assert_eq!(fingerprint, 0x6EF6_983C_3436_5FE3);
+        assert_eq!(
+            TerrainGenerator::with_seed(42).fingerprint(min, size),
+            fingerprint
+        );
+        assert_ne!(
+            TerrainGenerator::with_seed(43).fingerprint(min, size),
+            fingerprint
+        );
+        assert_ne!(
+            TerrainGenerator::with_seed(42).fingerprint((min.0 + 1, min.1), size),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn seed_domains_keep_existing_worlds() {
+        // Blocks around the surface, so tree and flower placement are
+        // covered along with the noise fields. One byte per block: every
+        // id fits, and it keeps the value pinned below.
+        let generator = TerrainGenerator::with_seed(12345);
+        let mut hasher = Fnv1a::new();
+        for z in (-96..96).step_by(3) {
+            for x in (-96..96).step_by(3) {
+                let height = i64::from(generator.height_at(x, z));
+                for y in height - 2..height + 12 {
+                    let block = generator.block_at_world(x, y, z);
+                    hasher.write(&[u8::try_from(block.0).expect("block ids fit in a byte")]);
+                }
+            }
+        }
+
+        // Pinned before tree params and `MasterSeed` sub-seeds existed.
+        assert_eq!(hasher.finish(), 0x5AC8_F1D8_E9D9_F361);
+    }
+
     #[test]
     fn world_has_meaningful_vertical_relief() {
         let generator = TerrainGenerator::with_seed(42);
@@ -917,4 +1864,108 @@ mod tests {
             "Tree trunk base must override flower at ({root_x}, {root_y}, {root_z})"
         );
     }
+
+    #[test]
+    fn noise_kind_changes_terrain_but_stays_deterministic() {
+        let heights = |noise_kind| {
+            let generator = TerrainGenerator::new(TerrainConfig {
+                seed: 21,
+                noise_kind,
+                ..Default::default()
+            });
+            generator
+                .export_heightmap((-2048, -2048), (64, 64), 64)
+                .iter()
+                .map(|sample| sample.surface_height)
+                .collect::<Vec<_>>()
+        };
+
+        let kinds = [NoiseKind::Perlin, NoiseKind::Simplex, NoiseKind::Value];
+        let maps: Vec<_> = kinds.iter().map(|&kind| heights(kind)).collect();
+        for (kind, map) in kinds.iter().zip(&maps) {
+            assert_eq!(*map, heights(*kind), "{kind:?} is not reproducible");
+        }
+
+        for a in 0..maps.len() {
+            for b in a + 1..maps.len() {
+                let mean_diff = maps[a]
+                    .iter()
+                    .zip(&maps[b])
+                    .map(|(ha, hb)| f64::from((ha - hb).abs()))
+                    .sum::<f64>()
+                    / maps[a].len() as f64;
+                assert!(
+                    mean_diff > 4.0,
+                    "{:?} vs {:?}: mean height difference {mean_diff}",
+                    kinds[a],
+                    kinds[b]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn forest_pages_are_estimated_costlier_than_plains() {
+        // Default biomes share one density, so thin out the plains.
+        let mut tree_params = default_tree_params();
+        tree_params.insert(TerrainBiome::Plains, TreeParams::with_density(0.1));
+        let generator = TerrainGenerator::new(TerrainConfig {
+            seed: 42,
+            tree_params,
+            ..Default::default()
+        });
+        // A 32-wide page footprint whose probes all land in one biome.
+        let page_in = |biome: TerrainBiome| {
+            (-128..128)
+                .flat_map(|z| (-128..128).map(move |x| (x * 32, z * 32)))
+                .find(|&(x, z)| {
+                    (0..3).all(|dz| {
+                        (0..3).all(|dx| {
+                            let sample = generator.surface_at(x + 31 * dx / 2, z + 31 * dz / 2);
+                            sample.biome == biome && sample.top_block == BlockId::GRASS
+                        })
+                    })
+                })
+                .map(|(x, z)| (x, i64::from(generator.height_at(x, z)) - 16, z))
+                .expect("a page footprint inside the biome")
+        };
+
+        let forest = generator.estimate_page_cost(page_in(TerrainBiome::Forest), 32);
+        let plains = generator.estimate_page_cost(page_in(TerrainBiome::Plains), 32);
+        assert!(forest > plains, "forest {forest}, plains {plains}");
+        assert!(plains > 1);
+
+        // Open sky and deep stone skip the surface entirely.
+        assert_eq!(generator.estimate_page_cost((0, 4_000, 0), 32), 1);
+        assert_eq!(generator.estimate_page_cost((0, -4_000, 0), 32), 1);
+    }
+
+    #[test]
+    fn coverage_report_matches_distribution_tests() {
+        let generator = TerrainGenerator::with_seed(42);
+
+        // Same columns as `deserts_have_large_presence`.
+        let wide = generator.coverage_report((-8192, -8192), (16385, 16385), 128);
+        assert_eq!(wide.samples, 129 * 129);
+        let total: f64 = wide.biome_percent.values().sum();
+        assert!(
+            (total - 100.0).abs() < 1e-6,
+            "biome fractions sum to {total}"
+        );
+        assert_eq!(wide.biome_percent.len(), 5);
+        assert!(wide.biome(TerrainBiome::Desert) >= 6.0);
+        assert!(wide.biome(TerrainBiome::Hills) > 0.0);
+        assert!(wide.tree_count > 0);
+
+        // Same columns as `world_has_meaningful_vertical_relief`.
+        let near = generator.coverage_report((-2048, -2048), (4097, 4097), 64);
+        assert!(near.relief() >= 30, "relief {}", near.relief());
+        assert!(
+            (f64::from(near.min_height)..=f64::from(near.max_height)).contains(&near.mean_height)
+        );
+
+        let watery =
+            TerrainGenerator::with_seed(12345).coverage_report((-2048, -2048), (4097, 4097), 32);
+        assert!(watery.water_percent > 0.0);
+    }
 }
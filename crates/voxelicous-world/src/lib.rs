@@ -1,10 +1,31 @@
 //! Clipmap world generation and streaming for the Voxelicous engine.
 
 pub mod clipmap_streaming;
+pub mod diagnostics;
+pub mod edit_history;
+pub mod error;
 pub mod generation;
+pub mod occlusion;
+pub mod page_cache;
+pub mod snapshot;
+pub mod world_set;
 
-pub use clipmap_streaming::{ClipmapDirtyState, ClipmapStreamingController};
-pub use generation::{TerrainConfig, TerrainGenerator};
+pub use clipmap_streaming::{
+    ClipmapDirtyState, ClipmapStreamingController, LodStatus, PageBuildFairness, StreamingMode,
+};
+pub use diagnostics::{DiagnosticSnapshot, ProfilerSummary};
+pub use edit_history::EditHistory;
+pub use error::{Result, WorldError};
+pub use generation::{
+    default_tree_params, Biome3d, CoverageReport, HeightFn, NoiseKind, SurfaceSample, TerrainBiome,
+    TerrainConfig, TerrainGenerator, TreeParams, MAX_EROSION_ITERATIONS,
+};
+#[cfg(any(test, feature = "testing"))]
+pub use generation::{NoiseChannel, NoiseOverrideFn};
+pub use occlusion::OcclusionGrid;
+pub use page_cache::CacheCodec;
+pub use snapshot::WorldSnapshot;
+pub use world_set::WorldSet;
 
 /// World seed for procedural generation.
 pub type WorldSeed = u64;
@@ -0,0 +1,170 @@
+//! Coarse CPU occlusion culling against a terrain heightmap.
+
+use glam::{Vec2, Vec3};
+use voxelicous_voxel::PAGE_VOXELS_PER_AXIS;
+
+use crate::generation::TerrainGenerator;
+
+const PAGE_VOXELS: i64 = PAGE_VOXELS_PER_AXIS as i64;
+
+/// Low-resolution heightmap used to skip pages hidden behind terrain.
+///
+/// Each cell stores the *lowest* surface height of the columns it covers, so
+/// the grid never claims more terrain than exists. A page is reported hidden
+/// only when every sight line from the camera to its top face dips below the
+/// heightmap, making the test conservative for height-field terrain. Runtime
+/// edits that dig holes are not seen until the grid is rebuilt.
+#[derive(Debug, Clone)]
+pub struct OcclusionGrid {
+    origin: (i64, i64),
+    cell_size: i64,
+    cells: (u32, u32),
+    heights: Vec<i32>,
+}
+
+impl OcclusionGrid {
+    /// Create a grid from per-cell occluder heights (row-major, X fastest).
+    ///
+    /// Cell `(cx, cz)` covers world columns starting at
+    /// `origin + (cx, cz) * cell_size`.
+    ///
+    /// # Panics
+    /// Panics if `heights` does not hold one entry per cell.
+    #[must_use]
+    pub fn new(origin: (i64, i64), cell_size: i64, cells: (u32, u32), heights: Vec<i32>) -> Self {
+        assert_eq!(
+            heights.len(),
+            cells.0 as usize * cells.1 as usize,
+            "one height per occlusion cell"
+        );
+        Self {
+            origin,
+            cell_size: cell_size.max(1),
+            cells,
+            heights,
+        }
+    }
+
+    /// Build a grid covering `cells` cells from the generator's surface.
+    #[must_use]
+    pub fn from_generator(
+        generator: &TerrainGenerator,
+        origin: (i64, i64),
+        cell_size: i64,
+        cells: (u32, u32),
+    ) -> Self {
+        let cell_size = cell_size.max(1);
+        let mut heights = Vec::with_capacity(cells.0 as usize * cells.1 as usize);
+        for cz in 0..i64::from(cells.1) {
+            for cx in 0..i64::from(cells.0) {
+                let x0 = origin.0 + cx * cell_size;
+                let z0 = origin.1 + cz * cell_size;
+                let lowest = (z0..z0 + cell_size)
+                    .flat_map(|z| (x0..x0 + cell_size).map(move |x| (x, z)))
+                    .map(|(x, z)| generator.height_at(x, z))
+                    .min()
+                    .unwrap_or(i32::MIN);
+                heights.push(lowest);
+            }
+        }
+        Self::new(origin, cell_size, cells, heights)
+    }
+
+    /// Occluder height of the cell containing a world column, if covered.
+    #[must_use]
+    pub fn height_at(&self, x: i64, z: i64) -> Option<i32> {
+        let cx = u32::try_from((x - self.origin.0).div_euclid(self.cell_size)).ok()?;
+        let cz = u32::try_from((z - self.origin.1).div_euclid(self.cell_size)).ok()?;
+        if cx >= self.cells.0 || cz >= self.cells.1 {
+            return None;
+        }
+        Some(self.heights[cz as usize * self.cells.0 as usize + cx as usize])
+    }
+
+    /// Whether any part of a page could be visible from `camera`.
+    ///
+    /// `page` is a clipmap page coordinate and `voxel_size` the LOD voxel size
+    /// (`1` for LOD0). Returns `true` whenever the test is inconclusive, e.g.
+    /// when the camera is below the heightmap.
+    #[must_use]
+    pub fn is_potentially_visible(
+        &self,
+        page: (i64, i64, i64),
+        voxel_size: i64,
+        camera: Vec3,
+    ) -> bool {
+        let camera_column = (camera.x.floor() as i64, camera.z.floor() as i64);
+        if self
+            .height_at(camera_column.0, camera_column.1)
+            .is_some_and(|h| camera.y < h as f32)
+        {
+            return true;
+        }
+
+        let page_size = voxel_size * PAGE_VOXELS;
+        let min = Vec2::new((page.0 * page_size) as f32, (page.2 * page_size) as f32);
+        let top = ((page.1 + 1) * page_size) as f32;
+
+        // One target per occlusion cell across the top face (edges included),
+        // so no gap wider than a cell can slip between neighbouring rays.
+        let steps = (page_size / self.cell_size).max(1);
+        let spacing = page_size as f32 / steps as f32;
+        (0..=steps)
+            .flat_map(|i| (0..=steps).map(move |j| (i, j)))
+            .any(|(i, j)| {
+                let target = Vec3::new(
+                    (i as f32).mul_add(spacing, min.x),
+                    top,
+                    (j as f32).mul_add(spacing, min.y),
+                );
+                !self.ray_blocked(camera, target)
+            })
+    }
+
+    fn ray_blocked(&self, from: Vec3, to: Vec3) -> bool {
+        let horizontal = Vec2::new(to.x - from.x, to.z - from.z).length();
+        let step = self.cell_size as f32 * 0.5;
+        let samples = (horizontal / step).ceil().max(1.0) as u32;
+        (1..=samples).any(|s| {
+            let t = s as f32 / samples as f32;
+            let p = from.lerp(to, t);
+            self.height_at(p.x.floor() as i64, p.z.floor() as i64)
+                .is_some_and(|h| h as f32 > p.y)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::TerrainConfig;
+
+    fn ridge_world() -> TerrainGenerator {
+        // Flat ground at y=10 with a tall north-south ridge at x=40..48.
+        TerrainGenerator::with_height_fn(
+            TerrainConfig::default(),
+            Box::new(|x, _z| if (40..48).contains(&x) { 200 } else { 10 }),
+        )
+    }
+
+    #[test]
+    fn ridge_hides_pages_behind_it() {
+        let grid = OcclusionGrid::from_generator(&ridge_world(), (-128, -128), 8, (32, 32));
+        let camera = Vec3::new(4.0, 20.0, 4.0);
+
+        // x=64..96 sits behind the ridge.
+        assert!(!grid.is_potentially_visible((2, 0, 0), 1, camera));
+        // Same distance along open ground.
+        assert!(grid.is_potentially_visible((0, 0, 2), 1, camera));
+        // Pages in front of the ridge and high above it stay visible.
+        assert!(grid.is_potentially_visible((1, 0, 0), 1, camera));
+        assert!(grid.is_potentially_visible((2, 12, 0), 1, camera));
+        // Fully underground pages are hidden from above ground.
+        assert!(!grid.is_potentially_visible((0, -2, 0), 1, camera));
+
+        // From high up the ridge no longer blocks the view.
+        assert!(grid.is_potentially_visible((2, 0, 0), 1, Vec3::new(4.0, 600.0, 4.0)));
+        // A camera below the heightmap is inconclusive, so nothing is hidden.
+        assert!(grid.is_potentially_visible((2, 0, 0), 1, Vec3::new(4.0, 0.0, 4.0)));
+    }
+}
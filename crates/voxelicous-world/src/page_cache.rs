@@ -0,0 +1,272 @@
+//! Page serialization for an on-disk page cache.
+//!
+//! Pages can always be regenerated from the seed and the edit table, so a
+//! cache trades disk space and I/O for generation time. A cached page is a
+//! one-byte [`CacheCodec`] tag followed by the page's voxels, brick by brick
+//! as little-endian block ids, compressed with that codec. An all-air page
+//! stores no voxels.
+//!
+//! A cache directory holds one file per page, under a subdirectory per
+//! generator and voxel size; see [`page_path`]. Cached pages hold generator
+//! output only, never edits.
+
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use voxelicous_core::types::BlockId;
+use voxelicous_voxel::{BRICK_VOXELS, PAGE_BRICKS};
+
+use crate::error::{Result, WorldError};
+
+/// Compression applied to cached page bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CacheCodec {
+    /// Raw voxels; fastest, largest.
+    None,
+    /// LZ4 block compression; cheap to decode while streaming.
+    #[default]
+    Lz4,
+    /// Zstandard; smaller pages for more CPU, suited to large worlds.
+    Zstd,
+}
+
+impl CacheCodec {
+    /// Zstandard level used for [`Self::Zstd`].
+    pub const ZSTD_LEVEL: i32 = 3;
+
+    const fn tag(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Lz4 => 1,
+            Self::Zstd => 2,
+        }
+    }
+
+    const fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::None),
+            1 => Some(Self::Lz4),
+            2 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Compress `bytes` with this codec.
+    pub fn compress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::None => bytes.to_vec(),
+            Self::Lz4 => lz4_flex::compress_prepend_size(bytes),
+            Self::Zstd => zstd::bulk::compress(bytes, Self::ZSTD_LEVEL)?,
+        })
+    }
+
+    /// Undo [`Self::compress`]; malformed input is [`WorldError::SaveCorrupt`].
+    pub fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Lz4 => {
+                lz4_flex::decompress_size_prepended(bytes).map_err(|_| WorldError::SaveCorrupt)
+            }
+            Self::Zstd => zstd::stream::decode_all(bytes).map_err(|_| WorldError::SaveCorrupt),
+        }
+    }
+}
+
+/// Write a page's bricks to `writer`, compressed with `codec`.
+///
+/// `bricks` is either empty (an all-air page) or holds all
+/// [`PAGE_BRICKS`] bricks.
+pub fn write_page(
+    writer: &mut impl Write,
+    bricks: &[[BlockId; BRICK_VOXELS]],
+    codec: CacheCodec,
+) -> Result<()> {
+    debug_assert!(bricks.is_empty() || bricks.len() == PAGE_BRICKS);
+    let mut raw = Vec::with_capacity(bricks.len() * BRICK_VOXELS * 2);
+    for voxel in bricks.iter().flatten() {
+        raw.extend_from_slice(&voxel.0.to_le_bytes());
+    }
+    writer.write_all(&[codec.tag()])?;
+    writer.write_all(&codec.compress(&raw)?)?;
+    Ok(())
+}
+
+/// Read a page written by [`write_page`], whatever codec it used.
+///
+/// Returns no bricks for an all-air page.
+pub fn read_page(reader: &mut impl Read) -> Result<Vec<[BlockId; BRICK_VOXELS]>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let (&tag, payload) = bytes.split_first().ok_or(WorldError::SaveCorrupt)?;
+    let codec = CacheCodec::from_tag(tag).ok_or(WorldError::SaveCorrupt)?;
+    let raw = codec.decompress(payload)?;
+
+    let brick_bytes = BRICK_VOXELS * 2;
+    if !raw.is_empty() && raw.len() != PAGE_BRICKS * brick_bytes {
+        return Err(WorldError::SaveCorrupt);
+    }
+    Ok(raw
+        .chunks_exact(brick_bytes)
+        .map(|brick| {
+            let mut voxels = [BlockId::AIR; BRICK_VOXELS];
+            for (voxel, bytes) in voxels.iter_mut().zip(brick.chunks_exact(2)) {
+                *voxel = BlockId(u16::from_le_bytes([bytes[0], bytes[1]]));
+            }
+            voxels
+        })
+        .collect())
+}
+
+/// Cache file of the page at `page_coord` whose voxels are `voxel_size`
+/// base voxels wide, inside the cache directory `dir`.
+///
+/// `generator_key` is the [`TerrainGenerator::cache_key`] of the generator
+/// that built the page, so other seeds and configurations never read it.
+///
+/// [`TerrainGenerator::cache_key`]: crate::TerrainGenerator::cache_key
+#[must_use]
+pub fn page_path(
+    dir: &Path,
+    generator_key: u64,
+    page_coord: (i64, i64, i64),
+    voxel_size: i64,
+) -> PathBuf {
+    let (x, y, z) = page_coord;
+    dir.join(format!("{generator_key:016x}"))
+        .join(voxel_size.to_string())
+        .join(format!("{x}_{y}_{z}.page"))
+}
+
+/// Read the cached page at `path`.
+pub fn load_page(path: &Path) -> Result<Vec<[BlockId; BRICK_VOXELS]>> {
+    read_page(&mut File::open(path)?)
+}
+
+/// Write a page to `path`, creating its directory if needed.
+pub fn store_page(
+    path: &Path,
+    bricks: &[[BlockId; BRICK_VOXELS]],
+    codec: CacheCodec,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_page(&mut File::create(path)?, bricks, codec)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use voxelicous_voxel::PAGE_VOXELS_PER_AXIS;
+
+    use super::*;
+    use crate::{ClipmapStreamingController, StreamingMode, TerrainGenerator};
+
+    #[test]
+    fn pages_round_trip_with_every_codec() {
+        let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(7));
+        assert_eq!(controller.cache_codec(), CacheCodec::Lz4);
+
+        // A page holding the terrain surface, with air, ground and water.
+        let page_size = i64::try_from(PAGE_VOXELS_PER_AXIS).unwrap();
+        let height = i64::from(controller.generator().height_at(16, 16));
+        let coord = (0, height.div_euclid(page_size), 0);
+
+        let mut sizes = Vec::new();
+        let mut pages = Vec::new();
+        for codec in [CacheCodec::None, CacheCodec::Lz4, CacheCodec::Zstd] {
+            controller.set_cache_codec(codec);
+            let mut bytes = Vec::new();
+            controller.write_cached_page(0, coord, &mut bytes).unwrap();
+            sizes.push(bytes.len());
+            pages.push(read_page(&mut bytes.as_slice()).unwrap());
+        }
+        assert_eq!(pages[0].len(), PAGE_BRICKS);
+        assert!(pages.iter().all(|page| *page == pages[0]));
+        assert!(sizes[2] < sizes[0], "zstd {} vs raw {}", sizes[2], sizes[0]);
+
+        // All-air pages store nothing but the codec tag.
+        let mut air = Vec::new();
+        write_page(&mut air, &[], CacheCodec::None).unwrap();
+        assert_eq!(air.len(), 1);
+        assert!(read_page(&mut air.as_slice()).unwrap().is_empty());
+
+        // Bad tags and truncated payloads are reported, not panicked on.
+        let mut bytes = Vec::new();
+        write_page(&mut bytes, &pages[0], CacheCodec::Zstd).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        assert!(matches!(
+            read_page(&mut bytes.as_slice()),
+            Err(WorldError::SaveCorrupt)
+        ));
+        assert!(matches!(
+            read_page(&mut [9u8, 0, 0].as_slice()),
+            Err(WorldError::SaveCorrupt)
+        ));
+    }
+
+    #[test]
+    fn streaming_reuses_cached_pages() {
+        let dir =
+            std::env::temp_dir().join(format!("voxelicous-page-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let controller = |seed: u64, cache: bool| {
+            let mut controller = ClipmapStreamingController::new(TerrainGenerator::with_seed(seed));
+            controller.set_streaming_mode(StreamingMode::Sync);
+            controller.set_visible_page_grid(2);
+            if cache {
+                controller.set_page_cache_dir(Some(dir.clone()));
+            }
+            controller
+        };
+        let stream = |mut controller: ClipmapStreamingController| {
+            controller.update_sync(Vec3::new(0.5, 70.0, 0.5)).unwrap();
+            controller
+        };
+
+        // The first world generates and stores its pages; the second loads
+        // the same pages back.
+        let generated = stream(controller(7, true));
+        let cached = stream(controller(7, true));
+        for lod in 0..generated.active_lod_count() {
+            assert_eq!(cached.page_coords(lod), generated.page_coords(lod));
+            assert_eq!(cached.page_occ(lod), generated.page_occ(lod));
+        }
+
+        // Loads really come from the cache: blank out a solid page.
+        let (slot, coord) = generated
+            .page_coords(0)
+            .iter()
+            .zip(generated.page_occ(0))
+            .enumerate()
+            .find(|(_, (_, occ))| **occ != [0, 0])
+            .map(|(slot, (coord, _))| (slot, coord.map(i64::from)))
+            .expect("a solid page around the camera");
+        let key = generated.generator().cache_key();
+        let path = page_path(&dir, key, (coord[0], coord[1], coord[2]), 1);
+        assert!(path.exists());
+        store_page(&path, &[], CacheCodec::None).unwrap();
+        assert_eq!(stream(controller(7, true)).page_occ(0)[slot], [0, 0]);
+
+        // Pages with edits are built, never loaded or stored.
+        let page_size = i64::try_from(PAGE_VOXELS_PER_AXIS).unwrap();
+        let [x, y, z] = [coord[0], coord[1], coord[2]].map(|c| c * page_size);
+        let mut edited = controller(7, true);
+        assert!(edited.set_block_at_world(x, y, z, BlockId::ICE));
+        let edited = stream(edited);
+        assert_ne!(edited.page_occ(0)[slot], [0, 0]);
+        assert!(load_page(&path).unwrap().is_empty());
+
+        // Other seeds never read this seed's pages.
+        let other = stream(controller(8, true));
+        let uncached = stream(controller(8, false));
+        for lod in 0..other.active_lod_count() {
+            assert_eq!(other.page_occ(lod), uncached.page_occ(lod));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,216 @@
+//! Named collection of independent worlds (dimensions).
+
+use glam::Vec3;
+
+use crate::clipmap_streaming::ClipmapStreamingController;
+use crate::error::{Result, WorldError};
+
+/// Named worlds, each with its own generator, edits and streaming state.
+///
+/// Exactly one world is active at a time; [`Self::update`] only streams the
+/// active world so background dimensions keep their pages without costing
+/// frame time. A renderer draws whichever world is active. When
+/// [`Self::set_active`] reports a switch, call
+/// `ClipmapRenderer::invalidate` so the new world's pages (which it marks
+/// dirty) are uploaded into fresh buffers.
+pub struct WorldSet {
+    worlds: Vec<(String, ClipmapStreamingController)>,
+    active: usize,
+}
+
+impl WorldSet {
+    /// Create a set holding a single, active world.
+    pub fn new(name: impl Into<String>, world: ClipmapStreamingController) -> Self {
+        Self {
+            worlds: vec![(name.into(), world)],
+            active: 0,
+        }
+    }
+
+    /// Add a world, returning the one it replaced under the same name.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        world: ClipmapStreamingController,
+    ) -> Option<ClipmapStreamingController> {
+        let name = name.into();
+        if let Some(index) = self.index_of(&name) {
+            return Some(std::mem::replace(&mut self.worlds[index].1, world));
+        }
+        self.worlds.push((name, world));
+        None
+    }
+
+    /// Number of worlds in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.worlds.len()
+    }
+
+    /// Always `false`; a set holds at least one world.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.worlds.is_empty()
+    }
+
+    /// World names in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.worlds.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Look up a world by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&ClipmapStreamingController> {
+        self.index_of(name).map(|index| &self.worlds[index].1)
+    }
+
+    /// Look up a world by name, mutably.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ClipmapStreamingController> {
+        self.index_of(name).map(|index| &mut self.worlds[index].1)
+    }
+
+    /// Name of the active world.
+    #[must_use]
+    pub fn active_name(&self) -> &str {
+        &self.worlds[self.active].0
+    }
+
+    /// The active world.
+    #[must_use]
+    pub fn active(&self) -> &ClipmapStreamingController {
+        &self.worlds[self.active].1
+    }
+
+    /// The active world, mutably.
+    pub fn active_mut(&mut self) -> &mut ClipmapStreamingController {
+        &mut self.worlds[self.active].1
+    }
+
+    /// Make `name` the active world.
+    ///
+    /// Returns `true` when the active world changed. The newly active world
+    /// has all of its loaded pages marked dirty so a renderer can rebuild
+    /// its GPU state from the next dirty state.
+    pub fn set_active(&mut self, name: &str) -> Result<bool> {
+        let index = self
+            .index_of(name)
+            .ok_or_else(|| WorldError::UnknownWorld(name.to_string()))?;
+        if index == self.active {
+            return Ok(false);
+        }
+        self.active = index;
+        self.worlds[index].1.mark_all_pages_dirty();
+        Ok(true)
+    }
+
+    /// Stream the active world around the camera.
+    pub fn update(&mut self, camera_pos: Vec3) {
+        self.active_mut().update(camera_pos);
+    }
+
+    /// Stream a specific world, e.g. to prefetch a dimension before switching.
+    pub fn update_world(&mut self, name: &str, camera_pos: Vec3) -> Result<()> {
+        self.get_mut(name)
+            .ok_or_else(|| WorldError::UnknownWorld(name.to_string()))?
+            .update(camera_pos);
+        Ok(())
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.worlds.iter().position(|(world, _)| world == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxelicous_core::types::BlockId;
+    use voxelicous_voxel::WorldCoord;
+
+    use super::*;
+    use crate::TerrainGenerator;
+
+    fn loaded_pages(world: &ClipmapStreamingController) -> Vec<[i32; 4]> {
+        let mut pages: Vec<_> = world
+            .page_coords(0)
+            .iter()
+            .copied()
+            .filter(|coord| coord[0] != i32::MIN)
+            .collect();
+        pages.sort_unstable();
+        pages
+    }
+
+    fn stream_until(
+        set: &mut WorldSet,
+        name: &str,
+        camera: Vec3,
+        done: impl Fn(&ClipmapStreamingController) -> bool,
+    ) {
+        for _ in 0..2000 {
+            set.update_world(name, camera).unwrap();
+            if done(set.get(name).unwrap()) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("world {name} did not finish streaming");
+    }
+
+    fn streamed_world(seed: u64) -> ClipmapStreamingController {
+        let mut world = ClipmapStreamingController::new(TerrainGenerator::with_seed(seed));
+        world.set_visible_page_grid(4);
+        world
+    }
+
+    #[test]
+    fn worlds_in_a_set_stream_independently() {
+        let mut set = WorldSet::new("overworld", streamed_world(1));
+        assert!(set.insert("nether", streamed_world(99)).is_none());
+        assert_eq!(set.names().collect::<Vec<_>>(), ["overworld", "nether"]);
+
+        let camera = Vec3::new(0.0, 60.0, 0.0);
+        for name in ["overworld", "nether"] {
+            stream_until(&mut set, name, camera, |world| world.lod_ready(0));
+        }
+        let nether_pages = loaded_pages(set.get("nether").unwrap());
+        assert!(!nether_pages.is_empty());
+        assert!(!loaded_pages(set.active()).is_empty());
+        assert_ne!(
+            set.get("nether").unwrap().generator().config().seed,
+            set.active().generator().config().seed
+        );
+
+        // Streaming and editing the active world leaves the other untouched.
+        let far = Vec3::new(4_000.0, 60.0, -4_000.0);
+        stream_until(&mut set, "overworld", far, |world| {
+            loaded_pages(world)
+                .iter()
+                .all(|page| !nether_pages.contains(page))
+        });
+        set.active_mut()
+            .edit_sphere(WorldCoord { x: 0, y: 400, z: 0 }, 2, BlockId::STONE);
+        let nether = set.get("nether").unwrap();
+        assert_eq!(loaded_pages(nether), nether_pages);
+        assert_eq!(nether.block_at_world(0, 400, 0), BlockId::AIR);
+        assert_ne!(loaded_pages(set.active()), nether_pages);
+
+        // Switching marks every loaded page of the new world for re-upload.
+        set.get_mut("nether").unwrap().take_dirty_state();
+        assert!(set.set_active("nether").unwrap());
+        assert!(!set.set_active("nether").unwrap());
+        assert_eq!(set.active_name(), "nether");
+        let dirty = set.active_mut().take_dirty_state();
+        let loaded = set
+            .active()
+            .page_coords(0)
+            .iter()
+            .filter(|coord| coord[0] != i32::MIN)
+            .count();
+        assert_eq!(dirty.dirty_pages[0].len(), loaded);
+
+        assert!(matches!(
+            set.set_active("end"),
+            Err(WorldError::UnknownWorld(name)) if name == "end"
+        ));
+    }
+}